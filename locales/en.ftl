@@ -0,0 +1,67 @@
+login-required = Please login to the Trieve CLI with your credentials. Run `trieve login` to get started.
+
+## Login
+login-welcome = Welcome to the Trieve CLI! Let's get started by configuring your API Key and Organization ID.
+login-welcome-back = Welcome back to the Trieve CLI! Let's update your configuration.
+login-callback-port-error = Could not start the login callback server on port { $port }. Pass a free port with --callback-port or log in with --api-key.
+login-browser-error = Error opening browser. Please visit the URL manually.
+login-visit-url = Please go to the following URL to get a Trieve API Key: { $url }
+login-paste-api-key = Paste the API key shown after logging in:
+login-timeout = Timed out waiting for the browser login. Run `trieve login` again, or use `trieve login --no-browser`.
+login-session-instructions = Log in to { $url } in your browser, then copy the `vault` cookie of that site from the developer tools, or a JWT your SSO provider issued for it.
+login-paste-session-token = Paste the session cookie or JWT:
+login-session-rejected = The server did not accept the session token: { $error }
+credentials-invalid = Your credentials for profile '{ $profile }' are invalid. The API key may have been revoked or expired.
+credentials-login-hint = Run `trieve login --profile-name { $profile }` to log in again.
+credentials-updated = Updated the credentials of profile '{ $profile }'. Run the command again.
+config-unreadable = Could not read the Trieve CLI configuration: { $error }
+config-backed-up = The unreadable configuration was backed up to { $path }. Run `trieve login` to set up a new profile.
+config-too-new = The Trieve CLI configuration was written by a newer version of the CLI (configuration version { $version }, this version supports up to { $supported }). Run `trieve update` to upgrade.
+config-set = Set { $key } to '{ $value }' for profile '{ $profile }'.
+config-unset = Reset { $key } to its default for profile '{ $profile }'.
+config-list-header = Settings of profile '{ $profile }':
+login-welcome-user = Welcome, { $name }!
+login-complete = Configuration complete! Your profile has been saved as '{ $profile }'.
+
+## Profiles
+profile-not-found = Profile '{ $profile }' not found.
+profile-switched = Switched to profile '{ $profile }'.
+profile-deleted = Deleted profile '{ $profile }'.
+profile-delete-last = Cannot delete the last profile.
+profile-list-header = Profiles:
+profile-protected = Profile '{ $profile }' is now protected. Destructive commands run with it must be confirmed by typing its name.
+profile-unprotected = Profile '{ $profile }' is no longer protected.
+profile-protected-confirm = Profile '{ $profile }' is protected. Type its name to { $action }:
+profile-protected-mismatch = The name did not match profile '{ $profile }', nothing was changed.
+
+## Organizations
+organization-switched = Switched to organization '{ $organization }'.
+organization-created = Organization '{ $organization }' created.
+organization-updated = Organization '{ $organization }' updated.
+
+## Datasets
+dataset-list-header = Datasets for organization: { $organization }
+dataset-created = Dataset created successfully!
+dataset-default-used = Using dataset { $dataset }, the default dataset of this profile.
+dataset-env-used = Using dataset { $dataset } from { $var }.
+dataset-required = No dataset given. Pass --dataset-id, or set a default dataset with `trieve dataset use`.
+dataset-default-set = '{ $dataset }' is now the default dataset of profile '{ $profile }'.
+dataset-default-cleared = Removed the default dataset of profile '{ $profile }'.
+dataset-updated = Dataset updated successfully!
+dataset-deletion-cancelled = Dataset deletion cancelled.
+dataset-deleted = Dataset deleted successfully!
+dataset-field-id = ID: { $id }
+dataset-field-name = Name: { $name }
+dataset-field-organization = Organization ID: { $organization }
+dataset-field-tracking-id = Tracking ID: { $tracking_id }
+seed-adding = Adding seed data to dataset: { $dataset }
+seed-added = Seed data added successfully!
+seed-example-added = Example dataset added successfully!
+
+## API keys
+api-key-generated = API Key generated successfully!
+api-key-field-name = Name: { $name }
+api-key-field-role = Role: { $role }
+api-key-field-datasets = Datasets: { $datasets }
+api-key-field-scopes = Scopes: { $scopes }
+api-key-field-key = API Key: { $key }
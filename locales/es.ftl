@@ -0,0 +1,67 @@
+login-required = Inicia sesión en la CLI de Trieve con tus credenciales. Ejecuta `trieve login` para empezar.
+
+## Login
+login-welcome = ¡Bienvenido a la CLI de Trieve! Empecemos configurando tu clave de API y tu ID de organización.
+login-welcome-back = ¡Bienvenido de nuevo a la CLI de Trieve! Actualicemos tu configuración.
+login-callback-port-error = No se pudo iniciar el servidor de retorno del inicio de sesión en el puerto { $port }. Indica un puerto libre con --callback-port o inicia sesión con --api-key.
+login-browser-error = No se pudo abrir el navegador. Visita la URL manualmente.
+login-visit-url = Visita la siguiente URL para obtener una clave de API de Trieve: { $url }
+login-paste-api-key = Pega la clave de API que aparece tras iniciar sesión:
+login-timeout = Se agotó el tiempo de espera del inicio de sesión en el navegador. Ejecuta `trieve login` de nuevo o usa `trieve login --no-browser`.
+login-session-instructions = Inicia sesión en { $url } en tu navegador y copia la cookie `vault` de ese sitio desde las herramientas de desarrollo, o un JWT que tu proveedor de SSO haya emitido para él.
+login-paste-session-token = Pega la cookie de sesión o el JWT:
+login-session-rejected = El servidor no aceptó el token de sesión: { $error }
+credentials-invalid = Tus credenciales del perfil '{ $profile }' no son válidas. Es posible que la clave de API se haya revocado o haya caducado.
+credentials-login-hint = Ejecuta `trieve login --profile-name { $profile }` para iniciar sesión de nuevo.
+credentials-updated = Se actualizaron las credenciales del perfil '{ $profile }'. Ejecuta el comando de nuevo.
+config-unreadable = No se pudo leer la configuración de la CLI de Trieve: { $error }
+config-backed-up = Se hizo una copia de seguridad de la configuración ilegible en { $path }. Ejecuta `trieve login` para configurar un nuevo perfil.
+config-too-new = La configuración de la CLI de Trieve fue escrita por una versión más reciente de la CLI (versión de configuración { $version }, esta versión admite hasta la { $supported }). Ejecuta `trieve update` para actualizar.
+config-set = Se estableció { $key } en '{ $value }' para el perfil '{ $profile }'.
+config-unset = Se restableció { $key } a su valor predeterminado para el perfil '{ $profile }'.
+config-list-header = Configuración del perfil '{ $profile }':
+login-welcome-user = ¡Bienvenido, { $name }!
+login-complete = ¡Configuración completada! Tu perfil se ha guardado como '{ $profile }'.
+
+## Profiles
+profile-not-found = No se encontró el perfil '{ $profile }'.
+profile-switched = Se cambió al perfil '{ $profile }'.
+profile-deleted = Se eliminó el perfil '{ $profile }'.
+profile-delete-last = No se puede eliminar el último perfil.
+profile-list-header = Perfiles:
+profile-protected = El perfil '{ $profile }' ahora está protegido. Los comandos destructivos que se ejecuten con él deben confirmarse escribiendo su nombre.
+profile-unprotected = El perfil '{ $profile }' ya no está protegido.
+profile-protected-confirm = El perfil '{ $profile }' está protegido. Escribe su nombre para confirmar ({ $action }):
+profile-protected-mismatch = El nombre no coincide con el perfil '{ $profile }', no se cambió nada.
+
+## Organizations
+organization-switched = Se cambió a la organización '{ $organization }'.
+organization-created = Se creó la organización '{ $organization }'.
+organization-updated = Se actualizó la organización '{ $organization }'.
+
+## Datasets
+dataset-list-header = Datasets de la organización: { $organization }
+dataset-created = ¡Dataset creado correctamente!
+dataset-default-used = Usando el conjunto de datos { $dataset }, el predeterminado de este perfil.
+dataset-env-used = Usando el conjunto de datos { $dataset } de { $var }.
+dataset-required = No se indicó ningún conjunto de datos. Usa --dataset-id o establece uno predeterminado con `trieve dataset use`.
+dataset-default-set = '{ $dataset }' es ahora el conjunto de datos predeterminado del perfil '{ $profile }'.
+dataset-default-cleared = Se quitó el conjunto de datos predeterminado del perfil '{ $profile }'.
+dataset-updated = ¡Dataset actualizado correctamente!
+dataset-deletion-cancelled = Se canceló la eliminación del dataset.
+dataset-deleted = ¡Dataset eliminado correctamente!
+dataset-field-id = ID: { $id }
+dataset-field-name = Nombre: { $name }
+dataset-field-organization = ID de organización: { $organization }
+dataset-field-tracking-id = ID de seguimiento: { $tracking_id }
+seed-adding = Añadiendo datos de ejemplo al dataset: { $dataset }
+seed-added = ¡Datos de ejemplo añadidos correctamente!
+seed-example-added = ¡Dataset de ejemplo añadido correctamente!
+
+## API keys
+api-key-generated = ¡Clave de API generada correctamente!
+api-key-field-name = Nombre: { $name }
+api-key-field-role = Rol: { $role }
+api-key-field-datasets = Datasets: { $datasets }
+api-key-field-scopes = Alcances: { $scopes }
+api-key-field-key = Clave de API: { $key }
@@ -0,0 +1,1864 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::commands;
+
+#[derive(Parser)]
+#[command(author, version)]
+#[command(
+    name = "trieve",
+    about = "Trieve CLI - CLI for Trieve Search Product",
+    long_about = "Trieve CLI is a CLI for the Trieve Search Product. 
+    
+    It allows you to interact with the Trieve Search Product from the command line by adding data."
+)]
+#[command(arg_required_else_help(true))]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+    /// The name of the profile to use for this command only, e.g. `trieve dataset list -p staging`.
+    /// The selected profile is left as it is
+    #[arg(short, long, global = true, env = "TRIEVE_PROFILE")]
+    pub profile: Option<String>,
+    /// Print the wall time, API calls, bytes transferred and retries after the command finishes
+    #[arg(long, global = true)]
+    pub stats: bool,
+    /// Send all requests through this HTTP(S) proxy
+    #[arg(long, global = true, env = "HTTPS_PROXY")]
+    pub proxy: Option<String>,
+    /// Trust the CA certificate(s) in this PEM file in addition to the system ones
+    #[arg(long, global = true, env = "TRIEVE_CA_CERT")]
+    pub ca_cert: Option<PathBuf>,
+    /// Disable TLS certificate verification. Only use this for lab setups
+    #[arg(long, global = true)]
+    pub no_verify_tls: bool,
+    /// Request timeout in seconds, overriding the `timeout_secs` of the profile
+    #[arg(long, global = true, value_name = "SECS")]
+    pub timeout: Option<u64>,
+    /// Language for messages, e.g. `en` or `es`. Defaults to the `LANG` of the environment
+    #[arg(long, global = true, env = "TRIEVE_LANG")]
+    pub lang: Option<String>,
+    /// Output format. With `json`, failures are printed as JSON objects with the error code,
+    /// message and the request ID of the failed API call
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        env = "TRIEVE_OUTPUT",
+        default_value = "text"
+    )]
+    pub output: OutputFormat,
+    /// Print only essential identifiers, such as the ID of a created dataset, and never prompt
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+    /// Show only these columns of list tables, in this order, e.g. `id,name,chunks`. With
+    /// `--quiet` they are printed tab-separated without a header
+    #[arg(long, global = true, value_delimiter = ',', value_name = "COLUMNS")]
+    pub columns: Vec<String>,
+    /// How dates are shown in tables. By default, dates from the server are shown without the
+    /// time and times recorded by the CLI in local time
+    #[arg(long, global = true, value_enum, env = "TRIEVE_DATE_FORMAT")]
+    pub date_format: Option<DateFormat>,
+    /// Print without colors and other styling. Setting `NO_COLOR` does the same
+    #[arg(long, global = true)]
+    pub no_color: bool,
+    /// Print long lists directly instead of through `$PAGER`
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+    /// Export upload metrics (batches sent, failures, latency) to this OpenTelemetry collector
+    /// over OTLP/HTTP, e.g. `http://localhost:4318`
+    #[arg(long, global = true, env = "OTEL_EXPORTER_OTLP_ENDPOINT")]
+    pub otlp_endpoint: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Configures the Trieve CLI with your API key
+    Login(Login),
+    /// Commands for interacting with datasets in the Trieve service
+    #[command(subcommand)]
+    Dataset(DatasetCommands),
+    /// Name datasets, so the name can be passed wherever a dataset ID is
+    #[command(subcommand)]
+    Alias(AliasCommands),
+    #[command(subcommand, about = "Commands for managing API Keys")]
+    ApiKey(ApiKeyCommands),
+    /// Command to manage profiles
+    #[command(subcommand)]
+    Profile(Profile),
+    /// View and change the settings of the current profile
+    #[command(subcommand)]
+    Config(ConfigCommands),
+    /// Command to interact with organizations
+    #[command(subcommand)]
+    Organization(Organization),
+    /// Search the chunks in a dataset
+    Search(Search),
+    /// Ask a question and get an answer generated from the chunks in a dataset
+    Ask(Ask),
+    /// Measure search relevance against queries with known expected results
+    Eval(Eval),
+    /// Commands for the topics (chat conversations) of a dataset
+    #[command(subcommand)]
+    Topic(TopicCommands),
+    /// Compare the answers of RAG configurations to a set of prompts
+    #[command(subcommand)]
+    Rag(RagCommands),
+    /// Measure ingestion and search latency and throughput of a Trieve server
+    #[command(subcommand)]
+    Bench(BenchCommands),
+    /// Commands for managing chunks in a dataset
+    #[command(subcommand)]
+    Chunk(ChunkCommands),
+    /// Extract content from local files and other sources and upload it in chunks
+    #[command(subcommand)]
+    Ingest(IngestCommands),
+    /// Commands for chunk groups in a dataset
+    #[command(subcommand)]
+    Group(GroupCommands),
+    /// Commands for managing the files uploaded to a dataset
+    #[command(subcommand)]
+    File(FileCommands),
+    /// Create, update and delete datasets and API keys to match a YAML manifest
+    Apply(Apply),
+    /// Show what `trieve apply` would change, without changing anything
+    Diff(Diff),
+    /// Start an interactive shell that keeps the profile and dataset between commands
+    Shell,
+    /// Debug webhooks and events sent by Trieve
+    #[command(subcommand)]
+    Webhook(WebhookCommands),
+    /// Commands for inspecting background jobs started with `--detach` and running pipelines
+    #[command(subcommand)]
+    Jobs(JobsCommands),
+    /// Review the changes made with the CLI on this machine
+    #[command(subcommand)]
+    Audit(AuditCommands),
+    /// Open a page of the Trieve dashboard or search UI in the browser
+    #[command(subcommand)]
+    Open(OpenCommands),
+    /// Update the Trieve CLI to the latest release
+    Update(Update),
+    /// Check the CLI installation and the connection to the Trieve server
+    Doctor,
+}
+
+#[derive(Subcommand)]
+pub enum Profile {
+    /// Switch to a different profile
+    Switch(SwitchProfile),
+    /// Delete a profile
+    Delete(DeleteProfile),
+    /// List all profiles
+    List(ListProfile),
+    /// Print the profile, organization and server the command runs with
+    Current(CurrentProfile),
+    /// Require typing the profile name to confirm destructive commands run with a profile
+    Protect(ProtectProfile),
+    /// Stop requiring confirmation of destructive commands run with a profile
+    Unprotect(UnprotectProfile),
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Print the value of a setting
+    Get(GetConfig),
+    /// Change a setting
+    Set(SetConfig),
+    /// Reset a setting to its default
+    Unset(UnsetConfig),
+    /// List all settings and their values
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum Organization {
+    /// Switch to a different organization
+    Switch(SwitchOrganization),
+    /// Create an organization
+    Create(CreateOrganization),
+    /// Rename an organization or change its settings
+    Update(UpdateOrganization),
+    /// Delete an organization
+    Delete(DeleteOrganization),
+    /// Export all datasets of the current organization to a local directory
+    Export(ExportOrganization),
+    /// Manage names for organizations in the current profile
+    #[command(subcommand)]
+    Alias(OrganizationAliasCommands),
+    /// Manage the invitations to join the current organization
+    #[command(subcommand)]
+    Invitations(InvitationCommands),
+    /// Manage the members of the current organization
+    #[command(subcommand)]
+    Users(UserCommands),
+}
+
+#[derive(Subcommand)]
+pub enum UserCommands {
+    /// Change the role of a member of the current organization
+    SetRole(SetUserRole),
+}
+
+#[derive(Args)]
+pub struct SetUserRole {
+    /// The email or ID of the member (prompted for from the members when not given)
+    pub user: Option<String>,
+    /// The new role of the member
+    #[arg(long, value_enum)]
+    pub role: UserRole,
+}
+
+#[derive(Subcommand)]
+pub enum InvitationCommands {
+    /// List the invitations of the current organization
+    List(ListInvitations),
+    /// Invite people to the current organization by email
+    Create(CreateInvitation),
+    /// Revoke invitations, so they can no longer be accepted
+    Revoke(RevokeInvitation),
+    /// Revoke invitations and send them again with the same email and role
+    Resend(ResendInvitation),
+}
+
+#[derive(Args)]
+pub struct ListInvitations {
+    /// Only list the invitations that have not been accepted yet
+    #[arg(long)]
+    pub pending: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum UserRole {
+    /// Can use the datasets of the organization
+    User,
+    /// Can also manage datasets, API keys and members
+    Admin,
+    /// Can also manage billing and delete the organization
+    Owner,
+}
+
+#[derive(Args)]
+pub struct InvitationLinks {
+    /// The URL of the Trieve dashboard the invitation email links to
+    #[arg(long, default_value = "https://dashboard.trieve.ai")]
+    pub app_url: String,
+    /// Where the invited user is sent after accepting (defaults to the dashboard)
+    #[arg(long)]
+    pub redirect_uri: Option<String>,
+}
+
+#[derive(Args)]
+pub struct CreateInvitation {
+    /// The email addresses to invite
+    #[arg(required = true)]
+    pub emails: Vec<String>,
+    /// The role the invited users get in the organization
+    #[arg(long, value_enum, default_value = "user")]
+    pub role: UserRole,
+    #[command(flatten)]
+    pub links: InvitationLinks,
+}
+
+#[derive(Args)]
+pub struct RevokeInvitation {
+    /// The IDs of the invitations to revoke
+    #[arg(required = true)]
+    pub invitation_ids: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct ResendInvitation {
+    /// The IDs of the invitations to send again
+    #[arg(required = true)]
+    pub invitation_ids: Vec<String>,
+    #[command(flatten)]
+    pub links: InvitationLinks,
+}
+
+#[derive(Subcommand)]
+pub enum OrganizationAliasCommands {
+    /// Make an alias stand for an organization in the current profile
+    Set(SetOrganizationAlias),
+    /// List the organization aliases of the current profile
+    List(ListOrganizationAliases),
+    /// Remove an organization alias from the current profile
+    Rm(RemoveOrganizationAlias),
+}
+
+#[derive(Args)]
+pub struct SetOrganizationAlias {
+    /// The alias, e.g. `acme`
+    pub alias: String,
+    /// The ID or name of the organization the alias stands for
+    pub organization: String,
+}
+
+#[derive(Args)]
+pub struct ListOrganizationAliases;
+
+#[derive(Args)]
+pub struct RemoveOrganizationAlias {
+    /// The alias to remove
+    pub alias: String,
+}
+
+#[derive(Subcommand)]
+pub enum ChunkCommands {
+    /// Upload chunks from a CSV, JSON or JSONL file
+    Upload(UploadChunks),
+    /// Create chunks from a file, or from JSONL piped to stdin with `-`
+    Create(CreateChunks),
+    /// Split an HTML page into chunks by its headings and upload them
+    Split(SplitChunks),
+    /// Add and remove tags of every chunk matching a filter
+    Retag(RetagChunks),
+    /// Set and remove metadata fields of every chunk matching a filter
+    SetMetadata(SetChunkMetadata),
+    /// Boost a chunk in full-text and semantic searches for a phrase, or many chunks from a CSV
+    /// file
+    Boost(BoostChunks),
+    /// Check a CSV, JSON or JSONL file of chunks for unknown fields, wrong types and missing
+    /// chunk_html without uploading it
+    Validate(ValidateChunks),
+    /// Print a chunk with the group and file it came from
+    Get(GetChunk),
+    /// Generate realistic synthetic chunks with tags and metadata and upload them, for load tests
+    /// and demos
+    Generate(GenerateChunks),
+}
+
+#[derive(Subcommand)]
+pub enum IngestCommands {
+    /// Extract the text of PDF files locally and upload it in chunks, page by page
+    Pdf(IngestPdf),
+    /// Chunk the source files and READMEs of a git repository and upload them
+    Git(IngestGit),
+    /// Upload the pages of a Notion workspace export (Markdown or HTML), one group per page
+    NotionExport(IngestExport),
+    /// Upload the pages of a Confluence space exported as HTML, one group per page
+    ConfluenceExport(IngestExport),
+    /// Upload the entries of an RSS or Atom feed, updating entries ingested before
+    Rss(IngestRss),
+    /// Upload every endpoint and schema of an OpenAPI or Swagger spec as a chunk, grouped by tag
+    #[command(name = "openapi")]
+    OpenApi(IngestOpenApi),
+}
+
+#[derive(Subcommand)]
+pub enum GroupCommands {
+    /// Search the chunks of a single group
+    Search(GroupSearch),
+}
+
+#[derive(Subcommand)]
+pub enum FileCommands {
+    /// Upload files, or the files in directories, to be split into chunks by the server
+    Upload(UploadFiles),
+    /// List the files of a dataset
+    List(ListFiles),
+    /// List the chunks created from a file
+    Chunks(FileChunks),
+    /// Download the original document of a file
+    Get(GetFile),
+    /// Delete a file, optionally with its chunks
+    Delete(DeleteFile),
+}
+
+#[derive(Subcommand)]
+pub enum WebhookCommands {
+    /// Print the webhook payloads sent to a local port, or poll the events of a dataset
+    Listen(WebhookListen),
+}
+
+#[derive(Args)]
+pub struct UploadFiles {
+    /// The files and directories to upload
+    #[arg(required = true)]
+    pub paths: Vec<PathBuf>,
+    /// The ID of the dataset to upload to (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// Upload the files in the subdirectories of the directories as well
+    #[arg(short, long)]
+    pub recursive: bool,
+    /// A tag to add to the chunks of every file (can be repeated)
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+    /// Print which files would be uploaded and skipped without uploading them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct ListFiles {
+    /// The ID of the dataset (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+}
+
+#[derive(Args)]
+pub struct FileChunks {
+    /// The ID of the file
+    pub file_id: String,
+    /// The ID of the dataset of the file (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+}
+
+#[derive(Args)]
+pub struct GetFile {
+    /// The ID of the file to download
+    pub file_id: String,
+    /// The ID of the dataset of the file (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// The directory to download to, or the path of the downloaded file
+    #[arg(short, long, default_value = ".")]
+    pub out: PathBuf,
+}
+
+#[derive(Args)]
+pub struct DeleteFile {
+    /// The ID of the file to delete
+    pub file_id: String,
+    /// The ID of the dataset of the file (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// Also delete the chunks created from the file
+    #[arg(long)]
+    pub delete_chunks: bool,
+}
+
+#[derive(Args)]
+pub struct WebhookListen {
+    /// Only print payloads of this dataset; the dataset whose events are polled with `--poll`
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// The port to listen on
+    #[arg(long, default_value_t = 9000)]
+    pub port: u16,
+    /// The address to listen on, e.g. 0.0.0.0 to accept webhooks from other machines
+    #[arg(long, default_value = "127.0.0.1")]
+    pub host: String,
+    /// Poll the events API of the dataset instead of listening for webhooks
+    #[arg(long)]
+    pub poll: bool,
+    /// Seconds between polls with `--poll`
+    #[arg(long, default_value_t = 5, requires = "poll")]
+    pub interval: u64,
+}
+
+#[derive(Subcommand)]
+pub enum AliasCommands {
+    /// Make an alias stand for a dataset in the current profile
+    Set(SetAlias),
+    /// List the aliases of the current profile
+    List(ListAliases),
+    /// Remove an alias from the current profile
+    Rm(RemoveAlias),
+}
+
+#[derive(Args)]
+pub struct SetAlias {
+    /// The alias, e.g. `prod-docs`
+    pub alias: String,
+    /// The ID of the dataset the alias stands for
+    pub dataset_id: String,
+}
+
+#[derive(Args)]
+pub struct ListAliases;
+
+#[derive(Args)]
+pub struct RemoveAlias {
+    /// The alias to remove
+    pub alias: String,
+}
+
+#[derive(Subcommand)]
+pub enum AuditCommands {
+    /// List the recorded changes, oldest first
+    List(ListAudit),
+}
+
+#[derive(Subcommand)]
+pub enum OpenCommands {
+    /// Open the dashboard page of a dataset
+    Dataset(OpenDataset),
+    /// Open the dashboard page of the current organization
+    #[command(alias = "organization")]
+    Org(OpenOrganization),
+    /// Open the search UI for a dataset
+    Search(OpenDataset),
+}
+
+#[derive(Args)]
+pub struct OpenDataset {
+    /// The ID of the dataset (defaults to the default dataset of the profile)
+    pub dataset_id: Option<String>,
+    /// Print the URL instead of opening it
+    #[arg(long)]
+    pub print: bool,
+}
+
+#[derive(Args)]
+pub struct OpenOrganization {
+    /// Print the URL instead of opening it
+    #[arg(long)]
+    pub print: bool,
+}
+
+#[derive(Subcommand)]
+pub enum JobsCommands {
+    /// List background jobs
+    List(ListJobs),
+    /// Print the log of a background job
+    Logs(JobLogs),
+    /// Run the steps of a YAML pipeline one after another, with retries and a summary
+    Pipeline(RunPipeline),
+    /// Run a detached job (used internally by `--detach`)
+    #[command(hide = true)]
+    Run(RunJob),
+}
+
+#[derive(Subcommand)]
+pub enum ApiKeyCommands {
+    /// Generate a new API Key
+    Generate(ApiKeyData),
+}
+
+#[derive(Subcommand)]
+pub enum DatasetCommands {
+    /// Create a dataset in the Trieve service
+    Create(CreateDataset),
+    /// List all datasets in the Trieve service
+    List(ListDatasets),
+    /// Rename a dataset or change its tracking ID
+    Update(UpdateDataset),
+    /// Delete a dataset in the Trieve service
+    Delete(DeleteDataset),
+    /// Re-create a deleted dataset from the backup made when it was deleted
+    Restore(RestoreDataset),
+    /// Export the chunks of a dataset to a JSON lines file
+    Export(ExportDataset),
+    /// Copy the chunks of a dataset into a new dataset that embeds them with another model
+    Reembed(ReembedDataset),
+    /// Set the default dataset of the profile, used when a command is run without a dataset
+    Use(UseDataset),
+    /// Add seed data to a dataset in the Trieve service
+    Example(AddSeedData),
+    /// Install several example datasets at once, each into its own new dataset
+    Demo(InstallDemo),
+    /// Show the rate limits and quotas that apply to a dataset
+    Limits(DatasetLimits),
+    /// Show chunk, group and file counts and a breakdown of a sample of the chunks
+    Stats(DatasetStats),
+    /// Show the chunk count of a dataset, or keep watching it while chunks are indexed
+    Usage(DatasetUsage),
+    /// Count the uploaded files of a dataset by processing status, with the latest upload errors
+    FilesStatus(FilesStatus),
+    /// Print example queries generated by an LLM from the content of a dataset
+    SuggestQueries(SuggestQueries),
+    /// Save and manage dataset configuration templates
+    #[command(subcommand)]
+    Template(TemplateCommands),
+    /// Manage the synonym groups of a dataset, terms that match each other in searches
+    #[command(subcommand)]
+    Synonyms(WordListCommands),
+    /// Manage the stop words of a dataset, words left out of full-text searches
+    #[command(subcommand)]
+    Stopwords(WordListCommands),
+}
+
+#[derive(Subcommand)]
+pub enum TemplateCommands {
+    /// Save the configuration of a dataset as a template
+    Save(SaveTemplate),
+    /// List the saved templates
+    List,
+    /// Delete a saved template
+    Delete(DeleteTemplate),
+}
+
+#[derive(Args)]
+pub struct SaveTemplate {
+    /// The ID of the dataset whose configuration to save
+    pub dataset_id: Option<String>,
+    /// The name of the template
+    #[arg(short, long)]
+    pub name: Option<String>,
+    /// Overwrite an existing template with the same name
+    #[arg(long)]
+    pub force: bool,
+}
+
+#[derive(Subcommand)]
+pub enum WordListCommands {
+    /// Replace the list with the words of a file, checking the whole file first
+    Import(ImportWords),
+    /// Print the list
+    List(ListWords),
+    /// Remove every word from the list
+    Clear(ClearWords),
+}
+
+#[derive(Args)]
+pub struct ImportWords {
+    /// A CSV file: one synonym group per line, or stop words separated by commas or newlines.
+    /// Blank lines and lines starting with `#` are skipped
+    pub file: PathBuf,
+    /// The ID of the dataset (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// Add the words to the list instead of replacing it
+    #[arg(long)]
+    pub append: bool,
+    /// Check the file and print the resulting list without changing the dataset
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct ListWords {
+    /// The ID of the dataset (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ClearWords {
+    /// The ID of the dataset (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DeleteTemplate {
+    /// The name of the template to delete
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct Login {
+    /// API Key from the Trieve dashboard (https://dashboard.trieve.ai)
+    #[arg(short, long, env = "TRIEVE_API_KEY")]
+    pub api_key: Option<String>,
+    /// The URL of the Trieve server if you are using a self-hosted version of Trieve
+    #[arg(long, required = false)]
+    pub api_url: Option<String>,
+    /// Name the profile you are configuring
+    #[arg(long, required = false)]
+    pub profile_name: Option<String>,
+    /// The local port the browser login redirects to. By default port 65535 is used, falling back
+    /// to another free port when it is taken
+    #[arg(long, env = "TRIEVE_CALLBACK_PORT")]
+    pub callback_port: Option<u16>,
+    /// Print the login URL and paste the API key instead of opening a browser. This is the default
+    /// in SSH sessions without a display
+    #[arg(long, conflicts_with = "callback_port")]
+    pub no_browser: bool,
+    /// Log in with the session cookie or JWT of a browser session, for servers behind SSO whose
+    /// login cannot redirect to this machine. It is exchanged for an API key. Without a value, you
+    /// are asked to paste it
+    #[arg(
+        long,
+        env = "TRIEVE_SESSION_TOKEN",
+        num_args = 0..=1,
+        default_missing_value = "",
+        hide_env_values = true,
+        conflicts_with_all = ["api_key", "callback_port", "no_browser"]
+    )]
+    pub session_token: Option<String>,
+}
+
+#[derive(Args)]
+pub struct CreateDataset {
+    /// The name of the dataset
+    #[arg(short, long)]
+    pub name: Option<String>,
+    /// Create the dataset with the configuration of a saved template
+    #[arg(short, long)]
+    pub template: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ListDatasets {
+    /// The maximum number of datasets to list
+    #[arg(long)]
+    pub limit: Option<u32>,
+    /// The number of datasets to skip
+    #[arg(long)]
+    pub offset: Option<u32>,
+    /// Sort by name, by creation date (newest first) or by chunk count (largest first)
+    #[arg(long, value_enum)]
+    pub sort_by: Option<DatasetSortBy>,
+    /// Only list datasets whose name contains this text (case-insensitive)
+    #[arg(long, value_name = "SUBSTRING")]
+    pub filter_name: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum DatasetSortBy {
+    Name,
+    Created,
+    Chunks,
+}
+
+#[derive(Args)]
+pub struct UpdateDataset {
+    /// The ID of the dataset to update
+    pub dataset_id: Option<String>,
+    /// The new name of the dataset
+    #[arg(short, long)]
+    pub name: Option<String>,
+    /// The new tracking ID of the dataset
+    #[arg(short, long)]
+    pub tracking_id: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DeleteDataset {
+    /// The ID of the dataset to delete
+    pub dataset_id: Option<String>,
+    /// Export the chunks of the dataset to the trash directory first, so it can be restored with
+    /// `trieve dataset restore` (the default when run interactively)
+    #[arg(long, conflicts_with = "no_backup")]
+    pub backup: bool,
+    /// Delete the dataset without backing it up
+    #[arg(long)]
+    pub no_backup: bool,
+}
+
+#[derive(Args)]
+pub struct RestoreDataset {
+    /// The backup directory printed when the dataset was deleted
+    pub archive: PathBuf,
+    /// The name of the restored dataset (defaults to the name of the deleted one)
+    #[arg(long)]
+    pub name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ReembedDataset {
+    /// The ID of the dataset to migrate
+    #[arg(long)]
+    pub from: String,
+    /// The embedding model of the new dataset, e.g. `bge-m3` or `text-embedding-3-small`
+    #[arg(long)]
+    pub embedding_model: String,
+    /// The dimensions of the embeddings, needed for models the CLI does not know
+    #[arg(long)]
+    pub embedding_size: Option<u64>,
+    /// The URL of the server computing the embeddings, needed for models the CLI does not know
+    #[arg(long)]
+    pub embedding_base_url: Option<String>,
+    /// The name of the new dataset (defaults to the name of the old one followed by the model)
+    #[arg(long)]
+    pub name: Option<String>,
+    /// Move the tracking ID of the old dataset to the new one once every chunk is uploaded
+    #[arg(long)]
+    pub swap: bool,
+    /// Discard the progress of a previous run and start a new migration
+    #[arg(long)]
+    pub restart: bool,
+    /// Run in the background and return immediately with a job ID
+    #[arg(long)]
+    pub detach: bool,
+}
+
+#[derive(Args)]
+pub struct UseDataset {
+    /// The ID of the dataset to use by default
+    #[arg(conflicts_with = "clear")]
+    pub dataset_id: Option<String>,
+    /// Remove the default dataset of the profile
+    #[arg(long)]
+    pub clear: bool,
+}
+
+#[derive(Args)]
+pub struct ExportDataset {
+    /// The ID of the dataset to export (defaults to the default dataset of the profile)
+    pub dataset_id: Option<String>,
+    /// The file to export into (defaults to `<DATASET_ID>.jsonl` in the current path)
+    #[arg(short, long)]
+    pub file: Option<PathBuf>,
+    /// Run in the background and return immediately with a job ID
+    #[arg(long)]
+    pub detach: bool,
+}
+
+#[derive(Args)]
+pub struct DatasetLimits {
+    /// The ID of the dataset to show limits for
+    pub dataset_id: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DatasetStats {
+    /// The ID of the dataset to show stats for
+    pub dataset_id: Option<String>,
+    /// The number of chunks to sample for the length and tag breakdown
+    #[arg(long, default_value_t = 1000)]
+    pub sample_size: usize,
+}
+
+#[derive(Args)]
+pub struct FilesStatus {
+    /// The ID of the dataset (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// The number of the most recent upload errors to show
+    #[arg(long, default_value_t = 5)]
+    pub errors: usize,
+    /// Exit with status 1 while files are still queued or when any failed, e.g. to wait for an
+    /// upload to finish in a script
+    #[arg(long)]
+    pub exit_code: bool,
+}
+
+#[derive(Args)]
+pub struct SuggestQueries {
+    /// The ID of the dataset to generate queries for (defaults to the default dataset of the
+    /// profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// The number of queries to print
+    #[arg(long, default_value_t = 10)]
+    pub count: usize,
+    /// The kind of queries: questions, keyword queries or natural language statements
+    #[arg(long = "type", value_enum, default_value = "question")]
+    pub suggestion_type: SuggestionType,
+    /// Generate queries related to this one instead of to the dataset as a whole
+    #[arg(long)]
+    pub query: Option<String>,
+    /// What the queries should be about, e.g. `customers comparing pricing plans`
+    #[arg(long)]
+    pub context: Option<String>,
+    /// Exit with code 6 when no queries were generated
+    #[arg(long)]
+    pub fail_on_empty: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SuggestionType {
+    Question,
+    Keyword,
+    Semantic,
+}
+
+#[derive(Args)]
+pub struct DatasetUsage {
+    /// The ID of the dataset to show usage for
+    pub dataset_id: Option<String>,
+    /// Keep polling the usage and update it in place until interrupted
+    #[arg(long)]
+    pub watch: bool,
+    /// The number of seconds between polls
+    #[arg(long, default_value_t = 5, requires = "watch")]
+    pub interval: u64,
+    /// Exit with code 6 when the dataset has no chunks
+    #[arg(long, conflicts_with = "watch")]
+    pub fail_on_empty: bool,
+}
+
+#[derive(Args)]
+pub struct AddSeedData {
+    /// The ID of the dataset to add seed data to
+    pub dataset_id: Option<String>,
+    /// The example dataset to add
+    #[arg(short, long, value_enum)]
+    pub example: Option<SeedExample>,
+    /// URL of a CSV, JSON or JSONL file to load instead of an example dataset
+    #[arg(long, conflicts_with_all = ["example", "file"])]
+    pub url: Option<String>,
+    /// Path to a local CSV, JSON or JSONL file to load instead of an example dataset
+    #[arg(long, conflicts_with = "example")]
+    pub file: Option<PathBuf>,
+    /// The format of the file passed with `--url` or `--file` (inferred from the extension by default)
+    #[arg(long, value_enum)]
+    pub format: Option<InputFormat>,
+    /// Map a chunk field to a CSV column (index or header) or JSON key, optionally followed by
+    /// transforms, e.g. `--map chunk_html=2 --map tag_set=tags:split(|) --map metadata.price=price`
+    #[arg(long, value_name = "FIELD=COLUMN[:TRANSFORM]")]
+    pub map: Vec<String>,
+    /// Path to a YAML file mapping chunk fields to columns, using the same syntax as `--map`
+    #[arg(long)]
+    pub mapping_file: Option<PathBuf>,
+    /// Continue an interrupted load from its last checkpoint instead of starting over
+    #[arg(long)]
+    pub resume: bool,
+    /// Run in the background and return immediately with a job ID
+    #[arg(long)]
+    pub detach: bool,
+}
+
+#[derive(Args)]
+pub struct InstallDemo {
+    /// The example datasets to install (can be repeated, prompts when omitted)
+    #[arg(short, long, value_enum)]
+    pub example: Vec<SeedExample>,
+    /// Install every example dataset
+    #[arg(long, conflicts_with = "example")]
+    pub all: bool,
+    /// Prefix for the names of the created datasets
+    #[arg(long, default_value = "demo")]
+    pub prefix: String,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum InputFormat {
+    Csv,
+    Json,
+    /// Newline-delimited JSON, streamed line by line
+    Jsonl,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SeedExample {
+    YcCompanies,
+    PhilosophizeThis,
+    TrieveDocs,
+    MintlifyDocs,
+}
+
+#[derive(Args)]
+pub struct ApiKeyData {
+    /// The name of the API Key
+    #[arg(short, long)]
+    pub name: Option<String>,
+    /// The role of the API Key (`read-write` or `read`)
+    #[arg(short, long)]
+    pub role: Option<String>,
+    /// Restrict the API Key to this dataset (can be repeated)
+    #[arg(short, long)]
+    pub dataset_id: Vec<String>,
+    /// Restrict the API Key to this API route scope (can be repeated)
+    #[arg(short, long)]
+    pub scope: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct SwitchProfile {
+    /// The name of the profile to switch to
+    pub profile_name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DeleteProfile {
+    /// The name of the profile to delete
+    pub profile_name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ListProfile;
+
+#[derive(Args)]
+pub struct ProtectProfile {
+    /// The name of the profile to protect
+    pub profile_name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct UnprotectProfile {
+    /// The name of the profile to unprotect
+    pub profile_name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct CurrentProfile {
+    /// How to print the profile: `starship` prints one short line for a shell prompt segment
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: ProfileFormat,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ProfileFormat {
+    /// The profile, organization and server on separate lines
+    Text,
+    /// `<profile> (<organization>)`, with the alias of the organization or the start of its ID
+    Starship,
+    /// A JSON object with the profile, organization and server
+    Json,
+}
+
+#[derive(Args)]
+pub struct GetConfig {
+    /// The name of the setting, as shown by `trieve config list`
+    pub key: String,
+}
+
+#[derive(Args)]
+pub struct SetConfig {
+    /// The name of the setting, as shown by `trieve config list`
+    pub key: String,
+    /// The new value
+    pub value: String,
+}
+
+#[derive(Args)]
+pub struct UnsetConfig {
+    /// The name of the setting, as shown by `trieve config list`
+    pub key: String,
+}
+
+#[derive(Args)]
+pub struct Ask {
+    /// The question to ask
+    pub question: Option<String>,
+    /// The ID of the dataset to answer from (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// A local file with extra context to send along with the retrieved chunks (can be repeated)
+    #[arg(long, value_name = "FILE")]
+    pub context_file: Vec<PathBuf>,
+    /// The search type used to retrieve chunks
+    #[arg(long, value_enum, default_value = "hybrid")]
+    pub search_type: SearchType,
+    /// The number of chunks to retrieve for the answer
+    #[arg(short, long, default_value_t = 8)]
+    pub n_retrievals: u32,
+}
+
+#[derive(Args)]
+pub struct Apply {
+    /// The YAML manifest describing the organization, its datasets and its API keys
+    #[arg(short, long, default_value = "trieve.yaml")]
+    pub file: PathBuf,
+    /// Apply the plan without asking for confirmation
+    #[arg(short, long)]
+    pub yes: bool,
+    /// Delete the datasets pruned by the manifest without backing them up
+    #[arg(long)]
+    pub no_backup: bool,
+}
+
+#[derive(Args)]
+pub struct Diff {
+    /// The YAML manifest describing the organization, its datasets and its API keys
+    #[arg(short, long, default_value = "trieve.yaml")]
+    pub file: PathBuf,
+    /// Exit with status 1 when there are differences, e.g. to detect drift in CI
+    #[arg(long)]
+    pub exit_code: bool,
+}
+
+#[derive(Args)]
+pub struct Update {
+    /// Only check whether a newer version is available, without installing it
+    #[arg(long)]
+    pub check_only: bool,
+}
+
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct Search {
+    #[command(subcommand)]
+    pub command: Option<SearchCommands>,
+    #[command(flatten)]
+    pub query: SearchQuery,
+}
+
+#[derive(Subcommand)]
+pub enum SearchCommands {
+    /// Run the same queries against two datasets and compare the top results side by side
+    Compare(CompareSearch),
+    /// Save a search with its options under a name
+    Save(SaveSearch),
+    /// Run a saved search
+    Run(RunSearch),
+    /// List the saved searches
+    List,
+    /// Delete a saved search
+    Delete(DeleteSearch),
+    /// Run every query of a file and write the top results to a CSV, JSON or JSONL file
+    Batch(BatchSearch),
+}
+
+#[derive(Args)]
+pub struct BatchSearch {
+    /// A text file with one query per line
+    #[arg(long, value_name = "FILE")]
+    pub query_file: PathBuf,
+    /// The file to write the results to, one row per query and result
+    #[arg(short, long, value_name = "FILE")]
+    pub out: PathBuf,
+    /// The format of the results file (inferred from its extension when not set)
+    #[arg(long, value_enum)]
+    pub format: Option<InputFormat>,
+    /// The ID of the dataset to search (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// The type of search to run
+    #[arg(long, value_enum, default_value = "hybrid")]
+    pub search_type: SearchType,
+    /// The number of top results to keep per query
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub page_size: u32,
+    /// Only return chunks matching this filter (can be repeated, all have to match)
+    #[arg(long = "filter")]
+    pub filters: Vec<String>,
+    #[command(flatten)]
+    pub sort: SortArgs,
+}
+
+#[derive(Args)]
+pub struct SaveSearch {
+    /// The name to save the search as
+    #[arg(long)]
+    pub name: String,
+    /// The search query
+    #[arg(long)]
+    pub query: String,
+    /// Overwrite a saved search with the same name
+    #[arg(long)]
+    pub force: bool,
+    #[command(flatten)]
+    pub options: SearchOptions,
+}
+
+#[derive(Args)]
+pub struct RunSearch {
+    /// The name of the saved search
+    pub name: String,
+    /// Search this dataset instead of the one the search was saved with
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DeleteSearch {
+    /// The name of the saved search
+    pub name: String,
+}
+
+#[derive(Args)]
+pub struct CompareSearch {
+    /// The ID of the first dataset
+    #[arg(long)]
+    pub dataset_a: String,
+    /// The ID of the second dataset
+    #[arg(long)]
+    pub dataset_b: String,
+    /// A text file with one query per line
+    #[arg(long, value_name = "FILE")]
+    pub query_file: PathBuf,
+    /// The type of search to run
+    #[arg(long, value_enum, default_value = "hybrid")]
+    pub search_type: SearchType,
+    /// The number of top results to compare per query
+    #[arg(short = 'n', long, default_value_t = 5)]
+    pub page_size: u32,
+}
+
+#[derive(Args)]
+pub struct SearchQuery {
+    /// The search query
+    pub query: Option<String>,
+    #[command(flatten)]
+    pub options: SearchOptions,
+}
+
+#[derive(Args, Serialize, Deserialize)]
+pub struct SearchOptions {
+    /// The ID of the dataset to search (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// Search every dataset of the organization and merge the results by score
+    #[arg(long, conflicts_with_all = ["dataset_id", "group_by"])]
+    #[serde(default)]
+    pub all_datasets: bool,
+    /// The type of search to run
+    #[arg(long, value_enum, default_value = "hybrid")]
+    pub search_type: SearchType,
+    /// The number of results to show
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub page_size: u32,
+    /// The page of results to show
+    #[arg(long, default_value_t = 1)]
+    #[serde(default = "first_page")]
+    pub page: u32,
+    /// Exit with code 6 when there are no results, e.g. to alert from a monitoring script
+    #[arg(long)]
+    #[serde(default)]
+    pub fail_on_empty: bool,
+    /// Only return chunks matching this filter: FIELD=VALUE[,VALUE...], FIELD!=VALUE[,VALUE...]
+    /// or a filter as JSON (can be repeated, all have to match)
+    #[arg(long = "filter")]
+    pub filters: Vec<String>,
+    /// Print the full chunk of every result as JSON instead of a snippet
+    #[arg(long)]
+    pub full: bool,
+    /// The maximum number of characters shown per result
+    #[arg(long, default_value_t = 300)]
+    pub snippet_length: usize,
+    /// Search over chunk groups and show the top groups with their best matching chunks
+    #[arg(long)]
+    pub group_by: bool,
+    /// The number of chunks shown per group with `--group-by`
+    #[arg(long, default_value_t = 3, requires = "group_by")]
+    pub group_size: u32,
+    #[command(flatten)]
+    pub sort: SortArgs,
+}
+
+fn first_page() -> u32 {
+    1
+}
+
+#[derive(Args)]
+pub struct GroupSearch {
+    /// The search query
+    pub query: Option<String>,
+    /// The ID of the group to search
+    #[arg(short, long)]
+    pub group_id: String,
+    /// The ID of the dataset of the group (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// The type of search to run
+    #[arg(long, value_enum, default_value = "hybrid")]
+    pub search_type: SearchType,
+    /// The number of results to show
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub page_size: u32,
+    /// Print the full chunk of every result as JSON instead of a snippet
+    #[arg(long)]
+    pub full: bool,
+    /// The maximum number of characters shown per result
+    #[arg(long, default_value_t = 300)]
+    pub snippet_length: usize,
+    /// Exit with code 6 when there are no results
+    #[arg(long)]
+    pub fail_on_empty: bool,
+    #[command(flatten)]
+    pub sort: SortArgs,
+}
+
+#[derive(Args, Serialize, Deserialize)]
+pub struct SortArgs {
+    /// Re-rank the results with this model
+    #[arg(long, value_enum)]
+    pub rerank: Option<RerankType>,
+    /// Sort the results by a field instead of relevance, e.g. `metadata.price` or
+    /// `time_stamp:asc` (descending unless `:asc` is appended)
+    #[arg(long, value_name = "FIELD[:asc|desc]", conflicts_with = "rerank")]
+    pub sort_by: Option<String>,
+    /// How strongly newer chunks are favored, from 0 (not at all) to 1
+    #[arg(long)]
+    pub recency_bias: Option<f32>,
+    /// Weigh results with a tag by this factor, as TAG=WEIGHT (can be repeated)
+    #[arg(long = "tag-weight", value_name = "TAG=WEIGHT")]
+    pub tag_weights: Vec<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RerankType {
+    /// The cross encoder model of the dataset
+    CrossEncoder,
+    /// BM25 scores
+    Bm25,
+    /// Full-text (SPLADE) scores
+    Fulltext,
+}
+
+#[derive(Args)]
+pub struct Eval {
+    /// A YAML list of `query` and `expected` tracking IDs, or a CSV file with those columns
+    #[arg(long, value_name = "FILE")]
+    pub queries: PathBuf,
+    /// The ID of a dataset to evaluate (can be repeated to compare datasets)
+    #[arg(short, long, required = true)]
+    pub dataset_id: Vec<String>,
+    /// The search type to evaluate (can be repeated to compare search types)
+    #[arg(long, value_enum, default_value = "hybrid")]
+    pub search_type: Vec<SearchType>,
+    /// The number of results to consider per query
+    #[arg(short, default_value_t = 10)]
+    pub k: u32,
+    /// Only list the queries that missed expected results
+    #[arg(long)]
+    pub failures_only: bool,
+}
+
+#[derive(Subcommand)]
+pub enum TopicCommands {
+    /// Write every topic of a dataset with its messages to a JSONL file
+    Export(ExportTopics),
+}
+
+#[derive(Args)]
+pub struct ExportTopics {
+    /// The ID of the dataset of the topics (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// The JSONL file to write, one topic per line, or `-` for stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub out: PathBuf,
+    /// Only export the topics of this owner (can be repeated). By default the topics are found in
+    /// the RAG analytics of the dataset
+    #[arg(long)]
+    pub owner_id: Vec<String>,
+    /// Exit with code 6 when no topics were found
+    #[arg(long)]
+    pub fail_on_empty: bool,
+}
+
+#[derive(Subcommand)]
+pub enum RagCommands {
+    /// Answer every prompt in a file with every RAG configuration in it and compare the answers
+    Test(RagTest),
+}
+
+#[derive(Args)]
+pub struct RagTest {
+    /// A YAML file with a list of `prompts` and a list of `configurations` to answer them with
+    #[arg(long, value_name = "FILE")]
+    pub prompt_file: PathBuf,
+    /// The ID of the dataset to answer from (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// Write the report to this file, as JSON when it ends in `.json` and as Markdown otherwise,
+    /// or as Markdown to stdout with `-`
+    #[arg(long, value_name = "FILE")]
+    pub report: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+pub enum BenchCommands {
+    /// Upload generated or replayed chunks and measure the latency of each batch
+    Ingest(BenchIngest),
+    /// Send generated or replayed searches and measure their latency
+    Search(BenchSearch),
+}
+
+#[derive(Args)]
+pub struct BenchIngest {
+    /// The ID of the dataset to upload to. Use a dataset you can throw away afterwards
+    #[arg(short, long)]
+    pub dataset_id: String,
+    /// Replay the chunks in this CSV, JSON or JSONL file instead of generating them
+    #[arg(short, long)]
+    pub file: Option<PathBuf>,
+    /// The number of chunks to upload
+    #[arg(long, default_value_t = 1000)]
+    pub chunks: usize,
+    /// The number of chunks sent per request
+    #[arg(long, default_value_t = commands::upload::CHUNKS_PER_BATCH)]
+    pub batch_size: usize,
+    /// The number of requests in flight at once
+    #[arg(short, long, default_value_t = 4)]
+    pub concurrency: usize,
+    /// Write a JSON report to this file, or to stdout with `-`
+    #[arg(long, value_name = "FILE")]
+    pub report: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct BenchSearch {
+    /// The ID of the dataset to search
+    #[arg(short, long)]
+    pub dataset_id: String,
+    /// Replay the queries in this file (one per line) instead of generating them
+    #[arg(long, value_name = "FILE")]
+    pub query_file: Option<PathBuf>,
+    /// The number of searches to send
+    #[arg(long, default_value_t = 100)]
+    pub requests: usize,
+    /// The number of requests in flight at once
+    #[arg(short, long, default_value_t = 4)]
+    pub concurrency: usize,
+    /// The type of search to run
+    #[arg(long, value_enum, default_value = "hybrid")]
+    pub search_type: SearchType,
+    /// The number of results requested per search
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub page_size: u32,
+    /// Write a JSON report to this file, or to stdout with `-`
+    #[arg(long, value_name = "FILE")]
+    pub report: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable output
+    #[default]
+    Text,
+    /// JSON, including errors printed as `{"error": {...}}`
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum DateFormat {
+    /// The date in UTC, e.g. `2024-05-01`
+    Date,
+    /// The time in UTC as RFC 3339, e.g. `2024-05-01T09:30:00Z`
+    Iso,
+    /// The date and time in the local time zone, e.g. `2024-05-01 11:30:00`
+    Local,
+    /// How long ago, e.g. `3 days ago`
+    Relative,
+}
+
+#[derive(Clone, Copy, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchType {
+    Semantic,
+    Fulltext,
+    Hybrid,
+}
+
+#[derive(Args)]
+pub struct UploadChunks {
+    /// Path to a CSV, JSON or JSONL file of chunks, or `-` to read JSONL from stdin
+    #[arg(short, long)]
+    pub file: PathBuf,
+    /// The ID of the dataset to upload to (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// The format of the file (inferred from the extension by default)
+    #[arg(long, value_enum)]
+    pub format: Option<InputFormat>,
+    /// Map a chunk field to a CSV column (index or header) or JSON key, optionally followed by
+    /// transforms, e.g. `--map chunk_html=2 --map tag_set=tags:split(|) --map metadata.price=price`
+    #[arg(long, value_name = "FIELD=COLUMN[:TRANSFORM]")]
+    pub map: Vec<String>,
+    /// Path to a YAML file mapping chunk fields to columns, using the same syntax as `--map`
+    #[arg(long)]
+    pub mapping_file: Option<PathBuf>,
+    /// Continue from the checkpoint left by a previous, interrupted upload of the same file
+    #[arg(long)]
+    pub resume: bool,
+    /// Run in the background and return immediately with a job ID
+    #[arg(long)]
+    pub detach: bool,
+    /// Skip checking every record of the file before the upload starts
+    #[arg(long)]
+    pub no_validate: bool,
+    /// Write the chunks of failed batches, with their error, to this JSONL file so they can be
+    /// retried with `chunk upload --file`
+    #[arg(long, value_name = "FILE")]
+    pub errors_out: Option<PathBuf>,
+    #[command(flatten)]
+    pub dedupe: DedupeArgs,
+}
+
+#[derive(Args)]
+pub struct CreateChunks {
+    /// Path to a CSV, JSON or JSONL file of chunks, or `-` to read JSONL from stdin
+    #[arg(required_unless_present = "html")]
+    pub source: Option<PathBuf>,
+    /// The ID of the dataset to create the chunks in (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// The format of the input (inferred from the extension by default, JSONL for stdin)
+    #[arg(long, value_enum)]
+    pub format: Option<InputFormat>,
+    /// Map a chunk field to a CSV column (index or header) or JSON key, optionally followed by
+    /// transforms, e.g. `--map chunk_html=2 --map tag_set=tags:split(|) --map metadata.price=price`
+    #[arg(long, value_name = "FIELD=COLUMN[:TRANSFORM]")]
+    pub map: Vec<String>,
+    /// Path to a YAML file mapping chunk fields to columns, using the same syntax as `--map`
+    #[arg(long)]
+    pub mapping_file: Option<PathBuf>,
+    /// Create a single chunk with this HTML instead of reading a file
+    #[arg(
+        long,
+        conflicts_with_all = ["source", "format", "map", "mapping_file", "dedupe", "dedupe_existing"]
+    )]
+    pub html: Option<String>,
+    /// The link of the single chunk
+    #[arg(long, requires = "html")]
+    pub link: Option<String>,
+    /// A tag of the single chunk (can be repeated)
+    #[arg(long = "tag", requires = "html")]
+    pub tags: Vec<String>,
+    /// The tracking ID of the single chunk, replacing the chunk with the same tracking ID
+    #[arg(long, requires = "html")]
+    pub tracking_id: Option<String>,
+    /// The metadata of the single chunk as a JSON object
+    #[arg(long, requires = "html")]
+    pub metadata: Option<String>,
+    /// Skip checking every record of the file before the upload starts
+    #[arg(long, conflicts_with = "html")]
+    pub no_validate: bool,
+    /// Write the chunks of failed batches, with their error, to this JSONL file so they can be
+    /// retried with `chunk upload --file`
+    #[arg(long, value_name = "FILE", conflicts_with = "html")]
+    pub errors_out: Option<PathBuf>,
+    #[command(flatten)]
+    pub dedupe: DedupeArgs,
+}
+
+#[derive(Args)]
+pub struct GetChunk {
+    /// The ID or tracking ID of the chunk
+    pub id: String,
+    /// The ID of the dataset the chunk is in (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// Print the HTML of the chunk as readable text instead of the chunk as JSON
+    #[arg(long)]
+    pub render: bool,
+    /// Open the link of the chunk in the browser
+    #[arg(long)]
+    pub web: bool,
+}
+
+#[derive(Args)]
+pub struct GenerateChunks {
+    /// The ID of the dataset to upload to (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// The number of chunks to generate
+    #[arg(long, default_value_t = 100)]
+    pub count: usize,
+    /// The kind of content: lorem ipsum paragraphs, documentation pages or FAQ entries
+    #[arg(long, value_enum, default_value = "lorem")]
+    pub distribution: Distribution,
+    /// Generate the same chunks as a previous run with this seed. Their tracking IDs are the same
+    /// too, so uploading them again replaces the chunks instead of adding more
+    #[arg(long)]
+    pub seed: Option<u64>,
+    /// Print the chunks as JSONL instead of uploading them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Distribution {
+    Lorem,
+    Markdown,
+    Faq,
+}
+
+#[derive(Args)]
+pub struct ValidateChunks {
+    /// Path to a CSV, JSON or JSONL file of chunks
+    #[arg(short, long)]
+    pub file: PathBuf,
+    /// The format of the file (inferred from the extension by default)
+    #[arg(long, value_enum)]
+    pub format: Option<InputFormat>,
+    /// Map a chunk field to a CSV column (index or header) or JSON key, as with `chunk upload`
+    #[arg(long, value_name = "FIELD=COLUMN[:TRANSFORM]")]
+    pub map: Vec<String>,
+    /// Path to a YAML file mapping chunk fields to columns, using the same syntax as `--map`
+    #[arg(long)]
+    pub mapping_file: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct SplitChunks {
+    /// Path to the HTML page to split
+    #[arg(short, long)]
+    pub file: PathBuf,
+    /// The ID of the dataset to upload to (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    #[command(flatten)]
+    pub chunking: ChunkingArgs,
+    /// The link of the uploaded chunks
+    #[arg(long)]
+    pub link: Option<String>,
+    /// A tag of the uploaded chunks (can be repeated)
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+    /// Print the chunk boundaries instead of uploading the chunks
+    #[arg(long)]
+    pub dry_run: bool,
+    #[command(flatten)]
+    pub dedupe: DedupeArgs,
+}
+
+#[derive(Args)]
+pub struct RetagChunks {
+    /// The ID of the dataset the chunks are in (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// Which chunks to change: FIELD=VALUE[,VALUE...], FIELD!=VALUE[,VALUE...] or a filter as
+    /// JSON, e.g. `tag_set=draft` (can be repeated, all have to match)
+    #[arg(long = "filter", required = true)]
+    pub filters: Vec<String>,
+    /// A tag to add to the matching chunks (can be repeated)
+    #[arg(long = "add-tag")]
+    pub add_tags: Vec<String>,
+    /// A tag to remove from the matching chunks (can be repeated)
+    #[arg(long = "remove-tag")]
+    pub remove_tags: Vec<String>,
+    /// Print how many chunks would change instead of changing them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct SetChunkMetadata {
+    /// The ID of the dataset the chunks are in (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// Which chunks to change: FIELD=VALUE[,VALUE...], FIELD!=VALUE[,VALUE...] or a filter as
+    /// JSON, e.g. `metadata.source=docs` (can be repeated, all have to match)
+    #[arg(long = "filter", required = true)]
+    pub filters: Vec<String>,
+    /// A metadata field to set, as KEY=VALUE. VALUE is read as JSON when it is valid JSON and as
+    /// a string otherwise (can be repeated)
+    #[arg(long = "set")]
+    pub set: Vec<String>,
+    /// A metadata field to remove (can be repeated)
+    #[arg(long = "unset")]
+    pub unset: Vec<String>,
+    /// Print how many chunks would change instead of changing them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct BoostChunks {
+    /// The ID or tracking ID of the chunk to boost
+    #[arg(required_unless_present = "file")]
+    pub id: Option<String>,
+    /// The ID of the dataset the chunks are in (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// Multiply the full-text score of the chunk by this factor for queries with the phrase
+    #[arg(long, value_name = "FACTOR")]
+    pub fulltext: Option<f64>,
+    /// The phrase the full-text boost applies to (defaults to --semantic-phrase)
+    #[arg(long)]
+    pub fulltext_phrase: Option<String>,
+    /// Move the embedding of the chunk towards this phrase, so it ranks higher in semantic
+    /// searches for it
+    #[arg(long)]
+    pub semantic_phrase: Option<String>,
+    /// How far to move the embedding of the chunk towards --semantic-phrase (defaults to 0.5)
+    #[arg(long, value_name = "FACTOR")]
+    pub semantic: Option<f64>,
+    /// A CSV file with one boost per row, with the columns `id`, `fulltext`, `fulltext_phrase`,
+    /// `semantic` and `semantic_phrase`; empty cells are left out
+    #[arg(long, conflicts_with_all = ["id", "fulltext", "fulltext_phrase", "semantic_phrase", "semantic"])]
+    pub file: Option<PathBuf>,
+    /// Print the updates as JSONL instead of sending them
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Args)]
+pub struct ChunkingArgs {
+    /// The estimated maximum number of tokens in a chunk
+    #[arg(long, default_value_t = 400)]
+    pub max_tokens: usize,
+    /// The estimated number of tokens repeated from the end of one chunk at the start of the next
+    #[arg(long, default_value_t = 40)]
+    pub overlap: usize,
+}
+
+#[derive(Args)]
+pub struct DedupeArgs {
+    /// Skip chunks whose text, ignoring markup, case and whitespace, was already uploaded in this run
+    #[arg(long)]
+    pub dedupe: bool,
+    /// Like `--dedupe`, and also skip chunks whose tracking ID already exists in the dataset
+    #[arg(long)]
+    pub dedupe_existing: bool,
+}
+
+#[derive(Args)]
+pub struct IngestPdf {
+    /// Paths to the PDF files
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    /// The ID of the dataset to upload to (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    #[command(flatten)]
+    pub chunking: ChunkingArgs,
+    /// A tag of the uploaded chunks (can be repeated)
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+    /// Print the chunks extracted from every file instead of uploading them
+    #[arg(long)]
+    pub dry_run: bool,
+    #[command(flatten)]
+    pub dedupe: DedupeArgs,
+}
+
+#[derive(Args)]
+pub struct IngestGit {
+    /// The path of a local repository, or the URL of a repository to clone
+    pub repository: String,
+    /// The ID of the dataset to upload to (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// Only ingest files matching this glob (can be repeated), e.g. `--include 'src/**/*.rs'`
+    #[arg(long)]
+    pub include: Vec<String>,
+    /// Skip files matching this glob (can be repeated)
+    #[arg(long)]
+    pub exclude: Vec<String>,
+    /// The branch or tag to clone when the repository is a URL
+    #[arg(long)]
+    pub branch: Option<String>,
+    #[command(flatten)]
+    pub chunking: ChunkingArgs,
+    /// A tag of the uploaded chunks (can be repeated)
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+    /// Print the chunks of every file instead of uploading them
+    #[arg(long)]
+    pub dry_run: bool,
+    #[command(flatten)]
+    pub dedupe: DedupeArgs,
+}
+
+#[derive(Args)]
+pub struct IngestExport {
+    /// Path to the exported zip file
+    pub file: PathBuf,
+    /// The ID of the dataset to upload to (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    #[command(flatten)]
+    pub chunking: ChunkingArgs,
+    /// A tag of the uploaded chunks (can be repeated)
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+    /// Print the chunks of every page instead of uploading them
+    #[arg(long)]
+    pub dry_run: bool,
+    #[command(flatten)]
+    pub dedupe: DedupeArgs,
+}
+
+#[derive(Args)]
+pub struct IngestRss {
+    /// The URL or path of the feed
+    pub feed: String,
+    /// The ID of the dataset to upload to (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// Only ingest entries published after this date (`2024-05-01`), timestamp or duration before
+    /// now (`24h`, `7d`)
+    #[arg(long)]
+    pub since: Option<String>,
+    #[command(flatten)]
+    pub chunking: ChunkingArgs,
+    /// A tag of the uploaded chunks (can be repeated)
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+    /// Print the chunks of every entry instead of uploading them
+    #[arg(long)]
+    pub dry_run: bool,
+    #[command(flatten)]
+    pub dedupe: DedupeArgs,
+}
+
+#[derive(Args)]
+pub struct IngestOpenApi {
+    /// The path or URL of the spec, in YAML or JSON
+    pub spec: String,
+    /// The ID of the dataset to upload to (defaults to the default dataset of the profile)
+    #[arg(short, long)]
+    pub dataset_id: Option<String>,
+    /// A tag of the uploaded chunks (can be repeated)
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+    /// Print the chunk of every endpoint and schema instead of uploading them
+    #[arg(long)]
+    pub dry_run: bool,
+    #[command(flatten)]
+    pub dedupe: DedupeArgs,
+}
+
+#[derive(Args)]
+pub struct ListJobs;
+
+#[derive(Args)]
+pub struct ListAudit {
+    /// Only list changes whose action starts with this, e.g. `dataset delete` or `chunk`
+    #[arg(long)]
+    pub action: Option<String>,
+    /// Only list changes since a date (2024-05-01), a timestamp or a duration like 24h or 7d
+    #[arg(long)]
+    pub since: Option<String>,
+    /// Only list the most recent changes
+    #[arg(short = 'n', long)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Args)]
+pub struct JobLogs {
+    /// The ID of the job
+    pub job_id: String,
+    /// Keep printing new output until the job finishes
+    #[arg(short, long)]
+    pub follow: bool,
+}
+
+#[derive(Args)]
+pub struct RunPipeline {
+    /// The YAML file describing the pipeline
+    pub file: PathBuf,
+    /// Run the remaining steps after a step failed instead of skipping them
+    #[arg(long)]
+    pub keep_going: bool,
+}
+
+#[derive(Args)]
+pub struct RunJob {
+    /// The ID of the job to run
+    pub job_id: String,
+}
+
+#[derive(Args)]
+pub struct SwitchOrganization {
+    /// The ID, name or alias of the organization to switch to. Names may be abbreviated, e.g.
+    /// `acme` for `Acme Production`; when several organizations match, you are asked to pick one
+    pub organization_id: Option<String>,
+    /// An API key scoped to the organization, saved in the profile and used whenever this
+    /// organization is selected
+    #[arg(long)]
+    pub api_key: Option<String>,
+}
+
+#[derive(Args)]
+pub struct CreateOrganization {
+    /// The name of the organization to create
+    pub name: Option<String>,
+}
+
+#[derive(Args)]
+pub struct UpdateOrganization {
+    /// The ID or alias of the organization to update (defaults to the current organization)
+    pub organization_id: Option<String>,
+    /// The new name of the organization
+    #[arg(short, long)]
+    pub name: Option<String>,
+    /// Set an organization setting, such as branding, as KEY=VALUE (can be repeated)
+    #[arg(short, long, value_name = "KEY=VALUE")]
+    pub config: Vec<String>,
+    /// A JSON file with organization settings to set
+    #[arg(long, value_name = "FILE")]
+    pub config_file: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct DeleteOrganization {
+    /// The ID or alias of the organization to delete
+    pub organization_id: Option<String>,
+    /// Export and verify all datasets of the organization into this directory before deleting it
+    #[arg(long, value_name = "DIR")]
+    pub export_first: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct ExportOrganization {
+    /// The directory to export into (defaults to a timestamped directory in the current path)
+    #[arg(short, long)]
+    pub dir: Option<PathBuf>,
+    /// Run in the background and return immediately with a job ID (requires `--dir`)
+    #[arg(long, requires = "dir")]
+    pub detach: bool,
+}
@@ -0,0 +1,158 @@
+use serde_json::Value;
+
+use crate::cli::{RemoveAlias, SetAlias};
+
+use super::{
+    audit,
+    config::profile_required,
+    configure::{load_profiles, require_login, TrieveConfiguration},
+    dataset::DefaultError,
+    output::quiet,
+    rest::Rest,
+    table::{self, Column},
+};
+
+/// Returns the dataset ID an alias of the profile stands for, or `dataset` itself when it is not
+/// an alias.
+pub fn resolve_dataset(settings: &TrieveConfiguration, dataset: &str) -> String {
+    settings
+        .dataset_aliases
+        .get(dataset)
+        .cloned()
+        .unwrap_or_else(|| dataset.to_string())
+}
+
+/// Changes the aliases of a profile and saves it.
+pub fn update_aliases(
+    profile_name: &str,
+    update: impl FnOnce(&mut TrieveConfiguration) -> Result<(), DefaultError>,
+) -> Result<(), DefaultError> {
+    let mut profiles = load_profiles()?;
+    let profile = profiles
+        .iter_mut()
+        .find(|p| p.name == profile_name)
+        .ok_or_else(|| DefaultError {
+            message: format!("Profile '{}' not found.", profile_name),
+        })?;
+
+    update(&mut profile.settings)?;
+    confy::store("trieve", "profiles", profiles).map_err(|e| DefaultError {
+        message: format!("Error saving configuration: {}", e),
+    })
+}
+
+pub async fn set_alias(
+    settings: TrieveConfiguration,
+    profile_name: Option<String>,
+    set: SetAlias,
+) -> Result<(), DefaultError> {
+    let profile_name =
+        profile_required(profile_name).map_err(|message| DefaultError { message })?;
+
+    if set.alias.parse::<uuid::Uuid>().is_ok() {
+        return Err(DefaultError {
+            message: format!("'{}' is a dataset ID and cannot be an alias", set.alias),
+        });
+    }
+
+    require_login(&settings)?;
+
+    // An alias given as the target is saved as the dataset it stands for, so removing it later
+    // does not break the new alias.
+    let dataset_id = resolve_dataset(&settings, &set.dataset_id);
+    // Check that the dataset exists before saving the alias.
+    let dataset: Value = Rest::new(&settings)
+        .dataset(&dataset_id)
+        .get(&format!("/api/dataset/{}", dataset_id))
+        .await?;
+
+    update_aliases(&profile_name, |settings| {
+        settings
+            .dataset_aliases
+            .insert(set.alias.clone(), dataset_id.clone());
+        Ok(())
+    })?;
+    audit::record(
+        &settings,
+        "alias set",
+        Some(&set.alias),
+        serde_json::json!({ "dataset_id": dataset_id, "profile": profile_name }),
+    );
+
+    if !quiet() {
+        println!(
+            "{} now stands for dataset {} ({}) in profile {}.",
+            set.alias,
+            dataset["name"].as_str().unwrap_or_default(),
+            dataset_id,
+            profile_name
+        );
+    }
+
+    Ok(())
+}
+
+/// The columns of `alias list`.
+const ALIAS_COLUMNS: [Column<(String, String)>; 2] = [
+    Column {
+        key: "alias",
+        header: "Alias",
+        value: |(alias, _)| alias.clone(),
+    },
+    Column {
+        key: "dataset_id",
+        header: "Dataset ID",
+        value: |(_, dataset_id)| dataset_id.clone(),
+    },
+];
+
+pub fn list_aliases(settings: &TrieveConfiguration) -> Result<(), DefaultError> {
+    let aliases = settings
+        .dataset_aliases
+        .clone()
+        .into_iter()
+        .collect::<Vec<_>>();
+    if quiet() {
+        return table::print_quiet(&ALIAS_COLUMNS, &aliases, &["alias", "dataset_id"]);
+    }
+
+    if settings.dataset_aliases.is_empty() {
+        println!("No aliases. Add one with `trieve alias set <ALIAS> <DATASET_ID>`.");
+        return Ok(());
+    }
+
+    println!("{}", table::render(&ALIAS_COLUMNS, &aliases)?);
+
+    Ok(())
+}
+
+pub fn remove_alias(
+    settings: &TrieveConfiguration,
+    profile_name: Option<String>,
+    remove: RemoveAlias,
+) -> Result<(), DefaultError> {
+    let profile_name =
+        profile_required(profile_name).map_err(|message| DefaultError { message })?;
+
+    update_aliases(&profile_name, |settings| {
+        settings
+            .dataset_aliases
+            .remove(&remove.alias)
+            .map(|_| ())
+            .ok_or_else(|| DefaultError {
+                message: format!("No alias '{}' in profile {}", remove.alias, profile_name),
+            })
+    })?;
+    audit::record(
+        settings,
+        "alias rm",
+        Some(&remove.alias),
+        serde_json::json!({ "profile": profile_name }),
+    );
+
+    if !quiet() {
+        println!("Removed alias {}.", remove.alias);
+    }
+
+    Ok(())
+}
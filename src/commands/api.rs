@@ -0,0 +1,154 @@
+use std::future::Future;
+
+use trieve_client::{
+    apis::{
+        auth_api::get_me,
+        chunk_group_api::create_chunk_group,
+        dataset_api::{create_dataset, delete_dataset, get_datasets_from_organization},
+        organization_api::{create_organization, delete_organization},
+        user_api::set_user_api_key,
+    },
+    models::{
+        CreateChunkGroupReqPayloadEnum, CreateDatasetRequest, CreateOrganizationReqPayload,
+        Dataset, DatasetAndUsage, Organization, SetUserApiKeyRequest, SlimUser,
+    },
+};
+
+use super::{client::ApiClient, dataset::DefaultError, rest::or_rest};
+
+/// The calls the commands make through the generated `trieve_client`. `ApiClient` makes them
+/// against the server of the profile; tests implement the trait to run the code built on it
+/// without a server. All other endpoints go through `Rest`, which a mock server at the `api_url`
+/// of the profile covers.
+pub trait TrieveApi {
+    /// The logged in user and the organizations they belong to.
+    fn me(&self) -> impl Future<Output = Result<SlimUser, DefaultError>>;
+
+    /// The datasets of the organization, skipping the first `offset` and returning at most
+    /// `limit` of them.
+    fn datasets(
+        &self,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> impl Future<Output = Result<Vec<DatasetAndUsage>, DefaultError>>;
+
+    fn create_dataset(
+        &self,
+        request: CreateDatasetRequest,
+    ) -> impl Future<Output = Result<Dataset, DefaultError>>;
+
+    fn delete_dataset(&self, dataset_id: &str) -> impl Future<Output = Result<(), DefaultError>>;
+
+    fn create_chunk_group(
+        &self,
+        dataset_id: &str,
+        group: CreateChunkGroupReqPayloadEnum,
+    ) -> impl Future<Output = Result<(), DefaultError>>;
+
+    /// Creates an API key for the logged in user and returns it.
+    fn create_api_key(
+        &self,
+        request: SetUserApiKeyRequest,
+    ) -> impl Future<Output = Result<String, DefaultError>>;
+
+    fn create_organization(
+        &self,
+        name: String,
+    ) -> impl Future<Output = Result<Organization, DefaultError>>;
+
+    fn delete_organization(
+        &self,
+        organization_id: &str,
+    ) -> impl Future<Output = Result<(), DefaultError>>;
+}
+
+fn api_error(e: impl std::fmt::Display) -> DefaultError {
+    DefaultError {
+        message: e.to_string(),
+    }
+}
+
+impl TrieveApi for ApiClient {
+    async fn me(&self) -> Result<SlimUser, DefaultError> {
+        let rest = self.rest();
+        or_rest(self.retry(get_me), || rest.get("/api/auth/me")).await
+    }
+
+    async fn datasets(
+        &self,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<DatasetAndUsage>, DefaultError> {
+        let organization_id = self.organization_id();
+        let query = [("limit", limit), ("offset", offset)]
+            .iter()
+            .filter_map(|(name, value)| value.map(|value| format!("{}={}", name, value)))
+            .collect::<Vec<String>>()
+            .join("&");
+        let path = if query.is_empty() {
+            format!("/api/dataset/organization/{}", organization_id)
+        } else {
+            format!("/api/dataset/organization/{}?{}", organization_id, query)
+        };
+        let rest = self.rest();
+
+        or_rest(
+            self.retry(|config| {
+                get_datasets_from_organization(
+                    config,
+                    &organization_id,
+                    &organization_id,
+                    limit,
+                    offset,
+                )
+            }),
+            || rest.get(&path),
+        )
+        .await
+    }
+
+    async fn create_dataset(&self, request: CreateDatasetRequest) -> Result<Dataset, DefaultError> {
+        let organization_id = self.organization_id();
+        self.retry(|config| create_dataset(config, &organization_id, request.clone()))
+            .await
+            .map_err(api_error)
+    }
+
+    async fn delete_dataset(&self, dataset_id: &str) -> Result<(), DefaultError> {
+        self.retry(|config| delete_dataset(config, dataset_id, dataset_id))
+            .await
+            .map_err(api_error)
+    }
+
+    async fn create_chunk_group(
+        &self,
+        dataset_id: &str,
+        group: CreateChunkGroupReqPayloadEnum,
+    ) -> Result<(), DefaultError> {
+        self.retry(|config| create_chunk_group(config, dataset_id, group.clone()))
+            .await
+            .map(|_| ())
+            .map_err(api_error)
+    }
+
+    async fn create_api_key(&self, request: SetUserApiKeyRequest) -> Result<String, DefaultError> {
+        self.retry(|config| set_user_api_key(config, request.clone()))
+            .await
+            .map(|response| response.api_key)
+            .map_err(api_error)
+    }
+
+    async fn create_organization(&self, name: String) -> Result<Organization, DefaultError> {
+        self.retry(|config| {
+            create_organization(config, CreateOrganizationReqPayload { name: name.clone() })
+        })
+        .await
+        .map_err(api_error)
+    }
+
+    async fn delete_organization(&self, organization_id: &str) -> Result<(), DefaultError> {
+        self.retry(|config| delete_organization(config, organization_id, organization_id))
+            .await
+            .map_err(api_error)
+    }
+}
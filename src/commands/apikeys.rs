@@ -0,0 +1,189 @@
+use std::fmt;
+
+use trieve_client::models::SetUserApiKeyRequest;
+use uuid::Uuid;
+
+use crate::cli::ApiKeyData;
+
+use super::{
+    alias::resolve_dataset,
+    api::TrieveApi,
+    audit,
+    client::ApiClient,
+    configure::{require_login, TrieveConfiguration},
+    i18n::{tr, tr_args},
+    output::{ensure_interactive, quiet},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ApiKeyRole {
+    Read,
+    ReadWrite,
+}
+
+impl ApiKeyRole {
+    const ALL: [ApiKeyRole; 2] = [ApiKeyRole::ReadWrite, ApiKeyRole::Read];
+
+    /// Parses a role as shown in the prompt (`Read + Write`, `Read`), in kebab case
+    /// (`read-write`, `read`) or as the numeric value sent to the API (`1`, `0`).
+    pub fn parse(role: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        match role.trim().to_lowercase().replace(' ', "").as_str() {
+            "read+write" | "read-write" | "readwrite" | "rw" | "1" => Ok(ApiKeyRole::ReadWrite),
+            "read" | "r" | "0" => Ok(ApiKeyRole::Read),
+            _ => Err(format!(
+                "Invalid role: {}. Expected one of `read-write` or `read`",
+                role
+            )
+            .into()),
+        }
+    }
+
+    pub fn value(self) -> i32 {
+        match self {
+            ApiKeyRole::Read => 0,
+            ApiKeyRole::ReadWrite => 1,
+        }
+    }
+}
+
+impl fmt::Display for ApiKeyRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiKeyRole::Read => write!(f, "Read"),
+            ApiKeyRole::ReadWrite => write!(f, "Read + Write"),
+        }
+    }
+}
+
+/// What a new API key is allowed to do. Empty lists leave the key unrestricted.
+#[derive(Clone, Debug)]
+pub struct ApiKeyScope {
+    pub role: ApiKeyRole,
+    pub dataset_ids: Vec<Uuid>,
+    pub scopes: Vec<String>,
+}
+
+fn non_empty<T>(values: Vec<T>) -> Option<Option<Vec<T>>> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(Some(values))
+    }
+}
+
+fn build_request(name: String, scope: ApiKeyScope) -> SetUserApiKeyRequest {
+    SetUserApiKeyRequest {
+        name,
+        role: scope.role.value(),
+        dataset_ids: non_empty(scope.dataset_ids),
+        organization_ids: None,
+        scopes: non_empty(scope.scopes),
+    }
+}
+
+/// Creates an API key for the logged in user and returns it.
+pub async fn create_api_key(
+    settings: &TrieveConfiguration,
+    name: String,
+    scope: ApiKeyScope,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let api_key = ApiClient::from(settings)
+        .create_api_key(build_request(name, scope))
+        .await
+        .map_err(|e| format!("Error generating API Key: {}", e.message))?;
+
+    Ok(api_key)
+}
+
+pub async fn generate_api_key(
+    settings: TrieveConfiguration,
+    api_key_data: ApiKeyData,
+) -> Result<(), Box<dyn std::error::Error>> {
+    require_login(&settings)?;
+
+    let name = match api_key_data.name {
+        Some(name) => name,
+        None => {
+            ensure_interactive("--name")?;
+            inquire::Text::new("Enter a name for the API Key:")
+                .with_help_message("This name will help you identify the API Key in the future.")
+                .prompt()
+                .unwrap()
+        }
+    };
+
+    let role = match api_key_data.role {
+        Some(role) => ApiKeyRole::parse(&role)?,
+        None => {
+            ensure_interactive("--role")?;
+            inquire::Select::new("Select a role for the API Key:", ApiKeyRole::ALL.to_vec())
+                .prompt()
+                .unwrap()
+        }
+    };
+
+    let dataset_ids = api_key_data
+        .dataset_id
+        .iter()
+        .map(|id| {
+            resolve_dataset(&settings, id)
+                .parse::<Uuid>()
+                .map_err(|e| format!("Invalid dataset ID {}: {}", id, e))
+        })
+        .collect::<Result<Vec<Uuid>, String>>()?;
+
+    let scope = ApiKeyScope {
+        role,
+        dataset_ids,
+        scopes: api_key_data.scope,
+    };
+
+    let api_key = create_api_key(&settings, name.clone(), scope.clone()).await?;
+    // The key itself is never recorded.
+    audit::record(
+        &settings,
+        "api-key generate",
+        Some(&name),
+        serde_json::json!({
+            "role": scope.role.to_string(),
+            "dataset_ids": scope.dataset_ids,
+            "scopes": scope.scopes,
+        }),
+    );
+
+    if quiet() {
+        println!("{}", api_key);
+        return Ok(());
+    }
+
+    println!("\n{}\n", tr("api-key-generated"));
+    println!("{}", tr_args("api-key-field-name", &[("name", name)]));
+    println!(
+        "{}",
+        tr_args("api-key-field-role", &[("role", scope.role.to_string())])
+    );
+    if !scope.dataset_ids.is_empty() {
+        let datasets = scope
+            .dataset_ids
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        println!(
+            "{}",
+            tr_args("api-key-field-datasets", &[("datasets", datasets)])
+        );
+    }
+    if !scope.scopes.is_empty() {
+        println!(
+            "{}",
+            tr_args(
+                "api-key-field-scopes",
+                &[("scopes", scope.scopes.join(", "))]
+            )
+        );
+    }
+    println!("{}", tr_args("api-key-field-key", &[("key", api_key)]));
+
+    Ok(())
+}
@@ -0,0 +1,746 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::Path,
+};
+
+use inquire::Confirm;
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use trieve_client::models::DatasetAndUsage;
+
+use crate::cli::Apply;
+
+use super::{
+    apikeys::ApiKeyRole,
+    audit,
+    backup::backup_dataset,
+    configure::{get_user, require_login, TrieveConfiguration},
+    dataset::{get_datasets_from_org, DefaultError},
+    guard,
+    output::{ensure_interactive, quiet},
+    rest::Rest,
+    style::{bold, dim},
+    templates::load_template,
+};
+
+/// A dataset as described in the manifest. It is matched to an existing dataset by its tracking
+/// ID when it has one, and by its name otherwise.
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DatasetSpec {
+    pub name: String,
+    pub tracking_id: Option<String>,
+    /// A saved template whose configuration the dataset starts from.
+    pub template: Option<String>,
+    /// `server_configuration` fields to enforce. Fields left out keep their current value.
+    #[serde(default)]
+    pub configuration: Map<String, Value>,
+}
+
+/// An organization API key as described in the manifest.
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeySpec {
+    pub name: String,
+    /// `read` or `read-write`.
+    pub role: String,
+    /// The names, tracking IDs or IDs of the datasets the key is limited to.
+    #[serde(default)]
+    pub datasets: Vec<String>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+/// The desired state of an organization, read from a `trieve.yaml`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// The name or ID of the organization (defaults to the organization of the profile).
+    pub organization: Option<String>,
+    #[serde(default)]
+    pub datasets: Vec<DatasetSpec>,
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeySpec>,
+    /// Delete the datasets and API keys of the organization that are not in the manifest.
+    #[serde(default)]
+    pub prune: bool,
+}
+
+/// A change needed to bring the organization to the state of the manifest.
+pub enum Change {
+    CreateOrganization {
+        name: String,
+    },
+    CreateDataset {
+        spec: DatasetSpec,
+        configuration: Map<String, Value>,
+    },
+    UpdateDataset {
+        id: String,
+        name: String,
+        new_name: Option<String>,
+        /// The drifted configuration fields, with their current and desired value.
+        configuration: Vec<(String, Value, Value)>,
+    },
+    DeleteDataset {
+        id: String,
+        name: String,
+    },
+    CreateApiKey {
+        spec: ApiKeySpec,
+    },
+    /// API keys cannot be changed, so a key that drifted is deleted and created again. Clients
+    /// using the old key stop working.
+    ReplaceApiKey {
+        id: String,
+        spec: ApiKeySpec,
+        /// The fields that differ (`role`, `datasets`, `scopes`), with their current and desired
+        /// value.
+        differences: Vec<(String, Value, Value)>,
+    },
+    DeleteApiKey {
+        id: String,
+        name: String,
+    },
+}
+
+pub struct Plan {
+    /// The settings for the organization of the manifest, or `None` when the plan creates it.
+    pub settings: Option<TrieveConfiguration>,
+    pub changes: Vec<Change>,
+}
+
+impl Plan {
+    /// Counts the changes as (create, update, replace, delete).
+    pub fn counts(&self) -> (usize, usize, usize, usize) {
+        self.changes
+            .iter()
+            .fold((0, 0, 0, 0), |(c, u, r, d), change| match change {
+                Change::CreateOrganization { .. }
+                | Change::CreateDataset { .. }
+                | Change::CreateApiKey { .. } => (c + 1, u, r, d),
+                Change::UpdateDataset { .. } => (c, u + 1, r, d),
+                Change::ReplaceApiKey { .. } => (c, u, r + 1, d),
+                Change::DeleteDataset { .. } | Change::DeleteApiKey { .. } => (c, u, r, d + 1),
+            })
+    }
+
+    pub fn summary(&self) -> String {
+        let (create, update, replace, delete) = self.counts();
+        format!(
+            "{} to create, {} to update, {} to replace, {} to delete.",
+            create, update, replace, delete
+        )
+    }
+}
+
+pub fn load_manifest(path: &Path) -> Result<Manifest, DefaultError> {
+    let contents = fs::read_to_string(path).map_err(|e| DefaultError {
+        message: format!("Could not read {}: {}", path.display(), e),
+    })?;
+    let manifest: Manifest = serde_yaml::from_str(&contents).map_err(|e| DefaultError {
+        message: format!("Invalid manifest {}: {}", path.display(), e),
+    })?;
+
+    for key in &manifest.api_keys {
+        ApiKeyRole::parse(&key.role).map_err(|e| DefaultError {
+            message: format!("API key '{}': {}", key.name, e),
+        })?;
+    }
+
+    Ok(manifest)
+}
+
+/// The configuration a dataset of the manifest should have: its template, if any, with the
+/// `configuration` of the manifest on top.
+fn desired_configuration(spec: &DatasetSpec) -> Result<Map<String, Value>, DefaultError> {
+    let mut configuration = match &spec.template {
+        Some(template) => load_template(template)
+            .map_err(|e| DefaultError {
+                message: e.to_string(),
+            })?
+            .server_configuration
+            .as_object()
+            .cloned()
+            .unwrap_or_default(),
+        None => Map::new(),
+    };
+    configuration.extend(spec.configuration.clone());
+    Ok(configuration)
+}
+
+fn string_set(value: &Value) -> BTreeSet<String> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| item.as_str().map(str::to_string))
+        .collect()
+}
+
+/// Maps the names, tracking IDs and IDs of datasets, which are all accepted in the manifest, to
+/// their IDs.
+fn dataset_ids(datasets: &[DatasetAndUsage]) -> HashMap<String, Option<String>> {
+    let mut ids = HashMap::new();
+    for dataset in datasets {
+        let id = dataset.dataset.id.to_string();
+        ids.insert(dataset.dataset.name.clone(), Some(id.clone()));
+        if let Some(Some(tracking_id)) = &dataset.dataset.tracking_id {
+            ids.insert(tracking_id.clone(), Some(id.clone()));
+        }
+        ids.insert(id.clone(), Some(id));
+    }
+    ids
+}
+
+/// Resolves the datasets an API key is limited to into dataset IDs. Datasets the plan creates
+/// have no ID yet and resolve to `None`.
+fn resolve_datasets(
+    spec: &ApiKeySpec,
+    ids: &HashMap<String, Option<String>>,
+) -> Result<Vec<Option<String>>, DefaultError> {
+    spec.datasets
+        .iter()
+        .map(|dataset| {
+            ids.get(dataset).cloned().ok_or_else(|| DefaultError {
+                message: format!(
+                    "API key '{}' refers to dataset '{}', which is neither in the manifest nor in the organization",
+                    spec.name, dataset
+                ),
+            })
+        })
+        .collect()
+}
+
+/// Compares the manifest with the organization and lists the changes needed to converge. Nothing
+/// is changed on the server.
+pub async fn plan(
+    settings: &TrieveConfiguration,
+    manifest: &Manifest,
+) -> Result<Plan, DefaultError> {
+    let mut changes = vec![];
+
+    let settings = match &manifest.organization {
+        None => Some(settings.clone()),
+        Some(organization) => {
+            let user = get_user(settings).await?;
+            match user
+                .orgs
+                .iter()
+                .find(|org| org.id.to_string() == *organization || org.name == *organization)
+            {
+                Some(org) => Some(TrieveConfiguration {
+                    organization_id: org.id,
+                    ..settings.clone()
+                }),
+                None => {
+                    changes.push(Change::CreateOrganization {
+                        name: organization.clone(),
+                    });
+                    None
+                }
+            }
+        }
+    };
+
+    let datasets = match &settings {
+        Some(settings) => get_datasets_from_org(settings.clone()).await?,
+        None => vec![],
+    };
+    let mut dataset_ids = dataset_ids(&datasets);
+    let dataset_names = datasets
+        .iter()
+        .map(|dataset| (dataset.dataset.id.to_string(), dataset.dataset.name.clone()))
+        .collect::<HashMap<String, String>>();
+
+    let mut kept = BTreeSet::new();
+    for spec in &manifest.datasets {
+        let configuration = desired_configuration(spec)?;
+        let existing = datasets.iter().find(|dataset| match &spec.tracking_id {
+            Some(tracking_id) => dataset.dataset.tracking_id == Some(Some(tracking_id.clone())),
+            None => dataset.dataset.name == spec.name,
+        });
+
+        let Some(existing) = existing else {
+            dataset_ids.insert(spec.name.clone(), None);
+            if let Some(tracking_id) = &spec.tracking_id {
+                dataset_ids.insert(tracking_id.clone(), None);
+            }
+            changes.push(Change::CreateDataset {
+                spec: spec.clone(),
+                configuration,
+            });
+            continue;
+        };
+
+        let id = existing.dataset.id.to_string();
+        kept.insert(id.clone());
+        dataset_ids.insert(spec.name.clone(), Some(id.clone()));
+
+        let current: Value = Rest::new(settings.as_ref().unwrap())
+            .dataset(&id)
+            .get(&format!("/api/dataset/{}", id))
+            .await?;
+        let drifted = configuration
+            .iter()
+            .filter(|(key, value)| current["server_configuration"].get(key.as_str()) != Some(value))
+            .map(|(key, value)| {
+                (
+                    key.clone(),
+                    current["server_configuration"][key.as_str()].clone(),
+                    value.clone(),
+                )
+            })
+            .collect::<Vec<(String, Value, Value)>>();
+        let new_name = (existing.dataset.name != spec.name).then(|| spec.name.clone());
+
+        if new_name.is_some() || !drifted.is_empty() {
+            changes.push(Change::UpdateDataset {
+                id,
+                name: existing.dataset.name.clone(),
+                new_name,
+                configuration: drifted,
+            });
+        }
+    }
+
+    let keys: Vec<Value> = match &settings {
+        Some(settings) => Rest::new(settings).get("/api/organization/api_key").await?,
+        None => vec![],
+    };
+    let mut kept_keys = BTreeSet::new();
+    for spec in &manifest.api_keys {
+        let datasets = resolve_datasets(spec, &dataset_ids)?;
+        let Some(existing) = keys
+            .iter()
+            .find(|key| key["name"].as_str() == Some(&spec.name))
+        else {
+            changes.push(Change::CreateApiKey { spec: spec.clone() });
+            continue;
+        };
+
+        let id = existing["id"].as_str().unwrap_or_default().to_string();
+        kept_keys.insert(id.clone());
+
+        let mut differences = vec![];
+        let role = ApiKeyRole::parse(&spec.role).map_err(|e| DefaultError {
+            message: e.to_string(),
+        })?;
+        if existing["role"].as_i64() != Some(role.value() as i64) {
+            let current = match existing["role"].as_i64() {
+                Some(0) => ApiKeyRole::Read.to_string(),
+                Some(1) => ApiKeyRole::ReadWrite.to_string(),
+                _ => existing["role"].to_string(),
+            };
+            differences.push(("role".to_string(), json!(current), json!(role.to_string())));
+        }
+        let current_datasets = string_set(&existing["dataset_ids"]);
+        let desired_datasets = datasets.iter().flatten().cloned().collect::<BTreeSet<_>>();
+        if datasets.iter().any(Option::is_none) || current_datasets != desired_datasets {
+            // Datasets are shown by name rather than by ID.
+            let current = current_datasets
+                .iter()
+                .map(|id| dataset_names.get(id).unwrap_or(id))
+                .collect::<Vec<_>>();
+            differences.push(("datasets".to_string(), json!(current), json!(spec.datasets)));
+        }
+        let current_scopes = string_set(&existing["scopes"]);
+        if current_scopes != spec.scopes.iter().cloned().collect() {
+            differences.push((
+                "scopes".to_string(),
+                json!(current_scopes),
+                json!(spec.scopes),
+            ));
+        }
+
+        if !differences.is_empty() {
+            changes.push(Change::ReplaceApiKey {
+                id,
+                spec: spec.clone(),
+                differences,
+            });
+        }
+    }
+
+    if manifest.prune {
+        for dataset in &datasets {
+            let id = dataset.dataset.id.to_string();
+            if !kept.contains(&id) {
+                changes.push(Change::DeleteDataset {
+                    id,
+                    name: dataset.dataset.name.clone(),
+                });
+            }
+        }
+        for key in &keys {
+            let id = key["id"].as_str().unwrap_or_default().to_string();
+            if !kept_keys.contains(&id) {
+                changes.push(Change::DeleteApiKey {
+                    id,
+                    name: key["name"].as_str().unwrap_or_default().to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(Plan { settings, changes })
+}
+
+fn print_plan(plan: &Plan) {
+    for change in &plan.changes {
+        match change {
+            Change::CreateOrganization { name } => println!("+ create organization {}", bold(name)),
+            Change::CreateDataset {
+                spec,
+                configuration,
+            } => {
+                println!("+ create dataset {}", bold(&spec.name));
+                for (key, value) in configuration {
+                    println!("    {} = {}", key, value);
+                }
+            }
+            Change::UpdateDataset {
+                id,
+                name,
+                new_name,
+                configuration,
+            } => {
+                println!(
+                    "~ update dataset {} {}",
+                    bold(name),
+                    dim(&format!("({})", id))
+                );
+                if let Some(new_name) = new_name {
+                    println!("    name: {} -> {}", name, new_name);
+                }
+                for (key, current, desired) in configuration {
+                    println!("    {}: {} -> {}", key, current, desired);
+                }
+            }
+            Change::DeleteDataset { id, name } => {
+                println!(
+                    "- delete dataset {} {}",
+                    bold(name),
+                    dim(&format!("({})", id))
+                )
+            }
+            Change::CreateApiKey { spec } => println!("+ create API key {}", bold(&spec.name)),
+            Change::ReplaceApiKey {
+                id,
+                spec,
+                differences,
+            } => {
+                println!(
+                    "-/+ replace API key {} {}",
+                    bold(&spec.name),
+                    dim(&format!("({})", id))
+                );
+                for (key, current, desired) in differences {
+                    println!("    {}: {} -> {}", key, current, desired);
+                }
+            }
+            Change::DeleteApiKey { id, name } => {
+                println!(
+                    "- delete API key {} {}",
+                    bold(name),
+                    dim(&format!("({})", id))
+                )
+            }
+        }
+    }
+    println!();
+    println!("Plan: {}", plan.summary());
+}
+
+async fn create_organization_api_key(
+    settings: &TrieveConfiguration,
+    spec: &ApiKeySpec,
+    dataset_ids: &HashMap<String, Option<String>>,
+) -> Result<(), DefaultError> {
+    let role = ApiKeyRole::parse(&spec.role).map_err(|e| DefaultError {
+        message: e.to_string(),
+    })?;
+    let datasets = resolve_datasets(spec, dataset_ids)?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<String>>();
+    let body = json!({
+        "name": spec.name,
+        "role": role.value(),
+        "dataset_ids": (!datasets.is_empty()).then_some(&datasets),
+        "scopes": (!spec.scopes.is_empty()).then_some(&spec.scopes),
+    });
+    let response: Value = Rest::new(settings)
+        .post("/api/organization/api_key", &body)
+        .await?;
+    // The key itself is never recorded.
+    audit::record(
+        settings,
+        "api-key generate",
+        Some(&spec.name),
+        json!({
+            "role": role.to_string(),
+            "dataset_ids": datasets,
+            "scopes": spec.scopes,
+        }),
+    );
+
+    // The key is only returned once, so it is printed even in quiet mode.
+    let api_key = response["api_key"].as_str().unwrap_or_default();
+    if quiet() {
+        println!("{}\t{}", spec.name, api_key);
+    } else {
+        println!("Created API key {}: {}", bold(&spec.name), api_key);
+    }
+
+    Ok(())
+}
+
+/// Makes the changes of a plan, creating before deleting, so API keys can be limited to new
+/// datasets and nothing is deleted when an earlier change fails.
+async fn execute(
+    settings: &TrieveConfiguration,
+    plan: Plan,
+    backup: bool,
+) -> Result<(), DefaultError> {
+    let datasets = match &plan.settings {
+        Some(settings) => get_datasets_from_org(settings.clone()).await?,
+        None => vec![],
+    };
+    let mut dataset_ids = dataset_ids(&datasets);
+    let mut settings = plan.settings.unwrap_or_else(|| settings.clone());
+
+    let (deletions, changes): (Vec<Change>, Vec<Change>) =
+        plan.changes.into_iter().partition(|change| {
+            matches!(
+                change,
+                Change::DeleteDataset { .. } | Change::DeleteApiKey { .. }
+            )
+        });
+
+    for change in changes.into_iter().chain(deletions) {
+        match change {
+            Change::CreateOrganization { name } => {
+                let organization: Value = Rest::new(&settings)
+                    .post("/api/organization", &json!({ "name": name }))
+                    .await?;
+                let id = organization["id"].as_str().unwrap_or_default();
+                settings.organization_id = id.parse().map_err(|_| DefaultError {
+                    message: format!("The server returned an invalid organization ID '{}'", id),
+                })?;
+                audit::record(
+                    &settings,
+                    "organization create",
+                    Some(id),
+                    json!({ "name": name }),
+                );
+                if !quiet() {
+                    println!(
+                        "Created organization {} {}",
+                        bold(&name),
+                        dim(&format!("({})", id))
+                    );
+                }
+            }
+            Change::CreateDataset {
+                spec,
+                configuration,
+            } => {
+                let dataset: Value = Rest::new(&settings)
+                    .post(
+                        "/api/dataset",
+                        &json!({
+                            "dataset_name": spec.name,
+                            "organization_id": settings.organization_id,
+                            "tracking_id": spec.tracking_id,
+                            "server_configuration": configuration,
+                        }),
+                    )
+                    .await?;
+                let id = dataset["id"].as_str().unwrap_or_default().to_string();
+                audit::record(
+                    &settings,
+                    "dataset create",
+                    Some(&id),
+                    json!({ "name": spec.name, "template": spec.template }),
+                );
+                dataset_ids.insert(spec.name.clone(), Some(id.clone()));
+                if let Some(tracking_id) = &spec.tracking_id {
+                    dataset_ids.insert(tracking_id.clone(), Some(id.clone()));
+                }
+                if !quiet() {
+                    println!(
+                        "Created dataset {} {}",
+                        bold(&spec.name),
+                        dim(&format!("({})", id))
+                    );
+                }
+            }
+            Change::UpdateDataset {
+                id,
+                name,
+                new_name,
+                configuration,
+            } => {
+                let mut body = json!({ "dataset_id": id });
+                if let Some(new_name) = &new_name {
+                    body["dataset_name"] = json!(new_name);
+                }
+                if !configuration.is_empty() {
+                    let current: Value = Rest::new(&settings)
+                        .dataset(&id)
+                        .get(&format!("/api/dataset/{}", id))
+                        .await?;
+                    let mut server_configuration = current["server_configuration"]
+                        .as_object()
+                        .cloned()
+                        .unwrap_or_default();
+                    for (key, _, desired) in configuration {
+                        server_configuration.insert(key, desired);
+                    }
+                    body["server_configuration"] = Value::Object(server_configuration);
+                }
+                let _: Value = Rest::new(&settings)
+                    .dataset(&id)
+                    .put("/api/dataset", &body)
+                    .await?;
+                audit::record(&settings, "dataset update", Some(&id), body);
+                if !quiet() {
+                    println!(
+                        "Updated dataset {} {}",
+                        bold(new_name.as_ref().unwrap_or(&name)),
+                        dim(&format!("({})", id))
+                    );
+                }
+            }
+            Change::CreateApiKey { spec } => {
+                create_organization_api_key(&settings, &spec, &dataset_ids).await?;
+            }
+            Change::ReplaceApiKey { id, spec, .. } => {
+                Rest::new(&settings)
+                    .delete(&format!("/api/organization/api_key/{}", id))
+                    .await?;
+                audit::record(
+                    &settings,
+                    "api-key delete",
+                    Some(&id),
+                    json!({ "name": spec.name }),
+                );
+                create_organization_api_key(&settings, &spec, &dataset_ids).await?;
+            }
+            Change::DeleteApiKey { id, name } => {
+                Rest::new(&settings)
+                    .delete(&format!("/api/organization/api_key/{}", id))
+                    .await?;
+                audit::record(
+                    &settings,
+                    "api-key delete",
+                    Some(&id),
+                    json!({ "name": name }),
+                );
+                if !quiet() {
+                    println!(
+                        "Deleted API key {} {}",
+                        bold(&name),
+                        dim(&format!("({})", id))
+                    );
+                }
+            }
+            Change::DeleteDataset { id, name } => {
+                let backup_dir = if backup {
+                    Some(
+                        backup_dataset(&settings, &id)
+                            .await
+                            .map_err(|e| DefaultError {
+                                message: format!(
+                                    "Backup failed, dataset {} was not deleted: {}",
+                                    name, e.message
+                                ),
+                            })?,
+                    )
+                } else {
+                    None
+                };
+                Rest::new(&settings)
+                    .dataset(&id)
+                    .delete(&format!("/api/dataset/{}", id))
+                    .await?;
+                audit::record(
+                    &settings,
+                    "dataset delete",
+                    Some(&id),
+                    json!({ "name": name, "backup": backup_dir }),
+                );
+                if !quiet() {
+                    println!(
+                        "Deleted dataset {} {}",
+                        bold(&name),
+                        dim(&format!("({})", id))
+                    );
+                    if let Some(dir) = backup_dir {
+                        println!(
+                            "Restore it with `trieve dataset restore {}`.",
+                            dir.display()
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn apply(settings: TrieveConfiguration, apply: Apply) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let manifest = load_manifest(&apply.file)?;
+    let plan = plan(&settings, &manifest).await?;
+
+    if plan.changes.is_empty() {
+        if !quiet() {
+            println!(
+                "Nothing to change, the organization matches {}.",
+                apply.file.display()
+            );
+        }
+        return Ok(());
+    }
+
+    if !quiet() {
+        print_plan(&plan);
+    }
+
+    if !apply.yes {
+        ensure_interactive("--yes")?;
+        let confirmed = Confirm::new("Apply these changes?")
+            .with_default(false)
+            .prompt()
+            .unwrap_or(false);
+        if !confirmed {
+            println!("Nothing was changed.");
+            return Ok(());
+        }
+    }
+
+    let deletions = plan
+        .changes
+        .iter()
+        .filter(|change| {
+            matches!(
+                change,
+                Change::DeleteDataset { .. } | Change::DeleteApiKey { .. }
+            )
+        })
+        .count();
+    if deletions > 0 {
+        guard::confirm_destructive(&format!(
+            "apply {} deleting {} datasets and API keys",
+            apply.file.display(),
+            deletions
+        ))?;
+    }
+
+    execute(&settings, plan, !apply.no_backup).await
+}
@@ -0,0 +1,139 @@
+use std::fs;
+
+use reqwest::Method;
+use serde_json::{json, Value};
+
+use crate::cli::Ask;
+
+use super::{
+    configure::{require_login, TrieveConfiguration},
+    dataset::{require_dataset, DefaultError},
+    output::{ensure_interactive, quiet},
+    rest::Rest,
+    search::{search_hits, SearchHit},
+};
+
+async fn post_json(
+    settings: &TrieveConfiguration,
+    path: &str,
+    dataset_id: &str,
+    body: Value,
+) -> Result<String, DefaultError> {
+    Ok(Rest::new(settings)
+        .dataset(dataset_id)
+        .send(Method::POST, path, Some(&body))
+        .await?
+        .body)
+}
+
+/// Asks the LLM of the dataset to answer the last of `messages` from the chunks of `hits`.
+/// `options` are added to the generate request, e.g. `temperature` or `prompt`.
+pub async fn generate_answer(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    hits: &[SearchHit],
+    messages: Vec<Value>,
+    options: Value,
+) -> Result<String, DefaultError> {
+    let mut body = json!({
+        "chunk_ids": hits.iter().map(|hit| hit.chunk["id"].clone()).collect::<Vec<Value>>(),
+        "prev_messages": messages,
+        "stream_response": false,
+    });
+    if let (Some(body), Some(options)) = (body.as_object_mut(), options.as_object()) {
+        body.extend(options.clone());
+    }
+
+    let answer = post_json(settings, "/api/chunk/generate", dataset_id, body).await?;
+
+    // Non-streaming responses may still be prefixed with the cited chunks, separated by `||`.
+    let answer = match answer.split_once("||") {
+        Some((_, answer)) => answer,
+        None => &answer,
+    };
+    Ok(answer.trim().to_string())
+}
+
+pub async fn ask(settings: TrieveConfiguration, ask: Ask) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(ask.dataset_id, &settings)?;
+
+    let question = match ask.question {
+        Some(question) => question,
+        None => {
+            ensure_interactive("the question")?;
+            inquire::Text::new("What would you like to ask?")
+                .prompt()
+                .unwrap()
+        }
+    };
+
+    let context = ask
+        .context_file
+        .iter()
+        .map(|path| {
+            fs::read_to_string(path).map_err(|e| DefaultError {
+                message: format!("Could not read {}: {}", path.display(), e),
+            })
+        })
+        .collect::<Result<Vec<String>, DefaultError>>()?;
+
+    let search_response = post_json(
+        &settings,
+        "/api/chunk/search",
+        &dataset_id,
+        json!({
+            "query": question,
+            "search_type": ask.search_type,
+            "page_size": ask.n_retrievals,
+        }),
+    )
+    .await?;
+    let search_response: Value =
+        serde_json::from_str(&search_response).map_err(|e| DefaultError {
+            message: e.to_string(),
+        })?;
+    let hits = search_hits(&search_response);
+
+    let mut prev_messages = vec![];
+    if !context.is_empty() {
+        prev_messages.push(json!({
+            "role": "user",
+            "content": format!(
+                "Use the following additional context together with the retrieved documents when answering.\n\n{}",
+                context.join("\n\n---\n\n")
+            ),
+        }));
+        prev_messages.push(json!({
+            "role": "assistant",
+            "content": "Understood. I will use this context along with the retrieved documents.",
+        }));
+    }
+    prev_messages.push(json!({
+        "role": "user",
+        "content": question,
+    }));
+
+    let answer = generate_answer(&settings, &dataset_id, &hits, prev_messages, json!({})).await?;
+
+    println!("{}", answer);
+    if quiet() {
+        return Ok(());
+    }
+
+    let sources = hits
+        .iter()
+        .filter_map(|hit| hit.chunk["link"].as_str())
+        .filter(|link| !link.is_empty())
+        .collect::<Vec<&str>>();
+    if !sources.is_empty() {
+        println!();
+        println!("Sources:");
+        for link in sources {
+            println!("  - {}", link);
+        }
+    }
+
+    Ok(())
+}
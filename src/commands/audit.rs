@@ -0,0 +1,134 @@
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    sync::OnceLock,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tabled::{builder::Builder, settings::Style};
+
+use crate::cli::ListAudit;
+
+use super::{
+    configure::TrieveConfiguration, date::format_time, ingest::parse_since, output::quiet, pager,
+};
+
+static PROFILE: OnceLock<Option<String>> = OnceLock::new();
+
+/// A change made through the CLI, as recorded in `audit.jsonl`.
+#[derive(Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: DateTime<Utc>,
+    /// The local user that ran the command.
+    pub user: String,
+    pub profile: Option<String>,
+    pub organization_id: String,
+    /// What was done, e.g. `dataset delete`.
+    pub action: String,
+    /// The ID of the dataset, key or organization that was changed.
+    pub target: Option<String>,
+    pub details: Value,
+}
+
+/// Sets the profile that changes are recorded for.
+pub fn init(profile: Option<String>) {
+    let _ = PROFILE.set(profile);
+}
+
+fn audit_log_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let config_path = confy::get_configuration_file_path("trieve", "profiles")?;
+    let dir = config_path
+        .parent()
+        .ok_or("Could not determine the Trieve configuration directory")?;
+    fs::create_dir_all(dir)?;
+    Ok(dir.join("audit.jsonl"))
+}
+
+/// Appends a change to the audit log. A log that cannot be written is reported, but does not fail
+/// the command, since the change has already been made.
+pub fn record(settings: &TrieveConfiguration, action: &str, target: Option<&str>, details: Value) {
+    let entry = AuditEntry {
+        timestamp: Utc::now(),
+        user: env::var("USER")
+            .or_else(|_| env::var("USERNAME"))
+            .unwrap_or_default(),
+        profile: PROFILE.get().cloned().flatten(),
+        organization_id: settings.organization_id.to_string(),
+        action: action.to_string(),
+        target: target.map(str::to_string),
+        details,
+    };
+
+    let written = audit_log_path().and_then(|path| {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        Ok(())
+    });
+    if let Err(e) = written {
+        eprintln!("Could not write to the audit log: {}", e);
+    }
+}
+
+pub fn list_audit(list: ListAudit) -> Result<(), Box<dyn std::error::Error>> {
+    let path = audit_log_path()?;
+    let contents = fs::read_to_string(&path).unwrap_or_default();
+    let since = list
+        .since
+        .as_deref()
+        .map(parse_since)
+        .transpose()
+        .map_err(|e| e.message)?;
+
+    let mut entries = contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+        .filter(|entry| {
+            list.action
+                .as_ref()
+                .is_none_or(|action| entry.action.starts_with(action.as_str()))
+        })
+        .filter(|entry| since.is_none_or(|since| entry.timestamp >= since))
+        .collect::<Vec<AuditEntry>>();
+    if let Some(limit) = list.limit {
+        entries = entries.split_off(entries.len().saturating_sub(limit));
+    }
+
+    if quiet() {
+        for entry in entries {
+            println!("{}", serde_json::to_string(&entry)?);
+        }
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No changes recorded in {}.", path.display());
+        return Ok(());
+    }
+
+    let mut builder = Builder::default();
+
+    builder.push_record(["Time", "User", "Profile", "Action", "Target", "Details"]);
+
+    for entry in entries {
+        builder.push_record([
+            format_time(entry.timestamp),
+            entry.user,
+            entry.profile.unwrap_or_default(),
+            entry.action,
+            entry.target.unwrap_or_default(),
+            if entry.details.as_object().is_some_and(|d| d.is_empty()) {
+                String::new()
+            } else {
+                entry.details.to_string()
+            },
+        ]);
+    }
+
+    pager::print(&builder.build().with(Style::rounded()).to_string());
+
+    Ok(())
+}
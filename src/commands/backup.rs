@@ -0,0 +1,154 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use serde_json::{json, Value};
+use trieve_client::models::CreateDatasetRequest;
+
+use crate::cli::RestoreDataset;
+
+use super::{
+    api::TrieveApi,
+    audit,
+    chunk::report_upload,
+    client::ApiClient,
+    configure::{config_subdir, require_login, TrieveConfiguration},
+    dataset::DefaultError,
+    export::export_dataset,
+    mapping::ColumnMapping,
+    output::quiet,
+    rest::Rest,
+    upload::{chunk_records, open_source, upload_chunk_stream},
+};
+
+const DATASET_FILE: &str = "dataset.json";
+const CHUNKS_FILE: &str = "chunks.jsonl";
+
+/// Exports a dataset about to be deleted into a timestamped directory under `trash` next to the
+/// configuration file: its settings to `dataset.json` and its chunks to `chunks.jsonl`. Returns
+/// the directory, which `trieve dataset restore` re-creates the dataset from.
+pub async fn backup_dataset(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+) -> Result<PathBuf, DefaultError> {
+    let dataset: Value = Rest::new(settings)
+        .dataset(dataset_id)
+        .get(&format!("/api/dataset/{}", dataset_id))
+        .await?;
+
+    let dir = config_subdir("trash")
+        .map_err(|e| DefaultError {
+            message: e.to_string(),
+        })?
+        .join(format!(
+            "{}-{}",
+            dataset_id,
+            Utc::now().format("%Y%m%d%H%M%S")
+        ));
+    fs::create_dir_all(&dir).map_err(|e| DefaultError {
+        message: format!("Could not create {}: {}", dir.display(), e),
+    })?;
+
+    let dataset_path = dir.join(DATASET_FILE);
+    fs::write(
+        &dataset_path,
+        serde_json::to_string_pretty(&dataset).unwrap_or_default(),
+    )
+    .map_err(|e| DefaultError {
+        message: format!("Could not write {}: {}", dataset_path.display(), e),
+    })?;
+
+    if !quiet() {
+        println!(
+            "Backing up dataset {} to {}...",
+            dataset["name"].as_str().unwrap_or(dataset_id),
+            dir.display()
+        );
+    }
+    let chunk_count = export_dataset(settings, dataset_id, &dir.join(CHUNKS_FILE)).await?;
+    if !quiet() {
+        println!("Backed up {} chunks.", chunk_count);
+    }
+
+    Ok(dir)
+}
+
+/// Re-creates a dataset from a backup made when it was deleted: a new dataset is created with the
+/// name and configuration of the deleted one, and its chunks are uploaded again.
+pub async fn restore_dataset(
+    settings: TrieveConfiguration,
+    restore: RestoreDataset,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let archive = restore.archive.as_path();
+    let dataset = read_backup(archive)?;
+    let name = restore
+        .name
+        .clone()
+        .or_else(|| dataset["name"].as_str().map(str::to_string))
+        .ok_or_else(|| DefaultError {
+            message: format!("{} has no dataset name, pass --name", DATASET_FILE),
+        })?;
+
+    let data = CreateDatasetRequest {
+        organization_id: settings.organization_id,
+        dataset_name: name.clone(),
+        server_configuration: Some(Some(dataset["server_configuration"].clone())),
+        tracking_id: None,
+    };
+    let created = ApiClient::from(&settings).create_dataset(data).await?;
+    let dataset_id = created.id.to_string();
+    if !quiet() {
+        println!("Created dataset {} ({})", name, dataset_id);
+    }
+
+    let chunks = archive.join(CHUNKS_FILE);
+    let (reader, format) = open_source(None, Some(&chunks), None).await?;
+    let records = chunk_records(reader, format, &ColumnMapping::from_args(&[], None)?)?;
+    let summary = upload_chunk_stream(
+        settings.clone(),
+        dataset_id.clone(),
+        records,
+        None,
+        None,
+        None,
+    )
+    .await?;
+
+    audit::record(
+        &settings,
+        "dataset restore",
+        Some(&dataset_id),
+        json!({
+            "archive": archive.display().to_string(),
+            "deleted_dataset_id": dataset["id"],
+            "name": name,
+        }),
+    );
+
+    if quiet() {
+        println!("{}", dataset_id);
+    }
+    report_upload(&summary, &dataset_id, false)?;
+
+    Ok(())
+}
+
+fn read_backup(archive: &Path) -> Result<Value, DefaultError> {
+    let dataset_path = archive.join(DATASET_FILE);
+    let contents = fs::read_to_string(&dataset_path).map_err(|e| DefaultError {
+        message: format!(
+            "{} is not a dataset backup, could not read {}: {}",
+            archive.display(),
+            DATASET_FILE,
+            e
+        ),
+    })?;
+
+    serde_json::from_str(&contents).map_err(|e| DefaultError {
+        message: format!("Invalid {}: {}", dataset_path.display(), e),
+    })
+}
@@ -0,0 +1,396 @@
+use std::{
+    fs,
+    future::Future,
+    path::Path,
+    pin::Pin,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+use serde_json::json;
+use tabled::{builder::Builder, settings::Style};
+use tokio::task::JoinSet;
+use trieve_client::models::ChunkReqPayload;
+
+use crate::cli::{BenchIngest, BenchSearch};
+
+use super::{
+    alias::resolve_dataset,
+    configure::{require_login, TrieveConfiguration},
+    dataset::DefaultError,
+    mapping::ColumnMapping,
+    search::search_dataset,
+    synthetic::Rng,
+    upload::{chunk_records, open_source, upload_batch},
+};
+
+/// The tag every generated chunk is created with, so benchmark data is easy to find and delete.
+const BENCH_TAG: &str = "trieve-bench";
+
+/// The vocabulary generated chunks and queries are made of.
+const WORDS: &str = "search vector index query latency dataset chunk embedding ranking semantic \
+    keyword hybrid cluster server storage memory network cache document answer model token filter \
+    score group metadata upload throughput replica shard request response payload schema record";
+
+/// A benchmark request, resolving to the number of items (chunks or searches) it processed.
+type Request = Pin<Box<dyn Future<Output = Result<usize, DefaultError>> + Send>>;
+
+struct RunResult {
+    latencies: Vec<Duration>,
+    items: usize,
+    errors: usize,
+    first_error: Option<String>,
+    elapsed: Duration,
+}
+
+/// A small deterministic generator, so repeated runs send the same synthetic workload.
+struct WordGenerator {
+    rng: Rng,
+    words: Vec<&'static str>,
+}
+
+impl WordGenerator {
+    fn new(seed: u64) -> Self {
+        WordGenerator {
+            rng: Rng::new(seed),
+            words: WORDS.split_whitespace().collect(),
+        }
+    }
+
+    fn next_word(&mut self) -> &'static str {
+        self.rng.pick(self.words.as_slice())
+    }
+
+    fn sentence(&mut self, words: usize) -> String {
+        (0..words)
+            .map(|_| self.next_word())
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+}
+
+#[derive(Serialize)]
+struct LatencyStats {
+    min: f64,
+    mean: f64,
+    p50: f64,
+    p95: f64,
+    p99: f64,
+    max: f64,
+}
+
+impl LatencyStats {
+    fn from_durations(durations: &[Duration]) -> Self {
+        let mut ms = durations
+            .iter()
+            .map(|d| d.as_secs_f64() * 1000.0)
+            .collect::<Vec<f64>>();
+        ms.sort_by(|a, b| a.total_cmp(b));
+
+        // Nearest-rank percentile.
+        let percentile = |p: f64| {
+            if ms.is_empty() {
+                return 0.0;
+            }
+            let rank = ((p / 100.0) * ms.len() as f64).ceil() as usize;
+            ms[rank.clamp(1, ms.len()) - 1]
+        };
+
+        LatencyStats {
+            min: ms.first().copied().unwrap_or_default(),
+            mean: if ms.is_empty() {
+                0.0
+            } else {
+                ms.iter().sum::<f64>() / ms.len() as f64
+            },
+            p50: percentile(50.0),
+            p95: percentile(95.0),
+            p99: percentile(99.0),
+            max: ms.last().copied().unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BenchReport {
+    mode: &'static str,
+    api_url: String,
+    dataset_id: String,
+    concurrency: usize,
+    requests: usize,
+    errors: usize,
+    duration_secs: f64,
+    requests_per_sec: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chunks_per_sec: Option<f64>,
+    latency_ms: LatencyStats,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first_error: Option<String>,
+}
+
+impl BenchReport {
+    fn new(
+        mode: &'static str,
+        settings: &TrieveConfiguration,
+        dataset_id: &str,
+        concurrency: usize,
+        run: &RunResult,
+    ) -> Self {
+        BenchReport {
+            mode,
+            api_url: settings.api_url.clone(),
+            dataset_id: dataset_id.to_string(),
+            concurrency,
+            requests: run.latencies.len() + run.errors,
+            errors: run.errors,
+            duration_secs: run.elapsed.as_secs_f64(),
+            requests_per_sec: run.latencies.len() as f64 / run.elapsed.as_secs_f64(),
+            chunks_per_sec: None,
+            latency_ms: LatencyStats::from_durations(&run.latencies),
+            first_error: run.first_error.clone(),
+        }
+    }
+
+    fn print(&self) {
+        let mut builder = Builder::default();
+        builder.push_record(["Metric", "Value"]);
+        builder.push_record(["Requests".to_string(), self.requests.to_string()]);
+        builder.push_record(["Errors".to_string(), self.errors.to_string()]);
+        builder.push_record(["Concurrency".to_string(), self.concurrency.to_string()]);
+        builder.push_record([
+            "Duration".to_string(),
+            format!("{:.2}s", self.duration_secs),
+        ]);
+        builder.push_record([
+            "Throughput".to_string(),
+            format!("{:.2} requests/s", self.requests_per_sec),
+        ]);
+        if let Some(chunks_per_sec) = self.chunks_per_sec {
+            builder.push_record([
+                "Ingest rate".to_string(),
+                format!("{:.1} chunks/s", chunks_per_sec),
+            ]);
+        }
+        for (label, value) in [
+            ("Latency min", self.latency_ms.min),
+            ("Latency mean", self.latency_ms.mean),
+            ("Latency p50", self.latency_ms.p50),
+            ("Latency p95", self.latency_ms.p95),
+            ("Latency p99", self.latency_ms.p99),
+            ("Latency max", self.latency_ms.max),
+        ] {
+            builder.push_record([label.to_string(), format!("{:.1} ms", value)]);
+        }
+
+        println!("{}", builder.build().with(Style::rounded()));
+        if let Some(error) = &self.first_error {
+            eprintln!("First error: {}", error);
+        }
+    }
+
+    /// Writes the report as JSON to `path`, or to stdout when `path` is `-`.
+    fn write(&self, path: &Path) -> Result<(), DefaultError> {
+        let report = serde_json::to_string_pretty(self).map_err(|e| DefaultError {
+            message: e.to_string(),
+        })?;
+
+        if path.as_os_str() == "-" {
+            println!("{}", report);
+            return Ok(());
+        }
+
+        fs::write(path, report).map_err(|e| DefaultError {
+            message: format!("Could not write {}: {}", path.display(), e),
+        })
+    }
+}
+
+/// Runs the requests with at most `concurrency` in flight, timing each of them.
+async fn run_requests(requests: Vec<Request>, concurrency: usize) -> RunResult {
+    let started = Instant::now();
+    let mut result = RunResult {
+        latencies: vec![],
+        items: 0,
+        errors: 0,
+        first_error: None,
+        elapsed: Duration::ZERO,
+    };
+    let mut in_flight = JoinSet::new();
+
+    let mut record = |finished: Result<(Duration, usize), DefaultError>| match finished {
+        Ok((latency, items)) => {
+            result.latencies.push(latency);
+            result.items += items;
+        }
+        Err(e) => {
+            result.errors += 1;
+            result.first_error.get_or_insert(e.message);
+        }
+    };
+
+    for request in requests {
+        while in_flight.len() >= concurrency.max(1) {
+            if let Some(finished) = in_flight.join_next().await {
+                record(finished.unwrap());
+            }
+        }
+        in_flight.spawn(async move {
+            let started = Instant::now();
+            request.await.map(|items| (started.elapsed(), items))
+        });
+    }
+    while let Some(finished) = in_flight.join_next().await {
+        record(finished.unwrap());
+    }
+
+    result.elapsed = started.elapsed();
+    result
+}
+
+fn generated_chunks(count: usize) -> Result<Vec<ChunkReqPayload>, DefaultError> {
+    let run_id = uuid::Uuid::new_v4();
+    let mut words = WordGenerator::new(count as u64);
+
+    (0..count)
+        .map(|i| {
+            serde_json::from_value(json!({
+                "chunk_html": format!("<p>{}</p>", words.sentence(120)),
+                "tracking_id": format!("{}-{}-{}", BENCH_TAG, run_id, i),
+                "tag_set": [BENCH_TAG],
+            }))
+            .map_err(|e| DefaultError {
+                message: e.to_string(),
+            })
+        })
+        .collect()
+}
+
+pub async fn bench_ingest(
+    settings: TrieveConfiguration,
+    mut bench: BenchIngest,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+    bench.dataset_id = resolve_dataset(&settings, &bench.dataset_id);
+
+    let chunks = match &bench.file {
+        Some(file) => {
+            let (reader, format) = open_source(None, Some(file), None).await?;
+            chunk_records(reader, format, &ColumnMapping::default())?
+                .take(bench.chunks)
+                .collect::<Result<Vec<ChunkReqPayload>, DefaultError>>()?
+        }
+        None => generated_chunks(bench.chunks)?,
+    };
+    let chunk_count = chunks.len();
+
+    let requests = chunks
+        .chunks(bench.batch_size.max(1))
+        .map(|batch| {
+            let settings = settings.clone();
+            let dataset_id = bench.dataset_id.clone();
+            let batch = batch.to_vec();
+            Box::pin(async move {
+                let len = batch.len();
+                upload_batch(settings, dataset_id, &batch)
+                    .await
+                    .map(|_| len)
+            }) as Request
+        })
+        .collect::<Vec<Request>>();
+
+    println!(
+        "Uploading {} chunks in {} requests to dataset {}...",
+        chunk_count,
+        requests.len(),
+        bench.dataset_id
+    );
+    let run = run_requests(requests, bench.concurrency).await;
+    let mut report = BenchReport::new(
+        "ingest",
+        &settings,
+        &bench.dataset_id,
+        bench.concurrency,
+        &run,
+    );
+    report.chunks_per_sec = Some(run.items as f64 / run.elapsed.as_secs_f64());
+
+    report.print();
+    if let Some(path) = &bench.report {
+        report.write(path)?;
+    }
+
+    Ok(())
+}
+
+pub async fn bench_search(
+    settings: TrieveConfiguration,
+    mut bench: BenchSearch,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+    bench.dataset_id = resolve_dataset(&settings, &bench.dataset_id);
+
+    let queries = match &bench.query_file {
+        Some(path) => fs::read_to_string(path)
+            .map_err(|e| DefaultError {
+                message: format!("Could not read {}: {}", path.display(), e),
+            })?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_string)
+            .collect::<Vec<String>>(),
+        None => {
+            let mut words = WordGenerator::new(bench.requests as u64);
+            (0..bench.requests)
+                .map(|i| words.sentence(2 + i % 3))
+                .collect()
+        }
+    };
+    if queries.is_empty() {
+        return Err(DefaultError {
+            message: "No queries to run".to_string(),
+        });
+    }
+
+    // Replayed query files are cycled through until `--requests` searches have been sent.
+    let requests = queries
+        .iter()
+        .cycle()
+        .take(bench.requests)
+        .map(|query| {
+            let settings = settings.clone();
+            let dataset_id = bench.dataset_id.clone();
+            let body = json!({
+                "query": query,
+                "search_type": bench.search_type,
+                "page_size": bench.page_size,
+            });
+            Box::pin(async move {
+                search_dataset(&settings, &dataset_id, &body)
+                    .await
+                    .map(|_| 1usize)
+            }) as Request
+        })
+        .collect::<Vec<Request>>();
+
+    println!(
+        "Sending {} searches to dataset {}...",
+        requests.len(),
+        bench.dataset_id
+    );
+    let run = run_requests(requests, bench.concurrency).await;
+    let report = BenchReport::new(
+        "search",
+        &settings,
+        &bench.dataset_id,
+        bench.concurrency,
+        &run,
+    );
+
+    report.print();
+    if let Some(path) = &bench.report {
+        report.write(path)?;
+    }
+
+    Ok(())
+}
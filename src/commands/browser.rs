@@ -0,0 +1,83 @@
+use std::{
+    env, io,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+/// Opens the login page. `from_env` picks the implementation, so the login flow does not depend
+/// on a desktop being available.
+pub trait Browser {
+    fn open(&self, url: &str) -> io::Result<()>;
+}
+
+/// The default browser of the system: `open` on macOS, `xdg-open` and friends on Linux and
+/// `start` on Windows. The launcher is not waited for, as some of them only return once the
+/// browser is closed.
+pub struct SystemBrowser;
+
+impl Browser for SystemBrowser {
+    fn open(&self, url: &str) -> io::Result<()> {
+        open::that_detached(url)
+    }
+}
+
+/// A command from the `BROWSER` environment variable. The URL replaces `%s` in the command, or is
+/// appended as its last argument. Pointing it at a script that requests the URL lets CI run
+/// `trieve login` without a browser.
+pub struct CommandBrowser {
+    command: String,
+}
+
+impl Browser for CommandBrowser {
+    fn open(&self, url: &str) -> io::Result<()> {
+        // A path with spaces, such as one under `C:\Program Files`, is a program of its own.
+        if Path::new(&self.command).is_file() {
+            return Command::new(&self.command)
+                .arg(url)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .spawn()
+                .map(|_| ());
+        }
+
+        let mut args = self
+            .command
+            .split_whitespace()
+            .map(|arg| arg.replace("%s", url));
+        let program = args
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "BROWSER is empty"))?;
+        let mut args = args.collect::<Vec<String>>();
+        if !self.command.contains("%s") {
+            args.push(url.to_string());
+        }
+
+        Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .spawn()
+            .map(|_| ())
+    }
+}
+
+/// The browser named by `BROWSER`, or the system browser. `BROWSER` may list several commands
+/// separated by the path separator, of which the first is used.
+pub fn from_env() -> Box<dyn Browser> {
+    let command = env::var_os("BROWSER").and_then(|value| {
+        env::split_paths(&value)
+            .next()
+            .map(|command| command.to_string_lossy().trim().to_string())
+            .filter(|command| !command.is_empty())
+    });
+
+    match command {
+        Some(command) => Box::new(CommandBrowser { command }),
+        None => Box::new(SystemBrowser),
+    }
+}
+
+/// Whether `BROWSER` names a command, which is used even where no desktop is detected.
+pub fn configured() -> bool {
+    env::var_os("BROWSER").is_some_and(|value| !value.is_empty())
+}
@@ -0,0 +1,892 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tabled::{builder::Builder, settings::Style};
+use tokio::task::JoinSet;
+use trieve_client::models::ChunkReqPayload;
+
+use crate::cli::{
+    BoostChunks, CreateChunks, GetChunk, RetagChunks, SetChunkMetadata, SplitChunks, UploadChunks,
+};
+
+use super::{
+    audit, browser,
+    chunking::{split_html, ChunkingOptions},
+    configure::{require_login, TrieveConfiguration},
+    dataset::{require_dataset, DefaultError},
+    export::{scroll_filtered_chunks, SCROLL_PAGE_SIZE},
+    filter::parse_filters,
+    guard, jobs,
+    mapping::ColumnMapping,
+    output::{fail, interrupted, quiet, EXIT_ERROR},
+    render::{render_html, terminal_width},
+    rest::Rest,
+    style::dim,
+    upload::{
+        chunk_records, open_source, upload_chunk_stream, Checkpoint, Dedupe, FailureLog,
+        UploadSummary, MAX_CONCURRENT_BATCHES, STDIN,
+    },
+    validate::validate_before_upload,
+};
+
+pub async fn upload_chunks(
+    settings: TrieveConfiguration,
+    upload: UploadChunks,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(upload.dataset_id.clone(), &settings)?;
+
+    // Validated before detaching, so the problems are printed to the terminal.
+    let mapping = ColumnMapping::from_args(&upload.map, upload.mapping_file.as_deref())?;
+    if !upload.no_validate && upload.file != Path::new(STDIN) {
+        validate_before_upload(&upload.file, upload.format, &mapping).await?;
+    }
+
+    if upload.detach {
+        return jobs::detach();
+    }
+
+    let (reader, format) = open_source(None, Some(&upload.file), upload.format).await?;
+    let records = chunk_records(reader, format, &mapping)?;
+
+    // Input piped through stdin cannot be read again, so there is nothing to resume from.
+    let resumable = upload.file != Path::new(STDIN);
+    let checkpoint = if resumable {
+        Some(Checkpoint::open(&upload.file, &dataset_id, upload.resume)?)
+    } else {
+        None
+    };
+
+    let dedupe = Dedupe::from_args(&upload.dedupe, &settings, &dataset_id).await?;
+    let failures = upload
+        .errors_out
+        .as_deref()
+        .map(FailureLog::create)
+        .transpose()?;
+    let summary = upload_chunk_stream(
+        settings,
+        dataset_id.clone(),
+        records,
+        checkpoint,
+        dedupe,
+        failures,
+    )
+    .await?;
+
+    report_upload(&summary, &dataset_id, resumable)?;
+
+    Ok(())
+}
+
+/// Creates chunks from a file, or from JSONL piped to stdin when the file is `-`, so other tools
+/// can feed chunks to a dataset, e.g. `my-scraper | trieve chunk create - --dataset-id <ID>`.
+/// With `--html` a single chunk is created from the flags instead.
+pub async fn create_chunks(
+    settings: TrieveConfiguration,
+    create: CreateChunks,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(create.dataset_id.clone(), &settings)?;
+
+    let Some(source) = create.source.clone() else {
+        return create_single_chunk(&settings, &dataset_id, create).await;
+    };
+
+    let mapping = ColumnMapping::from_args(&create.map, create.mapping_file.as_deref())?;
+    if !create.no_validate && source != Path::new(STDIN) {
+        validate_before_upload(&source, create.format, &mapping).await?;
+    }
+    let (reader, format) = open_source(None, Some(&source), create.format).await?;
+    let records = chunk_records(reader, format, &mapping)?;
+
+    let dedupe = Dedupe::from_args(&create.dedupe, &settings, &dataset_id).await?;
+    let failures = create
+        .errors_out
+        .as_deref()
+        .map(FailureLog::create)
+        .transpose()?;
+    let summary = upload_chunk_stream(
+        settings,
+        dataset_id.clone(),
+        records,
+        None,
+        dedupe,
+        failures,
+    )
+    .await?;
+
+    report_upload(&summary, &dataset_id, false)?;
+
+    Ok(())
+}
+
+async fn create_single_chunk(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    create: CreateChunks,
+) -> Result<(), DefaultError> {
+    let metadata = create
+        .metadata
+        .map(|metadata| serde_json::from_str::<Value>(&metadata))
+        .transpose()
+        .map_err(|e| DefaultError {
+            message: format!("Invalid --metadata JSON: {}", e),
+        })?;
+    if metadata
+        .as_ref()
+        .is_some_and(|metadata| !metadata.is_object())
+    {
+        return Err(DefaultError {
+            message: "--metadata must be a JSON object".to_string(),
+        });
+    }
+
+    let chunk = ChunkReqPayload {
+        chunk_html: Some(create.html),
+        link: Some(create.link),
+        tag_set: Some(Some(create.tags).filter(|tags| !tags.is_empty())),
+        upsert_by_tracking_id: Some(create.tracking_id.as_ref().map(|_| true)),
+        tracking_id: Some(create.tracking_id),
+        metadata: Some(metadata),
+        ..Default::default()
+    };
+    let body = serde_json::to_value(chunk).map_err(|e| DefaultError {
+        message: e.to_string(),
+    })?;
+
+    let response: Value = Rest::new(settings)
+        .dataset(dataset_id)
+        .post("/api/chunk", &body)
+        .await?;
+    let chunk_id = response["chunk_metadata"]["id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    audit::record(
+        settings,
+        "chunk create",
+        Some(dataset_id),
+        json!({ "chunk_id": chunk_id, "tracking_id": body["tracking_id"] }),
+    );
+
+    if quiet() {
+        println!("{}", chunk_id);
+    } else {
+        println!("Created chunk {} in dataset {}", chunk_id, dataset_id);
+    }
+
+    Ok(())
+}
+
+/// Splits an HTML page into chunks by its headings and uploads them, or with `--dry-run` prints
+/// where the chunk boundaries fall.
+pub async fn split_chunks(
+    settings: TrieveConfiguration,
+    split: SplitChunks,
+) -> Result<(), DefaultError> {
+    let html = fs::read_to_string(&split.file).map_err(|e| DefaultError {
+        message: format!("Could not read {}: {}", split.file.display(), e),
+    })?;
+    let options = ChunkingOptions::from(&split.chunking);
+    let chunks = split_html(&html, &options);
+
+    if split.dry_run {
+        if quiet() {
+            println!("{}", chunks.len());
+            return Ok(());
+        }
+
+        let mut builder = Builder::default();
+        builder.push_record(["#", "Tokens", "Section", "Start"]);
+        for (i, chunk) in chunks.iter().enumerate() {
+            builder.push_record([
+                (i + 1).to_string(),
+                chunk.tokens.to_string(),
+                chunk.headings.join(" > "),
+                chunk.preview(60),
+            ]);
+        }
+        println!("{}", builder.build().with(Style::rounded()));
+        println!("{} chunks from {}", chunks.len(), split.file.display());
+        return Ok(());
+    }
+
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(split.dataset_id.clone(), &settings)?;
+
+    let dedupe = Dedupe::from_args(&split.dedupe, &settings, &dataset_id).await?;
+    let source = split.file.display().to_string();
+    let tags = Some(split.tags.clone()).filter(|tags| !tags.is_empty());
+    let records = chunks.into_iter().map(move |chunk| {
+        Ok(ChunkReqPayload {
+            chunk_html: Some(Some(chunk.html)),
+            link: Some(split.link.clone()),
+            tag_set: Some(tags.clone()),
+            metadata: Some(Some(json!({
+                "source": source,
+                "headings": chunk.headings,
+            }))),
+            ..Default::default()
+        })
+    });
+
+    let summary = upload_chunk_stream(
+        settings,
+        dataset_id.clone(),
+        Box::new(records),
+        None,
+        dedupe,
+        None,
+    )
+    .await?;
+
+    report_upload(&summary, &dataset_id, false)?;
+
+    Ok(())
+}
+
+/// Prints the outcome of an upload and fails when it did not complete, with `EXIT_INTERRUPTED`
+/// when it was interrupted.
+pub fn report_upload(
+    summary: &UploadSummary,
+    dataset_id: &str,
+    resumable: bool,
+) -> Result<(), DefaultError> {
+    if !quiet() {
+        println!(
+            "Uploaded {} chunks to dataset {}{}",
+            summary.uploaded,
+            dataset_id,
+            if summary.skipped > 0 {
+                format!(" ({} skipped from a previous run)", summary.skipped)
+            } else {
+                String::new()
+            }
+        );
+        if summary.duplicates > 0 {
+            println!("Dropped {} duplicate chunks", summary.duplicates);
+        }
+    }
+
+    if summary.interrupted {
+        return Err(interrupted(if resumable {
+            "Upload interrupted. Run the same command with `--resume` to continue the upload."
+                .to_string()
+        } else {
+            "Upload interrupted.".to_string()
+        }));
+    }
+
+    if summary.failed_batches > 0 {
+        return Err(fail(
+            EXIT_ERROR,
+            "upload_failed",
+            if resumable {
+                format!(
+                    "{} batches failed to upload. Run the same command with `--resume` to retry from the first failed batch.",
+                    summary.failed_batches
+                )
+            } else {
+                format!("{} batches failed to upload.", summary.failed_batches)
+            },
+        ));
+    }
+
+    Ok(())
+}
+
+/// The tags of a chunk, which older servers return as a comma separated string.
+/// Fetches a chunk by its ID, or by its tracking ID when `id` is not a UUID.
+pub async fn fetch_chunk(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    id: &str,
+) -> Result<Value, DefaultError> {
+    let path = if id.parse::<uuid::Uuid>().is_ok() {
+        format!("/api/chunk/{}", id)
+    } else {
+        format!("/api/chunk/tracking_id/{}", id)
+    };
+    Rest::new(settings).dataset(dataset_id).get(&path).await
+}
+
+/// The groups a chunk is in, each with the uploaded file the group was created for, if any.
+async fn chunk_sources(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    chunk_id: &str,
+) -> Result<Vec<(Value, Option<Value>)>, DefaultError> {
+    let rest = Rest::new(settings).dataset(dataset_id);
+    let response: Value = rest
+        .post(
+            "/api/chunk_group/chunks",
+            &json!({ "chunk_ids": [chunk_id] }),
+        )
+        .await?;
+
+    let groups = response
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|item| item["chunk_uuid"].as_str() == Some(chunk_id))
+        .flat_map(|item| item["slim_groups"].as_array().cloned().unwrap_or_default());
+
+    let mut sources = vec![];
+    for group in groups {
+        let file = match group["file_id"].as_str() {
+            Some(file_id) => Some(rest.get(&format!("/api/file/{}", file_id)).await?),
+            None => None,
+        };
+        sources.push((group, file));
+    }
+
+    Ok(sources)
+}
+
+pub async fn get_chunk(settings: TrieveConfiguration, get: GetChunk) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(get.dataset_id, &settings)?;
+    let chunk = fetch_chunk(&settings, &dataset_id, &get.id).await?;
+    let link = chunk["link"]
+        .as_str()
+        .filter(|link| !link.is_empty())
+        .map(str::to_string);
+    if get.web && link.is_none() {
+        return Err(DefaultError {
+            message: format!("Chunk {} has no link to open", get.id),
+        });
+    }
+
+    if get.render {
+        print_rendered_chunk(&chunk);
+    } else if quiet() {
+        println!("{}", chunk);
+    } else {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&chunk).unwrap_or_default()
+        );
+    }
+
+    if let Some(link) = link.filter(|_| get.web) {
+        browser::from_env().open(&link).map_err(|e| DefaultError {
+            message: format!("Could not open {}: {}", link, e),
+        })?;
+    }
+    if quiet() {
+        return Ok(());
+    }
+
+    let chunk_id = chunk["id"].as_str().unwrap_or_default();
+    let sources = chunk_sources(&settings, &dataset_id, chunk_id).await?;
+    println!();
+    if sources.is_empty() {
+        println!("{} {}", dim("source:"), dim("not in any group"));
+    }
+    for (group, file) in sources {
+        println!(
+            "{} {} {}",
+            dim("group:"),
+            group["name"].as_str().unwrap_or_default(),
+            dim(&format!("({})", group["id"].as_str().unwrap_or_default()))
+        );
+        if let Some(file) = file {
+            println!(
+                "{} {} {}",
+                dim("file: "),
+                file["file_name"].as_str().unwrap_or_default(),
+                dim(&format!("({})", file["id"].as_str().unwrap_or_default()))
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the HTML of a chunk as text, below its ID, tracking ID, link and tags. In quiet mode
+/// only the text is printed.
+fn print_rendered_chunk(chunk: &Value) {
+    if !quiet() {
+        let tags = chunk_tags(chunk);
+        let fields = [
+            (
+                "id:      ",
+                chunk["id"].as_str().unwrap_or_default().to_string(),
+            ),
+            (
+                "tracking:",
+                chunk["tracking_id"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            ),
+            (
+                "link:    ",
+                chunk["link"].as_str().unwrap_or_default().to_string(),
+            ),
+            ("tags:    ", tags.join(", ")),
+        ];
+        for (label, value) in fields.iter().filter(|(_, value)| !value.is_empty()) {
+            println!("{} {}", dim(label), value);
+        }
+        println!();
+    }
+
+    println!(
+        "{}",
+        render_html(
+            chunk["chunk_html"].as_str().unwrap_or_default(),
+            terminal_width()
+        )
+    );
+}
+
+fn chunk_tags(chunk: &Value) -> Vec<String> {
+    match &chunk["tag_set"] {
+        Value::Array(tags) => tags
+            .iter()
+            .filter_map(|tag| tag.as_str().map(str::to_string))
+            .collect(),
+        Value::String(tags) => tags
+            .split(',')
+            .filter(|tag| !tag.is_empty())
+            .map(str::to_string)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Applies `update` to every chunk of a dataset matching `filters`, a page of chunks at a time.
+/// `update` returns the fields to change, or `None` when a chunk needs no change. Updates run
+/// `upload_concurrency` at a time, and with `dry_run` only the number of chunks that would change
+/// is printed. Otherwise the update is recorded in the audit log as `action` with `details`.
+async fn update_matching_chunks(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    filters: &Value,
+    dry_run: bool,
+    (action, mut details): (&str, Value),
+    update: impl Fn(&Value) -> Option<Value>,
+) -> Result<(), DefaultError> {
+    let max_in_flight = settings
+        .upload_concurrency
+        .unwrap_or(MAX_CONCURRENT_BATCHES)
+        .max(1);
+
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut offset: Option<String> = None;
+    let mut changed = 0;
+    let mut failed = 0;
+
+    loop {
+        let chunks =
+            scroll_filtered_chunks(settings, dataset_id, Some(filters), offset.as_deref()).await?;
+        let page_len = chunks.len();
+        let mut new_chunks = 0;
+        let mut in_flight = JoinSet::new();
+
+        for chunk in chunks {
+            let id = chunk["id"].as_str().unwrap_or_default().to_string();
+            // The offset chunk may be returned again at the start of the next page.
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            offset = Some(id.clone());
+            new_chunks += 1;
+
+            let Some(mut changes) = update(&chunk) else {
+                continue;
+            };
+            changed += 1;
+            if dry_run {
+                continue;
+            }
+
+            while in_flight.len() >= max_in_flight {
+                if let Some(finished) = in_flight.join_next().await {
+                    failed += report_chunk_update(finished.unwrap());
+                }
+            }
+
+            changes["chunk_id"] = json!(id);
+            let settings = settings.clone();
+            let dataset_id = dataset_id.to_string();
+            in_flight.spawn(async move {
+                let result = Rest::new(&settings)
+                    .dataset(&dataset_id)
+                    .put::<Value>("/api/chunk", &changes)
+                    .await;
+                (id, result)
+            });
+        }
+
+        // Every update of a page is finished before the next one is requested, so chunks that
+        // stop matching the filter once updated cannot shift the pages.
+        while let Some(finished) = in_flight.join_next().await {
+            failed += report_chunk_update(finished.unwrap());
+        }
+
+        if !dry_run && !quiet() {
+            println!(
+                "Updated {} of {} matching chunks so far",
+                changed - failed,
+                seen.len()
+            );
+        }
+
+        if new_chunks == 0 || page_len < SCROLL_PAGE_SIZE {
+            break;
+        }
+    }
+
+    if !dry_run {
+        details["filters"] = filters.clone();
+        details["updated"] = json!(changed - failed);
+        details["failed"] = json!(failed);
+        audit::record(settings, action, Some(dataset_id), details);
+    }
+
+    if quiet() {
+        println!("{}", changed - failed);
+    } else if dry_run {
+        println!(
+            "{} of {} matching chunks would change in dataset {}",
+            changed,
+            seen.len(),
+            dataset_id
+        );
+    } else {
+        println!(
+            "Updated {} of {} matching chunks in dataset {}",
+            changed - failed,
+            seen.len(),
+            dataset_id
+        );
+    }
+
+    if failed > 0 {
+        return Err(DefaultError {
+            message: format!("{} chunks could not be updated", failed),
+        });
+    }
+
+    Ok(())
+}
+
+/// Prints why a chunk could not be updated and returns the number of failures.
+fn report_chunk_update((id, result): (String, Result<Value, DefaultError>)) -> usize {
+    match result {
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("Could not update chunk {}: {}", id, e.message);
+            1
+        }
+    }
+}
+
+/// Adds and removes tags of every chunk matching the filters.
+pub async fn retag_chunks(
+    settings: TrieveConfiguration,
+    retag: RetagChunks,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    if retag.add_tags.is_empty() && retag.remove_tags.is_empty() {
+        return Err(DefaultError {
+            message: "Pass at least one --add-tag or --remove-tag".to_string(),
+        });
+    }
+
+    let dataset_id = require_dataset(retag.dataset_id.clone(), &settings)?;
+    let filters = parse_filters(&retag.filters)?;
+
+    if !retag.dry_run {
+        guard::confirm_destructive(&format!(
+            "retag the chunks of dataset {} matching the filters",
+            dataset_id
+        ))?;
+    }
+
+    let audit = (
+        "chunk retag",
+        json!({ "add_tags": retag.add_tags, "remove_tags": retag.remove_tags }),
+    );
+    update_matching_chunks(
+        &settings,
+        &dataset_id,
+        &filters,
+        retag.dry_run,
+        audit,
+        |chunk| {
+            let tags = chunk_tags(chunk);
+            let mut retagged: Vec<String> = tags
+                .iter()
+                .filter(|tag| !retag.remove_tags.contains(tag))
+                .cloned()
+                .collect();
+            for tag in &retag.add_tags {
+                if !retagged.contains(tag) {
+                    retagged.push(tag.clone());
+                }
+            }
+
+            (retagged != tags).then(|| json!({ "tag_set": retagged }))
+        },
+    )
+    .await
+}
+
+/// Parses `--set KEY=VALUE`, reading the value as JSON when it is valid JSON, so numbers and
+/// booleans keep their type, and as a string otherwise.
+fn parse_metadata_field(field: &str) -> Result<(String, Value), DefaultError> {
+    let (key, value) = field.split_once('=').ok_or_else(|| DefaultError {
+        message: format!("Invalid --set '{}', expected KEY=VALUE", field),
+    })?;
+    let value = serde_json::from_str(value).unwrap_or_else(|_| json!(value));
+
+    Ok((key.trim().to_string(), value))
+}
+
+/// Sets and removes metadata fields of every chunk matching the filters, keeping the other fields.
+pub async fn set_chunk_metadata(
+    settings: TrieveConfiguration,
+    set_metadata: SetChunkMetadata,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    if set_metadata.set.is_empty() && set_metadata.unset.is_empty() {
+        return Err(DefaultError {
+            message: "Pass at least one --set or --unset".to_string(),
+        });
+    }
+
+    let fields = set_metadata
+        .set
+        .iter()
+        .map(|field| parse_metadata_field(field))
+        .collect::<Result<Vec<(String, Value)>, DefaultError>>()?;
+    let dataset_id = require_dataset(set_metadata.dataset_id.clone(), &settings)?;
+    let filters = parse_filters(&set_metadata.filters)?;
+
+    if !set_metadata.dry_run {
+        guard::confirm_destructive(&format!(
+            "change the metadata of the chunks of dataset {} matching the filters",
+            dataset_id
+        ))?;
+    }
+
+    let set = fields
+        .iter()
+        .cloned()
+        .collect::<serde_json::Map<String, Value>>();
+    let audit = (
+        "chunk set-metadata",
+        json!({ "set": set, "unset": set_metadata.unset }),
+    );
+    update_matching_chunks(
+        &settings,
+        &dataset_id,
+        &filters,
+        set_metadata.dry_run,
+        audit,
+        |chunk| {
+            let metadata = chunk["metadata"].as_object().cloned().unwrap_or_default();
+            let mut updated = metadata.clone();
+            for key in &set_metadata.unset {
+                updated.remove(key);
+            }
+            for (key, value) in &fields {
+                updated.insert(key.clone(), value.clone());
+            }
+
+            (updated != metadata).then(|| json!({ "metadata": updated }))
+        },
+    )
+    .await
+}
+
+/// How far `chunk boost` moves the embedding of a chunk towards the semantic phrase by default.
+const DEFAULT_DISTANCE_FACTOR: f64 = 0.5;
+
+/// A row of the CSV file passed to `chunk boost --file`.
+#[derive(Deserialize)]
+struct BoostRow {
+    id: String,
+    fulltext: Option<f64>,
+    fulltext_phrase: Option<String>,
+    semantic: Option<f64>,
+    semantic_phrase: Option<String>,
+}
+
+/// The body of the chunk update that boosts a chunk. The full-text boost uses the semantic phrase
+/// unless it has its own.
+fn boost_update(row: BoostRow) -> Result<Value, String> {
+    let id = row.id.trim();
+    if id.is_empty() {
+        return Err("the chunk ID is missing".to_string());
+    }
+    let fulltext_phrase = row.fulltext_phrase.filter(|phrase| !phrase.is_empty());
+    let semantic_phrase = row.semantic_phrase.filter(|phrase| !phrase.is_empty());
+
+    let mut update = if id.parse::<uuid::Uuid>().is_ok() {
+        json!({ "chunk_id": id })
+    } else {
+        json!({ "tracking_id": id })
+    };
+    if let Some(factor) = row.fulltext {
+        let phrase = fulltext_phrase
+            .or_else(|| semantic_phrase.clone())
+            .ok_or("a full-text boost needs a fulltext_phrase or a semantic_phrase")?;
+        update["fulltext_boost"] = json!({ "phrase": phrase, "boost_factor": factor });
+    } else if fulltext_phrase.is_some() {
+        return Err("a fulltext_phrase needs a fulltext factor".to_string());
+    }
+    if let Some(phrase) = semantic_phrase {
+        update["semantic_boost"] = json!({
+            "phrase": phrase,
+            "distance_factor": row.semantic.unwrap_or(DEFAULT_DISTANCE_FACTOR),
+        });
+    } else if row.semantic.is_some() {
+        return Err("a semantic factor needs a semantic_phrase".to_string());
+    }
+    if update.get("fulltext_boost").is_none() && update.get("semantic_boost").is_none() {
+        return Err("there is nothing to boost".to_string());
+    }
+
+    Ok(update)
+}
+
+/// Reads the boosts of `chunk boost --file`, failing on the first invalid row before any chunk
+/// is changed.
+fn read_boosts(path: &Path) -> Result<Vec<Value>, DefaultError> {
+    let read_error = |e: String| DefaultError {
+        message: format!("Could not read {}: {}", path.display(), e),
+    };
+
+    csv::Reader::from_path(path)
+        .map_err(|e| read_error(e.to_string()))?
+        .deserialize::<BoostRow>()
+        .enumerate()
+        .map(|(i, row)| {
+            // The header is line 1.
+            let line = i + 2;
+            row.map_err(|e| read_error(e.to_string())).and_then(|row| {
+                boost_update(row).map_err(|e| read_error(format!("line {}: {}", line, e)))
+            })
+        })
+        .collect()
+}
+
+/// Boosts a chunk for a phrase in full-text and semantic searches, or every chunk listed in a CSV
+/// file, so ranking adjustments can be scripted.
+pub async fn boost_chunks(
+    settings: TrieveConfiguration,
+    boost: BoostChunks,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let updates = match &boost.file {
+        Some(path) => read_boosts(path)?,
+        None => vec![boost_update(BoostRow {
+            id: boost.id.clone().unwrap_or_default(),
+            fulltext: boost.fulltext,
+            fulltext_phrase: boost.fulltext_phrase.clone(),
+            semantic: boost.semantic,
+            semantic_phrase: boost.semantic_phrase.clone(),
+        })
+        .map_err(|e| DefaultError {
+            message: format!(
+                "Invalid boost: {}. Pass --fulltext with a phrase or --semantic-phrase",
+                e
+            ),
+        })?],
+    };
+
+    if boost.dry_run {
+        for update in &updates {
+            println!("{}", update);
+        }
+        return Ok(());
+    }
+
+    let dataset_id = require_dataset(boost.dataset_id.clone(), &settings)?;
+    if boost.file.is_some() {
+        guard::confirm_destructive(&format!(
+            "boost {} chunks of dataset {}",
+            updates.len(),
+            dataset_id
+        ))?;
+    }
+    let max_in_flight = settings
+        .upload_concurrency
+        .unwrap_or(MAX_CONCURRENT_BATCHES)
+        .max(1);
+    let mut in_flight = JoinSet::new();
+    let mut failed = 0;
+
+    for update in updates.iter().cloned() {
+        while in_flight.len() >= max_in_flight {
+            if let Some(finished) = in_flight.join_next().await {
+                failed += report_chunk_update(finished.unwrap());
+            }
+        }
+
+        let id = update["chunk_id"]
+            .as_str()
+            .or(update["tracking_id"].as_str())
+            .unwrap_or_default()
+            .to_string();
+        let settings = settings.clone();
+        let dataset_id = dataset_id.clone();
+        in_flight.spawn(async move {
+            let result = Rest::new(&settings)
+                .dataset(&dataset_id)
+                .put::<Value>("/api/chunk", &update)
+                .await;
+            (id, result)
+        });
+    }
+    while let Some(finished) = in_flight.join_next().await {
+        failed += report_chunk_update(finished.unwrap());
+    }
+
+    let boosted = updates.len() - failed;
+    match (&boost.file, &boost.id) {
+        (Some(file), _) => audit::record(
+            &settings,
+            "chunk boost",
+            Some(&dataset_id),
+            json!({ "file": file, "boosted": boosted, "failed": failed }),
+        ),
+        (None, id) if failed == 0 => {
+            let mut details = updates[0].clone();
+            details["dataset_id"] = json!(dataset_id);
+            audit::record(&settings, "chunk boost", id.as_deref(), details);
+        }
+        _ => {}
+    }
+
+    if quiet() {
+        println!("{}", boosted);
+    } else {
+        println!(
+            "Boosted {} of {} chunks in dataset {}",
+            boosted,
+            updates.len(),
+            dataset_id
+        );
+    }
+
+    if failed > 0 {
+        return Err(DefaultError {
+            message: format!("{} chunks could not be boosted", failed),
+        });
+    }
+
+    Ok(())
+}
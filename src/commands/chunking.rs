@@ -0,0 +1,560 @@
+use scraper::{ElementRef, Html, Node};
+
+use crate::cli::ChunkingArgs;
+
+/// Elements whose content is never part of a chunk.
+const SKIPPED: [&str; 11] = [
+    "head", "script", "style", "noscript", "template", "svg", "iframe", "nav", "footer", "form",
+    "button",
+];
+/// Elements whose whole content is kept together as one block.
+const BLOCKS: [&str; 11] = [
+    "p",
+    "pre",
+    "li",
+    "blockquote",
+    "dt",
+    "dd",
+    "td",
+    "th",
+    "figcaption",
+    "caption",
+    "address",
+];
+/// Elements that are part of the surrounding text. Any other element ends the current block.
+const INLINE: [&str; 20] = [
+    "a", "abbr", "b", "bdi", "cite", "code", "data", "dfn", "em", "i", "kbd", "mark", "q", "s",
+    "samp", "small", "span", "strong", "sub", "sup",
+];
+
+/// How HTML is split into chunks. Token counts are estimates, see `estimate_tokens`.
+#[derive(Clone, Copy, Debug)]
+pub struct ChunkingOptions {
+    pub max_tokens: usize,
+    /// The number of tokens at the end of a chunk that are repeated at the start of the next chunk
+    /// of the same section.
+    pub overlap_tokens: usize,
+}
+
+impl Default for ChunkingOptions {
+    fn default() -> Self {
+        ChunkingOptions {
+            max_tokens: 400,
+            overlap_tokens: 40,
+        }
+    }
+}
+
+impl From<&ChunkingArgs> for ChunkingOptions {
+    fn from(args: &ChunkingArgs) -> Self {
+        ChunkingOptions {
+            max_tokens: args.max_tokens.max(1),
+            overlap_tokens: args.overlap,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TextChunk {
+    /// The headings of the section the chunk is in, outermost first.
+    pub headings: Vec<String>,
+    pub html: String,
+    pub text: String,
+    pub tokens: usize,
+}
+
+impl TextChunk {
+    /// The start of the text after the heading on a single line, for previews.
+    pub fn preview(&self, len: usize) -> String {
+        let body = self
+            .text
+            .strip_prefix(self.headings.last().map(String::as_str).unwrap_or_default())
+            .unwrap_or(&self.text)
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ");
+
+        let mut preview = body.chars().take(len).collect::<String>();
+        if body.chars().count() > len {
+            preview.push('…');
+        }
+        preview
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Block {
+    html: String,
+    text: String,
+    tokens: usize,
+}
+
+impl Block {
+    fn text(text: String) -> Self {
+        Block {
+            html: format!("<p>{}</p>", escape_html(&text)),
+            tokens: estimate_tokens(&text),
+            text,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Section {
+    headings: Vec<String>,
+    heading_html: Option<String>,
+    blocks: Vec<Block>,
+}
+
+impl Section {
+    fn titled(heading: Option<String>) -> Self {
+        Section {
+            heading_html: heading
+                .as_ref()
+                .map(|heading| format!("<h2>{}</h2>", escape_html(heading))),
+            headings: heading.into_iter().collect(),
+            blocks: vec![],
+        }
+    }
+}
+
+/// Walks the document in order, starting a new section at every heading and collecting the blocks
+/// of text in between.
+#[derive(Default)]
+struct Walker {
+    sections: Vec<Section>,
+    /// The headings enclosing the current position, with their level.
+    path: Vec<(usize, String)>,
+    /// Text outside of any block element, which becomes a paragraph of its own.
+    pending: String,
+}
+
+impl Walker {
+    fn walk(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.pending.push_str(text),
+                Node::Element(_) => {
+                    let Some(child) = ElementRef::wrap(child) else {
+                        continue;
+                    };
+                    let name = child.value().name();
+
+                    if SKIPPED.contains(&name) {
+                        continue;
+                    } else if name == "br" {
+                        self.pending.push(' ');
+                    } else if INLINE.contains(&name) {
+                        self.walk(child);
+                    } else if let Some(level) = heading_level(name) {
+                        self.flush();
+                        self.start_section(level, child);
+                    } else if BLOCKS.contains(&name) {
+                        self.flush();
+                        let text = normalize_whitespace(&child.text().collect::<String>());
+                        if !text.is_empty() {
+                            self.push(Block {
+                                html: child.html(),
+                                tokens: estimate_tokens(&text),
+                                text,
+                            });
+                        }
+                    } else {
+                        self.flush();
+                        self.walk(child);
+                        self.flush();
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn start_section(&mut self, level: usize, heading: ElementRef) {
+        let text = normalize_whitespace(&heading.text().collect::<String>());
+        self.path.retain(|(l, _)| *l < level);
+        self.path.push((level, text));
+        self.sections.push(Section {
+            headings: self.path.iter().map(|(_, text)| text.clone()).collect(),
+            heading_html: Some(heading.html()),
+            blocks: vec![],
+        });
+    }
+
+    fn flush(&mut self) {
+        let text = normalize_whitespace(&std::mem::take(&mut self.pending));
+        if !text.is_empty() {
+            self.push(Block::text(text));
+        }
+    }
+
+    fn push(&mut self, block: Block) {
+        if self.sections.is_empty() {
+            self.sections.push(Section::default());
+        }
+        if let Some(section) = self.sections.last_mut() {
+            section.blocks.push(block);
+        }
+    }
+}
+
+fn heading_level(name: &str) -> Option<usize> {
+    match name {
+        "h1" => Some(1),
+        "h2" => Some(2),
+        "h3" => Some(3),
+        "h4" => Some(4),
+        "h5" => Some(5),
+        "h6" => Some(6),
+        _ => None,
+    }
+}
+
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A rough token count, assuming about four characters per token as with English text and the
+/// common embedding model tokenizers.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Splits a block larger than `budget` into sentences, and sentences that are still too large into
+/// runs of words.
+fn split_block(block: Block, budget: usize) -> Vec<Block> {
+    let mut pieces = vec![];
+
+    for sentence in block
+        .text
+        .split_inclusive(['.', '!', '?'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        if estimate_tokens(sentence) <= budget {
+            pieces.push(Block::text(sentence.to_string()));
+            continue;
+        }
+
+        let mut words = String::new();
+        for word in sentence.split_whitespace() {
+            if !words.is_empty() && estimate_tokens(&words) + estimate_tokens(word) >= budget {
+                pieces.push(Block::text(std::mem::take(&mut words)));
+            }
+            if !words.is_empty() {
+                words.push(' ');
+            }
+            words.push_str(word);
+        }
+        if !words.is_empty() {
+            pieces.push(Block::text(words));
+        }
+    }
+
+    pieces
+}
+
+fn build_chunk(section: &Section, blocks: &[Block]) -> TextChunk {
+    let mut html = section
+        .heading_html
+        .iter()
+        .cloned()
+        .collect::<Vec<String>>();
+    let mut text = section
+        .headings
+        .last()
+        .cloned()
+        .into_iter()
+        .collect::<Vec<String>>();
+    html.extend(blocks.iter().map(|b| b.html.clone()));
+    text.extend(blocks.iter().map(|b| b.text.clone()));
+    let text = text.join("\n\n");
+
+    TextChunk {
+        headings: section.headings.clone(),
+        html: html.join("\n"),
+        tokens: estimate_tokens(&text),
+        text,
+    }
+}
+
+/// Packs the blocks of a section into chunks of at most `max_tokens`, each repeating up to
+/// `overlap_tokens` of trailing blocks from the chunk before it.
+fn pack_section(section: &Section, options: &ChunkingOptions) -> Vec<TextChunk> {
+    let heading_tokens = section
+        .headings
+        .last()
+        .map(|heading| estimate_tokens(heading))
+        .unwrap_or(0);
+    let budget = options.max_tokens.saturating_sub(heading_tokens).max(1);
+
+    let pieces = section.blocks.iter().cloned().flat_map(|block| {
+        if block.tokens > budget {
+            split_block(block, budget)
+        } else {
+            vec![block]
+        }
+    });
+
+    let mut chunks = vec![];
+    let mut current: Vec<Block> = vec![];
+    let mut current_tokens = 0;
+    // The number of blocks in `current` that are not repeated from the previous chunk.
+    let mut fresh = 0;
+
+    for piece in pieces {
+        if fresh > 0 && current_tokens + piece.tokens > budget {
+            chunks.push(build_chunk(section, &current));
+
+            let mut overlap = vec![];
+            let mut overlap_tokens = 0;
+            for block in current.iter().rev() {
+                if overlap_tokens + block.tokens > options.overlap_tokens {
+                    break;
+                }
+                overlap_tokens += block.tokens;
+                overlap.push(block.clone());
+            }
+            overlap.reverse();
+
+            while !overlap.is_empty() && overlap_tokens + piece.tokens > budget {
+                overlap_tokens -= overlap.remove(0).tokens;
+            }
+            current = overlap;
+            current_tokens = overlap_tokens;
+            fresh = 0;
+        }
+
+        current_tokens += piece.tokens;
+        current.push(piece);
+        fresh += 1;
+    }
+
+    if fresh > 0 {
+        chunks.push(build_chunk(section, &current));
+    }
+
+    chunks
+}
+
+/// Splits an HTML page into chunks that follow its headings, so a chunk never spans two sections,
+/// and keeps every chunk under `max_tokens`.
+pub fn split_html(html: &str, options: &ChunkingOptions) -> Vec<TextChunk> {
+    let document = Html::parse_document(html);
+    let mut walker = Walker::default();
+    walker.walk(document.root_element());
+    walker.flush();
+
+    walker
+        .sections
+        .iter()
+        .flat_map(|section| pack_section(section, options))
+        .collect()
+}
+
+/// Whether a paragraph of extracted text looks like a heading: a single short line that does not
+/// end like a sentence.
+fn is_text_heading(paragraph: &str) -> bool {
+    !paragraph.contains('\n')
+        && paragraph.chars().count() <= 80
+        && paragraph.chars().any(char::is_alphabetic)
+        && !paragraph.ends_with(['.', ',', ';', ':', '?', '!'])
+}
+
+/// Splits text on blank lines.
+fn paragraphs(text: &str) -> Vec<String> {
+    let mut paragraphs = vec![];
+    let mut current: Vec<&str> = vec![];
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() {
+            if !current.is_empty() {
+                paragraphs.push(current.join("\n"));
+                current.clear();
+            }
+        } else {
+            current.push(line);
+        }
+    }
+    if !current.is_empty() {
+        paragraphs.push(current.join("\n"));
+    }
+
+    paragraphs
+}
+
+/// Splits the pages of a document extracted as plain text into chunks, returned with the number of
+/// the page they are on. A chunk never spans two pages. Paragraphs are separated by blank lines,
+/// and a short line on its own is taken as a heading, which carries over to the following pages
+/// until the next one.
+pub fn split_pages(pages: &[String], options: &ChunkingOptions) -> Vec<(usize, TextChunk)> {
+    let mut heading: Option<String> = None;
+    let mut chunks = vec![];
+
+    for (i, page) in pages.iter().enumerate() {
+        let mut sections = vec![Section::titled(heading.clone())];
+
+        for paragraph in paragraphs(page) {
+            if is_text_heading(&paragraph) {
+                heading = Some(paragraph);
+                sections.push(Section::titled(heading.clone()));
+            } else if let Some(section) = sections.last_mut() {
+                section
+                    .blocks
+                    .push(Block::text(normalize_whitespace(&paragraph)));
+            }
+        }
+
+        chunks.extend(
+            sections
+                .iter()
+                .flat_map(|section| pack_section(section, options))
+                .map(|chunk| (i + 1, chunk)),
+        );
+    }
+
+    chunks
+}
+
+/// Splits text into runs of whole lines of at most `budget` tokens. Lines longer than the budget
+/// are kept whole.
+fn split_lines(text: &str, budget: usize) -> Vec<String> {
+    let mut runs = vec![];
+    let mut current = String::new();
+
+    for line in text.lines() {
+        if !current.is_empty() && estimate_tokens(&current) + estimate_tokens(line) > budget {
+            runs.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(line);
+    }
+    if !current.trim().is_empty() {
+        runs.push(current);
+    }
+
+    runs
+}
+
+/// Splits source code into chunks along top-level definitions: a line that is not indented and
+/// follows a blank line starts a new block, so functions, classes and their leading comments stay
+/// together. Blocks larger than `max_tokens` are split between lines.
+pub fn split_code(code: &str, language: &str, options: &ChunkingOptions) -> Vec<TextChunk> {
+    let mut section = Section::default();
+    let mut block: Vec<&str> = vec![];
+    let mut previous_blank = false;
+
+    let push_block = |block: &mut Vec<&str>, section: &mut Section| {
+        let text = block.join("\n");
+        block.clear();
+        for run in split_lines(text.trim_matches('\n'), options.max_tokens) {
+            section.blocks.push(Block {
+                html: String::new(),
+                tokens: estimate_tokens(&run),
+                text: run,
+            });
+        }
+    };
+
+    for line in code.lines() {
+        let top_level = !line.is_empty() && !line.starts_with([' ', '\t', '}', ')', ']']);
+        if top_level && previous_blank && !block.is_empty() {
+            push_block(&mut block, &mut section);
+        }
+        previous_blank = line.trim().is_empty();
+        block.push(line);
+    }
+    push_block(&mut block, &mut section);
+
+    pack_section(&section, options)
+        .into_iter()
+        .map(|mut chunk| {
+            chunk.text = chunk.text.trim_end().to_string();
+            chunk.html = format!(
+                "<pre><code class=\"language-{}\">{}</code></pre>",
+                language,
+                escape_html(&chunk.text)
+            );
+            chunk
+        })
+        .collect()
+}
+
+/// Splits Markdown into chunks by its `#` headings and paragraphs, keeping fenced code blocks
+/// whole.
+pub fn split_markdown(markdown: &str, options: &ChunkingOptions) -> Vec<TextChunk> {
+    let mut path: Vec<(usize, String)> = vec![];
+    let mut sections = vec![Section::default()];
+    let mut paragraph: Vec<&str> = vec![];
+    let mut in_fence = false;
+
+    let flush = |paragraph: &mut Vec<&str>, sections: &mut Vec<Section>| {
+        let text = paragraph.join("\n");
+        paragraph.clear();
+        if text.trim().is_empty() {
+            return;
+        }
+        let block = if text.trim_start().starts_with("```") {
+            Block {
+                html: format!("<pre>{}</pre>", escape_html(&text)),
+                tokens: estimate_tokens(&text),
+                text,
+            }
+        } else {
+            Block::text(normalize_whitespace(&text))
+        };
+        if let Some(section) = sections.last_mut() {
+            section.blocks.push(block);
+        }
+    };
+
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            if !in_fence {
+                flush(&mut paragraph, &mut sections);
+            }
+            in_fence = !in_fence;
+            paragraph.push(line);
+            if !in_fence {
+                flush(&mut paragraph, &mut sections);
+            }
+            continue;
+        }
+        if in_fence {
+            paragraph.push(line);
+            continue;
+        }
+
+        let level = line.chars().take_while(|c| *c == '#').count();
+        if (1..=6).contains(&level) && line[level..].starts_with(' ') {
+            flush(&mut paragraph, &mut sections);
+            let heading = line[level..].trim().to_string();
+            path.retain(|(l, _)| *l < level);
+            path.push((level, heading.clone()));
+            sections.push(Section {
+                headings: path.iter().map(|(_, text)| text.clone()).collect(),
+                heading_html: Some(format!("<h{0}>{1}</h{0}>", level, escape_html(&heading))),
+                blocks: vec![],
+            });
+        } else if line.trim().is_empty() {
+            flush(&mut paragraph, &mut sections);
+        } else {
+            paragraph.push(line);
+        }
+    }
+    flush(&mut paragraph, &mut sections);
+
+    sections
+        .iter()
+        .flat_map(|section| pack_section(section, options))
+        .collect()
+}
@@ -0,0 +1,99 @@
+use std::{future::Future, ops::Deref, time::Duration};
+
+use reqwest::StatusCode;
+use trieve_client::apis::{
+    configuration::{ApiKey, Configuration},
+    Error,
+};
+
+use super::{configure::TrieveConfiguration, http, output, rest::Rest, session};
+
+const MAX_RETRIES: u32 = 3;
+
+pub const USER_AGENT: &str = concat!("trieve-cli/", env!("CARGO_PKG_VERSION"));
+
+/// The version of the bundled `trieve_client`, which follows the API version of the server it was
+/// generated from. Keep it in sync with `Cargo.toml`.
+pub const TRIEVE_CLIENT_VERSION: &str = "0.11.6";
+
+/// The generated `trieve_client` configuration for a profile, built on the shared HTTP client.
+/// It derefs to `Configuration`, so `&client` can be passed straight to the generated API calls.
+pub struct ApiClient {
+    pub settings: TrieveConfiguration,
+    configuration: Configuration,
+}
+
+impl From<&TrieveConfiguration> for ApiClient {
+    fn from(settings: &TrieveConfiguration) -> Self {
+        ApiClient {
+            settings: settings.clone(),
+            configuration: Configuration {
+                base_path: settings.api_url.clone(),
+                user_agent: Some(USER_AGENT.to_string()),
+                client: http::client(),
+                api_key: Some(ApiKey {
+                    prefix: None,
+                    key: settings.api_key.clone(),
+                }),
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl Deref for ApiClient {
+    type Target = Configuration;
+
+    fn deref(&self) -> &Self::Target {
+        &self.configuration
+    }
+}
+
+impl ApiClient {
+    pub fn organization_id(&self) -> String {
+        self.settings.organization_id.to_string()
+    }
+
+    /// A raw REST client with the same credentials, for endpoints the generated client lacks.
+    pub fn rest(&self) -> Rest<'_> {
+        Rest::new(&self.settings)
+    }
+
+    /// Runs a generated API call, retrying it with exponential backoff when the server is rate
+    /// limiting (`429`) or temporarily unavailable (`503`), since neither processed the request.
+    /// Rejected credentials (`401`, `403`) are reported by `session::on_unauthorized`.
+    pub async fn retry<'a, T, E, F, Fut>(&'a self, call: F) -> Result<T, Error<E>>
+    where
+        F: Fn(&'a Configuration) -> Fut,
+        Fut: Future<Output = Result<T, Error<E>>>,
+    {
+        let mut attempt = 0;
+        loop {
+            let result = call(&self.configuration).await;
+            http::record_client_call(match &result {
+                Err(Error::ResponseError(response)) => Some(response.status),
+                _ => None,
+            });
+
+            match result {
+                Err(Error::ResponseError(response))
+                    if attempt < MAX_RETRIES
+                        && (response.status == StatusCode::TOO_MANY_REQUESTS
+                            || response.status == StatusCode::SERVICE_UNAVAILABLE) =>
+                {
+                    attempt += 1;
+                    http::record_client_retry();
+                    tokio::time::sleep(Duration::from_secs(2u64.pow(attempt))).await;
+                }
+                Err(Error::ResponseError(response)) => {
+                    output::record_api_error(response.status, &[]);
+                    // The report of invalid credentials is recorded as the failure of the
+                    // command, so the error of the response is all that needs returning.
+                    let _ = session::on_unauthorized(response.status).await;
+                    return Err(Error::ResponseError(response));
+                }
+                result => return result,
+            }
+        }
+    }
+}
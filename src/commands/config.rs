@@ -0,0 +1,357 @@
+use std::{collections::BTreeMap, env, fmt, sync::OnceLock};
+
+use tabled::{builder::Builder, settings::Style};
+
+use crate::cli::{GetConfig, SetConfig, UnsetConfig};
+
+use super::{
+    configure::{load_profiles, TrieveConfiguration},
+    i18n::tr_args,
+    output::quiet,
+};
+
+/// A profile setting that can be read and changed with `trieve config`.
+struct ConfigKey {
+    name: &'static str,
+    description: &'static str,
+    /// The environment variables overriding the profile value, in order of precedence.
+    env: &'static [&'static str],
+    /// Whether the value is masked in `trieve config list`.
+    secret: bool,
+    get: fn(&TrieveConfiguration) -> Option<String>,
+    /// Sets the value, or resets it to the default when `None`.
+    set: fn(&mut TrieveConfiguration, Option<&str>) -> Result<(), String>,
+}
+
+fn parse_positive<T: std::str::FromStr + PartialOrd + Default>(
+    key: &str,
+    value: &str,
+) -> Result<T, String> {
+    value
+        .parse::<T>()
+        .ok()
+        .filter(|value| *value > T::default())
+        .ok_or_else(|| format!("{} must be a positive number", key))
+}
+
+const KEYS: &[ConfigKey] = &[
+    ConfigKey {
+        name: "api_key",
+        description: "The API key requests are authenticated with",
+        env: &["TRIEVE_API_KEY"],
+        secret: true,
+        get: |settings| Some(settings.api_key.clone()).filter(|key| !key.is_empty()),
+        set: |settings, value| {
+            settings.api_key = value.unwrap_or_default().to_string();
+            Ok(())
+        },
+    },
+    ConfigKey {
+        name: "organization_id",
+        description: "The organization requests are made in",
+        env: &["TRIEVE_ORGANIZATION_ID", "TRIEVE_ORG_ID"],
+        secret: false,
+        get: |settings| {
+            Some(settings.organization_id.to_string())
+                .filter(|_| !settings.organization_id.is_nil())
+        },
+        set: |settings, value| {
+            settings.organization_id = value
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|_| format!("'{}' is not an organization ID", value))
+                })
+                .transpose()?
+                .unwrap_or_default();
+            Ok(())
+        },
+    },
+    ConfigKey {
+        name: "api_url",
+        description: "The URL of the Trieve server",
+        env: &["TRIEVE_API_URL"],
+        secret: false,
+        get: |settings| Some(settings.api_url.clone()),
+        set: |settings, value| {
+            let value = value.unwrap_or("https://api.trieve.ai");
+            if !value.starts_with("http://") && !value.starts_with("https://") {
+                return Err("api_url must start with http:// or https://".to_string());
+            }
+            settings.api_url = value.trim_end_matches('/').to_string();
+            Ok(())
+        },
+    },
+    ConfigKey {
+        name: "dataset",
+        description: "The dataset used by chunk, search and ask commands when none is passed",
+        env: &["TRIEVE_DATASET_ID"],
+        secret: false,
+        get: |settings| settings.default_dataset_id.clone(),
+        set: |settings, value| {
+            if let Some(value) =
+                value.filter(|value| !settings.dataset_aliases.contains_key(*value))
+            {
+                value
+                    .parse::<uuid::Uuid>()
+                    .map_err(|_| format!("'{}' is not a dataset ID or alias", value))?;
+            }
+            settings.default_dataset_id = value.map(str::to_string);
+            Ok(())
+        },
+    },
+    ConfigKey {
+        name: "timeout",
+        description: "Request timeout in seconds, unlimited when not set",
+        env: &["TRIEVE_TIMEOUT_SECS"],
+        secret: false,
+        get: |settings| settings.timeout_secs.map(|timeout| timeout.to_string()),
+        set: |settings, value| {
+            settings.timeout_secs = value
+                .map(|value| parse_positive("timeout", value))
+                .transpose()?;
+            Ok(())
+        },
+    },
+    ConfigKey {
+        name: "concurrency",
+        description:
+            "The number of chunk batches uploaded at once (adapted to the server when not set)",
+        env: &["TRIEVE_CONCURRENCY"],
+        secret: false,
+        get: |settings| settings.upload_concurrency.map(|c| c.to_string()),
+        set: |settings, value| {
+            settings.upload_concurrency = value
+                .map(|value| parse_positive("concurrency", value))
+                .transpose()?;
+            Ok(())
+        },
+    },
+];
+
+/// Where the effective value of a setting comes from.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Source {
+    Flag,
+    Env(&'static str),
+    Profile,
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Flag => write!(f, "flag"),
+            Source::Env(var) => write!(f, "env ({})", var),
+            Source::Profile => write!(f, "profile"),
+            Source::Default => write!(f, "default"),
+        }
+    }
+}
+
+static SOURCES: OnceLock<BTreeMap<&'static str, Source>> = OnceLock::new();
+
+/// Where the effective value of a setting came from, as determined by `resolve`.
+pub fn source_of(key: &str) -> Source {
+    SOURCES
+        .get()
+        .and_then(|sources| sources.get(key))
+        .copied()
+        .unwrap_or(Source::Default)
+}
+
+/// Builds the settings a command runs with. Each setting is taken from the first layer that sets
+/// it: a command line flag, then its environment variables, then the profile, then the default.
+pub fn resolve(
+    profile: Option<&TrieveConfiguration>,
+    flags: &[(&str, String)],
+) -> Result<TrieveConfiguration, String> {
+    let mut settings = profile.cloned().unwrap_or_default();
+    let mut sources = BTreeMap::new();
+
+    for key in KEYS {
+        let flag = flags.iter().find(|(name, _)| *name == key.name);
+        let env_value = key.env.iter().find_map(|var| {
+            env::var(var)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .map(|value| (*var, value))
+        });
+
+        let source = if let Some((_, value)) = flag {
+            (key.set)(&mut settings, Some(value))?;
+            Source::Flag
+        } else if let Some((var, value)) = env_value {
+            (key.set)(&mut settings, Some(&value)).map_err(|e| format!("{}: {}", var, e))?;
+            Source::Env(var)
+        } else if profile.is_some_and(|profile| (key.get)(profile).is_some()) {
+            Source::Profile
+        } else {
+            Source::Default
+        };
+        sources.insert(key.name, source);
+    }
+
+    let _ = SOURCES.set(sources);
+    Ok(settings)
+}
+
+fn find_key(name: &str) -> Result<&'static ConfigKey, String> {
+    KEYS.iter().find(|key| key.name == name).ok_or_else(|| {
+        format!(
+            "Unknown setting '{}'. Available settings: {}",
+            name,
+            KEYS.iter()
+                .map(|key| key.name)
+                .collect::<Vec<&str>>()
+                .join(", ")
+        )
+    })
+}
+
+pub fn profile_required(profile_name: Option<String>) -> Result<String, String> {
+    profile_name.ok_or_else(|| {
+        "Settings are stored in profiles, which are not used when TRIEVE_NO_PROFILE is set"
+            .to_string()
+    })
+}
+
+/// Changes a setting of the profile and saves it.
+pub fn set_profile_setting(
+    profile_name: &str,
+    key: &str,
+    value: Option<&str>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let key = find_key(key)?;
+    let mut profiles = load_profiles()?;
+    let profile = profiles
+        .iter_mut()
+        .find(|p| p.name == profile_name)
+        .ok_or_else(|| format!("Profile '{}' not found.", profile_name))?;
+
+    (key.set)(&mut profile.settings, value)?;
+
+    // The key of each organization is also stored in `api_keys`, which `organization switch`
+    // reads, so it is kept in step with the key and organization of the settings.
+    let organization_id = profile.settings.organization_id;
+    match key.name {
+        "api_key" if !organization_id.is_nil() => {
+            if profile.settings.api_key.is_empty() {
+                profile.api_keys.remove(&organization_id);
+            } else {
+                profile
+                    .api_keys
+                    .insert(organization_id, profile.settings.api_key.clone());
+            }
+        }
+        "organization_id" => profile.settings.api_key = profile.api_key_for(organization_id),
+        _ => {}
+    }
+    confy::store("trieve", "profiles", profiles)?;
+
+    Ok(())
+}
+
+pub fn get_config(
+    get: GetConfig,
+    settings: &TrieveConfiguration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let key = find_key(&get.key)?;
+    if let Some(value) = (key.get)(settings) {
+        println!("{}", value);
+    }
+
+    Ok(())
+}
+
+pub fn set_config(
+    set: SetConfig,
+    profile_name: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let profile_name = profile_required(profile_name)?;
+    let key = find_key(&set.key)?;
+    set_profile_setting(&profile_name, key.name, Some(&set.value))?;
+    if quiet() {
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        tr_args(
+            "config-set",
+            &[
+                ("key", key.name.to_string()),
+                ("value", set.value),
+                ("profile", profile_name)
+            ]
+        )
+    );
+
+    Ok(())
+}
+
+pub fn unset_config(
+    unset: UnsetConfig,
+    profile_name: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let profile_name = profile_required(profile_name)?;
+    let key = find_key(&unset.key)?;
+    set_profile_setting(&profile_name, key.name, None)?;
+    if quiet() {
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        tr_args(
+            "config-unset",
+            &[("key", key.name.to_string()), ("profile", profile_name)]
+        )
+    );
+
+    Ok(())
+}
+
+pub fn list_config(
+    settings: &TrieveConfiguration,
+    profile_name: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if quiet() {
+        for key in KEYS {
+            if let Some(value) = (key.get)(settings).filter(|_| !key.secret) {
+                println!("{}={}", key.name, value);
+            }
+        }
+        return Ok(());
+    }
+
+    let mut builder = Builder::default();
+
+    builder.push_record(["Setting", "Value", "Source", "Description"]);
+
+    for key in KEYS {
+        let value = (key.get)(settings).unwrap_or_default();
+        builder.push_record([
+            key.name.to_string(),
+            if key.secret && !value.is_empty() {
+                "********".to_string()
+            } else {
+                value
+            },
+            source_of(key.name).to_string(),
+            key.description.to_string(),
+        ]);
+    }
+
+    let table = builder.build().with(Style::rounded()).to_string();
+    match profile_name {
+        Some(profile_name) => println!(
+            "{}",
+            tr_args("config-list-header", &[("profile", profile_name)])
+        ),
+        None => println!("Settings from the environment:"),
+    }
+    println!("{}", table);
+
+    Ok(())
+}
@@ -1,19 +1,29 @@
 use std::{
-    env, fmt,
+    collections::BTreeMap,
+    env, fmt, fs,
     ops::{Deref, DerefMut},
+    path::{Path, PathBuf},
 };
 
-use crate::{commands::login_server::server, Login};
-use inquire::{Confirm, Text};
-use serde::{Deserialize, Serialize};
-use tokio::sync::mpsc;
-use trieve_client::{
-    apis::{
-        auth_api::get_me,
-        configuration::{ApiKey, Configuration},
+use crate::{
+    cli::Login,
+    commands::{
+        api::TrieveApi,
+        browser,
+        client::ApiClient,
+        dataset::DefaultError,
+        http,
+        i18n::{tr, tr_args},
+        login_server::{CallbackServer, DEFAULT_CALLBACK_PORT, LOGIN_TIMEOUT},
+        output::{ensure_interactive, fail, login_required, EXIT_ERROR},
+        picker::pick,
     },
-    models::{Organization, SlimUser},
 };
+use inquire::{Confirm, Text};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use trieve_client::models::{Organization, SlimUser};
 use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
@@ -21,6 +31,22 @@ pub struct TrieveConfiguration {
     pub api_key: String,
     pub organization_id: uuid::Uuid,
     pub api_url: String,
+    /// Request timeout in seconds, unlimited when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// The number of chunk batches uploaded at once. When not set, it starts at
+    /// `MAX_CONCURRENT_BATCHES` and adapts to the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_concurrency: Option<usize>,
+    /// The dataset used by chunk, search and ask commands when none is passed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_dataset_id: Option<String>,
+    /// Names for datasets, accepted wherever a dataset ID is
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dataset_aliases: BTreeMap<String, String>,
+    /// Names for organizations, accepted by `organization switch`, `update` and `delete`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub organization_aliases: BTreeMap<String, String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -28,6 +54,13 @@ pub struct TrieveProfileInner {
     pub name: String,
     pub settings: TrieveConfiguration,
     pub selected: bool,
+    /// The API key to use for each organization, for accounts with keys scoped to a single
+    /// organization. `settings.api_key` is the key of the current organization.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub api_keys: BTreeMap<Uuid, String>,
+    /// Destructive commands run with a protected profile must be confirmed by typing its name.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub protected: bool,
 }
 
 impl Default for TrieveProfileInner {
@@ -36,23 +69,72 @@ impl Default for TrieveProfileInner {
             name: "default".to_string(),
             settings: TrieveConfiguration::default(),
             selected: false,
+            api_keys: BTreeMap::new(),
+            protected: false,
         }
     }
 }
 
+impl TrieveProfileInner {
+    /// The API key stored for an organization, falling back to the key of the current one.
+    pub fn api_key_for(&self, organization_id: Uuid) -> String {
+        self.api_keys
+            .get(&organization_id)
+            .cloned()
+            .unwrap_or_else(|| self.settings.api_key.clone())
+    }
+}
+
+/// The layout of the profiles file written by this version of the CLI:
+///
+/// 1. A single API key per profile. Files without a `version` have this layout.
+/// 2. API keys stored per organization in `api_keys`.
+pub const CONFIG_VERSION: u32 = 2;
+
+/// Upgrades the profiles from the layout `i + 1` to the next one.
+const MIGRATIONS: [fn(&mut TrieveProfile); CONFIG_VERSION as usize - 1] = [
+    // 1 -> 2: the single key becomes the key of the current organization.
+    |profiles| {
+        for profile in profiles.iter_mut() {
+            if !profile.settings.api_key.is_empty() && !profile.settings.organization_id.is_nil() {
+                profile
+                    .api_keys
+                    .entry(profile.settings.organization_id)
+                    .or_insert_with(|| profile.settings.api_key.clone());
+            }
+        }
+    },
+];
+
+fn legacy_config_version() -> u32 {
+    1
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TrieveProfile {
+    #[serde(default = "legacy_config_version")]
+    pub version: u32,
     pub inner: Vec<TrieveProfileInner>,
 }
 
 impl Default for TrieveProfile {
     fn default() -> Self {
         TrieveProfile {
+            version: CONFIG_VERSION,
             inner: vec![TrieveProfileInner::default()],
         }
     }
 }
 
+impl From<Vec<TrieveProfileInner>> for TrieveProfile {
+    fn from(inner: Vec<TrieveProfileInner>) -> Self {
+        TrieveProfile {
+            version: CONFIG_VERSION,
+            inner,
+        }
+    }
+}
+
 impl Deref for TrieveProfile {
     type Target = Vec<TrieveProfileInner>;
 
@@ -73,29 +155,100 @@ impl Default for TrieveConfiguration {
             api_key: "".to_string(),
             organization_id: uuid::Uuid::nil(),
             api_url: "https://api.trieve.ai".to_string(),
+            timeout_secs: None,
+            upload_concurrency: None,
+            default_dataset_id: None,
+            dataset_aliases: BTreeMap::new(),
+            organization_aliases: BTreeMap::new(),
         }
     }
 }
 
-impl TrieveConfiguration {
-    pub(crate) fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
-        let api_key = env::var("TRIEVE_API_KEY").unwrap_or_else(|_| String::new());
-        let organization_id = env::var("TRIEVE_ORG_ID").unwrap_or_else(|_| String::new());
-        let api_url =
-            env::var("TRIEVE_API_URL").unwrap_or_else(|_| "https://api.trieve.ai".to_string());
+/// Fails with `output::login_required` unless the profile holds an API key and an organization.
+pub fn require_login(settings: &TrieveConfiguration) -> Result<(), DefaultError> {
+    if settings.organization_id.is_nil() || settings.api_key.is_empty() {
+        return Err(login_required());
+    }
+    Ok(())
+}
 
-        let organization_id = if !organization_id.is_empty() {
-            organization_id.parse()?
-        } else {
-            Uuid::nil()
-        };
+/// Copies the profiles file next to itself, returning the path of the copy.
+fn backup_profiles(path: &Path) -> Result<PathBuf, std::io::Error> {
+    let backup = path.with_extension(format!(
+        "toml.{}.bak",
+        chrono::Local::now().format("%Y%m%d%H%M%S")
+    ));
+    fs::copy(path, &backup)?;
+    Ok(backup)
+}
 
-        Ok(Self {
-            api_key,
-            organization_id,
-            api_url,
-        })
+/// Loads the saved profiles, upgrading files written by older versions of the CLI through
+/// `MIGRATIONS`. A file that cannot be parsed is backed up and replaced by the defaults, rather
+/// than silently losing the profiles it held. A file written by a newer version of the CLI is
+/// left alone and fails to load.
+pub fn load_profiles() -> Result<TrieveProfile, DefaultError> {
+    let path = confy::get_configuration_file_path("trieve", "profiles").ok();
+
+    let mut profiles: TrieveProfile = match confy::load("trieve", "profiles") {
+        Ok(profiles) => profiles,
+        Err(e) => {
+            eprintln!(
+                "{}",
+                tr_args("config-unreadable", &[("error", e.to_string())])
+            );
+            if let Some(backup) = path.as_deref().and_then(|path| backup_profiles(path).ok()) {
+                eprintln!(
+                    "{}",
+                    tr_args(
+                        "config-backed-up",
+                        &[("path", backup.display().to_string())]
+                    )
+                );
+            }
+            return Ok(TrieveProfile::default());
+        }
+    };
+
+    if profiles.version > CONFIG_VERSION {
+        return Err(fail(
+            EXIT_ERROR,
+            "config_too_new",
+            tr_args(
+                "config-too-new",
+                &[
+                    ("version", profiles.version.to_string()),
+                    ("supported", CONFIG_VERSION.to_string()),
+                ],
+            ),
+        ));
     }
+
+    if profiles.version < CONFIG_VERSION {
+        if let Some(path) = &path {
+            let _ = backup_profiles(path);
+        }
+        for migration in &MIGRATIONS[profiles.version.max(1) as usize - 1..] {
+            migration(&mut profiles);
+        }
+        profiles.version = CONFIG_VERSION;
+
+        if let Err(e) = confy::store("trieve", "profiles", &profiles) {
+            eprintln!("Error saving configuration: {:?}", e);
+        }
+    }
+
+    Ok(profiles)
+}
+
+/// Returns a directory next to the confy profiles file, creating it if needed.
+pub fn config_subdir(name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let config_path = confy::get_configuration_file_path("trieve", "profiles")?;
+    let dir = config_path
+        .parent()
+        .ok_or("Could not determine the Trieve configuration directory")?
+        .join(name);
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -107,91 +260,200 @@ impl fmt::Display for OrgDTO {
     }
 }
 
-pub async fn get_user(api_url: String, api_key: String) -> SlimUser {
-    let configuration = Configuration {
-        base_path: api_url.clone(),
-        api_key: Some(ApiKey {
-            prefix: None,
-            key: api_key.clone(),
-        }),
-        ..Default::default()
+pub async fn get_user(settings: &TrieveConfiguration) -> Result<SlimUser, DefaultError> {
+    ApiClient::from(settings)
+        .me()
+        .await
+        .map_err(|e| DefaultError {
+            message: format!("Error getting user: {}", e.message),
+        })
+}
+
+/// Whether a browser can be opened on the machine of the user. In an SSH session it would open
+/// on the remote machine, unless a Linux display is forwarded. A `BROWSER` command is always used.
+fn has_browser() -> bool {
+    if browser::configured() {
+        return true;
+    }
+    if env::var_os("SSH_CONNECTION").is_some() {
+        return cfg!(target_os = "linux")
+            && (env::var_os("DISPLAY").is_some() || env::var_os("WAYLAND_DISPLAY").is_some());
+    }
+    true
+}
+
+/// Prints the login URL and reads the API key the user pastes back, for machines that cannot
+/// open a browser or receive the login callback.
+fn prompt_api_key(api_url: &str) -> String {
+    let auth_url = format!(
+        "{api_url}/api/auth?redirect_uri={api_url}/auth/cli%3Fhost={api_url}",
+        api_url = api_url
+    );
+
+    println!("\n{}", tr_args("login-visit-url", &[("url", auth_url)]));
+
+    inquire::Password::new(&tr("login-paste-api-key"))
+        .without_confirmation()
+        .with_display_mode(inquire::PasswordDisplayMode::Masked)
+        .prompt()
+        .unwrap()
+        .trim()
+        .to_string()
+}
+
+/// Adds a pasted browser session to `request`: a JWT as a bearer token, and anything else as the
+/// session cookie, whether it was copied as a `Cookie` header, as `vault=<value>` or as the value
+/// alone.
+fn with_session(request: reqwest::RequestBuilder, token: &str) -> reqwest::RequestBuilder {
+    let token = token.trim();
+    if let Some(jwt) = token.strip_prefix("Bearer ") {
+        return request.bearer_auth(jwt.trim());
+    }
+    let is_jwt = token.starts_with("eyJ") && token.split('.').count() == 3;
+    if is_jwt {
+        request.bearer_auth(token)
+    } else if token.contains("vault=") {
+        request.header("Cookie", token)
+    } else {
+        request.header("Cookie", format!("vault={}", token))
+    }
+}
+
+/// Exchanges a session token pasted from a browser for a new API key, for servers behind SSO
+/// whose login cannot redirect to the local callback server.
+async fn api_key_from_session(api_url: &str, token: String) -> Result<String, DefaultError> {
+    let token = if token.trim().is_empty() {
+        ensure_interactive("--session-token")?;
+        println!(
+            "\n{}",
+            tr_args(
+                "login-session-instructions",
+                &[("url", api_url.to_string())]
+            )
+        );
+        inquire::Password::new(&tr("login-paste-session-token"))
+            .without_confirmation()
+            .with_display_mode(inquire::PasswordDisplayMode::Masked)
+            .prompt()
+            .unwrap()
+    } else {
+        token
     };
 
-    get_me(&configuration)
+    let request = with_session(
+        http::request(Method::POST, &format!("{}/api/user/api_key", api_url)),
+        &token,
+    );
+    http::send(request, Some(&json!({ "name": "Trieve CLI", "role": 1 })))
         .await
-        .map_err(|e| {
-            eprintln!("Error getting user: {:?}", e);
-            std::process::exit(1);
+        .and_then(|response| response.json::<Value>())
+        .and_then(|response| {
+            response["api_key"]
+                .as_str()
+                .filter(|api_key| !api_key.is_empty())
+                .map(str::to_string)
+                .ok_or_else(|| DefaultError {
+                    message: "the response has no API key".to_string(),
+                })
+        })
+        .map_err(|e| DefaultError {
+            message: tr_args("login-session-rejected", &[("error", e.message)]),
         })
-        .unwrap()
 }
 
-async fn configure(api_url: String, mut api_key: Option<String>) -> TrieveConfiguration {
+async fn configure(
+    api_url: String,
+    mut api_key: Option<String>,
+    callback_port: Option<u16>,
+    no_browser: bool,
+) -> Result<TrieveConfiguration, DefaultError> {
+    if api_key.is_none() && (no_browser || !has_browser()) {
+        api_key = Some(prompt_api_key(&api_url));
+    }
+
     if api_key.is_none() {
-        let (tx, mut rx) = mpsc::channel::<String>(100);
-
-        let server = tokio::spawn(async move {
-            server(tx.clone()).await.map_err(|e| {
-                eprintln!("Error starting server: {:?}", e);
-                std::process::exit(1);
-            })
-        });
-
-        let auth_url = format!(
-            "{api_url}/api/auth?redirect_uri={api_url}/auth/cli%3Fhost={api_url}",
-            api_url = api_url
-        );
+        let callback = CallbackServer::start(callback_port)
+            .await
+            .map_err(|e| DefaultError {
+                message: format!(
+                    "{} {}",
+                    tr_args(
+                        "login-callback-port-error",
+                        &[(
+                            "port",
+                            callback_port.unwrap_or(DEFAULT_CALLBACK_PORT).to_string()
+                        )]
+                    ),
+                    e
+                ),
+            })?;
+
+        let auth_url = callback.login_url(&api_url);
 
         let _ = Text::new("Press Enter to authenticate in browser: ")
             .prompt()
             .unwrap();
 
-        if open::that(auth_url.clone()).is_err() {
-            eprintln!("Error opening browser. Please visit the URL manually.");
+        if browser::from_env().open(&auth_url).is_err() {
+            eprintln!("{}", tr("login-browser-error"));
             println!(
-                "\nPlease go to the following URL to get a Trieve API Key: {}",
-                auth_url
+                "\n{}",
+                tr_args("login-visit-url", &[("url", auth_url.clone())])
             );
         }
 
-        api_key = Some(rx.recv().await.unwrap());
-
-        server.abort();
+        api_key = match callback.api_key(LOGIN_TIMEOUT).await {
+            Some(api_key) => Some(api_key),
+            None => {
+                return Err(DefaultError {
+                    message: tr("login-timeout"),
+                })
+            }
+        };
     }
 
-    let user = get_user(api_url.clone(), api_key.clone().unwrap()).await;
-
-    println!("\nWelcome, {}!", user.name.unwrap().unwrap());
+    let user = get_user(&TrieveConfiguration {
+        api_key: api_key.clone().unwrap(),
+        api_url: api_url.clone(),
+        ..Default::default()
+    })
+    .await?;
+
+    println!(
+        "\n{}",
+        tr_args(
+            "login-welcome-user",
+            &[("name", user.name.unwrap().unwrap())]
+        )
+    );
     let orgs = user
         .orgs
         .iter()
         .map(|org| OrgDTO(org.clone()))
         .collect::<Vec<OrgDTO>>();
 
-    let selected_organization = inquire::Select::new("Select an organization to use:", orgs)
-        .prompt()
-        .unwrap();
+    let selected_organization = pick("Select an organization to use:", "organizations", orgs)?;
 
-    TrieveConfiguration {
+    Ok(TrieveConfiguration {
         api_key: api_key.unwrap(),
         organization_id: selected_organization.0.id,
         api_url: api_url.clone(),
-    }
-}
-
-#[derive(Serialize, Deserialize, Clone)]
-struct ApiKeyResponse {
-    api_key: String,
+        timeout_secs: None,
+        upload_concurrency: None,
+        default_dataset_id: None,
+        dataset_aliases: BTreeMap::new(),
+        organization_aliases: BTreeMap::new(),
+    })
 }
 
-pub async fn login(init: Login, settings: TrieveConfiguration) {
-    let api_key = init.api_key;
+pub async fn login(init: Login, settings: TrieveConfiguration) -> Result<(), DefaultError> {
+    let mut api_key = init.api_key;
     let mut api_url = init.api_url;
 
     if settings.api_key.is_empty() && settings.organization_id.is_nil() {
-        println!("Welcome to the Trieve CLI! Let's get started by configuring your API Key and Organization ID.");
+        println!("{}", tr("login-welcome"));
     } else {
-        println!("Welcome back to the Trieve CLI! Let's update your configuration.");
+        println!("{}", tr("login-welcome-back"));
     }
 
     if api_url.is_none() {
@@ -213,7 +475,12 @@ pub async fn login(init: Login, settings: TrieveConfiguration) {
         }
     }
 
-    let config = configure(api_url.unwrap().clone(), api_key).await;
+    let api_url = api_url.unwrap();
+    if let Some(token) = init.session_token {
+        api_key = Some(api_key_from_session(&api_url, token).await?);
+    }
+
+    let config = configure(api_url, api_key, init.callback_port, init.no_browser).await?;
 
     let profile_name = if init.profile_name.is_none() {
         let profile_name = Text::new("Enter a name for this profile:")
@@ -221,19 +488,19 @@ pub async fn login(init: Login, settings: TrieveConfiguration) {
             .prompt()
             .unwrap();
         println!(
-            "Configuration complete! Your profile has been saved as '{}'.",
-            profile_name
+            "{}",
+            tr_args("login-complete", &[("profile", profile_name.clone())])
         );
         profile_name
     } else {
         init.profile_name.unwrap()
     };
 
-    let mut profiles: TrieveProfile = confy::load("trieve", "profiles")
-        .map_err(|e| {
-            eprintln!("Error loading configuration: {:?}", e);
-        })
-        .unwrap_or_default();
+    let mut profiles = load_profiles()?;
+    // Logging in again keeps the profile protected.
+    let protected = profiles
+        .iter()
+        .any(|p| p.name == profile_name && p.protected);
 
     if profiles
         .iter()
@@ -244,7 +511,7 @@ pub async fn login(init: Login, settings: TrieveConfiguration) {
             .prompt();
 
         if !overwrite.unwrap() {
-            std::process::exit(0);
+            return Ok(());
         }
 
         profiles.retain(|p| p.name != profile_name);
@@ -255,14 +522,38 @@ pub async fn login(init: Login, settings: TrieveConfiguration) {
 
     profiles.push(TrieveProfileInner {
         name: profile_name,
+        api_keys: BTreeMap::from([(config.organization_id, config.api_key.clone())]),
         settings: config,
         selected: true,
+        protected,
     });
 
-    confy::store("trieve", "profiles", profiles)
-        .map_err(|e| {
-            eprintln!("Error saving configuration: {:?}", e);
-            std::process::exit(1);
-        })
-        .unwrap();
+    confy::store("trieve", "profiles", profiles).map_err(|e| DefaultError {
+        message: format!("Error saving configuration: {:?}", e),
+    })
+}
+
+/// Runs the browser login again for a profile whose API key was rejected, replacing its API key
+/// and organization while keeping its name, server URL and other settings.
+pub async fn relogin(
+    profile_name: &str,
+    settings: &TrieveConfiguration,
+) -> Result<(), DefaultError> {
+    let config = configure(settings.api_url.clone(), None, None, false).await?;
+
+    let mut profiles = load_profiles()?;
+
+    if let Some(profile) = profiles.iter_mut().find(|p| p.name == profile_name) {
+        // The rejected key is dropped for every organization it was stored for.
+        profile.api_keys.retain(|_, key| *key != settings.api_key);
+        profile
+            .api_keys
+            .insert(config.organization_id, config.api_key.clone());
+        profile.settings.api_key = config.api_key;
+        profile.settings.organization_id = config.organization_id;
+    }
+
+    confy::store("trieve", "profiles", profiles).map_err(|e| DefaultError {
+        message: format!("Error saving configuration: {:?}", e),
+    })
 }
@@ -1,28 +1,50 @@
-use chrono::NaiveDateTime;
+use clap::ValueEnum;
 use csv::ReaderBuilder;
 use inquire::Confirm;
+use reqwest::Method;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tabled::{builder::Builder, settings::Style};
-use trieve_client::{
-    apis::{
-        chunk_api::create_chunk,
-        chunk_group_api::create_chunk_group,
-        configuration::{ApiKey, Configuration},
-        dataset_api::{create_dataset, delete_dataset, get_datasets_from_organization},
-    },
-    models::{
-        ChunkReqPayload, CreateChunkGroupReqPayloadEnum, CreateDatasetRequest,
-        CreateSingleChunkGroupReqPayload, Dataset, DatasetAndUsage,
-    },
-};
+use trieve_client::models::{ChunkReqPayload, CreateDatasetRequest, Dataset, DatasetAndUsage};
 
-use crate::{AddSeedData, CreateDataset, DeleteDataset};
+use crate::cli::{
+    AddSeedData, CreateDataset, DatasetLimits, DatasetSortBy, DeleteDataset, InstallDemo,
+    ListDatasets, OutputFormat, SeedExample, UpdateDataset, UseDataset,
+};
 
-use super::configure::TrieveConfiguration;
-use std::{collections::HashSet, fmt};
+use super::{
+    alias::resolve_dataset,
+    api::TrieveApi,
+    audit,
+    backup::backup_dataset,
+    client::ApiClient,
+    config::{profile_required, set_profile_setting, source_of, Source},
+    configure::{require_login, TrieveConfiguration},
+    date::format_timestamp,
+    guard, http,
+    i18n::{tr, tr_args},
+    jobs,
+    mapping::ColumnMapping,
+    output::{ensure_interactive, format, quiet, success},
+    pager,
+    picker::pick,
+    rest::Rest,
+    style::id,
+    table::{self, Column},
+    templates::load_template,
+    upload::{
+        chunk_records, open_source, upload_chunk_stream, Checkpoint, ChunkRecords, UploadSummary,
+        CHUNKS_PER_BATCH,
+    },
+    validate::{self, RecordError},
+};
+use std::{
+    fmt,
+    io::{stdin, IsTerminal},
+    path::{Path, PathBuf},
+};
 
-struct DatasetAndUsageDTO(DatasetAndUsage);
+pub struct DatasetAndUsageDTO(pub DatasetAndUsage);
 
 impl fmt::Display for DatasetAndUsageDTO {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -39,76 +61,206 @@ struct DatasetUsage {
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct DefaultError {
-    message: String,
+    pub message: String,
+}
+
+impl fmt::Display for DefaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
 }
 
-async fn get_datasets_from_org(
+impl std::error::Error for DefaultError {}
+
+pub async fn get_datasets_from_org(
     settings: TrieveConfiguration,
 ) -> Result<Vec<DatasetAndUsage>, DefaultError> {
-    let config = Configuration {
-        base_path: settings.api_url,
-        api_key: Some(ApiKey {
-            prefix: None,
-            key: settings.api_key,
-        }),
-        ..Default::default()
-    };
+    get_datasets_page(&settings, None, None).await
+}
 
-    let result = get_datasets_from_organization(
-        &config,
-        &settings.organization_id.to_string().clone(),
-        &settings.organization_id.to_string(),
-        None,
-        None,
-    )
-    .await
-    .map_err(|e| DefaultError {
-        message: e.to_string(),
-    })?;
+/// Fetches the datasets of the current organization, letting the server skip the first `offset`
+/// and return at most `limit` of them.
+pub async fn get_datasets_page(
+    settings: &TrieveConfiguration,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<DatasetAndUsage>, DefaultError> {
+    ApiClient::from(settings).datasets(limit, offset).await
+}
 
-    Ok(result)
+/// The dataset passed on the command line, or else the default dataset of the profile, which is
+/// announced on stderr so it is clear which dataset the command runs against. Aliases are resolved
+/// to the dataset they stand for.
+pub fn dataset_or_default(
+    dataset_id: Option<String>,
+    settings: &TrieveConfiguration,
+) -> Option<String> {
+    let dataset_id = dataset_id.or_else(|| {
+        let dataset_id = settings.default_dataset_id.clone()?;
+        if quiet() {
+            return Some(dataset_id);
+        }
+        match source_of("dataset") {
+            Source::Env(var) => eprintln!(
+                "{}",
+                tr_args(
+                    "dataset-env-used",
+                    &[("dataset", dataset_id.clone()), ("var", var.to_string())]
+                )
+            ),
+            _ => eprintln!(
+                "{}",
+                tr_args("dataset-default-used", &[("dataset", dataset_id.clone())])
+            ),
+        }
+        Some(dataset_id)
+    });
+
+    dataset_id.map(|dataset_id| resolve_dataset(settings, &dataset_id))
 }
 
-pub async fn list_datasets(settings: TrieveConfiguration) -> Result<(), DefaultError> {
-    if settings.organization_id.to_string().is_empty() || settings.api_key.is_empty() {
-        eprintln!("Please login to the Trieve CLI with your credentials. Run `trieve login` to get started.");
-        std::process::exit(1);
-    }
+/// Like `dataset_or_default`, but fails when neither a dataset nor a default dataset is set.
+pub fn require_dataset(
+    dataset_id: Option<String>,
+    settings: &TrieveConfiguration,
+) -> Result<String, DefaultError> {
+    dataset_or_default(dataset_id, settings).ok_or_else(|| DefaultError {
+        message: tr("dataset-required"),
+    })
+}
 
-    let datasets = get_datasets_from_org(settings.clone()).await.map_err(|e| {
-        eprintln!("Error listing datasets: {}", e.message);
-        std::process::exit(1);
-    })?;
+/// Prompts for one of the datasets of the current organization and returns its ID.
+pub async fn select_dataset_id(
+    settings: &TrieveConfiguration,
+    message: &str,
+) -> Result<String, DefaultError> {
+    ensure_interactive("--dataset-id")?;
+    let datasets = get_datasets_from_org(settings.clone())
+        .await?
+        .into_iter()
+        .map(DatasetAndUsageDTO)
+        .collect::<Vec<_>>();
+
+    Ok(pick(message, "datasets", datasets)?
+        .0
+        .dataset
+        .id
+        .to_string())
+}
 
-    let mut builder = Builder::default();
+/// Changes fields of the `server_configuration` of a dataset and keeps the others, as the API
+/// replaces the whole configuration. Returns the dataset as updated by the server.
+pub async fn update_server_configuration(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    changes: serde_json::Map<String, serde_json::Value>,
+) -> Result<serde_json::Value, DefaultError> {
+    let rest = Rest::new(settings).dataset(dataset_id);
+    let current: serde_json::Value = rest.get(&format!("/api/dataset/{}", dataset_id)).await?;
+    let mut server_configuration = current["server_configuration"]
+        .as_object()
+        .cloned()
+        .unwrap_or_default();
+    server_configuration.extend(changes);
+
+    let body = json!({
+        "dataset_id": dataset_id,
+        "server_configuration": server_configuration,
+    });
+    rest.put("/api/dataset", &body).await
+}
 
-    builder.push_record(["ID", "Name", "Created At", "Updated At", "Chunk Count"]);
+/// The columns of `dataset list`.
+const DATASET_COLUMNS: [Column<DatasetAndUsage>; 5] = [
+    Column {
+        key: "id",
+        header: "ID",
+        value: |d| d.dataset.id.to_string(),
+    },
+    Column {
+        key: "name",
+        header: "Name",
+        value: |d| d.dataset.name.clone(),
+    },
+    Column {
+        key: "created",
+        header: "Created At",
+        value: |d| format_timestamp(&d.dataset.created_at),
+    },
+    Column {
+        key: "updated",
+        header: "Updated At",
+        value: |d| format_timestamp(&d.dataset.updated_at),
+    },
+    Column {
+        key: "chunks",
+        header: "Chunk Count",
+        value: |d| d.dataset_usage.chunk_count.to_string(),
+    },
+];
 
-    for dataset in datasets {
-        builder.push_record([
-            dataset.dataset.id.to_string(),
-            dataset.dataset.name,
-            dataset
-                .dataset
-                .created_at
-                .parse::<NaiveDateTime>()
-                .unwrap()
-                .date()
-                .to_string(),
-            dataset
-                .dataset
-                .updated_at
-                .parse::<NaiveDateTime>()
-                .unwrap()
-                .date()
-                .to_string(),
-            dataset.dataset_usage.chunk_count.to_string(),
-        ]);
+pub async fn list_datasets(
+    settings: TrieveConfiguration,
+    list: ListDatasets,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let limit = list.limit.map(i64::from);
+    let offset = list.offset.map(i64::from);
+
+    // Without sorting or filtering the server can paginate, otherwise every dataset is needed.
+    let datasets = if list.sort_by.is_none() && list.filter_name.is_none() {
+        get_datasets_page(&settings, limit, offset).await
+    } else {
+        get_datasets_from_org(settings.clone()).await
+    }?;
+
+    let datasets = if list.sort_by.is_none() && list.filter_name.is_none() {
+        datasets
+    } else {
+        let filter = list.filter_name.as_deref().map(str::to_lowercase);
+        let mut datasets = datasets
+            .into_iter()
+            .filter(|d| {
+                filter
+                    .as_ref()
+                    .is_none_or(|filter| d.dataset.name.to_lowercase().contains(filter))
+            })
+            .collect::<Vec<DatasetAndUsage>>();
+
+        match list.sort_by {
+            Some(DatasetSortBy::Name) => datasets.sort_by_key(|d| d.dataset.name.to_lowercase()),
+            Some(DatasetSortBy::Created) => {
+                datasets.sort_by(|a, b| b.dataset.created_at.cmp(&a.dataset.created_at))
+            }
+            Some(DatasetSortBy::Chunks) => datasets.sort_by(|a, b| {
+                b.dataset_usage
+                    .chunk_count
+                    .cmp(&a.dataset_usage.chunk_count)
+            }),
+            None => {}
+        }
+
+        datasets
+            .into_iter()
+            .skip(list.offset.unwrap_or(0) as usize)
+            .take(list.limit.map_or(usize::MAX, |limit| limit as usize))
+            .collect()
+    };
+
+    if quiet() {
+        return table::print_quiet(&DATASET_COLUMNS, &datasets, &["id"]);
     }
 
-    let table = builder.build().with(Style::rounded()).to_string();
-    println!("Datasets for organization: {}", settings.organization_id);
-    println!("{}", table);
+    let table = table::render(&DATASET_COLUMNS, &datasets)?;
+    pager::print(&format!(
+        "{}\n{}",
+        tr_args(
+            "dataset-list-header",
+            &[("organization", id(&settings.organization_id.to_string()))]
+        ),
+        table
+    ));
     Ok(())
 }
 
@@ -116,29 +268,23 @@ pub async fn create_trieve_dataset(
     settings: TrieveConfiguration,
     create: CreateDataset,
 ) -> Result<Dataset, DefaultError> {
-    if settings.organization_id.to_string().is_empty() || settings.api_key.is_empty() {
-        eprintln!("Please login to the Trieve CLI with your credentials. Run `trieve login` to get started.");
-        std::process::exit(1);
-    }
+    require_login(&settings)?;
 
     let mut name = create.name.clone();
     if create.name.is_none() {
+        ensure_interactive("--name")?;
         name = Some(inquire::Text::new("Dataset Name: ").prompt().unwrap());
     }
 
-    let config = Configuration {
-        base_path: settings.api_url,
-        api_key: Some(ApiKey {
-            prefix: None,
-            key: settings.api_key,
-        }),
-        ..Default::default()
-    };
-
-    let data = CreateDatasetRequest {
-        organization_id: settings.organization_id,
-        dataset_name: name.unwrap(),
-        server_configuration: Some(Some(serde_json::json!({
+    let server_configuration = match &create.template {
+        Some(template) => {
+            load_template(template)
+                .map_err(|e| DefaultError {
+                    message: e.to_string(),
+                })?
+                .server_configuration
+        }
+        None => serde_json::json!({
             "LLM_BASE_URL": "",
             "LLM_DEFAULT_MODEL": "",
             "EMBEDDING_BASE_URL": "https://embedding.trieve.ai",
@@ -150,21 +296,105 @@ pub async fn create_trieve_dataset(
             "DOCUMENT_DOWNLOAD_FEATURE": true,
             "COLLISIONS_ENABLED": false,
             "FULLTEXT_ENABLED": true,
-        }))),
+        }),
+    };
+
+    let data = CreateDatasetRequest {
+        organization_id: settings.organization_id,
+        dataset_name: name.unwrap(),
+        server_configuration: Some(Some(server_configuration)),
         tracking_id: None,
     };
 
-    let dataset = create_dataset(&config, &settings.organization_id.to_string().clone(), data)
-        .await
-        .map_err(|e| DefaultError {
-            message: e.to_string(),
-        })?;
+    let dataset = ApiClient::from(&settings).create_dataset(data).await?;
+    audit::record(
+        &settings,
+        "dataset create",
+        Some(&dataset.id.to_string()),
+        json!({ "name": dataset.name, "template": create.template }),
+    );
+
+    if quiet() {
+        println!("{}", dataset.id);
+        return Ok(dataset);
+    }
 
-    println!("Dataset created successfully!");
+    success(&tr("dataset-created"));
     println!();
-    println!("ID: {}", dataset.id);
-    println!("Name: {}", dataset.name);
-    println!("Organization ID: {}", dataset.organization_id);
+    println!(
+        "{}",
+        tr_args("dataset-field-id", &[("id", id(&dataset.id.to_string()))])
+    );
+    println!(
+        "{}",
+        tr_args("dataset-field-name", &[("name", dataset.name.clone())])
+    );
+    println!(
+        "{}",
+        tr_args(
+            "dataset-field-organization",
+            &[("organization", dataset.organization_id.to_string())]
+        )
+    );
+
+    Ok(dataset)
+}
+
+pub async fn update_trieve_dataset(
+    settings: TrieveConfiguration,
+    update: UpdateDataset,
+) -> Result<Dataset, DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = match update.dataset_id {
+        Some(dataset_id) => resolve_dataset(&settings, &dataset_id),
+        None => select_dataset_id(&settings, "Select a dataset to update:").await?,
+    };
+
+    let mut name = update.name;
+    if name.is_none() && update.tracking_id.is_none() {
+        ensure_interactive("--name or --tracking-id")?;
+        name = Some(inquire::Text::new("New Dataset Name: ").prompt().unwrap());
+    }
+
+    // Fields left out of the request keep their current value.
+    let mut body = serde_json::json!({ "dataset_id": dataset_id });
+    if let Some(name) = name {
+        body["dataset_name"] = name.into();
+    }
+    if let Some(tracking_id) = update.tracking_id {
+        body["new_tracking_id"] = tracking_id.into();
+    }
+
+    let dataset: Dataset = Rest::new(&settings)
+        .dataset(&dataset_id)
+        .put("/api/dataset", &body)
+        .await?;
+    audit::record(&settings, "dataset update", Some(&dataset_id), body);
+
+    if quiet() {
+        return Ok(dataset);
+    }
+
+    success(&tr("dataset-updated"));
+    println!();
+    println!(
+        "{}",
+        tr_args("dataset-field-id", &[("id", id(&dataset.id.to_string()))])
+    );
+    println!(
+        "{}",
+        tr_args("dataset-field-name", &[("name", dataset.name.clone())])
+    );
+    if let Some(Some(tracking_id)) = &dataset.tracking_id {
+        println!(
+            "{}",
+            tr_args(
+                "dataset-field-tracking-id",
+                &[("tracking_id", tracking_id.clone())]
+            )
+        );
+    }
 
     Ok(dataset)
 }
@@ -173,75 +403,293 @@ pub async fn delete_trieve_dataset(
     settings: TrieveConfiguration,
     delete: DeleteDataset,
 ) -> Result<(), DefaultError> {
-    if settings.organization_id.to_string().is_empty() || settings.api_key.is_empty() {
-        eprintln!("Please login to the Trieve CLI with your credentials. Run `trieve login` to get started.");
-        std::process::exit(1);
-    }
+    require_login(&settings)?;
 
-    let mut dataset_id = delete.dataset_id.clone();
+    let mut dataset_id = delete
+        .dataset_id
+        .as_ref()
+        .map(|dataset_id| resolve_dataset(&settings, dataset_id));
 
     if dataset_id.is_none() {
+        ensure_interactive("--dataset-id")?;
         let datasets = get_datasets_from_org(settings.clone())
             .await
-            .map_err(|e| {
-                eprintln!("Error listing datasets: {}", e.message);
-                std::process::exit(1);
+            .map_err(|e| DefaultError {
+                message: format!("Error listing datasets: {}", e.message),
             })?
             .iter()
             .map(|d| DatasetAndUsageDTO(d.clone()))
             .collect::<Vec<_>>();
 
-        let selected_dataset = inquire::Select::new("Select a dataset to delete:", datasets)
-            .prompt()
-            .unwrap();
+        let selected_dataset = pick("Select a dataset to delete:", "datasets", datasets)?;
 
         let ans = Confirm::new("Are you sure you want to delete this dataset?")
             .with_default(false)
             .prompt();
 
         if !ans.unwrap() {
-            println!("Dataset deletion cancelled.");
-            std::process::exit(0);
+            println!("{}", tr("dataset-deletion-cancelled"));
+            return Ok(());
         }
 
         dataset_id = Some(selected_dataset.0.dataset.id.to_string());
     }
 
-    let config = Configuration {
-        base_path: settings.api_url,
-        api_key: Some(ApiKey {
-            prefix: None,
-            key: settings.api_key,
-        }),
-        ..Default::default()
+    guard::confirm_destructive(&format!(
+        "delete dataset {}",
+        dataset_id.clone().unwrap_or_default()
+    ))?;
+
+    let backup = delete.backup || (!delete.no_backup && !quiet() && stdin().is_terminal());
+    let backup_dir = if backup {
+        let dataset_id = dataset_id.clone().unwrap_or_default();
+        let dir = backup_dataset(&settings, &dataset_id)
+            .await
+            .map_err(|e| DefaultError {
+                message: format!("Backup failed, the dataset was not deleted: {}", e.message),
+            })?;
+        Some(dir)
+    } else {
+        None
     };
 
-    delete_dataset(
-        &config,
-        &dataset_id.clone().unwrap(),
-        &dataset_id.clone().unwrap(),
-    )
-    .await
-    .map_err(|e| DefaultError {
-        message: e.to_string(),
-    })?;
+    ApiClient::from(&settings)
+        .delete_dataset(&dataset_id.clone().unwrap())
+        .await?;
+    audit::record(
+        &settings,
+        "dataset delete",
+        dataset_id.as_deref(),
+        json!({ "backup": backup_dir }),
+    );
 
-    println!("Dataset deleted successfully!");
+    if !quiet() {
+        success(&tr("dataset-deleted"));
+        if let Some(dir) = backup_dir {
+            println!(
+                "Restore it with `trieve dataset restore {}`.",
+                dir.display()
+            );
+        }
+    }
 
     Ok(())
 }
 
-async fn add_yc_companies_seed_data(
+/// A row of `dataset limits`. `value` is `None` when the server does not report the limit.
+#[derive(Serialize)]
+struct LimitRow {
+    limit: String,
+    value: Option<serde_json::Value>,
+    usage: Option<serde_json::Value>,
+    source: String,
+}
+
+/// Prints a limit or usage value, without the quotes of JSON strings.
+fn limit_value(value: &Option<serde_json::Value>) -> Option<String> {
+    value.as_ref().map(|value| match value {
+        serde_json::Value::String(value) => value.clone(),
+        value => value.to_string(),
+    })
+}
+
+const LIMIT_COLUMNS: [Column<LimitRow>; 4] = [
+    Column {
+        key: "limit",
+        header: "Limit",
+        value: |row| row.limit.clone(),
+    },
+    Column {
+        key: "value",
+        header: "Value",
+        value: |row| limit_value(&row.value).unwrap_or("not reported".to_string()),
+    },
+    Column {
+        key: "usage",
+        header: "Current Usage",
+        value: |row| limit_value(&row.usage).unwrap_or_default(),
+    },
+    Column {
+        key: "source",
+        header: "Source",
+        value: |row| row.source.clone(),
+    },
+];
+
+pub async fn dataset_limits(
+    settings: TrieveConfiguration,
+    limits: DatasetLimits,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = match dataset_or_default(limits.dataset_id, &settings) {
+        Some(dataset_id) => dataset_id,
+        None => select_dataset_id(&settings, "Select a dataset:").await?,
+    };
+    let organization_id = settings.organization_id.to_string();
+
+    let dataset_response = Rest::new(&settings)
+        .dataset(&dataset_id)
+        .send(Method::GET, &format!("/api/dataset/{}", dataset_id), None)
+        .await?;
+    let (dataset, dataset_headers): (serde_json::Value, _) =
+        (dataset_response.json()?, dataset_response.headers);
+    let organization: serde_json::Value = Rest::new(&settings)
+        .get(&format!("/api/organization/{}", organization_id))
+        .await?;
+    let usage: serde_json::Value = Rest::new(&settings)
+        .get(&format!("/api/organization/usage/{}", organization_id))
+        .await?;
+
+    let plan = &organization["plan"];
+    let plan_name = plan["name"].as_str().unwrap_or("unknown plan").to_string();
+    let reported = |value: Option<&serde_json::Value>| value.filter(|v| !v.is_null()).cloned();
+    let mut rows = vec![];
+    for (label, key) in [
+        ("Chunks", "chunk_count"),
+        ("Datasets", "dataset_count"),
+        ("File storage", "file_storage"),
+        ("Messages", "message_count"),
+        ("Users", "user_count"),
+    ] {
+        rows.push(LimitRow {
+            limit: label.to_string(),
+            value: reported(plan.get(key)),
+            usage: reported(usage.get(key)),
+            source: format!("plan ({})", plan_name),
+        });
+    }
+
+    let rate_limit_headers = dataset_headers
+        .iter()
+        .filter(|(name, _)| {
+            let name = name.to_lowercase();
+            name.contains("ratelimit") || name == "retry-after"
+        })
+        .collect::<Vec<_>>();
+
+    if rate_limit_headers.is_empty() {
+        rows.push(LimitRow {
+            limit: "Requests per minute".to_string(),
+            value: None,
+            usage: None,
+            source: "response headers".to_string(),
+        });
+    }
+    for (name, value) in rate_limit_headers {
+        rows.push(LimitRow {
+            limit: name.clone(),
+            value: Some(json!(value)),
+            usage: None,
+            source: "response headers".to_string(),
+        });
+    }
+
+    // Size limits are only shown when the server reports them, in the dataset configuration or
+    // the plan.
+    for (label, keys) in [
+        ("Max chunk size", ["MAX_CHUNK_SIZE", "max_chunk_size"]),
+        ("Max file size", ["MAX_FILE_SIZE", "max_file_size"]),
+    ] {
+        let found = keys.iter().find_map(|key| {
+            [
+                (&dataset["server_configuration"], "dataset configuration"),
+                (plan, "plan"),
+            ]
+            .into_iter()
+            .find_map(|(values, source)| reported(values.get(key)).map(|v| (v, source)))
+        });
+        let (value, source) = match found {
+            Some((value, "plan")) => (Some(value), format!("plan ({})", plan_name)),
+            Some((value, source)) => (Some(value), source.to_string()),
+            None => (None, String::new()),
+        };
+        rows.push(LimitRow {
+            limit: label.to_string(),
+            value,
+            usage: None,
+            source,
+        });
+    }
+
+    rows.push(LimitRow {
+        limit: "Chunks per batch request".to_string(),
+        value: Some(json!(CHUNKS_PER_BATCH)),
+        usage: None,
+        source: "cli default".to_string(),
+    });
+
+    if matches!(format(), OutputFormat::Json) {
+        println!(
+            "{}",
+            json!({
+                "dataset_id": dataset_id,
+                "name": dataset["name"],
+                "limits": rows,
+            })
+        );
+        return Ok(());
+    }
+    if quiet() {
+        return table::print_quiet(&LIMIT_COLUMNS, &rows, &["limit", "value"]);
+    }
+
+    println!(
+        "Limits for dataset: {} ({})",
+        dataset["name"].as_str().unwrap_or_default(),
+        id(&dataset_id)
+    );
+    println!("{}", table::render(&LIMIT_COLUMNS, &rows)?);
+
+    Ok(())
+}
+
+/// Uploads the chunks of an example dataset through the shared upload pipeline, checkpointing
+/// progress under `source` so an interrupted load can be resumed.
+async fn create_chunks_in_batches(
     settings: TrieveConfiguration,
     dataset_id: Option<String>,
+    source: &str,
+    chunk_datas: Vec<ChunkReqPayload>,
+    resume: bool,
 ) -> Result<(), DefaultError> {
-    let response = ureq::get("https://gist.githubusercontent.com/densumesh/127bd58e026ccadaea58dc1aa3ad9648/raw/1dcf2fe14954047064ef5cfbec43bf74d54365d8/yc-company-data.csv").call().map_err(
-        |e| DefaultError {
-            message: e.to_string(),
-        }
-    )?;
+    let dataset_id = dataset_id.unwrap();
+    let checkpoint = Checkpoint::open(Path::new(source), &dataset_id, resume)?;
+    let records: ChunkRecords = Box::new(chunk_datas.into_iter().map(Ok));
+
+    let summary =
+        upload_chunk_stream(settings, dataset_id, records, Some(checkpoint), None, None).await?;
+    check_upload_summary(&summary)
+}
+
+fn check_upload_summary(summary: &UploadSummary) -> Result<(), DefaultError> {
+    if summary.interrupted {
+        return Err(DefaultError {
+            message: "Upload interrupted, run the same command with `--resume` to continue"
+                .to_string(),
+        });
+    }
+
+    if summary.failed_batches > 0 {
+        return Err(DefaultError {
+            message: format!(
+                "{} batches failed to upload, run the same command with `--resume` to retry them",
+                summary.failed_batches
+            ),
+        });
+    }
+
+    Ok(())
+}
+
+const YC_COMPANIES_URL: &str = "https://gist.githubusercontent.com/densumesh/127bd58e026ccadaea58dc1aa3ad9648/raw/1dcf2fe14954047064ef5cfbec43bf74d54365d8/yc-company-data.csv";
 
-    let mut rdr = csv::Reader::from_reader(response.into_reader());
+async fn add_yc_companies_seed_data(
+    settings: TrieveConfiguration,
+    dataset_id: Option<String>,
+    resume: bool,
+) -> Result<(), DefaultError> {
+    let mut rdr = csv::Reader::from_reader(http::download(YC_COMPANIES_URL).await?);
 
     let chunk_data: Vec<ChunkReqPayload> = rdr
         .records()
@@ -262,53 +710,19 @@ async fn add_yc_companies_seed_data(
         })
         .collect();
 
-    let mut handles = vec![];
-
-    for chunk in chunk_data.chunks(120) {
-        let settings = settings.clone();
-        let dataset_id = dataset_id.clone();
-        let chunk = chunk.to_vec();
-        let handle = tokio::spawn(async move {
-            let config = Configuration {
-                base_path: settings.api_url,
-                api_key: Some(ApiKey {
-                    prefix: None,
-                    key: settings.api_key,
-                }),
-                ..Default::default()
-            };
-
-            let data =
-                trieve_client::models::CreateChunkReqPayloadEnum::CreateBatchChunkReqPayload(chunk);
-
-            create_chunk(&config, &dataset_id.clone().unwrap(), data)
-                .await
-                .map_err(|e| DefaultError {
-                    message: e.to_string(),
-                })?;
-
-            Ok(())
-        });
-        handles.push(handle);
-    }
-
-    for handle in handles {
-        let _ = handle.await.unwrap().map_err(|e: DefaultError| {
-            eprintln!("Error adding seed data: {:?}", e);
-        });
-    }
-
-    Ok(())
+    create_chunks_in_batches(settings, dataset_id, YC_COMPANIES_URL, chunk_data, resume).await
 }
 
 async fn add_trieve_mintlify_docs(
     settings: TrieveConfiguration,
     dataset_id: Option<String>,
+    resume: bool,
 ) -> Result<(), DefaultError> {
     let res = add_json_dataset(
         "https://gist.githubusercontent.com/skeptrunedev/dc34aa54f7810c913794ad045cc767d2/raw/4205cf3ab0dd55fccdc3a336bc26ce6a16b82cf3/trieve-mintlify-docs-chunks.json",
         settings,
         dataset_id,
+        resume,
     )
     .await?;
 
@@ -318,11 +732,13 @@ async fn add_trieve_mintlify_docs(
 async fn add_mintlify_docs(
     settings: TrieveConfiguration,
     dataset_id: Option<String>,
+    resume: bool,
 ) -> Result<(), DefaultError> {
     let res = add_json_dataset(
         "https://gist.githubusercontent.com/densumesh/0400c4519e55dfcd8d8d2e4a171fc531/raw/df73e08c4173128ba321f506ff763b2bdce4e273/mintlify_chunks.json",
         settings,
         dataset_id,
+        resume,
     )
     .await?;
 
@@ -333,136 +749,140 @@ async fn add_json_dataset(
     gist_url: &str,
     settings: TrieveConfiguration,
     dataset_id: Option<String>,
+    resume: bool,
 ) -> Result<(), DefaultError> {
-    let chunks_to_create = ureq::get(gist_url).call().map_err(|e| DefaultError {
-        message: e.to_string(),
-    })?;
     let chunks_to_create: serde_json::Value =
-        chunks_to_create.into_json().map_err(|e| DefaultError {
-            message: e.to_string(),
-        })?;
-
-    let config = Configuration {
-        base_path: settings.api_url.clone(),
-        api_key: Some(ApiKey {
-            prefix: None,
-            key: settings.api_key.clone(),
-        }),
-        ..Default::default()
-    };
+        http::send(http::request(Method::GET, gist_url), None)
+            .await?
+            .json()?;
 
-    let mut group_tracking_ids: HashSet<String> = HashSet::new();
-
-    chunks_to_create
-        .as_array()
-        .expect("Should always be an array")
-        .iter()
-        .for_each(|chunk| {
-            let chunk = chunk.as_object().expect("Should always be an object");
-            chunk.get("group_tracking_ids").map(|g| {
-                g.as_array().map(|a| {
-                    a.iter().for_each(|v| {
-                        group_tracking_ids.insert(v.as_str().unwrap().to_string());
-                    })
-                })
-            });
-        });
+    add_json_chunks(gist_url, chunks_to_create, settings, dataset_id, resume).await
+}
 
-    let chunk_datas: Vec<ChunkReqPayload> = chunks_to_create
-        .as_array()
-        .expect("Should always be an array")
-        .iter()
-        .map(|chunk| {
-            let chunk = chunk.as_object().expect("Should always be an object");
-            let chunk_data = ChunkReqPayload {
-                link: Some(chunk["link"].as_str().map(|s| s.to_string())),
-                chunk_html: Some(chunk["chunk_html"].as_str().map(|s| s.to_string())),
-                metadata: Some(
-                    chunk["metadata"]
-                        .as_object()
-                        .map(|m| m.clone())
-                        .map(serde_json::Value::Object),
-                ),
-                tracking_id: Some(chunk["tracking_id"].as_str().map(|s| s.to_string())),
-                tag_set: Some(
-                    chunk["tag_set"]
-                        .as_str()
-                        .map(|s| s.split(',').map(|s| s.to_string()).collect()),
-                ),
-                group_tracking_ids: chunk.get("group_tracking_ids").map(|g| {
-                    g.as_array()
-                        .map(|a| a.iter().map(|v| v.as_str().unwrap().to_string()).collect())
-                }),
-                upsert_by_tracking_id: Some(Some(true)),
-                ..Default::default()
-            };
-            chunk_data
-        })
-        .collect();
+/// The tags of a chunk in the example JSON datasets, either a comma separated string or a list.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonTagSet {
+    Text(String),
+    List(Vec<String>),
+}
 
-    for tracking_id in group_tracking_ids {
-        let group_data = CreateChunkGroupReqPayloadEnum::CreateSingleChunkGroupReqPayload(
-            Box::new(CreateSingleChunkGroupReqPayload {
-                name: Some(Some(tracking_id.clone())),
-                tracking_id: Some(Some(tracking_id.clone())),
-                ..Default::default()
-            }),
-        );
+/// A chunk of the example JSON datasets. Every field is optional, so a record that lacks one is
+/// still imported without it.
+#[derive(Deserialize)]
+struct JsonChunk {
+    chunk_html: Option<String>,
+    link: Option<String>,
+    tracking_id: Option<String>,
+    tag_set: Option<JsonTagSet>,
+    metadata: Option<serde_json::Map<String, serde_json::Value>>,
+    group_tracking_ids: Option<Vec<String>>,
+}
 
-        create_chunk_group(&config, &dataset_id.clone().unwrap(), group_data)
-            .await
-            .map_err(|e| DefaultError {
-                message: e.to_string(),
-            })?;
+impl From<JsonChunk> for ChunkReqPayload {
+    fn from(chunk: JsonChunk) -> Self {
+        ChunkReqPayload {
+            chunk_html: Some(chunk.chunk_html),
+            link: Some(chunk.link),
+            tracking_id: Some(chunk.tracking_id),
+            tag_set: Some(chunk.tag_set.map(|tag_set| match tag_set {
+                JsonTagSet::Text(tags) => tags.split(',').map(|s| s.to_string()).collect(),
+                JsonTagSet::List(tags) => tags,
+            })),
+            metadata: Some(chunk.metadata.map(serde_json::Value::Object)),
+            group_tracking_ids: Some(chunk.group_tracking_ids),
+            upsert_by_tracking_id: Some(Some(true)),
+            ..Default::default()
+        }
     }
+}
 
-    let mut handles = vec![];
+/// Uploads the chunks of an example JSON dataset. Records that cannot be read as a chunk are
+/// skipped and listed once the upload finished, instead of failing the whole import.
+async fn add_json_chunks(
+    source: &str,
+    chunks_to_create: serde_json::Value,
+    settings: TrieveConfiguration,
+    dataset_id: Option<String>,
+    resume: bool,
+) -> Result<(), DefaultError> {
+    let serde_json::Value::Array(records) = chunks_to_create else {
+        return Err(DefaultError {
+            message: format!("Expected a JSON array of chunks in {}", source),
+        });
+    };
 
-    for chunks in chunk_datas.chunks(120) {
-        let settings = settings.clone();
-        let dataset_id = dataset_id.clone();
-        let chunks = chunks.to_vec();
-        let handle = tokio::spawn(async move {
-            let config = Configuration {
-                base_path: settings.api_url.clone(),
-                api_key: Some(ApiKey {
-                    prefix: None,
-                    key: settings.api_key.clone(),
-                }),
-                ..Default::default()
-            };
+    let mut skipped = vec![];
+    let chunk_datas: Vec<ChunkReqPayload> = records
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, record)| {
+            let chunk = serde_json::from_value::<JsonChunk>(record)
+                .map_err(|e| e.to_string())
+                .and_then(|chunk| match &chunk.chunk_html {
+                    Some(html) if !html.trim().is_empty() => Ok(chunk),
+                    _ => Err("missing chunk_html".to_string()),
+                });
+            match chunk {
+                Ok(chunk) => Some(chunk.into()),
+                Err(message) => {
+                    skipped.push(RecordError {
+                        location: format!("record {}", i + 1),
+                        message,
+                    });
+                    None
+                }
+            }
+        })
+        .collect();
+    let total = chunk_datas.len() + skipped.len();
 
-            let data = trieve_client::models::CreateChunkReqPayloadEnum::CreateBatchChunkReqPayload(
-                chunks,
-            );
+    let result = create_chunks_in_batches(settings, dataset_id, source, chunk_datas, resume).await;
 
-            create_chunk(&config, &dataset_id.clone().unwrap(), data)
-                .await
-                .map_err(|e| DefaultError {
-                    message: e.to_string(),
-                })?;
+    if !skipped.is_empty() {
+        eprintln!("Skipped {} of {} records:", skipped.len(), total);
+        validate::print_errors(source, &skipped);
+    }
 
-            Ok(())
-        });
+    result
+}
 
-        handles.push(handle);
-    }
+async fn add_custom_seed_data(
+    settings: TrieveConfiguration,
+    dataset_id: Option<String>,
+    seed_data: &AddSeedData,
+) -> Result<(), DefaultError> {
+    let mapping = ColumnMapping::from_args(&seed_data.map, seed_data.mapping_file.as_deref())?;
+    let (reader, format) = open_source(
+        seed_data.url.as_deref(),
+        seed_data.file.as_deref(),
+        seed_data.format,
+    )
+    .await?;
+    let records = chunk_records(reader, format, &mapping)?;
 
-    for handle in handles {
-        let _: Result<(), DefaultError> = handle.await.unwrap();
-    }
+    let dataset_id = dataset_id.unwrap();
+    let source = match (&seed_data.url, &seed_data.file) {
+        (Some(url), _) => PathBuf::from(url),
+        (None, file) => file.clone().unwrap_or_default(),
+    };
+    let checkpoint = Checkpoint::open(&source, &dataset_id, seed_data.resume)?;
 
-    Ok(())
+    let summary =
+        upload_chunk_stream(settings, dataset_id, records, Some(checkpoint), None, None).await?;
+    check_upload_summary(&summary)
 }
 
 async fn add_philosophize_this_seed_data(
     settings: TrieveConfiguration,
     dataset_id: Option<String>,
+    resume: bool,
 ) -> Result<(), DefaultError> {
     let res = add_json_dataset(
         "https://gist.githubusercontent.com/aaryanpunia/b1a9262fdd68d6bd27aa4ead8dd16f9b/raw/c89773619d55895dbde9892b09076b51df81e177/gistfile1.txt",
         settings,
         dataset_id,
+        resume,
     )
     .await?;
 
@@ -473,19 +893,30 @@ pub async fn add_seed_data(
     settings: TrieveConfiguration,
     seed_data: AddSeedData,
 ) -> Result<(), DefaultError> {
-    if settings.organization_id.to_string().is_empty() || settings.api_key.is_empty() {
-        eprintln!("Please login to the Trieve CLI with your credentials. Run `trieve login` to get started.");
-        std::process::exit(1);
+    require_login(&settings)?;
+
+    if seed_data.detach {
+        let has_source =
+            seed_data.example.is_some() || seed_data.url.is_some() || seed_data.file.is_some();
+        if seed_data.dataset_id.is_none() || !has_source {
+            return Err(DefaultError {
+                message: "Please pass a dataset ID and one of `--example`, `--url` or `--file` when using `--detach`".to_string(),
+            });
+        }
+
+        return jobs::detach();
     }
 
-    let mut dataset_id = seed_data.dataset_id.clone();
+    let mut dataset_id = seed_data
+        .dataset_id
+        .as_ref()
+        .map(|dataset_id| resolve_dataset(&settings, dataset_id));
 
     if dataset_id.is_none() {
         let datasets = get_datasets_from_org(settings.clone())
             .await
-            .map_err(|e| {
-                eprintln!("Error listing datasets: {}", e.message);
-                std::process::exit(1);
+            .map_err(|e| DefaultError {
+                message: format!("Error listing datasets: {}", e.message),
             })?
             .iter()
             .map(|d| DatasetAndUsageDTO(d.clone()))
@@ -497,47 +928,234 @@ pub async fn add_seed_data(
             .prompt();
 
         if ans.unwrap() {
-            let create = CreateDataset { name: None };
+            let create = CreateDataset {
+                name: None,
+                template: None,
+            };
 
             let dataset = create_trieve_dataset(settings.clone(), create).await?;
             dataset_id = Some(dataset.id.to_string());
         } else {
-            let selected_dataset =
-                inquire::Select::new("Select a dataset to add seed data to:", datasets)
-                    .prompt()
-                    .unwrap();
+            let selected_dataset = pick(
+                "Select a dataset to add seed data to:",
+                "datasets",
+                datasets,
+            )?;
             dataset_id = Some(selected_dataset.0.dataset.id.to_string());
         }
     }
 
-    let selected_example = inquire::Select::new(
-        "Select an example dataset to add:",
-        vec![
-            "YC Companies",
-            "PhilosiphizeThis",
-            "Trieve Docs",
-            "Mintlify Docs",
-        ],
-    )
-    .prompt()
-    .unwrap();
+    if seed_data.url.is_some() || seed_data.file.is_some() {
+        println!(
+            "{}",
+            tr_args("seed-adding", &[("dataset", dataset_id.clone().unwrap())])
+        );
+        add_custom_seed_data(settings, dataset_id, &seed_data).await?;
+        success(&tr("seed-added"));
+        return Ok(());
+    }
+
+    let example = match seed_data.example {
+        Some(example) => example,
+        None => {
+            inquire::Select::new(
+                "Select an example dataset to add:",
+                SEED_EXAMPLES.map(SeedExampleDTO).to_vec(),
+            )
+            .prompt()
+            .unwrap()
+            .0
+        }
+    };
 
     println!(
-        "Adding seed data to dataset: {}",
-        dataset_id.clone().unwrap()
+        "{}",
+        tr_args("seed-adding", &[("dataset", dataset_id.clone().unwrap())])
     );
 
-    match selected_example {
-        "YC Companies" => add_yc_companies_seed_data(settings.clone(), dataset_id).await?,
-        "PhilosiphizeThis" => add_philosophize_this_seed_data(settings.clone(), dataset_id).await?,
-        "Trieve Docs" => add_trieve_mintlify_docs(settings.clone(), dataset_id).await?,
-        "Mintlify Docs" => add_mintlify_docs(settings.clone(), dataset_id).await?,
-        _ => {
-            eprintln!("Invalid example dataset selected: {}", selected_example);
-            std::process::exit(1);
+    install_example(settings, dataset_id, example, seed_data.resume).await?;
+
+    success(&tr("seed-example-added"));
+    Ok(())
+}
+
+const SEED_EXAMPLES: [SeedExample; 4] = [
+    SeedExample::YcCompanies,
+    SeedExample::PhilosophizeThis,
+    SeedExample::TrieveDocs,
+    SeedExample::MintlifyDocs,
+];
+
+#[derive(Clone)]
+struct SeedExampleDTO(SeedExample);
+
+impl fmt::Display for SeedExampleDTO {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            SeedExample::YcCompanies => write!(f, "YC Companies"),
+            SeedExample::PhilosophizeThis => write!(f, "PhilosiphizeThis"),
+            SeedExample::TrieveDocs => write!(f, "Trieve Docs"),
+            SeedExample::MintlifyDocs => write!(f, "Mintlify Docs"),
+        }
+    }
+}
+
+async fn install_example(
+    settings: TrieveConfiguration,
+    dataset_id: Option<String>,
+    example: SeedExample,
+    resume: bool,
+) -> Result<(), DefaultError> {
+    match example {
+        SeedExample::YcCompanies => add_yc_companies_seed_data(settings, dataset_id, resume).await,
+        SeedExample::PhilosophizeThis => {
+            add_philosophize_this_seed_data(settings, dataset_id, resume).await
+        }
+        SeedExample::TrieveDocs => add_trieve_mintlify_docs(settings, dataset_id, resume).await,
+        SeedExample::MintlifyDocs => add_mintlify_docs(settings, dataset_id, resume).await,
+    }
+}
+
+/// Installs several example datasets at once, each into a new dataset named
+/// `<prefix>-<example>`, and prints a summary of what was created.
+pub async fn install_demo(
+    settings: TrieveConfiguration,
+    demo: InstallDemo,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let examples = if demo.all {
+        SEED_EXAMPLES.to_vec()
+    } else if !demo.example.is_empty() {
+        demo.example.clone()
+    } else {
+        inquire::MultiSelect::new(
+            "Select the example datasets to install:",
+            SEED_EXAMPLES.map(SeedExampleDTO).to_vec(),
+        )
+        .with_all_selected_by_default()
+        .prompt()
+        .unwrap()
+        .into_iter()
+        .map(|example| example.0)
+        .collect()
+    };
+
+    if examples.is_empty() {
+        println!("No example datasets selected.");
+        return Ok(());
+    }
+
+    let mut builder = Builder::default();
+    builder.push_record(["Example", "Dataset Name", "Dataset ID", "Status"]);
+    let mut failed = 0;
+
+    for example in examples {
+        let slug = example
+            .to_possible_value()
+            .map(|value| value.get_name().to_string())
+            .unwrap_or_default();
+        let name = format!("{}-{}", demo.prefix, slug);
+
+        println!(
+            "Installing {} into new dataset '{}'...",
+            SeedExampleDTO(example),
+            name
+        );
+        let dataset = create_trieve_dataset(
+            settings.clone(),
+            CreateDataset {
+                name: Some(name.clone()),
+                template: None,
+            },
+        )
+        .await?;
+
+        let status = match install_example(
+            settings.clone(),
+            Some(dataset.id.to_string()),
+            example,
+            false,
+        )
+        .await
+        {
+            Ok(()) => "Installed".to_string(),
+            Err(e) => {
+                failed += 1;
+                format!("Failed: {}", e.message)
+            }
+        };
+
+        builder.push_record([
+            SeedExampleDTO(example).to_string(),
+            name,
+            dataset.id.to_string(),
+            status,
+        ]);
+    }
+
+    let mut table = builder.build();
+    table.with(Style::rounded());
+    println!("{}", table);
+
+    if failed > 0 {
+        return Err(DefaultError {
+            message: format!("{} example datasets failed to install", failed),
+        });
+    }
+
+    Ok(())
+}
+
+pub async fn use_dataset(
+    settings: TrieveConfiguration,
+    profile_name: Option<String>,
+    use_dataset: UseDataset,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let profile_name = profile_required(profile_name)?;
+
+    if use_dataset.clear {
+        set_profile_setting(&profile_name, "dataset", None)?;
+        if quiet() {
+            return Ok(());
         }
+        success(&tr_args(
+            "dataset-default-cleared",
+            &[("profile", profile_name)],
+        ));
+        return Ok(());
     }
 
-    println!("Example dataset added successfully!");
+    require_login(&settings)?;
+
+    let dataset_id = match use_dataset.dataset_id {
+        Some(dataset_id) => resolve_dataset(&settings, &dataset_id),
+        None => select_dataset_id(&settings, "Select the default dataset:")
+            .await
+            .map_err(|e| e.message)?,
+    };
+
+    // Check that the dataset exists in the organization before saving it.
+    let dataset: serde_json::Value = Rest::new(&settings)
+        .dataset(&dataset_id)
+        .get(&format!("/api/dataset/{}", dataset_id))
+        .await
+        .map_err(|e| e.message)?;
+
+    set_profile_setting(&profile_name, "dataset", Some(&dataset_id))?;
+    if quiet() {
+        return Ok(());
+    }
+    success(&tr_args(
+        "dataset-default-set",
+        &[
+            (
+                "dataset",
+                dataset["name"].as_str().unwrap_or(&dataset_id).to_string(),
+            ),
+            ("profile", profile_name),
+        ],
+    ));
+
     Ok(())
 }
@@ -0,0 +1,85 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, SecondsFormat, Utc};
+
+use crate::cli::DateFormat;
+
+/// The format passed with `--date-format`, `None` when it is not given.
+static FORMAT: OnceLock<Option<DateFormat>> = OnceLock::new();
+
+pub fn init(format: Option<DateFormat>) {
+    let _ = FORMAT.set(format);
+}
+
+/// Parses a timestamp sent by the API: RFC 3339, or a timestamp without a time zone, which the
+/// server sends in UTC, with a `T` or a space before the time, or a date alone.
+pub fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    let timestamp = timestamp.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(timestamp) {
+        return Some(time.with_timezone(&Utc));
+    }
+
+    timestamp
+        .parse::<NaiveDateTime>()
+        .or_else(|_| NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S%.f"))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(timestamp, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .map(|time| time.and_utc())
+}
+
+/// How long before `now` a time is, e.g. `3 days ago`, or `in 2 hours` for a time after it.
+fn relative(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = (now - time).num_seconds();
+    let elapsed = seconds.unsigned_abs();
+
+    let (count, unit) = match elapsed {
+        0..=59 => return "just now".to_string(),
+        60..=3_599 => (elapsed / 60, "minute"),
+        3_600..=86_399 => (elapsed / 3_600, "hour"),
+        86_400..=2_591_999 => (elapsed / 86_400, "day"),
+        2_592_000..=31_535_999 => (elapsed / 2_592_000, "month"),
+        _ => (elapsed / 31_536_000, "year"),
+    };
+    let unit = if count == 1 {
+        unit.to_string()
+    } else {
+        format!("{}s", unit)
+    };
+
+    if seconds < 0 {
+        format!("in {} {}", count, unit)
+    } else {
+        format!("{} {} ago", count, unit)
+    }
+}
+
+fn format(time: DateTime<Utc>, default: DateFormat) -> String {
+    match FORMAT.get().copied().flatten().unwrap_or(default) {
+        DateFormat::Date => time.date_naive().to_string(),
+        DateFormat::Iso => time.to_rfc3339_opts(SecondsFormat::Secs, true),
+        DateFormat::Local => time
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string(),
+        DateFormat::Relative => relative(time, Utc::now()),
+    }
+}
+
+/// Formats a timestamp of the API for a table, as a date unless `--date-format` says otherwise.
+/// A timestamp that cannot be parsed is returned as is.
+pub fn format_timestamp(timestamp: &str) -> String {
+    match parse_timestamp(timestamp) {
+        Some(time) => format(time, DateFormat::Date),
+        None => timestamp.to_string(),
+    }
+}
+
+/// Formats a time recorded by the CLI, such as the start of a job, for a table, in local time
+/// unless `--date-format` says otherwise.
+pub fn format_time(time: DateTime<Utc>) -> String {
+    format(time, DateFormat::Local)
+}
@@ -0,0 +1,153 @@
+use serde_json::Value;
+
+use crate::cli::Diff;
+
+use super::{
+    apply::{load_manifest, plan, Change},
+    configure::{require_login, TrieveConfiguration},
+    dataset::DefaultError,
+    output::{quiet, set_exit_code, EXIT_ERROR},
+    style::{added, bold, hunk, removed},
+};
+
+/// Formats a field as a line of the diff. Strings are shown without quotes and lists of strings
+/// as comma separated values.
+fn field(key: &str, value: &Value) -> String {
+    match value {
+        Value::String(text) => format!("{}: {}", key, text),
+        Value::Array(items) if items.iter().all(Value::is_string) => format!(
+            "{}: {}",
+            key,
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<&str>>()
+                .join(", ")
+        ),
+        _ => format!("{}: {}", key, value),
+    }
+}
+
+fn print_hunk(kind: &str, name: &str, id: Option<&str>) {
+    println!(
+        "{}",
+        hunk(&format!(
+            "@@ {} {} ({}) @@",
+            kind,
+            name,
+            id.unwrap_or("new")
+        ))
+    );
+}
+
+/// Prints what `trieve apply` would change, as a unified diff from the state of the server to the
+/// manifest. Nothing is changed.
+pub async fn diff(settings: TrieveConfiguration, diff: Diff) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let manifest = load_manifest(&diff.file)?;
+    let plan = plan(&settings, &manifest).await?;
+
+    if plan.changes.is_empty() {
+        if !quiet() {
+            println!(
+                "No differences, the organization matches {}.",
+                diff.file.display()
+            );
+        }
+        return Ok(());
+    }
+
+    if !quiet() {
+        let organization = match &plan.settings {
+            Some(settings) => settings.organization_id.to_string(),
+            None => manifest.organization.clone().unwrap_or_default(),
+        };
+        println!("{}", bold(&format!("--- organization {}", organization)));
+        println!("{}", bold(&format!("+++ {}", diff.file.display())));
+    }
+
+    for change in &plan.changes {
+        match change {
+            Change::CreateOrganization { name } => {
+                print_hunk("organization", name, None);
+                println!("{}", added(&format!("+name: {}", name)));
+            }
+            Change::CreateDataset {
+                spec,
+                configuration,
+            } => {
+                print_hunk("dataset", &spec.name, None);
+                println!("{}", added(&format!("+name: {}", spec.name)));
+                if let Some(tracking_id) = &spec.tracking_id {
+                    println!("{}", added(&format!("+tracking_id: {}", tracking_id)));
+                }
+                for (key, value) in configuration {
+                    println!("{}", added(&format!("+{}", field(key, value))));
+                }
+            }
+            Change::UpdateDataset {
+                id,
+                name,
+                new_name,
+                configuration,
+            } => {
+                print_hunk("dataset", name, Some(id));
+                if let Some(new_name) = new_name {
+                    println!("{}", removed(&format!("-name: {}", name)));
+                    println!("{}", added(&format!("+name: {}", new_name)));
+                }
+                for (key, current, desired) in configuration {
+                    if !current.is_null() {
+                        println!("{}", removed(&format!("-{}", field(key, current))));
+                    }
+                    println!("{}", added(&format!("+{}", field(key, desired))));
+                }
+            }
+            Change::DeleteDataset { id, name } => {
+                print_hunk("dataset", name, Some(id));
+                println!("{}", removed(&format!("-name: {}", name)));
+            }
+            Change::CreateApiKey { spec } => {
+                print_hunk("API key", &spec.name, None);
+                println!("{}", added(&format!("+name: {}", spec.name)));
+                println!("{}", added(&format!("+role: {}", spec.role)));
+                if !spec.datasets.is_empty() {
+                    println!(
+                        "{}",
+                        added(&format!("+datasets: {}", spec.datasets.join(", ")))
+                    );
+                }
+                if !spec.scopes.is_empty() {
+                    println!("{}", added(&format!("+scopes: {}", spec.scopes.join(", "))));
+                }
+            }
+            Change::ReplaceApiKey {
+                id,
+                spec,
+                differences,
+            } => {
+                print_hunk("API key", &spec.name, Some(id));
+                for (key, current, desired) in differences {
+                    println!("{}", removed(&format!("-{}", field(key, current))));
+                    println!("{}", added(&format!("+{}", field(key, desired))));
+                }
+            }
+            Change::DeleteApiKey { id, name } => {
+                print_hunk("API key", name, Some(id));
+                println!("{}", removed(&format!("-name: {}", name)));
+            }
+        }
+    }
+
+    if !quiet() {
+        println!();
+        println!("Plan: {}", plan.summary());
+    }
+
+    if diff.exit_code {
+        set_exit_code(EXIT_ERROR);
+    }
+
+    Ok(())
+}
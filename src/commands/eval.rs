@@ -0,0 +1,206 @@
+use std::{fs, path::Path};
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use serde_json::json;
+use tabled::{builder::Builder, settings::Style};
+
+use crate::cli::{Eval, SearchType};
+
+use super::{
+    alias::resolve_dataset,
+    configure::{require_login, TrieveConfiguration},
+    dataset::DefaultError,
+    search::search_dataset,
+};
+
+/// A query and the tracking IDs of the chunks it is expected to find.
+#[derive(Deserialize, Debug)]
+pub struct EvalQuery {
+    pub query: String,
+    #[serde(default)]
+    pub expected: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct CsvEvalQuery {
+    query: String,
+    #[serde(default)]
+    expected: String,
+}
+
+/// Reads the queries from a YAML (or JSON) list of `{ query, expected }` objects, or from a CSV
+/// file with `query` and `expected` columns, where expected tracking IDs are separated by `|`.
+pub fn read_queries(path: &Path) -> Result<Vec<EvalQuery>, DefaultError> {
+    let read_error = |e: String| DefaultError {
+        message: format!("Could not read {}: {}", path.display(), e),
+    };
+
+    let is_csv = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    let queries = if is_csv {
+        csv::Reader::from_path(path)
+            .map_err(|e| read_error(e.to_string()))?
+            .deserialize::<CsvEvalQuery>()
+            .map(|row| {
+                row.map(|row| EvalQuery {
+                    query: row.query,
+                    expected: row
+                        .expected
+                        .split('|')
+                        .map(|id| id.trim().to_string())
+                        .filter(|id| !id.is_empty())
+                        .collect(),
+                })
+            })
+            .collect::<Result<Vec<EvalQuery>, csv::Error>>()
+            .map_err(|e| read_error(e.to_string()))?
+    } else {
+        let file = fs::read_to_string(path).map_err(|e| read_error(e.to_string()))?;
+        serde_yaml::from_str(&file).map_err(|e| read_error(e.to_string()))?
+    };
+
+    Ok(queries)
+}
+
+pub fn search_type_name(search_type: SearchType) -> String {
+    search_type
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// The outcome of one query against one configuration.
+struct QueryResult {
+    recall: f64,
+    /// The 1-based rank of the first expected chunk in the results.
+    first_rank: Option<usize>,
+    missing: Vec<String>,
+}
+
+impl QueryResult {
+    fn new(expected: &[String], results: &[String]) -> Self {
+        let found = expected.iter().filter(|id| results.contains(id)).count();
+
+        QueryResult {
+            recall: if expected.is_empty() {
+                1.0
+            } else {
+                found as f64 / expected.len() as f64
+            },
+            first_rank: results
+                .iter()
+                .position(|id| expected.contains(id))
+                .map(|rank| rank + 1),
+            missing: expected
+                .iter()
+                .filter(|id| !results.contains(id))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    fn reciprocal_rank(&self) -> f64 {
+        self.first_rank.map_or(0.0, |rank| 1.0 / rank as f64)
+    }
+}
+
+pub async fn eval(settings: TrieveConfiguration, eval: Eval) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let queries = read_queries(&eval.queries)?;
+    if queries.is_empty() {
+        return Err(DefaultError {
+            message: format!("{} contains no queries", eval.queries.display()),
+        });
+    }
+
+    let configurations = eval
+        .dataset_id
+        .iter()
+        .map(|dataset_id| resolve_dataset(&settings, dataset_id))
+        .flat_map(|dataset_id| {
+            eval.search_type
+                .iter()
+                .map(move |search_type| (dataset_id.clone(), *search_type))
+        })
+        .collect::<Vec<(String, SearchType)>>();
+
+    let mut summary = Builder::default();
+    summary.push_record([
+        "Dataset".to_string(),
+        "Search type".to_string(),
+        format!("Recall@{}", eval.k),
+        "MRR".to_string(),
+        "Queries".to_string(),
+    ]);
+    let mut details = Builder::default();
+    details.push_record([
+        "Query",
+        "Dataset",
+        "Search type",
+        "Recall",
+        "First hit",
+        "Missing",
+    ]);
+
+    for (dataset_id, search_type) in &configurations {
+        let mut results = vec![];
+        for query in &queries {
+            let hits = search_dataset(
+                &settings,
+                dataset_id,
+                &json!({
+                    "query": query.query,
+                    "search_type": search_type,
+                    "page_size": eval.k,
+                }),
+            )
+            .await?;
+            let ids = hits
+                .iter()
+                .map(|hit| hit.id().to_string())
+                .collect::<Vec<String>>();
+            results.push(QueryResult::new(&query.expected, &ids));
+        }
+
+        let count = results.len() as f64;
+        summary.push_record([
+            dataset_id.clone(),
+            search_type_name(*search_type),
+            format!(
+                "{:.3}",
+                results.iter().map(|r| r.recall).sum::<f64>() / count
+            ),
+            format!(
+                "{:.3}",
+                results.iter().map(|r| r.reciprocal_rank()).sum::<f64>() / count
+            ),
+            results.len().to_string(),
+        ]);
+
+        for (query, result) in queries.iter().zip(&results) {
+            if eval.failures_only && result.missing.is_empty() {
+                continue;
+            }
+            details.push_record([
+                query.query.clone(),
+                dataset_id.clone(),
+                search_type_name(*search_type),
+                format!("{:.2}", result.recall),
+                result
+                    .first_rank
+                    .map(|rank| rank.to_string())
+                    .unwrap_or("-".to_string()),
+                result.missing.join(", "),
+            ]);
+        }
+    }
+
+    println!("{}", details.build().with(Style::rounded()));
+    println!();
+    println!("{}", summary.build().with(Style::rounded()));
+
+    Ok(())
+}
@@ -0,0 +1,288 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::cli::{ExportDataset, ExportOrganization};
+
+use super::{
+    configure::{require_login, TrieveConfiguration},
+    dataset::{get_datasets_from_org, require_dataset, DefaultError},
+    jobs,
+    output::quiet,
+    rest::Rest,
+};
+
+pub const SCROLL_PAGE_SIZE: usize = 1000;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportedDataset {
+    pub id: String,
+    pub name: String,
+    pub chunk_count: usize,
+    pub file: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportManifest {
+    pub organization_id: String,
+    pub exported_at: String,
+    pub datasets: Vec<ExportedDataset>,
+}
+
+/// Fetches one page of chunks from a dataset, starting at `offset_chunk_id` when provided.
+pub async fn scroll_chunks(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    offset_chunk_id: Option<&str>,
+) -> Result<Vec<serde_json::Value>, DefaultError> {
+    scroll_filtered_chunks(settings, dataset_id, None, offset_chunk_id).await
+}
+
+/// Like `scroll_chunks`, fetching only the chunks matching `filters`.
+pub async fn scroll_filtered_chunks(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    filters: Option<&serde_json::Value>,
+    offset_chunk_id: Option<&str>,
+) -> Result<Vec<serde_json::Value>, DefaultError> {
+    let response: serde_json::Value = Rest::new(settings)
+        .dataset(dataset_id)
+        .post(
+            "/api/chunks/scroll",
+            &json!({
+                "page_size": SCROLL_PAGE_SIZE,
+                "offset_chunk_id": offset_chunk_id,
+                "filters": filters,
+            }),
+        )
+        .await?;
+
+    Ok(response["chunks"].as_array().cloned().unwrap_or_default())
+}
+
+/// Writes every chunk of a dataset to `path` as JSON lines and returns the number of chunks written.
+pub async fn export_dataset(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    path: &Path,
+) -> Result<usize, DefaultError> {
+    let file = File::create(path).map_err(|e| DefaultError {
+        message: format!("Could not create {}: {}", path.display(), e),
+    })?;
+    let mut writer = BufWriter::new(file);
+
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut offset: Option<String> = None;
+
+    loop {
+        let chunks = scroll_chunks(settings, dataset_id, offset.as_deref()).await?;
+        let page_len = chunks.len();
+        let mut new_chunks = 0;
+
+        for chunk in chunks {
+            let id = chunk["id"].as_str().unwrap_or_default().to_string();
+            // The offset chunk may be returned again at the start of the next page.
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+
+            writeln!(writer, "{}", chunk).map_err(|e| DefaultError {
+                message: e.to_string(),
+            })?;
+            offset = Some(id);
+            new_chunks += 1;
+        }
+
+        if new_chunks == 0 || page_len < SCROLL_PAGE_SIZE {
+            break;
+        }
+    }
+
+    writer.flush().map_err(|e| DefaultError {
+        message: e.to_string(),
+    })?;
+
+    Ok(seen.len())
+}
+
+/// Reads an exported JSONL file back and counts the distinct chunk IDs in it, failing on the
+/// first line that is not a chunk.
+fn count_exported_chunks(path: &Path) -> Result<usize, DefaultError> {
+    let file = File::open(path).map_err(|e| DefaultError {
+        message: format!("Could not open {}: {}", path.display(), e),
+    })?;
+
+    let mut ids = HashSet::new();
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| DefaultError {
+            message: format!("Could not read {}: {}", path.display(), e),
+        })?;
+        let chunk: serde_json::Value = serde_json::from_str(&line).map_err(|e| DefaultError {
+            message: format!(
+                "{} line {} is not valid JSON: {}",
+                path.display(),
+                index + 1,
+                e
+            ),
+        })?;
+        let id = chunk["id"].as_str().ok_or_else(|| DefaultError {
+            message: format!("{} line {} has no chunk ID", path.display(), index + 1),
+        })?;
+        ids.insert(id.to_string());
+    }
+
+    Ok(ids.len())
+}
+
+/// Exports every dataset of an organization into `dir`, writing one JSONL file per dataset and a
+/// `manifest.json`. The export is verified against the chunk counts reported by the server.
+pub async fn export_organization_to_dir(
+    settings: TrieveConfiguration,
+    dir: &Path,
+) -> Result<ExportManifest, DefaultError> {
+    fs::create_dir_all(dir).map_err(|e| DefaultError {
+        message: format!("Could not create {}: {}", dir.display(), e),
+    })?;
+
+    let datasets = get_datasets_from_org(settings.clone()).await?;
+    let mut exported = vec![];
+
+    for dataset in datasets {
+        let id = dataset.dataset.id.to_string();
+        let file_name = format!("{}.jsonl", id);
+        let path = dir.join(&file_name);
+
+        if !quiet() {
+            println!("Exporting dataset {} ({})...", dataset.dataset.name, id);
+        }
+        let chunk_count = export_dataset(&settings, &id, &path).await?;
+
+        let expected = dataset.dataset_usage.chunk_count.max(0) as usize;
+        let in_file = count_exported_chunks(&path)?;
+        if in_file != chunk_count {
+            return Err(DefaultError {
+                message: format!(
+                    "Export of dataset {} could not be verified: {} chunks were exported, but {} holds {} distinct chunks",
+                    id,
+                    chunk_count,
+                    path.display(),
+                    in_file
+                ),
+            });
+        }
+        if in_file < expected {
+            return Err(DefaultError {
+                message: format!(
+                    "Export of dataset {} could not be verified: the server reports {} chunks, but {} holds {}",
+                    id,
+                    expected,
+                    path.display(),
+                    in_file
+                ),
+            });
+        }
+
+        exported.push(ExportedDataset {
+            id,
+            name: dataset.dataset.name.clone(),
+            chunk_count,
+            file: file_name,
+        });
+    }
+
+    let manifest = ExportManifest {
+        organization_id: settings.organization_id.to_string(),
+        exported_at: Utc::now().to_rfc3339(),
+        datasets: exported,
+    };
+
+    let manifest_path = dir.join("manifest.json");
+    fs::write(
+        &manifest_path,
+        serde_json::to_string_pretty(&manifest).map_err(|e| DefaultError {
+            message: e.to_string(),
+        })?,
+    )
+    .map_err(|e| DefaultError {
+        message: format!("Could not write {}: {}", manifest_path.display(), e),
+    })?;
+
+    Ok(manifest)
+}
+
+pub async fn export_organization(
+    data: ExportOrganization,
+    settings: TrieveConfiguration,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    if data.detach {
+        return jobs::detach();
+    }
+
+    let dir = data.dir.unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "trieve-export-{}-{}",
+            settings.organization_id,
+            Utc::now().format("%Y%m%d%H%M%S")
+        ))
+    });
+
+    let manifest = export_organization_to_dir(settings, &dir).await?;
+
+    if quiet() {
+        println!("{}", dir.display());
+        return Ok(());
+    }
+
+    println!(
+        "Exported {} datasets ({} chunks) to {}",
+        manifest.datasets.len(),
+        manifest
+            .datasets
+            .iter()
+            .map(|d| d.chunk_count)
+            .sum::<usize>(),
+        dir.display()
+    );
+
+    Ok(())
+}
+
+/// Exports the chunks of a dataset to a JSON lines file, which `chunk upload` reads back.
+pub async fn export_dataset_to_file(
+    settings: TrieveConfiguration,
+    export: ExportDataset,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(export.dataset_id, &settings)?;
+    if export.detach {
+        return jobs::detach();
+    }
+
+    let path = export
+        .file
+        .unwrap_or_else(|| PathBuf::from(format!("{}.jsonl", dataset_id)));
+    let chunk_count = export_dataset(&settings, &dataset_id, &path).await?;
+
+    if quiet() {
+        println!("{}", path.display());
+    } else {
+        println!(
+            "Exported {} chunks of dataset {} to {}",
+            chunk_count,
+            dataset_id,
+            path.display()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,431 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use serde_json::{json, Value};
+use tabled::{builder::Builder, settings::Style};
+
+use crate::cli::{DeleteFile, FileChunks, FilesStatus, GetFile, ListFiles};
+
+use super::{
+    audit,
+    configure::{require_login, TrieveConfiguration},
+    dataset::{require_dataset, DefaultError},
+    date::format_timestamp,
+    guard,
+    http::{download_resumable, format_bytes},
+    output::{quiet, set_exit_code, EXIT_ERROR},
+    pager,
+    rest::Rest,
+    search::html_to_text,
+    style::{self, bold, dim},
+    table::{self, Column},
+};
+
+/// Reads every page of the file listing of a dataset. Each item holds the `file` and the
+/// `group_id` of the chunk group its chunks were put in.
+async fn list_dataset_files(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+) -> Result<Vec<Value>, DefaultError> {
+    let rest = Rest::new(settings).dataset(dataset_id);
+    let mut files = vec![];
+    let mut page = 1;
+
+    loop {
+        let response: Value = rest
+            .get(&format!("/api/dataset/files/{}/{}", dataset_id, page))
+            .await?;
+        files.extend(
+            response["file_and_group_ids"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default(),
+        );
+
+        if page >= response["total_pages"].as_u64().unwrap_or(1) {
+            return Ok(files);
+        }
+        page += 1;
+    }
+}
+
+/// The number of characters of a chunk shown in the chunk listing of a file.
+const PREVIEW_LENGTH: usize = 60;
+
+/// Whether a listed file has been split into chunks, which gives it its chunk group.
+fn is_chunked(item: &Value) -> bool {
+    item["group_id"].is_string()
+}
+
+/// The columns of `file list`, whose rows hold the `file` and the `group_id` of its chunk group.
+const FILE_COLUMNS: [Column<Value>; 5] = [
+    Column {
+        key: "id",
+        header: "ID",
+        value: |item| item["file"]["id"].as_str().unwrap_or_default().to_string(),
+    },
+    Column {
+        key: "name",
+        header: "Name",
+        value: |item| {
+            item["file"]["file_name"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        },
+    },
+    Column {
+        key: "size",
+        header: "Size",
+        value: |item| {
+            item["file"]["size"]
+                .as_u64()
+                .map(format_bytes)
+                .unwrap_or("-".to_string())
+        },
+    },
+    Column {
+        key: "status",
+        header: "Status",
+        value: |item| {
+            if is_chunked(item) {
+                "chunked"
+            } else {
+                "processing"
+            }
+            .to_string()
+        },
+    },
+    Column {
+        key: "created",
+        header: "Created At",
+        value: |item| format_timestamp(item["file"]["created_at"].as_str().unwrap_or_default()),
+    },
+];
+
+pub async fn list_files(
+    settings: TrieveConfiguration,
+    list: ListFiles,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(list.dataset_id, &settings)?;
+    let files = list_dataset_files(&settings, &dataset_id).await?;
+
+    if quiet() {
+        return table::print_quiet(&FILE_COLUMNS, &files, &["id"]);
+    }
+
+    if files.is_empty() {
+        println!("No files in dataset {}.", dataset_id);
+        return Ok(());
+    }
+
+    pager::print(&table::render(&FILE_COLUMNS, &files)?);
+
+    Ok(())
+}
+
+/// Reads the `file_upload_failed` events of a dataset, newest first. Their `event_data` holds the
+/// `file_id` and the `error`.
+async fn file_upload_failures(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+) -> Result<Vec<Value>, DefaultError> {
+    let rest = Rest::new(settings).dataset(dataset_id);
+    let mut events = vec![];
+
+    for page in 1.. {
+        let response: Value = rest
+            .post(
+                "/api/events",
+                &json!({ "page": page, "page_size": 100, "event_types": ["file_upload_failed"] }),
+            )
+            .await?;
+        let page_events = response["events"].as_array().cloned().unwrap_or_default();
+        if page_events.is_empty() {
+            break;
+        }
+        events.extend(page_events);
+
+        if page >= response["page_count"].as_u64().unwrap_or(1) {
+            break;
+        }
+    }
+
+    // Older servers store the data of an event as a JSON string.
+    for event in &mut events {
+        if let Some(data) = event["event_data"].as_str() {
+            event["event_data"] = serde_json::from_str(data).unwrap_or_default();
+        }
+    }
+
+    Ok(events)
+}
+
+/// Counts the files of a dataset that are chunked, still queued for processing, or failed, and
+/// prints the most recent upload errors.
+pub async fn files_status(
+    settings: TrieveConfiguration,
+    status: FilesStatus,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(status.dataset_id, &settings)?;
+    let files = list_dataset_files(&settings, &dataset_id).await?;
+    let failures = file_upload_failures(&settings, &dataset_id).await?;
+
+    let names: HashMap<&str, &str> = files
+        .iter()
+        .map(|item| {
+            (
+                item["file"]["id"].as_str().unwrap_or_default(),
+                item["file"]["file_name"].as_str().unwrap_or_default(),
+            )
+        })
+        .collect();
+    let chunked_ids: HashSet<&str> = files
+        .iter()
+        .filter(|item| is_chunked(item))
+        .map(|item| item["file"]["id"].as_str().unwrap_or_default())
+        .collect();
+    // A file that failed and was chunked by a later attempt counts as chunked. Failed uploads may
+    // not be listed at all, so they are counted from the events.
+    let failed_ids: HashSet<&str> = failures
+        .iter()
+        .filter_map(|event| event["event_data"]["file_id"].as_str())
+        .filter(|file_id| !chunked_ids.contains(file_id))
+        .collect();
+    let queued = files
+        .iter()
+        .map(|item| item["file"]["id"].as_str().unwrap_or_default())
+        .filter(|file_id| !chunked_ids.contains(file_id) && !failed_ids.contains(file_id))
+        .count();
+    let counts = [
+        ("chunked", chunked_ids.len()),
+        ("queued", queued),
+        ("failed", failed_ids.len()),
+    ];
+
+    if quiet() {
+        for (name, count) in counts {
+            println!("{}\t{}", name, count);
+        }
+    } else {
+        let total: usize = counts.iter().map(|(_, count)| count).sum();
+        println!(
+            "{} files in dataset {}",
+            bold(&total.to_string()),
+            dataset_id
+        );
+        for (name, count) in counts {
+            let line = format!("  {:<8} {:>6}", name, count);
+            match name {
+                "failed" if count > 0 => println!("{}", style::error(&line)),
+                "queued" if count > 0 => println!("{}", style::warning(&line)),
+                _ => println!("{}", line),
+            }
+        }
+
+        let recent = failures
+            .iter()
+            .filter(|event| {
+                event["event_data"]["file_id"]
+                    .as_str()
+                    .is_some_and(|file_id| failed_ids.contains(file_id))
+            })
+            .take(status.errors)
+            .collect::<Vec<&Value>>();
+        if !recent.is_empty() {
+            println!();
+            println!("{}", bold("Most recent errors:"));
+        }
+        for event in recent {
+            let file_id = event["event_data"]["file_id"].as_str().unwrap_or_default();
+            println!(
+                "  {} {} {}",
+                dim(event["created_at"].as_str().unwrap_or_default()),
+                names.get(file_id).copied().unwrap_or(file_id),
+                dim(&format!("({})", file_id))
+            );
+            println!(
+                "    {}",
+                event["event_data"]["error"]
+                    .as_str()
+                    .unwrap_or("no error message")
+            );
+        }
+    }
+
+    if status.exit_code && (queued > 0 || !failed_ids.is_empty()) {
+        set_exit_code(EXIT_ERROR);
+    }
+
+    Ok(())
+}
+
+/// The start of the text of a chunk on a single line.
+fn preview(chunk: &Value) -> String {
+    let text = html_to_text(chunk["chunk_html"].as_str().unwrap_or_default()).text();
+    let mut preview = text.chars().take(PREVIEW_LENGTH).collect::<String>();
+    if text.chars().count() > PREVIEW_LENGTH {
+        preview.push('…');
+    }
+    preview
+}
+
+/// Lists the chunks created from an uploaded file. The chunks of a file are put in a group of
+/// their own, which is found through the file listing of the dataset.
+pub async fn list_file_chunks(
+    settings: TrieveConfiguration,
+    file_chunks: FileChunks,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(file_chunks.dataset_id, &settings)?;
+    let files = list_dataset_files(&settings, &dataset_id).await?;
+    let item = files
+        .iter()
+        .find(|item| item["file"]["id"].as_str() == Some(file_chunks.file_id.as_str()))
+        .ok_or_else(|| DefaultError {
+            message: format!(
+                "File {} not found in dataset {}",
+                file_chunks.file_id, dataset_id
+            ),
+        })?;
+    let group_id = item["group_id"].as_str().ok_or_else(|| DefaultError {
+        message: format!(
+            "File {} has not been split into chunks yet",
+            file_chunks.file_id
+        ),
+    })?;
+
+    let rest = Rest::new(&settings).dataset(&dataset_id);
+    let mut chunks = vec![];
+    let mut page = 1;
+    loop {
+        let response: Value = rest
+            .get(&format!("/api/chunk_group/{}/{}", group_id, page))
+            .await?;
+        chunks.extend(response["chunks"].as_array().cloned().unwrap_or_default());
+
+        if page >= response["total_pages"].as_u64().unwrap_or(1) {
+            break;
+        }
+        page += 1;
+    }
+
+    if quiet() {
+        for chunk in &chunks {
+            println!("{}", chunk["id"].as_str().unwrap_or_default());
+        }
+        return Ok(());
+    }
+
+    let mut builder = Builder::default();
+    builder.push_record(["ID", "Tracking ID", "Text"]);
+    for chunk in &chunks {
+        builder.push_record([
+            chunk["id"].as_str().unwrap_or_default().to_string(),
+            chunk["tracking_id"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+            preview(chunk),
+        ]);
+    }
+
+    pager::print(&format!(
+        "{} chunks from {} (group {}):\n{}",
+        chunks.len(),
+        item["file"]["file_name"].as_str().unwrap_or_default(),
+        group_id,
+        builder.build().with(Style::rounded())
+    ));
+
+    Ok(())
+}
+
+pub async fn get_file(settings: TrieveConfiguration, get: GetFile) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(get.dataset_id, &settings)?;
+    // The response holds a signed URL of the original document that expires after a while, so
+    // it is requested again on every run, including resumed ones.
+    let file: Value = Rest::new(&settings)
+        .dataset(&dataset_id)
+        .get(&format!("/api/file/{}", get.file_id))
+        .await?;
+    let url = file["s3_url"].as_str().ok_or_else(|| DefaultError {
+        message: format!(
+            "The server did not return a download URL for file {}",
+            get.file_id
+        ),
+    })?;
+
+    // Only the last component of the name is used, so a name cannot point outside `--out`.
+    let file_name = file["file_name"]
+        .as_str()
+        .and_then(|name| Path::new(name).file_name())
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or(get.file_id.clone());
+    let out = get.out.to_string_lossy();
+    let path = if get.out.is_dir() || out.ends_with('/') || out.ends_with('\\') {
+        fs::create_dir_all(&get.out).map_err(|e| DefaultError {
+            message: format!("Could not create {}: {}", get.out.display(), e),
+        })?;
+        get.out.join(file_name)
+    } else {
+        get.out.clone()
+    };
+
+    let size = download_resumable(url, &path).await?;
+
+    if quiet() {
+        println!("{}", path.display());
+    } else {
+        println!("Downloaded {} ({}).", path.display(), format_bytes(size));
+    }
+
+    Ok(())
+}
+
+pub async fn delete_file(
+    settings: TrieveConfiguration,
+    delete: DeleteFile,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(delete.dataset_id, &settings)?;
+    guard::confirm_destructive(&if delete.delete_chunks {
+        format!("delete file {} and its chunks", delete.file_id)
+    } else {
+        format!("delete file {}", delete.file_id)
+    })?;
+    Rest::new(&settings)
+        .dataset(&dataset_id)
+        .delete(&format!(
+            "/api/file/{}?delete_chunks={}",
+            delete.file_id, delete.delete_chunks
+        ))
+        .await?;
+    audit::record(
+        &settings,
+        "file delete",
+        Some(&delete.file_id),
+        json!({ "dataset_id": dataset_id, "delete_chunks": delete.delete_chunks }),
+    );
+
+    if !quiet() {
+        if delete.delete_chunks {
+            println!("Deleted file {} and its chunks.", delete.file_id);
+        } else {
+            println!("Deleted file {}.", delete.file_id);
+        }
+    }
+
+    Ok(())
+}
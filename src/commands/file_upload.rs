@@ -0,0 +1,427 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use base64::{engine::general_purpose::URL_SAFE, Engine};
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde_json::{json, Value};
+use tokio::task::JoinSet;
+
+use crate::cli::UploadFiles;
+
+use super::{
+    audit,
+    configure::{require_login, TrieveConfiguration},
+    dataset::{require_dataset, DefaultError},
+    output::quiet,
+    rest::Rest,
+    style::{dim, error, success, warning},
+    upload::MAX_CONCURRENT_BATCHES,
+};
+
+/// The file in a directory with the glob patterns of the files not to upload.
+const IGNORE_FILE: &str = ".trieveignore";
+
+/// The number of bytes read from the start of a file to tell text from binary data.
+const SNIFF_LENGTH: usize = 8192;
+
+/// The MIME types, or their prefixes, of the documents the server can split into chunks.
+const SUPPORTED_TYPES: [&str; 14] = [
+    "text/",
+    "application/pdf",
+    "application/json",
+    "application/xml",
+    "application/rtf",
+    "application/epub+zip",
+    "application/msword",
+    "application/vnd.ms-excel",
+    "application/vnd.ms-powerpoint",
+    "application/vnd.openxmlformats-officedocument.",
+    "application/vnd.oasis.opendocument.",
+    "message/rfc822",
+    "application/x-tex",
+    "application/x-sh",
+];
+
+/// What happened to a file found in the uploaded paths.
+enum Outcome {
+    Uploaded(String),
+    /// The file would be uploaded without `--dry-run`.
+    Pending,
+    Skipped(String),
+    Failed(String),
+}
+
+/// A file to upload, with the path it is shown and recorded with.
+struct Candidate {
+    path: PathBuf,
+    display: String,
+}
+
+/// Reads the `.trieveignore` file of a directory. Like `.gitignore`, a pattern without a `/`
+/// matches at any depth, and a pattern matching a directory skips everything in it.
+fn read_ignore_file(dir: &Path) -> Result<GlobSet, DefaultError> {
+    let mut builder = GlobSetBuilder::new();
+    let Ok(contents) = fs::read_to_string(dir.join(IGNORE_FILE)) else {
+        return Ok(builder.build().unwrap_or_default());
+    };
+
+    for pattern in contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    {
+        let pattern = pattern.trim_end_matches('/').trim_start_matches('/');
+        let mut globs = vec![pattern.to_string(), format!("{}/**", pattern)];
+        if !pattern.contains('/') {
+            globs.extend([format!("**/{}", pattern), format!("**/{}/**", pattern)]);
+        }
+        for glob in globs {
+            builder.add(
+                GlobBuilder::new(&glob)
+                    .literal_separator(true)
+                    .build()
+                    .map_err(|e| DefaultError {
+                        message: format!("Invalid pattern '{}' in {}: {}", pattern, IGNORE_FILE, e),
+                    })?,
+            );
+        }
+    }
+
+    builder.build().map_err(|e| DefaultError {
+        message: e.to_string(),
+    })
+}
+
+/// Why a file cannot be uploaded, if it cannot: binary data, or a type the server cannot read.
+/// Files of an unknown type are uploaded when they hold text.
+fn skip_reason(path: &Path) -> Option<String> {
+    let mut start = vec![];
+    if let Err(e) =
+        fs::File::open(path).and_then(|file| file.take(SNIFF_LENGTH as u64).read_to_end(&mut start))
+    {
+        return Some(format!("unreadable: {}", e));
+    }
+    if start.is_empty() {
+        return Some("empty".to_string());
+    }
+
+    // The start of a file may end in the middle of a character.
+    let is_text = !start.contains(&0)
+        && match std::str::from_utf8(&start) {
+            Ok(_) => true,
+            Err(e) => start.len() == SNIFF_LENGTH && e.error_len().is_none(),
+        };
+    // Files of an unknown type are guessed to be arbitrary bytes.
+    match mime_guess::from_path(path)
+        .first()
+        .filter(|mime| *mime != mime_guess::mime::APPLICATION_OCTET_STREAM)
+    {
+        Some(mime) if mime.type_() == "text" && !is_text => Some("binary".to_string()),
+        Some(mime)
+            if SUPPORTED_TYPES
+                .iter()
+                .any(|t| mime.essence_str().starts_with(t)) =>
+        {
+            None
+        }
+        Some(mime) => Some(format!("unsupported type {}", mime.essence_str())),
+        None if is_text => None,
+        None => Some("binary".to_string()),
+    }
+}
+
+/// Finds the files in `dir`, in the subdirectories too with `recursive`. Hidden files and files
+/// matching the ignore patterns are recorded as skipped.
+fn collect_dir(
+    root: &Path,
+    dir: &Path,
+    label: &str,
+    recursive: bool,
+    ignore: &GlobSet,
+    found: &mut Vec<(String, Option<Candidate>, Outcome)>,
+) -> Result<(), DefaultError> {
+    let read_error = |e: std::io::Error| DefaultError {
+        message: format!("Could not read {}: {}", dir.display(), e),
+    };
+    let mut entries = fs::read_dir(dir)
+        .map_err(read_error)?
+        .collect::<Result<Vec<fs::DirEntry>, std::io::Error>>()
+        .map_err(read_error)?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let path = entry.path();
+        let relative = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let display = format!("{}/{}", label, relative);
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_dir = path.is_dir();
+
+        if name.starts_with('.') {
+            if !is_dir && name != IGNORE_FILE {
+                found.push((display, None, Outcome::Skipped("hidden".to_string())));
+            }
+            continue;
+        }
+        if ignore.is_match(&relative) {
+            // An ignored directory is listed once instead of every file in it.
+            let display = if is_dir {
+                format!("{}/", display)
+            } else {
+                display
+            };
+            found.push((display, None, Outcome::Skipped("ignored".to_string())));
+            continue;
+        }
+        if is_dir {
+            if recursive {
+                collect_dir(root, &path, label, recursive, ignore, found)?;
+            }
+            continue;
+        }
+
+        match skip_reason(&path) {
+            Some(reason) => found.push((display, None, Outcome::Skipped(reason))),
+            None => found.push((
+                display.clone(),
+                Some(Candidate { path, display }),
+                Outcome::Pending,
+            )),
+        }
+    }
+
+    Ok(())
+}
+
+async fn upload_file(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    candidate: &Candidate,
+    tags: &[String],
+) -> Result<String, DefaultError> {
+    let contents = fs::read(&candidate.path).map_err(|e| DefaultError {
+        message: format!("Could not read {}: {}", candidate.path.display(), e),
+    })?;
+    let file_name = candidate
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut body = json!({
+        "base64_file": URL_SAFE.encode(contents),
+        "file_name": file_name,
+        "metadata": { "path": candidate.display },
+    });
+    if !tags.is_empty() {
+        body["tag_set"] = json!(tags);
+    }
+
+    let response: Value = Rest::new(settings)
+        .dataset(dataset_id)
+        .post("/api/file", &body)
+        .await?;
+    Ok(response["file_metadata"]["id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// A directory of the summary tree, with the outcome of each file in it.
+#[derive(Default)]
+struct Tree {
+    dirs: BTreeMap<String, Tree>,
+    files: BTreeMap<String, String>,
+}
+
+impl Tree {
+    fn insert(&mut self, path: &str, outcome: String) {
+        match path.split_once('/') {
+            Some((dir, rest)) if !rest.is_empty() => self
+                .dirs
+                .entry(format!("{}/", dir))
+                .or_default()
+                .insert(rest, outcome),
+            _ => {
+                self.files.insert(path.to_string(), outcome);
+            }
+        }
+    }
+
+    fn print(&self, prefix: &str) {
+        let count = self.dirs.len() + self.files.len();
+        let entries = self
+            .dirs
+            .iter()
+            .map(|(name, dir)| (name, Some(dir), None))
+            .chain(
+                self.files
+                    .iter()
+                    .map(|(name, outcome)| (name, None, Some(outcome))),
+            );
+
+        for (i, (name, dir, outcome)) in entries.enumerate() {
+            let last = i + 1 == count;
+            let branch = if last { "└── " } else { "├── " };
+            match (dir, outcome) {
+                (Some(dir), _) => {
+                    println!("{}{}{}", prefix, branch, name);
+                    dir.print(&format!("{}{}", prefix, if last { "    " } else { "│   " }));
+                }
+                (_, Some(outcome)) => println!("{}{}{}  {}", prefix, branch, name, outcome),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn describe(outcome: &Outcome, dry_run: bool) -> String {
+    match outcome {
+        Outcome::Uploaded(id) => format!("{} {}", success("uploaded"), dim(id)),
+        Outcome::Pending if dry_run => success("would be uploaded"),
+        Outcome::Pending => String::new(),
+        Outcome::Skipped(reason) => warning(&format!("skipped: {}", reason)),
+        Outcome::Failed(message) => error(&format!("failed: {}", message)),
+    }
+}
+
+/// Uploads files and the files in directories to be split into chunks by the server, a few at a
+/// time. Binary files, types the server cannot read and files matching the `.trieveignore` of a
+/// directory are skipped, and a tree of what happened to every file is printed at the end.
+pub async fn upload_files(
+    settings: TrieveConfiguration,
+    upload: UploadFiles,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let mut found = vec![];
+    for path in &upload.paths {
+        let label = path.to_string_lossy().trim_end_matches('/').to_string();
+        if path.is_dir() {
+            let ignore = read_ignore_file(path)?;
+            collect_dir(path, path, &label, upload.recursive, &ignore, &mut found)?;
+        } else if path.is_file() {
+            let outcome = skip_reason(path).map_or(Outcome::Pending, Outcome::Skipped);
+            let candidate = matches!(outcome, Outcome::Pending).then(|| Candidate {
+                path: path.clone(),
+                display: label.clone(),
+            });
+            found.push((label, candidate, outcome));
+        } else {
+            return Err(DefaultError {
+                message: format!("{} does not exist", path.display()),
+            });
+        }
+    }
+
+    let dataset_id = require_dataset(upload.dataset_id.clone(), &settings)?;
+    let pending = found
+        .iter()
+        .filter(|(_, candidate, _)| candidate.is_some())
+        .count();
+
+    if !upload.dry_run {
+        if !quiet() && pending > 0 {
+            println!("Uploading {} files to dataset {}...", pending, dataset_id);
+        }
+
+        let max_in_flight = settings
+            .upload_concurrency
+            .unwrap_or(MAX_CONCURRENT_BATCHES)
+            .max(1);
+        let mut in_flight = JoinSet::new();
+        let mut results = vec![];
+        for (i, (_, candidate, _)) in found.iter_mut().enumerate() {
+            let Some(candidate) = candidate.take() else {
+                continue;
+            };
+            while in_flight.len() >= max_in_flight {
+                if let Some(finished) = in_flight.join_next().await {
+                    results.push(finished.unwrap());
+                }
+            }
+
+            let settings = settings.clone();
+            let dataset_id = dataset_id.clone();
+            let tags = upload.tags.clone();
+            in_flight.spawn(async move {
+                let result = upload_file(&settings, &dataset_id, &candidate, &tags).await;
+                (i, result)
+            });
+        }
+        while let Some(finished) = in_flight.join_next().await {
+            results.push(finished.unwrap());
+        }
+
+        for (i, result) in results {
+            found[i].2 = match result {
+                Ok(file_id) => Outcome::Uploaded(file_id),
+                Err(e) => Outcome::Failed(e.message),
+            };
+        }
+    }
+
+    let count = |matches: fn(&Outcome) -> bool| {
+        found
+            .iter()
+            .filter(|(_, _, outcome)| matches(outcome))
+            .count()
+    };
+    let uploaded = count(|outcome| matches!(outcome, Outcome::Uploaded(_)));
+    let skipped = count(|outcome| matches!(outcome, Outcome::Skipped(_)));
+    let failed = count(|outcome| matches!(outcome, Outcome::Failed(_)));
+
+    if !upload.dry_run {
+        audit::record(
+            &settings,
+            "file upload",
+            Some(&dataset_id),
+            json!({ "uploaded": uploaded, "skipped": skipped, "failed": failed }),
+        );
+    }
+
+    if quiet() {
+        for (path, _, outcome) in &found {
+            let (status, detail) = match outcome {
+                Outcome::Uploaded(id) => ("uploaded", id.as_str()),
+                Outcome::Pending => ("pending", ""),
+                Outcome::Skipped(reason) => ("skipped", reason.as_str()),
+                Outcome::Failed(message) => ("failed", message.as_str()),
+            };
+            println!("{}\t{}\t{}", status, path, detail);
+        }
+    } else {
+        let mut tree = Tree::default();
+        for (path, _, outcome) in &found {
+            tree.insert(path, describe(outcome, upload.dry_run));
+        }
+        tree.print("");
+        println!();
+        if upload.dry_run {
+            println!(
+                "{} files would be uploaded to dataset {}, {} skipped",
+                pending, dataset_id, skipped
+            );
+        } else {
+            println!(
+                "Uploaded {} files to dataset {}, {} skipped, {} failed",
+                uploaded, dataset_id, skipped, failed
+            );
+        }
+    }
+
+    if failed > 0 {
+        return Err(DefaultError {
+            message: format!("{} files could not be uploaded", failed),
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use serde_json::{json, Map, Value};
+
+use super::dataset::DefaultError;
+
+/// The clauses of a Trieve chunk filter.
+const CLAUSES: [&str; 3] = ["must", "must_not", "should"];
+
+/// Builds a Trieve chunk filter from `--filter` arguments, which all have to match. Every argument
+/// is either a condition or a filter as JSON, whose clauses are merged with the others:
+///
+/// - `FIELD=VALUE[,VALUE...]` matches chunks where the field has any of the values
+/// - `FIELD!=VALUE[,VALUE...]` matches chunks where it has none of them
+///
+/// `FIELD` is `tag_set`, `link`, `metadata.<key>` or any other field Trieve can filter on.
+pub fn parse_filters(filters: &[String]) -> Result<Value, DefaultError> {
+    let mut clauses: Map<String, Value> = Map::new();
+    let mut push = |clause: &str, condition: Value| {
+        if let Value::Array(conditions) = clauses
+            .entry(clause)
+            .or_insert_with(|| Value::Array(vec![]))
+        {
+            conditions.push(condition);
+        }
+    };
+
+    for filter in filters {
+        if filter.trim_start().starts_with('{') {
+            let value: Value = serde_json::from_str(filter).map_err(|e| DefaultError {
+                message: format!("Invalid filter JSON '{}': {}", filter, e),
+            })?;
+            for clause in CLAUSES {
+                for condition in value[clause].as_array().into_iter().flatten() {
+                    push(clause, condition.clone());
+                }
+            }
+            continue;
+        }
+
+        let (clause, field, values) = if let Some((field, values)) = filter.split_once("!=") {
+            ("must_not", field, values)
+        } else if let Some((field, values)) = filter.split_once('=') {
+            ("must", field, values)
+        } else {
+            return Err(DefaultError {
+                message: format!(
+                    "Invalid filter '{}', expected FIELD=VALUE, FIELD!=VALUE or a filter as JSON",
+                    filter
+                ),
+            });
+        };
+
+        let values = values
+            .split(',')
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .collect::<Vec<&str>>();
+        push(
+            clause,
+            json!({ "field": field.trim(), "match_any": values }),
+        );
+    }
+
+    Ok(Value::Object(clauses))
+}
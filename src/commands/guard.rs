@@ -0,0 +1,52 @@
+use std::{
+    io::{stdin, IsTerminal},
+    sync::OnceLock,
+};
+
+use inquire::Text;
+
+use super::{
+    dataset::DefaultError,
+    i18n::tr_args,
+    output::{fail, EXIT_ERROR},
+};
+
+/// The name of the profile the command runs with, if it is protected.
+static PROTECTED_PROFILE: OnceLock<Option<String>> = OnceLock::new();
+
+/// Sets the protected profile the command runs with, if any.
+pub fn init(protected_profile: Option<String>) {
+    let _ = PROTECTED_PROFILE.set(protected_profile);
+}
+
+/// Called before a destructive change such as deleting a dataset. When the command runs with a
+/// protected profile, the name of the profile must be typed to continue, even with `--yes`, or
+/// the command fails without changing anything. Without a terminal the name is read from stdin.
+pub fn confirm_destructive(action: &str) -> Result<(), DefaultError> {
+    let Some(profile) = PROTECTED_PROFILE.get().cloned().flatten() else {
+        return Ok(());
+    };
+
+    let prompt = tr_args(
+        "profile-protected-confirm",
+        &[("profile", profile.clone()), ("action", action.to_string())],
+    );
+    let typed = if stdin().is_terminal() {
+        Text::new(&prompt).prompt().unwrap_or_default()
+    } else {
+        eprintln!("{}", prompt);
+        let mut line = String::new();
+        stdin().read_line(&mut line).unwrap_or_default();
+        line
+    };
+
+    if typed.trim() != profile {
+        return Err(fail(
+            EXIT_ERROR,
+            "profile_protected",
+            tr_args("profile-protected-mismatch", &[("profile", profile)]),
+        ));
+    }
+
+    Ok(())
+}
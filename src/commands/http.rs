@@ -0,0 +1,369 @@
+use std::{
+    env, fs,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        OnceLock,
+    },
+    time::Duration,
+};
+
+use reqwest::{header::RANGE, Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use tokio::io::AsyncWriteExt;
+
+use super::{
+    client::USER_AGENT, configure::TrieveConfiguration, dataset::DefaultError, output, session,
+};
+
+const MAX_RETRIES: u32 = 3;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Counters for every request sent through this module, printed by `--stats`.
+#[derive(Default)]
+struct Stats {
+    calls: AtomicU64,
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    retries: AtomicU64,
+    /// Responses telling the CLI to slow down: 429 and 5xx.
+    overloaded: AtomicU64,
+}
+
+/// Proxy, TLS and timeout settings applied to every HTTP client the CLI creates.
+#[derive(Default)]
+pub struct NetworkOptions {
+    pub proxy: Option<String>,
+    pub ca_cert: Option<PathBuf>,
+    pub no_verify_tls: bool,
+    /// Timeout for a whole request, including reading the response
+    pub timeout: Option<Duration>,
+}
+
+static STATS: OnceLock<Stats> = OnceLock::new();
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+fn stats() -> &'static Stats {
+    STATS.get_or_init(Stats::default)
+}
+
+fn client_builder() -> reqwest::ClientBuilder {
+    reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+}
+
+/// The shared, connection pooled client. It is used for raw requests and passed as
+/// `Configuration::client` to the generated `trieve_client`.
+pub fn client() -> reqwest::Client {
+    CLIENT
+        .get_or_init(|| client_builder().build().unwrap_or_default())
+        .clone()
+}
+
+/// Builds the shared client from the `--proxy`, `--ca-cert`, `--no-verify-tls` and `--timeout`
+/// options. Must be called before the first request is made.
+pub fn configure(options: NetworkOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let mut client = client_builder();
+
+    if let Some(timeout) = options.timeout {
+        client = client.timeout(timeout);
+    }
+
+    if let Some(proxy) = &options.proxy {
+        client = client.proxy(reqwest::Proxy::all(proxy)?);
+    }
+
+    if let Some(path) = &options.ca_cert {
+        let ca_cert =
+            fs::read(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        client = client.add_root_certificate(reqwest::Certificate::from_pem(&ca_cert)?);
+    }
+
+    if options.no_verify_tls {
+        client = client.danger_accept_invalid_certs(true);
+    }
+
+    CLIENT
+        .set(client.build()?)
+        .map_err(|_| "The HTTP client was created before the network options were applied")?;
+
+    Ok(())
+}
+
+pub struct Response {
+    pub body: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl Response {
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T, DefaultError> {
+        serde_json::from_str(&self.body).map_err(|e| DefaultError {
+            message: e.to_string(),
+        })
+    }
+}
+
+/// Starts a request against the Trieve API with the credentials of `settings`.
+pub fn api_request(settings: &TrieveConfiguration, method: Method, path: &str) -> RequestBuilder {
+    client()
+        .request(method, format!("{}{}", settings.api_url, path))
+        .header("Authorization", &settings.api_key)
+}
+
+/// Starts a request against an arbitrary URL, e.g. to download a seed file.
+pub fn request(method: Method, url: &str) -> RequestBuilder {
+    client().request(method, url)
+}
+
+fn request_error(e: reqwest::Error) -> DefaultError {
+    DefaultError {
+        message: e.to_string(),
+    }
+}
+
+/// Sends a request with an optional JSON body and reads the whole response. Requests that are
+/// rate limited (429) or hit a server that is temporarily unavailable (503) are retried up to
+/// `MAX_RETRIES` times, honoring `Retry-After` when it is set.
+pub async fn send(
+    request: RequestBuilder,
+    body: Option<&serde_json::Value>,
+) -> Result<Response, DefaultError> {
+    let body = body
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| DefaultError {
+            message: e.to_string(),
+        })?;
+
+    let mut attempt = 0;
+    loop {
+        let mut attempt_request = request.try_clone().ok_or_else(|| DefaultError {
+            message: "Request can not be retried".to_string(),
+        })?;
+        if let Some(body) = &body {
+            stats()
+                .bytes_sent
+                .fetch_add(body.len() as u64, Ordering::Relaxed);
+            attempt_request = attempt_request
+                .header("Content-Type", "application/json")
+                .body(body.clone());
+        }
+
+        stats().calls.fetch_add(1, Ordering::Relaxed);
+        let response = attempt_request.send().await.map_err(request_error)?;
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+            stats().overloaded.fetch_add(1, Ordering::Relaxed);
+        }
+
+        if matches!(
+            status,
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+        ) && attempt < MAX_RETRIES
+        {
+            attempt += 1;
+            stats().retries.fetch_add(1, Ordering::Relaxed);
+
+            let wait = response
+                .headers()
+                .get("Retry-After")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(2u64.pow(attempt));
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+            continue;
+        }
+
+        session::on_unauthorized(status).await?;
+
+        let url = response.url().to_string();
+        let response = read_response(response).await?;
+        if !status.is_success() {
+            output::record_api_error(status, &response.headers);
+            return Err(DefaultError {
+                message: format!(
+                    "{}: status code {}: {}",
+                    url,
+                    status.as_u16(),
+                    response.body
+                ),
+            });
+        }
+
+        return Ok(response);
+    }
+}
+
+async fn read_response(response: reqwest::Response) -> Result<Response, DefaultError> {
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|value| (name.to_string(), value.to_string()))
+        })
+        .collect();
+
+    let body = response.text().await.map_err(request_error)?;
+    stats()
+        .bytes_received
+        .fetch_add(body.len() as u64, Ordering::Relaxed);
+
+    Ok(Response { body, headers })
+}
+
+/// A downloaded file that is removed once the reader is dropped.
+struct TempDownload {
+    file: fs::File,
+    path: PathBuf,
+}
+
+impl Read for TempDownload {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+impl Drop for TempDownload {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Streams a file to a temporary location without buffering it in memory and returns a reader
+/// over it, so the parsers reading it do not block the runtime on the network.
+pub async fn download(url: &str) -> Result<Box<dyn Read + Send + Sync>, DefaultError> {
+    let io_error = |e: std::io::Error| DefaultError {
+        message: e.to_string(),
+    };
+
+    stats().calls.fetch_add(1, Ordering::Relaxed);
+    let mut response = request(Method::GET, url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(request_error)?;
+
+    let path = env::temp_dir().join(format!("trieve-download-{}", uuid::Uuid::new_v4()));
+    let mut file = tokio::fs::File::create(&path).await.map_err(io_error)?;
+
+    while let Some(chunk) = response.chunk().await.map_err(request_error)? {
+        stats()
+            .bytes_received
+            .fetch_add(chunk.len() as u64, Ordering::Relaxed);
+        file.write_all(&chunk).await.map_err(io_error)?;
+    }
+    file.flush().await.map_err(io_error)?;
+
+    let file = fs::File::open(&path).map_err(io_error)?;
+    Ok(Box::new(TempDownload { file, path }))
+}
+
+/// Downloads a file to `path` and returns its size. The data is written to `<path>.part` first;
+/// when a previous download left one behind, only the rest of the file is requested, so large
+/// files do not have to start over after an interrupted download. Servers that ignore the range
+/// send the whole file again.
+pub async fn download_resumable(url: &str, path: &Path) -> Result<u64, DefaultError> {
+    let io_error = |e: std::io::Error| DefaultError {
+        message: format!("{}: {}", path.display(), e),
+    };
+
+    let mut part = path.as_os_str().to_owned();
+    part.push(".part");
+    let part = PathBuf::from(part);
+    let offset = fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+
+    let mut request = request(Method::GET, url);
+    if offset > 0 {
+        request = request.header(RANGE, format!("bytes={}-", offset));
+    }
+    stats().calls.fetch_add(1, Ordering::Relaxed);
+    let mut response = request.send().await.map_err(request_error)?;
+
+    let mut file = match response.status() {
+        // The part file already holds the whole file.
+        StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => None,
+        StatusCode::PARTIAL_CONTENT if offset > 0 => {
+            if !output::quiet() {
+                eprintln!("Resuming the download at {}", format_bytes(offset));
+            }
+            Some(
+                tokio::fs::OpenOptions::new()
+                    .append(true)
+                    .open(&part)
+                    .await
+                    .map_err(io_error)?,
+            )
+        }
+        _ => {
+            response = response.error_for_status().map_err(request_error)?;
+            Some(tokio::fs::File::create(&part).await.map_err(io_error)?)
+        }
+    };
+
+    if let Some(file) = &mut file {
+        while let Some(chunk) = response.chunk().await.map_err(request_error)? {
+            stats()
+                .bytes_received
+                .fetch_add(chunk.len() as u64, Ordering::Relaxed);
+            file.write_all(&chunk).await.map_err(io_error)?;
+        }
+        file.flush().await.map_err(io_error)?;
+    }
+
+    fs::rename(&part, path).map_err(io_error)?;
+    fs::metadata(path).map(|m| m.len()).map_err(io_error)
+}
+
+/// Counts a call made through the generated `trieve_client`, which sends its requests itself
+/// rather than through `send`, with the status of the response when it failed. Its bytes are not
+/// known and are left out of `--stats`.
+pub fn record_client_call(status: Option<StatusCode>) {
+    stats().calls.fetch_add(1, Ordering::Relaxed);
+    if status
+        .is_some_and(|status| status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error())
+    {
+        stats().overloaded.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Counts a retry of a call made through the generated `trieve_client`.
+pub fn record_client_retry() {
+    stats().retries.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The number of 429 and 5xx responses received so far, which uploads slow down on.
+pub fn overloaded_responses() -> u64 {
+    stats().overloaded.load(Ordering::Relaxed)
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1048575 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1048576.0),
+    }
+}
+
+/// Prints the summary shown by `--stats` to stderr, so it does not mix with command output.
+pub fn print_stats(elapsed: Duration) {
+    let stats = stats();
+    eprintln!();
+    eprintln!("Wall time:      {:.2}s", elapsed.as_secs_f64());
+    eprintln!("API calls:      {}", stats.calls.load(Ordering::Relaxed));
+    eprintln!(
+        "Bytes sent:     {}",
+        format_bytes(stats.bytes_sent.load(Ordering::Relaxed))
+    );
+    eprintln!(
+        "Bytes received: {}",
+        format_bytes(stats.bytes_received.load(Ordering::Relaxed))
+    );
+    eprintln!("Retries:        {}", stats.retries.load(Ordering::Relaxed));
+}
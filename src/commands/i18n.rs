@@ -0,0 +1,91 @@
+use std::{env, sync::OnceLock};
+
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource, FluentValue};
+use unic_langid::LanguageIdentifier;
+
+const DEFAULT_LOCALE: &str = "en";
+const LOCALES: [(&str, &str); 2] = [
+    ("en", include_str!("../../locales/en.ftl")),
+    ("es", include_str!("../../locales/es.ftl")),
+];
+
+struct Translations {
+    selected: FluentBundle<FluentResource>,
+    fallback: FluentBundle<FluentResource>,
+}
+
+static TRANSLATIONS: OnceLock<Translations> = OnceLock::new();
+
+fn load_bundle(locale: &str) -> FluentBundle<FluentResource> {
+    let source = LOCALES
+        .iter()
+        .find(|(name, _)| *name == locale)
+        .map(|(_, source)| *source)
+        .unwrap_or(LOCALES[0].1);
+
+    let mut bundle = FluentBundle::new_concurrent(vec![locale
+        .parse::<LanguageIdentifier>()
+        .unwrap_or_default()]);
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(
+            FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, _)| resource),
+        )
+        .expect("Translation files should not define a message twice");
+
+    bundle
+}
+
+/// Selects the locale from `--lang` / `TRIEVE_LANG`, falling back to the `LANG` environment
+/// variable (e.g. `es_ES.UTF-8`) and then to English.
+pub fn init(lang: Option<String>) {
+    let requested = lang.or_else(|| env::var("LANG").ok()).unwrap_or_default();
+    let language = requested
+        .split(['_', '-', '.'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let locale = LOCALES
+        .iter()
+        .map(|(name, _)| *name)
+        .find(|name| *name == language)
+        .unwrap_or(DEFAULT_LOCALE);
+
+    let _ = TRANSLATIONS.set(Translations {
+        selected: load_bundle(locale),
+        fallback: load_bundle(DEFAULT_LOCALE),
+    });
+}
+
+fn format(bundle: &FluentBundle<FluentResource>, key: &str, args: &FluentArgs) -> Option<String> {
+    let pattern = bundle.get_message(key)?.value()?;
+    let mut errors = vec![];
+    Some(
+        bundle
+            .format_pattern(pattern, Some(args), &mut errors)
+            .into_owned(),
+    )
+}
+
+/// Returns the translation of `key`, falling back to English and then to the key itself.
+pub fn tr(key: &str) -> String {
+    tr_args(key, &[])
+}
+
+/// Like `tr`, filling in the `{ $name }` placeables of the message from `args`.
+pub fn tr_args(key: &str, args: &[(&str, String)]) -> String {
+    let translations = TRANSLATIONS.get_or_init(|| Translations {
+        selected: load_bundle(DEFAULT_LOCALE),
+        fallback: load_bundle(DEFAULT_LOCALE),
+    });
+
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, FluentValue::from(value.clone()));
+    }
+
+    format(&translations.selected, key, &fluent_args)
+        .or_else(|| format(&translations.fallback, key, &fluent_args))
+        .unwrap_or_else(|| key.to_string())
+}
@@ -0,0 +1,1042 @@
+use std::{
+    fs::{self, File},
+    io::{Cursor, Read, Seek},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use scraper::{Html, Selector};
+use serde_json::{json, Value};
+use tabled::{builder::Builder, settings::Style};
+use trieve_client::models::ChunkReqPayload;
+
+use crate::cli::{DedupeArgs, IngestExport, IngestGit, IngestOpenApi, IngestPdf, IngestRss};
+
+use super::{
+    chunk::report_upload,
+    chunking::{
+        escape_html, estimate_tokens, split_code, split_html, split_markdown, split_pages,
+        ChunkingOptions, TextChunk,
+    },
+    configure::{require_login, TrieveConfiguration},
+    dataset::{require_dataset, DefaultError},
+    http,
+    output::quiet,
+    upload::{upload_chunk_stream, Dedupe},
+};
+
+/// A chunk extracted from a local source, with where in the source it was found.
+struct SourceChunk {
+    /// The name of the file or other source the chunk was extracted from.
+    source: String,
+    /// Where in the source the chunk is, e.g. its page number.
+    location: String,
+    /// The tracking IDs of the groups the chunk is added to.
+    groups: Vec<String>,
+    link: Option<String>,
+    time_stamp: Option<String>,
+    chunk: TextChunk,
+    metadata: serde_json::Value,
+}
+
+impl SourceChunk {
+    /// Every chunk is tracked by its source and position, so ingesting the same source again
+    /// replaces its chunks instead of adding copies.
+    fn into_payload(self, index: usize, tags: &[String]) -> ChunkReqPayload {
+        ChunkReqPayload {
+            chunk_html: Some(Some(self.chunk.html)),
+            tracking_id: Some(Some(format!("{}:{}", self.source, index))),
+            tag_set: Some(Some(tags.to_vec()).filter(|tags| !tags.is_empty())),
+            group_tracking_ids: Some(Some(self.groups).filter(|groups| !groups.is_empty())),
+            link: Some(self.link),
+            time_stamp: Some(self.time_stamp),
+            metadata: Some(Some(self.metadata)),
+            upsert_by_tracking_id: Some(Some(true)),
+            ..Default::default()
+        }
+    }
+}
+
+fn print_preview(location_header: &str, chunks: &[SourceChunk]) {
+    if quiet() {
+        println!("{}", chunks.len());
+        return;
+    }
+
+    let mut builder = Builder::default();
+    builder.push_record(["Source", location_header, "Tokens", "Section", "Start"]);
+    for chunk in chunks {
+        builder.push_record([
+            chunk.source.clone(),
+            chunk.location.clone(),
+            chunk.chunk.tokens.to_string(),
+            chunk.chunk.headings.join(" > "),
+            chunk.chunk.preview(50),
+        ]);
+    }
+    println!("{}", builder.build().with(Style::rounded()));
+    println!("{} chunks", chunks.len());
+}
+
+/// Uploads the chunks of every source to the dataset, tracking the chunks of a source by their
+/// position within it.
+async fn upload_source_chunks(
+    settings: TrieveConfiguration,
+    dataset_id: String,
+    chunks: Vec<SourceChunk>,
+    tags: Vec<String>,
+    dedupe: &DedupeArgs,
+) -> Result<(), DefaultError> {
+    let dedupe = Dedupe::from_args(dedupe, &settings, &dataset_id).await?;
+    let mut index = 0;
+    let mut previous_source = String::new();
+    let records = chunks
+        .into_iter()
+        .map(move |chunk| {
+            if chunk.source != previous_source {
+                previous_source = chunk.source.clone();
+                index = 0;
+            }
+            index += 1;
+            Ok(chunk.into_payload(index, &tags))
+        })
+        .collect::<Vec<_>>();
+
+    let summary = upload_chunk_stream(
+        settings,
+        dataset_id.clone(),
+        Box::new(records.into_iter()),
+        None,
+        dedupe,
+        None,
+    )
+    .await?;
+
+    report_upload(&summary, &dataset_id, false)?;
+
+    Ok(())
+}
+
+fn source_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
+
+/// Extracts the text of PDF files locally, for documents the server fails to process, and uploads
+/// it in chunks that record the page they are on.
+pub async fn ingest_pdf(
+    settings: TrieveConfiguration,
+    ingest: IngestPdf,
+) -> Result<(), DefaultError> {
+    let options = ChunkingOptions::from(&ingest.chunking);
+    let mut chunks = vec![];
+
+    for file in &ingest.files {
+        let pages = pdf_extract::extract_text_by_pages(file).map_err(|e| DefaultError {
+            message: format!("Could not extract the text of {}: {}", file.display(), e),
+        })?;
+        if pages.iter().all(|page| page.trim().is_empty()) {
+            eprintln!(
+                "No text found in {}, it may only contain scanned images.",
+                file.display()
+            );
+            continue;
+        }
+
+        let source = source_name(file);
+        chunks.extend(
+            split_pages(&pages, &options)
+                .into_iter()
+                .map(|(page, chunk)| SourceChunk {
+                    metadata: json!({
+                        "source": source,
+                        "page": page,
+                        "headings": chunk.headings,
+                    }),
+                    source: source.clone(),
+                    location: page.to_string(),
+                    groups: vec![source.clone()],
+                    link: None,
+                    time_stamp: None,
+                    chunk,
+                }),
+        );
+    }
+
+    if ingest.dry_run {
+        print_preview("Page", &chunks);
+        return Ok(());
+    }
+
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(ingest.dataset_id.clone(), &settings)?;
+    upload_source_chunks(settings, dataset_id, chunks, ingest.tags, &ingest.dedupe).await
+}
+
+/// Files larger than this are skipped by `ingest git`, as they are usually generated or data.
+const MAX_SOURCE_FILE_SIZE: u64 = 256 * 1024;
+
+/// The languages of source files by extension, used as the code block language of their chunks.
+const LANGUAGES: [(&str, &str); 28] = [
+    ("rs", "rust"),
+    ("py", "python"),
+    ("js", "javascript"),
+    ("jsx", "javascript"),
+    ("mjs", "javascript"),
+    ("ts", "typescript"),
+    ("tsx", "typescript"),
+    ("go", "go"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cpp", "cpp"),
+    ("cc", "cpp"),
+    ("hpp", "cpp"),
+    ("cs", "csharp"),
+    ("rb", "ruby"),
+    ("php", "php"),
+    ("swift", "swift"),
+    ("scala", "scala"),
+    ("sh", "bash"),
+    ("sql", "sql"),
+    ("vue", "vue"),
+    ("svelte", "svelte"),
+    ("ex", "elixir"),
+    ("exs", "elixir"),
+    ("lua", "lua"),
+    ("zig", "zig"),
+];
+
+fn git(dir: Option<&Path>, args: &[&str]) -> Result<String, DefaultError> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    let output = command.args(args).output().map_err(|e| DefaultError {
+        message: format!("Could not run git: {}", e),
+    })?;
+
+    if !output.status.success() {
+        return Err(DefaultError {
+            message: format!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// A shallow clone of a remote repository, removed again when dropped.
+struct TemporaryClone(PathBuf);
+
+impl Drop for TemporaryClone {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn build_globs(globs: &[String]) -> Result<GlobSet, DefaultError> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        builder.add(Glob::new(glob).map_err(|e| DefaultError {
+            message: format!("Invalid glob '{}': {}", glob, e),
+        })?);
+    }
+    builder.build().map_err(|e| DefaultError {
+        message: e.to_string(),
+    })
+}
+
+/// The language of a file by its name, with `markdown` for Markdown files and READMEs.
+fn language_of(path: &str) -> Option<&'static str> {
+    let name = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+    let extension = name.rsplit_once('.').map(|(_, extension)| extension);
+
+    if name.starts_with("readme") || matches!(extension, Some("md" | "mdx" | "markdown")) {
+        return Some("markdown");
+    }
+
+    extension.and_then(|extension| {
+        LANGUAGES
+            .iter()
+            .find(|(e, _)| *e == extension)
+            .map(|(_, language)| *language)
+    })
+}
+
+/// Chunks the files of a local or remote git repository, recording the path, language and commit
+/// of every chunk, so code search datasets can be built from CI.
+pub async fn ingest_git(
+    settings: TrieveConfiguration,
+    ingest: IngestGit,
+) -> Result<(), DefaultError> {
+    let local = Path::new(&ingest.repository);
+    let (dir, _clone) = if local.exists() {
+        (local.to_path_buf(), None)
+    } else {
+        let dir = std::env::temp_dir().join(format!("trieve-ingest-{}", uuid::Uuid::new_v4()));
+        let target = dir.to_string_lossy().to_string();
+        let mut args = vec!["clone", "--quiet", "--depth", "1"];
+        if let Some(branch) = &ingest.branch {
+            args.extend(["--branch", branch.as_str()]);
+        }
+        args.extend([ingest.repository.as_str(), target.as_str()]);
+        git(None, &args)?;
+        (dir.clone(), Some(TemporaryClone(dir)))
+    };
+
+    let repository = ingest
+        .repository
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .rsplit(['/', ':'])
+        .next()
+        .filter(|name| !name.is_empty() && *name != ".")
+        .map(str::to_string)
+        .or_else(|| {
+            fs::canonicalize(&dir)
+                .ok()
+                .and_then(|dir| dir.file_name().map(|n| n.to_string_lossy().to_string()))
+        })
+        .unwrap_or_else(|| "repository".to_string());
+    let commit = git(Some(&dir), &["rev-parse", "HEAD"])?.trim().to_string();
+    let include = build_globs(&ingest.include)?;
+    let exclude = build_globs(&ingest.exclude)?;
+    let options = ChunkingOptions::from(&ingest.chunking);
+
+    let files = git(Some(&dir), &["ls-files", "-z"])?;
+    let mut chunks = vec![];
+
+    for path in files.split('\0').filter(|path| !path.is_empty()) {
+        if exclude.is_match(path) {
+            continue;
+        }
+        let language = match language_of(path) {
+            Some(language) if ingest.include.is_empty() || include.is_match(path) => language,
+            None if !ingest.include.is_empty() && include.is_match(path) => "plaintext",
+            _ => continue,
+        };
+
+        let file = dir.join(path);
+        if fs::metadata(&file).map_or(true, |m| !m.is_file() || m.len() > MAX_SOURCE_FILE_SIZE) {
+            continue;
+        }
+        // Binary files are not valid UTF-8 and are skipped.
+        let Ok(contents) = fs::read_to_string(&file) else {
+            continue;
+        };
+
+        let file_chunks = if language == "markdown" {
+            split_markdown(&contents, &options)
+        } else {
+            split_code(&contents, language, &options)
+        };
+
+        let source = format!("{}/{}", repository, path);
+        chunks.extend(file_chunks.into_iter().map(|chunk| SourceChunk {
+            metadata: json!({
+                "repository": repository,
+                "path": path,
+                "commit": commit,
+                "language": language,
+                "headings": chunk.headings,
+            }),
+            source: source.clone(),
+            location: language.to_string(),
+            groups: vec![repository.clone()],
+            link: None,
+            time_stamp: None,
+            chunk,
+        }));
+    }
+
+    if ingest.dry_run {
+        print_preview("Language", &chunks);
+        return Ok(());
+    }
+
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(ingest.dataset_id.clone(), &settings)?;
+    upload_source_chunks(settings, dataset_id, chunks, ingest.tags, &ingest.dedupe).await
+}
+
+/// A page of a Notion or Confluence export.
+struct ExportPage {
+    id: String,
+    title: String,
+    /// The titles of the pages above this one, outermost first.
+    ancestors: Vec<String>,
+    parent_id: Option<String>,
+    chunks: Vec<TextChunk>,
+}
+
+/// Reads every file in a zip archive. Archives inside the archive are read as well, as large
+/// Notion exports are split into several zip files wrapped in another one.
+fn read_zip<R: Read + Seek>(
+    reader: R,
+    files: &mut Vec<(String, Vec<u8>)>,
+) -> Result<(), DefaultError> {
+    let to_error = |e: zip::result::ZipError| DefaultError {
+        message: format!("Could not read the export: {}", e),
+    };
+    let mut archive = zip::ZipArchive::new(reader).map_err(to_error)?;
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(to_error)?;
+        if file.is_dir() {
+            continue;
+        }
+        let name = file.name().to_string();
+        let mut contents = vec![];
+        file.read_to_end(&mut contents).map_err(|e| DefaultError {
+            message: format!("Could not read {} from the export: {}", name, e),
+        })?;
+
+        if name.to_lowercase().ends_with(".zip") {
+            read_zip(Cursor::new(contents), files)?;
+        } else {
+            files.push((name, contents));
+        }
+    }
+
+    Ok(())
+}
+
+fn read_export(path: &Path) -> Result<Vec<(String, Vec<u8>)>, DefaultError> {
+    let file = File::open(path).map_err(|e| DefaultError {
+        message: format!("Could not open {}: {}", path.display(), e),
+    })?;
+    let mut files = vec![];
+    read_zip(file, &mut files)?;
+    Ok(files)
+}
+
+/// Splits a Notion file or directory name like `Onboarding 0123456789abcdef0123456789abcdef.md`
+/// into its title and page ID.
+fn notion_title_and_id(name: &str) -> (String, Option<String>) {
+    let stem = name
+        .strip_suffix(".md")
+        .or_else(|| name.strip_suffix(".html"))
+        .unwrap_or(name);
+
+    match stem.rsplit_once(' ') {
+        Some((title, id)) if id.len() == 32 && id.chars().all(|c| c.is_ascii_hexdigit()) => {
+            (title.to_string(), Some(id.to_string()))
+        }
+        _ => (stem.to_string(), None),
+    }
+}
+
+/// Reads the pages of a Notion "Markdown & CSV" or "HTML" export. Child pages are stored in a
+/// directory named like their parent page, which gives the hierarchy. Database CSVs and
+/// attachments are skipped.
+fn notion_pages(files: Vec<(String, Vec<u8>)>, options: &ChunkingOptions) -> Vec<ExportPage> {
+    let mut pages = vec![];
+
+    for (name, contents) in files {
+        let is_markdown = name.ends_with(".md");
+        if !is_markdown && !name.ends_with(".html") {
+            continue;
+        }
+        let Ok(contents) = String::from_utf8(contents) else {
+            continue;
+        };
+
+        let mut components = name.split('/').collect::<Vec<&str>>();
+        let file_name = components.pop().unwrap_or_default();
+        let (title, id) = notion_title_and_id(file_name);
+        let parents = components
+            .iter()
+            .map(|component| notion_title_and_id(component))
+            // Exports wrap everything in a directory named after the workspace or export.
+            .filter(|(_, id)| id.is_some())
+            .collect::<Vec<_>>();
+
+        let chunks = if is_markdown {
+            split_markdown(&contents, options)
+        } else {
+            split_html(&contents, options)
+        };
+
+        pages.push(ExportPage {
+            id: id.unwrap_or_else(|| name.clone()),
+            title,
+            ancestors: parents.iter().map(|(title, _)| title.clone()).collect(),
+            parent_id: parents.last().and_then(|(_, id)| id.clone()),
+            chunks,
+        });
+    }
+
+    pages
+}
+
+/// The ID of a Confluence page from its file name, like `Onboarding_123456.html` or
+/// `123456.html`.
+fn confluence_page_id(file_name: &str) -> Option<String> {
+    let stem = file_name.strip_suffix(".html")?;
+    let id = stem.rsplit('_').next().unwrap_or(stem);
+    Some(id.to_string()).filter(|id| !id.is_empty() && id.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Reads the pages of a Confluence space exported as HTML. The breadcrumbs of every page give the
+/// hierarchy; the space overview (`index.html`), attachments and styles are skipped.
+fn confluence_pages(
+    files: Vec<(String, Vec<u8>)>,
+    options: &ChunkingOptions,
+) -> Result<Vec<ExportPage>, DefaultError> {
+    let selector = |selector: &str| {
+        Selector::parse(selector).map_err(|e| DefaultError {
+            message: e.to_string(),
+        })
+    };
+    let title_selector = selector("#title-text")?;
+    let head_title_selector = selector("title")?;
+    let breadcrumb_selector = selector("#breadcrumbs a")?;
+    let content_selector = selector("#main-content")?;
+
+    let mut pages = vec![];
+
+    for (name, contents) in files {
+        let file_name = name.rsplit('/').next().unwrap_or(&name);
+        let Some(id) = confluence_page_id(file_name) else {
+            continue;
+        };
+        let contents = String::from_utf8_lossy(&contents);
+        let document = Html::parse_document(&contents);
+
+        let title = document
+            .select(&title_selector)
+            .next()
+            .or_else(|| document.select(&head_title_selector).next())
+            .map(|title| title.text().collect::<String>())
+            .unwrap_or_default();
+        // Titles are prefixed with the name of the space, e.g. `Engineering : Onboarding`.
+        let title = title
+            .split_once(" : ")
+            .map(|(_, title)| title)
+            .unwrap_or(&title)
+            .trim()
+            .to_string();
+
+        let breadcrumbs = document
+            .select(&breadcrumb_selector)
+            .map(|a| {
+                (
+                    a.text().collect::<String>().trim().to_string(),
+                    a.value().attr("href").and_then(confluence_page_id),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let content = document
+            .select(&content_selector)
+            .next()
+            .map(|content| content.html())
+            .unwrap_or_else(|| contents.to_string());
+
+        pages.push(ExportPage {
+            id,
+            title,
+            ancestors: breadcrumbs.iter().map(|(title, _)| title.clone()).collect(),
+            parent_id: breadcrumbs.last().and_then(|(_, id)| id.clone()),
+            chunks: split_html(&content, options),
+        });
+    }
+
+    Ok(pages)
+}
+
+/// Uploads the pages of a knowledge base export, one group per page, with the place of the page
+/// in the hierarchy in the metadata of its chunks.
+async fn ingest_export_pages(
+    settings: TrieveConfiguration,
+    ingest: IngestExport,
+    kind: &str,
+    pages: Vec<ExportPage>,
+) -> Result<(), DefaultError> {
+    let chunks = pages
+        .into_iter()
+        .flat_map(|page| {
+            let source = format!("{}:{}", kind, page.id);
+            page.chunks.into_iter().map(move |chunk| SourceChunk {
+                metadata: json!({
+                    "source": kind,
+                    "page_id": page.id,
+                    "parent_id": page.parent_id,
+                    "title": page.title,
+                    "ancestors": page.ancestors,
+                    "headings": chunk.headings,
+                }),
+                source: source.clone(),
+                location: page.title.clone(),
+                groups: vec![source.clone()],
+                link: None,
+                time_stamp: None,
+                chunk,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if ingest.dry_run {
+        print_preview("Page", &chunks);
+        return Ok(());
+    }
+
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(ingest.dataset_id.clone(), &settings)?;
+    upload_source_chunks(settings, dataset_id, chunks, ingest.tags, &ingest.dedupe).await
+}
+
+pub async fn ingest_notion_export(
+    settings: TrieveConfiguration,
+    ingest: IngestExport,
+) -> Result<(), DefaultError> {
+    let files = read_export(&ingest.file)?;
+    let pages = notion_pages(files, &ChunkingOptions::from(&ingest.chunking));
+    ingest_export_pages(settings, ingest, "notion", pages).await
+}
+
+pub async fn ingest_confluence_export(
+    settings: TrieveConfiguration,
+    ingest: IngestExport,
+) -> Result<(), DefaultError> {
+    let files = read_export(&ingest.file)?;
+    let pages = confluence_pages(files, &ChunkingOptions::from(&ingest.chunking))?;
+    ingest_export_pages(settings, ingest, "confluence", pages).await
+}
+
+/// Parses `--since`: a date (`2024-05-01`), a timestamp (`2024-05-01T12:00:00Z`) or a duration
+/// before now (`30m`, `24h`, `7d`).
+pub fn parse_since(since: &str) -> Result<DateTime<Utc>, DefaultError> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(since) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    if let Some(midnight) = NaiveDate::parse_from_str(since, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+    {
+        return Ok(midnight.and_utc());
+    }
+
+    let duration = since
+        .char_indices()
+        .last()
+        .and_then(|(i, unit)| Some((since[..i].parse::<i64>().ok()?, unit)))
+        .and_then(|(amount, unit)| match unit {
+            'm' => Some(Duration::minutes(amount)),
+            'h' => Some(Duration::hours(amount)),
+            'd' => Some(Duration::days(amount)),
+            'w' => Some(Duration::weeks(amount)),
+            _ => None,
+        })
+        .ok_or_else(|| DefaultError {
+            message: format!(
+                "Invalid --since '{}', expected a date like 2024-05-01, a timestamp or a duration like 24h or 7d",
+                since
+            ),
+        })?;
+
+    Ok(Utc::now() - duration)
+}
+
+/// Uploads the entries of an RSS or Atom feed, one chunk per section of an entry. The chunks of an
+/// entry are tracked by its GUID, so running it again from cron updates entries instead of adding
+/// them twice, and `--since` skips entries that were already ingested by an earlier run.
+pub async fn ingest_rss(
+    settings: TrieveConfiguration,
+    ingest: IngestRss,
+) -> Result<(), DefaultError> {
+    let since = ingest.since.as_deref().map(parse_since).transpose()?;
+    let options = ChunkingOptions::from(&ingest.chunking);
+
+    let reader: Box<dyn Read + Send + Sync> =
+        if ingest.feed.starts_with("http://") || ingest.feed.starts_with("https://") {
+            http::download(&ingest.feed).await?
+        } else {
+            Box::new(File::open(&ingest.feed).map_err(|e| DefaultError {
+                message: format!("Could not open {}: {}", ingest.feed, e),
+            })?)
+        };
+    let feed = feed_rs::parser::parse(reader).map_err(|e| DefaultError {
+        message: format!("Could not parse the feed {}: {}", ingest.feed, e),
+    })?;
+
+    let feed_title = feed
+        .title
+        .map(|title| title.content)
+        .unwrap_or_else(|| ingest.feed.clone());
+    let group = format!("rss:{}", ingest.feed);
+    let mut chunks = vec![];
+    let mut skipped = 0;
+
+    for entry in feed.entries {
+        let date = entry.published.or(entry.updated);
+        if since.is_some_and(|since| date.is_some_and(|date| date < since)) {
+            skipped += 1;
+            continue;
+        }
+
+        let body = entry
+            .content
+            .and_then(|content| content.body)
+            .or_else(|| entry.summary.map(|summary| summary.content))
+            .unwrap_or_default();
+        if body.trim().is_empty() {
+            continue;
+        }
+        let title = entry.title.map(|title| title.content).unwrap_or_default();
+        let link = entry.links.first().map(|link| link.href.clone());
+        let html = format!("<h1>{}</h1>{}", escape_html(&title), body);
+
+        let source = format!("rss:{}", entry.id);
+        chunks.extend(split_html(&html, &options).into_iter().map(|chunk| SourceChunk {
+            metadata: json!({
+                "source": "rss",
+                "feed": feed_title,
+                "guid": entry.id,
+                "title": title,
+                "published": date.map(|date| date.to_rfc3339()),
+                "authors": entry.authors.iter().map(|a| a.name.clone()).collect::<Vec<_>>(),
+                "categories": entry.categories.iter().map(|c| c.term.clone()).collect::<Vec<_>>(),
+                "headings": chunk.headings,
+            }),
+            source: source.clone(),
+            location: title.clone(),
+            groups: vec![group.clone()],
+            link: link.clone(),
+            time_stamp: date.map(|date| date.to_rfc3339()),
+            chunk,
+        }));
+    }
+
+    if skipped > 0 && !quiet() {
+        println!("Skipped {} entries published before --since", skipped);
+    }
+
+    if ingest.dry_run {
+        print_preview("Entry", &chunks);
+        return Ok(());
+    }
+
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(ingest.dataset_id.clone(), &settings)?;
+    upload_source_chunks(settings, dataset_id, chunks, ingest.tags, &ingest.dedupe).await
+}
+
+const HTTP_METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// A chunk made of a heading and paragraphs of plain text.
+fn text_chunk(heading: String, paragraphs: Vec<String>, headings: Vec<String>) -> TextChunk {
+    let html = std::iter::once(format!("<h2>{}</h2>", escape_html(&heading)))
+        .chain(
+            paragraphs
+                .iter()
+                .map(|paragraph| format!("<p>{}</p>", escape_html(paragraph))),
+        )
+        .collect::<Vec<String>>()
+        .join("\n");
+    let text = std::iter::once(heading)
+        .chain(paragraphs)
+        .collect::<Vec<String>>()
+        .join("\n\n");
+
+    TextChunk {
+        headings,
+        html,
+        tokens: estimate_tokens(&text),
+        text,
+    }
+}
+
+/// Follows a local `$ref` like `#/components/parameters/Limit` within the spec.
+fn resolve<'a>(spec: &'a Value, value: &'a Value) -> &'a Value {
+    value["$ref"]
+        .as_str()
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| spec.pointer(pointer))
+        .unwrap_or(value)
+}
+
+/// A short description of the type of a schema, using the name of referenced schemas.
+fn schema_type(schema: &Value) -> String {
+    if let Some(reference) = schema["$ref"].as_str() {
+        return reference
+            .rsplit('/')
+            .next()
+            .unwrap_or(reference)
+            .to_string();
+    }
+    match schema["type"].as_str() {
+        Some("array") => format!("array of {}", schema_type(&schema["items"])),
+        Some(kind) => kind.to_string(),
+        None if schema.get("oneOf").is_some() || schema.get("anyOf").is_some() => {
+            let variants = schema["oneOf"]
+                .as_array()
+                .or_else(|| schema["anyOf"].as_array())
+                .map(|variants| variants.iter().map(schema_type).collect::<Vec<_>>())
+                .unwrap_or_default();
+            format!("one of {}", variants.join(", "))
+        }
+        None => "object".to_string(),
+    }
+}
+
+/// The schema of a request body or response in OpenAPI 3 (`content`) or Swagger 2 (`schema`).
+fn content_schema(value: &Value) -> Option<(String, &Value)> {
+    if let Some(schema) = value.get("schema") {
+        return Some(("application/json".to_string(), schema));
+    }
+    value["content"]
+        .as_object()
+        .and_then(|content| content.iter().next())
+        .map(|(media_type, media)| (media_type.clone(), &media["schema"]))
+}
+
+fn description_paragraphs(value: &Value) -> Vec<String> {
+    ["summary", "description"]
+        .iter()
+        .filter_map(|key| value[*key].as_str())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn endpoint_chunk(
+    spec: &Value,
+    method: &str,
+    path: &str,
+    path_item: &Value,
+    operation: &Value,
+) -> TextChunk {
+    let mut paragraphs = description_paragraphs(operation);
+
+    if let Some(operation_id) = operation["operationId"].as_str() {
+        paragraphs.push(format!("Operation ID: {}", operation_id));
+    }
+
+    let parameters = path_item["parameters"]
+        .as_array()
+        .into_iter()
+        .chain(operation["parameters"].as_array())
+        .flatten()
+        .map(|parameter| resolve(spec, parameter));
+    for parameter in parameters {
+        let location = parameter["in"].as_str().unwrap_or_default();
+        let kind = if location == "body" {
+            schema_type(&parameter["schema"])
+        } else {
+            schema_type(parameter.get("schema").unwrap_or(parameter))
+        };
+        let mut line = format!(
+            "Parameter {} ({}, {}{})",
+            parameter["name"].as_str().unwrap_or_default(),
+            location,
+            kind,
+            if parameter["required"].as_bool().unwrap_or(false) {
+                ", required"
+            } else {
+                ""
+            }
+        );
+        if let Some(description) = parameter["description"].as_str() {
+            line.push_str(&format!(": {}", description.trim()));
+        }
+        paragraphs.push(line);
+    }
+
+    if let Some((media_type, schema)) = operation
+        .get("requestBody")
+        .map(|body| resolve(spec, body))
+        .and_then(content_schema)
+    {
+        paragraphs.push(format!(
+            "Request body ({}): {}",
+            media_type,
+            schema_type(schema)
+        ));
+    }
+
+    if let Some(responses) = operation["responses"].as_object() {
+        for (status, response) in responses {
+            let response = resolve(spec, response);
+            let mut line = format!(
+                "Response {}: {}",
+                status,
+                response["description"].as_str().unwrap_or_default().trim()
+            );
+            if let Some((_, schema)) = content_schema(response) {
+                line.push_str(&format!(" ({})", schema_type(schema)));
+            }
+            paragraphs.push(line);
+        }
+    }
+
+    text_chunk(
+        format!("{} {}", method.to_uppercase(), path),
+        paragraphs,
+        operation_tags(operation),
+    )
+}
+
+fn operation_tags(operation: &Value) -> Vec<String> {
+    operation["tags"]
+        .as_array()
+        .map(|tags| {
+            tags.iter()
+                .filter_map(|tag| tag.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn schema_chunk(name: &str, schema: &Value) -> TextChunk {
+    let mut paragraphs = description_paragraphs(schema);
+    let required = schema["required"]
+        .as_array()
+        .map(|required| {
+            required
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    if let Some(properties) = schema["properties"].as_object() {
+        for (property, value) in properties {
+            let mut line = format!(
+                "Property {} ({}{})",
+                property,
+                schema_type(value),
+                if required.contains(&property.as_str()) {
+                    ", required"
+                } else {
+                    ""
+                }
+            );
+            if let Some(description) = value["description"].as_str() {
+                line.push_str(&format!(": {}", description.trim()));
+            }
+            paragraphs.push(line);
+        }
+    } else {
+        paragraphs.push(format!("Type: {}", schema_type(schema)));
+    }
+
+    text_chunk(name.to_string(), paragraphs, vec!["Schemas".to_string()])
+}
+
+/// Turns every endpoint and schema of an OpenAPI 3 or Swagger 2 spec into a chunk, grouped by the
+/// tags of the endpoints, so API references can be searched.
+pub async fn ingest_openapi(
+    settings: TrieveConfiguration,
+    ingest: IngestOpenApi,
+) -> Result<(), DefaultError> {
+    let mut contents = String::new();
+    let mut reader: Box<dyn Read + Send + Sync> =
+        if ingest.spec.starts_with("http://") || ingest.spec.starts_with("https://") {
+            http::download(&ingest.spec).await?
+        } else {
+            Box::new(File::open(&ingest.spec).map_err(|e| DefaultError {
+                message: format!("Could not open {}: {}", ingest.spec, e),
+            })?)
+        };
+    reader
+        .read_to_string(&mut contents)
+        .map_err(|e| DefaultError {
+            message: format!("Could not read {}: {}", ingest.spec, e),
+        })?;
+    // YAML is a superset of JSON, so this reads specs in either format.
+    let spec: Value = serde_yaml::from_str(&contents).map_err(|e| DefaultError {
+        message: format!("Invalid spec {}: {}", ingest.spec, e),
+    })?;
+    if spec.get("openapi").is_none() && spec.get("swagger").is_none() {
+        return Err(DefaultError {
+            message: format!("{} is not an OpenAPI or Swagger spec", ingest.spec),
+        });
+    }
+
+    let api = spec["info"]["title"].as_str().unwrap_or("API").to_string();
+    let version = spec["info"]["version"].as_str().map(str::to_string);
+    let group = |name: &str| format!("openapi:{}:{}", api, name);
+    let mut chunks = vec![];
+
+    for (path, path_item) in spec["paths"].as_object().into_iter().flatten() {
+        for method in HTTP_METHODS {
+            let Some(operation) = path_item.get(method) else {
+                continue;
+            };
+            let chunk = endpoint_chunk(&spec, method, path, path_item, operation);
+            let tags = operation_tags(operation);
+            let endpoint = format!("{} {}", method.to_uppercase(), path);
+
+            chunks.push(SourceChunk {
+                metadata: json!({
+                    "source": "openapi",
+                    "api": api,
+                    "version": version,
+                    "kind": "endpoint",
+                    "method": method.to_uppercase(),
+                    "path": path,
+                    "operation_id": operation["operationId"],
+                    "tags": tags,
+                    "deprecated": operation["deprecated"].as_bool().unwrap_or(false),
+                }),
+                source: format!("openapi:{}:{}", api, endpoint),
+                location: endpoint,
+                groups: if tags.is_empty() {
+                    vec![group("untagged")]
+                } else {
+                    tags.iter().map(|tag| group(tag)).collect()
+                },
+                link: None,
+                time_stamp: None,
+                chunk,
+            });
+        }
+    }
+
+    let schemas = spec["components"]["schemas"]
+        .as_object()
+        .or_else(|| spec["definitions"].as_object());
+    for (name, schema) in schemas.into_iter().flatten() {
+        chunks.push(SourceChunk {
+            metadata: json!({
+                "source": "openapi",
+                "api": api,
+                "version": version,
+                "kind": "schema",
+                "schema": name,
+            }),
+            source: format!("openapi:{}:schema:{}", api, name),
+            location: name.clone(),
+            groups: vec![group("schemas")],
+            link: None,
+            time_stamp: None,
+            chunk: schema_chunk(name, schema),
+        });
+    }
+
+    if ingest.dry_run {
+        print_preview("Endpoint or Schema", &chunks);
+        return Ok(());
+    }
+
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(ingest.dataset_id.clone(), &settings)?;
+    upload_source_chunks(settings, dataset_id, chunks, ingest.tags, &ingest.dedupe).await
+}
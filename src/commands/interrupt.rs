@@ -0,0 +1,36 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Set once stopping was requested, see `request`.
+static REQUESTED: AtomicBool = AtomicBool::new(false);
+/// The number of operations running that stop cleanly when asked to, see `watch`.
+static WATCHING: AtomicUsize = AtomicUsize::new(0);
+
+/// Held by an operation that checks `requested` and stops cleanly, such as an upload, which
+/// finishes the batches in flight first.
+pub struct Watch;
+
+/// Starts watching for a request to stop, clearing the requests made before.
+pub fn watch() -> Watch {
+    if WATCHING.fetch_add(1, Ordering::SeqCst) == 0 {
+        REQUESTED.store(false, Ordering::SeqCst);
+    }
+    Watch
+}
+
+impl Drop for Watch {
+    fn drop(&mut self) {
+        WATCHING.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Asks the operations watching to stop. Returns whether any is, so the CLI can exit right away
+/// otherwise, as it does on Ctrl-C.
+pub fn request() -> bool {
+    REQUESTED.store(true, Ordering::SeqCst);
+    WATCHING.load(Ordering::SeqCst) > 0
+}
+
+/// Whether stopping was requested since the operation started watching.
+pub fn requested() -> bool {
+    REQUESTED.load(Ordering::SeqCst)
+}
@@ -0,0 +1,247 @@
+use reqwest::Method;
+use serde_json::{json, Value};
+
+use crate::cli::{
+    CreateInvitation, InvitationLinks, ListInvitations, ResendInvitation, RevokeInvitation,
+};
+
+use super::{
+    audit,
+    configure::{require_login, TrieveConfiguration},
+    dataset::DefaultError,
+    date::format_timestamp,
+    guard,
+    output::{quiet, success},
+    pager,
+    rest::Rest,
+    style::id,
+    table::{self, Column},
+    user::{role_level, role_name},
+};
+
+/// The columns of `organization invitations list`.
+const INVITATION_COLUMNS: [Column<Value>; 5] = [
+    Column {
+        key: "id",
+        header: "ID",
+        value: |invitation| invitation["id"].as_str().unwrap_or_default().to_string(),
+    },
+    Column {
+        key: "email",
+        header: "Email",
+        value: |invitation| invitation["email"].as_str().unwrap_or_default().to_string(),
+    },
+    Column {
+        key: "role",
+        header: "Role",
+        value: |invitation| role_name(invitation["role"].as_i64().unwrap_or_default()).to_string(),
+    },
+    Column {
+        key: "status",
+        header: "Status",
+        value: |invitation| {
+            if invitation["used"].as_bool().unwrap_or_default() {
+                "accepted"
+            } else {
+                "pending"
+            }
+            .to_string()
+        },
+    },
+    Column {
+        key: "created",
+        header: "Created At",
+        value: |invitation| format_timestamp(invitation["created_at"].as_str().unwrap_or_default()),
+    },
+];
+
+async fn get_invitations(settings: &TrieveConfiguration) -> Result<Vec<Value>, DefaultError> {
+    Rest::new(settings)
+        .get(&format!("/api/invitations/{}", settings.organization_id))
+        .await
+}
+
+/// Sends an invitation email. Users who already have an account are added to the organization
+/// right away instead.
+async fn send_invitation(
+    settings: &TrieveConfiguration,
+    email: &str,
+    role: i64,
+    links: &InvitationLinks,
+) -> Result<(), DefaultError> {
+    let body = json!({
+        "organization_id": settings.organization_id,
+        "email": email,
+        "user_role": role,
+        "app_url": links.app_url,
+        "redirect_uri": links.redirect_uri.as_ref().unwrap_or(&links.app_url),
+    });
+    Rest::new(settings)
+        .send(Method::POST, "/api/invitation", Some(&body))
+        .await?;
+    audit::record(
+        settings,
+        "organization invitation create",
+        Some(email),
+        json!({ "organization_id": settings.organization_id, "role": role_name(role) }),
+    );
+    Ok(())
+}
+
+async fn delete_invitation(
+    settings: &TrieveConfiguration,
+    invitation_id: &str,
+) -> Result<(), DefaultError> {
+    Rest::new(settings)
+        .delete(&format!("/api/invitation/{}", invitation_id))
+        .await?;
+    audit::record(
+        settings,
+        "organization invitation revoke",
+        Some(invitation_id),
+        json!({ "organization_id": settings.organization_id }),
+    );
+    Ok(())
+}
+
+/// Finds the invitations with the given IDs, failing on the first one that does not exist.
+async fn find_invitations(
+    settings: &TrieveConfiguration,
+    invitation_ids: &[String],
+) -> Result<Vec<Value>, DefaultError> {
+    let invitations = get_invitations(settings).await?;
+    invitation_ids
+        .iter()
+        .map(|invitation_id| {
+            invitations
+                .iter()
+                .find(|invitation| invitation["id"] == *invitation_id)
+                .cloned()
+                .ok_or_else(|| DefaultError {
+                    message: format!(
+                        "Organization {} has no invitation {}",
+                        settings.organization_id, invitation_id
+                    ),
+                })
+        })
+        .collect()
+}
+
+pub async fn list_invitations(
+    settings: TrieveConfiguration,
+    list: ListInvitations,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let mut invitations = get_invitations(&settings).await?;
+    if list.pending {
+        invitations.retain(|invitation| !invitation["used"].as_bool().unwrap_or_default());
+    }
+
+    if quiet() {
+        return table::print_quiet(&INVITATION_COLUMNS, &invitations, &["id"]);
+    }
+
+    if invitations.is_empty() {
+        println!(
+            "No {}invitations in organization {}.",
+            if list.pending { "pending " } else { "" },
+            settings.organization_id
+        );
+        return Ok(());
+    }
+
+    pager::print(&table::render(&INVITATION_COLUMNS, &invitations)?);
+
+    Ok(())
+}
+
+pub async fn create_invitations(
+    settings: TrieveConfiguration,
+    create: CreateInvitation,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    for email in &create.emails {
+        send_invitation(&settings, email, role_level(create.role), &create.links).await?;
+        if !quiet() {
+            success(&format!("Invited {}.", email));
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn revoke_invitations(
+    settings: TrieveConfiguration,
+    revoke: RevokeInvitation,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let invitations = find_invitations(&settings, &revoke.invitation_ids).await?;
+    let emails = invitations
+        .iter()
+        .map(|invitation| invitation["email"].as_str().unwrap_or_default())
+        .collect::<Vec<&str>>();
+    guard::confirm_destructive(&format!("revoke the invitations of {}", emails.join(", ")))?;
+
+    for invitation in &invitations {
+        let invitation_id = invitation["id"].as_str().unwrap_or_default();
+        delete_invitation(&settings, invitation_id).await?;
+        if !quiet() {
+            success(&format!(
+                "Revoked the invitation of {} ({}).",
+                invitation["email"].as_str().unwrap_or_default(),
+                id(invitation_id)
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn resend_invitations(
+    settings: TrieveConfiguration,
+    resend: ResendInvitation,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let invitations = find_invitations(&settings, &resend.invitation_ids).await?;
+    if let Some(accepted) = invitations
+        .iter()
+        .find(|invitation| invitation["used"].as_bool().unwrap_or_default())
+    {
+        return Err(DefaultError {
+            message: format!(
+                "The invitation of {} ({}) has already been accepted",
+                accepted["email"].as_str().unwrap_or_default(),
+                accepted["id"].as_str().unwrap_or_default()
+            ),
+        });
+    }
+
+    let emails = invitations
+        .iter()
+        .map(|invitation| invitation["email"].as_str().unwrap_or_default())
+        .collect::<Vec<&str>>();
+    guard::confirm_destructive(&format!("replace the invitations of {}", emails.join(", ")))?;
+
+    for invitation in &invitations {
+        let invitation_id = invitation["id"].as_str().unwrap_or_default();
+        let email = invitation["email"].as_str().unwrap_or_default();
+        // An invitation cannot be sent twice, so it is replaced with a new one.
+        delete_invitation(&settings, invitation_id).await?;
+        send_invitation(
+            &settings,
+            email,
+            invitation["role"].as_i64().unwrap_or_default(),
+            &resend.links,
+        )
+        .await?;
+        if !quiet() {
+            success(&format!("Sent the invitation to {} again.", email));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use std::{
+    env, fs,
+    io::{Read, Seek, SeekFrom},
+    path::PathBuf,
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::cli::{JobLogs, RunJob};
+
+use super::{
+    configure::config_subdir,
+    dataset::DefaultError,
+    date::format_time,
+    output::quiet,
+    pager,
+    table::{self, Column},
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JobRecord {
+    pub id: String,
+    pub args: Vec<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub exit_code: Option<i32>,
+}
+
+impl JobRecord {
+    fn status(&self) -> String {
+        match self.exit_code {
+            None => "running".to_string(),
+            Some(0) => "succeeded".to_string(),
+            Some(code) => format!("failed ({})", code),
+        }
+    }
+}
+
+fn jobs_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    config_subdir("jobs")
+}
+
+fn record_path(job_id: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(jobs_dir()?.join(format!("{}.json", job_id)))
+}
+
+fn log_path(job_id: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(jobs_dir()?.join(format!("{}.log", job_id)))
+}
+
+fn load_record(job_id: &str) -> Result<JobRecord, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(record_path(job_id)?)
+        .map_err(|_| format!("Job '{}' not found.", job_id))?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+fn store_record(record: &JobRecord) -> Result<(), Box<dyn std::error::Error>> {
+    fs::write(
+        record_path(&record.id)?,
+        serde_json::to_string_pretty(record)?,
+    )?;
+    Ok(())
+}
+
+/// Re-executes the current command in the background without the `--detach` flag and prints the
+/// job ID. Progress is written to the job log, and the command returns right after.
+pub fn detach() -> Result<(), DefaultError> {
+    let args = env::args()
+        .skip(1)
+        .filter(|a| a != "--detach")
+        .collect::<Vec<String>>();
+
+    let record = JobRecord {
+        id: uuid::Uuid::new_v4().to_string(),
+        args,
+        started_at: Utc::now(),
+        finished_at: None,
+        exit_code: None,
+    };
+
+    let spawned = store_record(&record).and_then(|_| {
+        let mut runner = Command::new(env::current_exe()?);
+        runner
+            .args(["jobs", "run", &record.id])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            // Move the runner into its own process group so closing the terminal does not stop it.
+            runner.process_group(0);
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            // The Windows counterpart: no console is attached, so closing the terminal does not
+            // stop the runner either.
+            const DETACHED_PROCESS: u32 = 0x0000_0008;
+            const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+            runner.creation_flags(DETACHED_PROCESS | CREATE_NEW_PROCESS_GROUP);
+        }
+
+        runner.spawn()?;
+        Ok(())
+    });
+
+    spawned.map_err(|e| DefaultError {
+        message: format!("Error starting background job: {}", e),
+    })?;
+
+    println!("Started background job: {}", record.id);
+    println!(
+        "Run `trieve jobs logs {} --follow` to watch its progress.",
+        record.id
+    );
+    Ok(())
+}
+
+/// Runs a detached job to completion, writing its output to the job log and recording its exit code.
+pub fn run_job(run: RunJob) -> Result<(), Box<dyn std::error::Error>> {
+    let mut record = load_record(&run.job_id)?;
+    let log = fs::File::create(log_path(&record.id)?)?;
+
+    let status = Command::new(env::current_exe()?)
+        .args(&record.args)
+        .env("TRIEVE_JOB_ID", &record.id)
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log)
+        .status()?;
+
+    record.finished_at = Some(Utc::now());
+    record.exit_code = Some(status.code().unwrap_or(-1));
+    store_record(&record)
+}
+
+/// The columns of `jobs list`.
+const JOB_COLUMNS: [Column<JobRecord>; 4] = [
+    Column {
+        key: "id",
+        header: "ID",
+        value: |record| record.id.clone(),
+    },
+    Column {
+        key: "command",
+        header: "Command",
+        value: |record| format!("trieve {}", record.args.join(" ")),
+    },
+    Column {
+        key: "started",
+        header: "Started At",
+        value: |record| format_time(record.started_at),
+    },
+    Column {
+        key: "status",
+        header: "Status",
+        value: |record| record.status(),
+    },
+];
+
+pub fn list_jobs() -> Result<(), Box<dyn std::error::Error>> {
+    let mut records = fs::read_dir(jobs_dir()?)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|e| e == "json"))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|contents| serde_json::from_str::<JobRecord>(&contents).ok())
+        .collect::<Vec<JobRecord>>();
+
+    records.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+
+    if quiet() {
+        table::print_quiet(&JOB_COLUMNS, &records, &["id"]).map_err(|e| e.message)?;
+        return Ok(());
+    }
+
+    let table = table::render(&JOB_COLUMNS, &records).map_err(|e| e.message)?;
+    pager::print(&format!("Jobs:\n{}", table));
+
+    Ok(())
+}
+
+pub fn job_logs(logs: JobLogs) -> Result<(), Box<dyn std::error::Error>> {
+    let record = load_record(&logs.job_id)?;
+    let mut file = fs::File::open(log_path(&record.id)?)
+        .map_err(|_| format!("No logs found for job '{}'.", record.id))?;
+
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    print!("{}", contents);
+
+    if !logs.follow {
+        return Ok(());
+    }
+
+    let mut position = file.stream_position()?;
+    loop {
+        std::thread::sleep(Duration::from_secs(1));
+
+        file.seek(SeekFrom::Start(position))?;
+        let mut new_contents = String::new();
+        file.read_to_string(&mut new_contents)?;
+        position = file.stream_position()?;
+        print!("{}", new_contents);
+
+        if new_contents.is_empty() && load_record(&record.id)?.exit_code.is_some() {
+            break;
+        }
+    }
+
+    println!(
+        "Job finished with status: {}",
+        load_record(&record.id)?.status()
+    );
+
+    Ok(())
+}
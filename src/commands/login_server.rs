@@ -1,71 +1,284 @@
 // Updated example from http://rosettacode.org/wiki/Hello_world/Web_server#Rust
 // to work with Rust 1.0 beta
 
+use std::{sync::Arc, time::Duration};
+
 use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
 
 use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+
+/// How long `trieve login` waits for the browser to redirect back.
+pub const LOGIN_TIMEOUT: Duration = Duration::from_secs(5 * 60);
 
-fn extract_api_key(request: &str) -> Option<String> {
-    // Split the request into lines and find the first line (request line)
-    let request_line = request.lines().next()?;
-    let api_key_prefix = "apiKey=";
+/// The largest request head accepted from the browser.
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
 
-    // Extract the URL part from the request line
-    let url_part = request_line.split_whitespace().nth(1)?;
+/// How long a connection may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
 
-    // Find the start of the apiKey parameter
-    if let Some(start_pos) = url_part.find(api_key_prefix) {
-        // Calculate the start position of the apiKey value
-        let value_start_pos = start_pos + api_key_prefix.len();
+const SUCCESS_PAGE: &str = "<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"UTF-8\"><meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\"><title>Login Success</title><link href=\"https://fonts.googleapis.com/css2?family=Roboto:wght@400;500&display=swap\" rel=\"stylesheet\"><style>body {font-family: 'Roboto', sans-serif; text-align: center; margin-top: 50px;} img {max-width: 200px;} h1, p {margin: 20px 0;}</style></head><body><img src=\"https://cdn.trieve.ai/trieve-logo.png\" alt=\"Trieve Logo\"><h1>Login Succeeded</h1><p>Return to your terminal to continue setup.</p></body></html>";
+
+/// The random value sent with the login request and expected back with the API key, so other
+/// pages cannot hand the CLI a key of their choosing.
+fn new_state() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
 
-        // Find the end of the apiKey value
-        let value_end_pos = url_part[value_start_pos..]
-            .find('&')
-            .map_or(url_part.len(), |pos| value_start_pos + pos);
+struct Request {
+    method: String,
+    path: String,
+    query: Vec<(String, String)>,
+}
+
+impl Request {
+    fn parse(head: &str) -> Option<Self> {
+        let mut request_line = head.lines().next()?.split_whitespace();
+        let method = request_line.next()?.to_string();
+        let target = request_line.next()?;
+        if !request_line.next()?.starts_with("HTTP/") {
+            return None;
+        }
+
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let query = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (percent_decode(key), percent_decode(value))
+            })
+            .collect();
+
+        Some(Request {
+            method,
+            path: path.to_string(),
+            query,
+        })
+    }
+
+    fn param(&self, name: &str) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
 
-        // Extract the apiKey value
-        let api_key_value = &url_part[value_start_pos..value_end_pos];
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
 
-        Some(api_key_value.to_owned())
-    } else {
-        None
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let byte = std::str::from_utf8(&bytes[i + 1..i + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match byte {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 3;
+                        continue;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            b'+' => decoded.push(b' '),
+            byte => decoded.push(byte),
+        }
+        i += 1;
     }
+
+    String::from_utf8_lossy(&decoded).into_owned()
 }
 
-async fn handle_read(stream: &mut TcpStream, tx: mpsc::Sender<String>) {
-    let mut buf = [0u8; 4096];
-    match stream.read(&mut buf).await {
-        Ok(_) => {
-            let req_str = String::from_utf8_lossy(&buf);
-            let _ = tx.send(extract_api_key(req_str.as_ref()).unwrap()).await;
+/// Reads up to the end of the request head. Bodies are never needed, so they are not read.
+async fn read_head(stream: &mut TcpStream) -> io::Result<String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() >= MAX_REQUEST_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request too large",
+            ));
+        }
+        let read = stream.read(&mut buf).await?;
+        if read == 0 {
+            break;
         }
-        Err(e) => println!("Unable to read stream: {}", e),
+        head.extend_from_slice(&buf[..read]);
     }
+
+    Ok(String::from_utf8_lossy(&head).into_owned())
 }
 
-async fn handle_write(mut stream: TcpStream) {
-    let response = b"HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=UTF-8\r\n\r\n<!DOCTYPE html><html lang=\"en\"><head><meta charset=\"UTF-8\"><meta name=\"viewport\" content=\"width=device-width, initial-scale=1.0\"><title>Login Success</title><link href=\"https://fonts.googleapis.com/css2?family=Roboto:wght@400;500&display=swap\" rel=\"stylesheet\"><style>body {font-family: 'Roboto', sans-serif; text-align: center; margin-top: 50px;} img {max-width: 200px;} h1, p {margin: 20px 0;}</style></head><body><img src=\"https://cdn.trieve.ai/trieve-logo.png\" alt=\"Trieve Logo\"><h1>Login Succeeded</h1><p>Return to your terminal to continue setup.</p></body></html>\r\n";
-    match stream.write(response).await {
-        Ok(_) => (),
-        Err(e) => println!("Failed sending response: {}", e),
+async fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}; charset=UTF-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    if let Err(e) = stream.write_all(response.as_bytes()).await {
+        eprintln!("Failed sending response: {}", e);
     }
+    let _ = stream.shutdown().await;
 }
 
-async fn handle_client(mut stream: TcpStream, tx: mpsc::Sender<String>) {
-    handle_read(&mut stream, tx).await;
-    handle_write(stream).await;
+async fn handle_client(mut stream: TcpStream, state: &str, tx: mpsc::Sender<String>) {
+    let request = match timeout(READ_TIMEOUT, read_head(&mut stream)).await {
+        Ok(Ok(head)) => Request::parse(&head),
+        _ => None,
+    };
+    let Some(request) = request else {
+        respond(&mut stream, "400 Bad Request", "text/plain", "Bad request").await;
+        return;
+    };
+
+    if request.method != "GET" {
+        respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            "Method not allowed",
+        )
+        .await;
+        return;
+    }
+
+    // Browsers also ask for /favicon.ico and the like, which are answered without failing login.
+    let (Some(api_key), true) = (request.param("apiKey"), request.path == "/") else {
+        respond(&mut stream, "404 Not Found", "text/plain", "Not found").await;
+        return;
+    };
+
+    if request.param("state") != Some(state) || api_key.is_empty() {
+        respond(
+            &mut stream,
+            "400 Bad Request",
+            "text/plain",
+            "This login request was not started by the Trieve CLI. Run `trieve login` again.",
+        )
+        .await;
+        return;
+    }
+
+    respond(&mut stream, "200 OK", "text/html", SUCCESS_PAGE).await;
+    let _ = tx.send(api_key.to_string()).await;
 }
 
-pub async fn server(tx: mpsc::Sender<String>) -> io::Result<()> {
-    let listener = TcpListener::bind("127.0.0.1:65535").await?;
+/// The port the login callback server listens on unless it is taken.
+pub const DEFAULT_CALLBACK_PORT: u16 = 65535;
+
+/// The ports tried, in order, when the default callback port is taken.
+const FALLBACK_CALLBACK_PORTS: std::ops::RangeInclusive<u16> = 65525..=65534;
 
-    loop {
-        let (socket, _) = listener.accept().await?;
+/// Binds the loopback addresses on `port`. IPv4 has to work; IPv6 is bound on the same port when
+/// the system supports it, because browsers on Windows and some Linux systems resolve
+/// `localhost` to `::1` first. Only loopback addresses are bound, so the Windows firewall does not
+/// prompt for network access.
+async fn bind_loopback(port: u16) -> io::Result<Vec<TcpListener>> {
+    let ipv4 = TcpListener::bind(("127.0.0.1", port)).await?;
+    let port = ipv4.local_addr()?.port();
+    let mut listeners = vec![ipv4];
+    if let Ok(ipv6) = TcpListener::bind(("::1", port)).await {
+        listeners.push(ipv6);
+    }
+    Ok(listeners)
+}
+
+/// Binds the login callback server. An explicit `port` is used as is, otherwise the default port
+/// and then the fallback ports are tried, before letting the OS pick a free port.
+async fn bind(port: Option<u16>) -> io::Result<Vec<TcpListener>> {
+    if let Some(port) = port {
+        return bind_loopback(port).await;
+    }
+
+    for port in std::iter::once(DEFAULT_CALLBACK_PORT).chain(FALLBACK_CALLBACK_PORTS.rev()) {
+        if let Ok(listeners) = bind_loopback(port).await {
+            return Ok(listeners);
+        }
+    }
+
+    bind_loopback(0).await
+}
+
+async fn serve(listener: TcpListener, state: Arc<String>, tx: mpsc::Sender<String>) {
+    while let Ok((socket, _)) = listener.accept().await {
         let tx = tx.clone();
+        let state = state.clone();
         tokio::spawn(async move {
             // Process each socket concurrently.
-            handle_client(socket, tx).await
+            handle_client(socket, &state, tx).await
         });
     }
 }
+
+/// The local server the browser is redirected to with the API key once the user logged in.
+/// Anything that can send a GET request, such as a test, can complete the login through it.
+pub struct CallbackServer {
+    port: u16,
+    state: String,
+    rx: mpsc::Receiver<String>,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl CallbackServer {
+    /// Starts the server on `port`, or on the first free default port when it is not given.
+    pub async fn start(port: Option<u16>) -> io::Result<Self> {
+        let listeners = bind(port).await?;
+        let port = listeners[0].local_addr()?.port();
+        let state = new_state();
+        let (tx, rx) = mpsc::channel::<String>(100);
+
+        let shared_state = Arc::new(state.clone());
+        let tasks = listeners
+            .into_iter()
+            .map(|listener| tokio::spawn(serve(listener, shared_state.clone(), tx.clone())))
+            .collect();
+
+        Ok(CallbackServer {
+            port,
+            state,
+            rx,
+            tasks,
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// The value the redirect has to carry back, see `new_state`.
+    pub fn state(&self) -> &str {
+        &self.state
+    }
+
+    /// The page that logs the user in to the Trieve server at `api_url` and redirects back to this
+    /// server with an API key.
+    pub fn login_url(&self, api_url: &str) -> String {
+        format!(
+            "{api_url}/api/auth?redirect_uri={api_url}/auth/cli%3Fhost={api_url}%26port={port}%26state={state}",
+            api_url = api_url,
+            port = self.port,
+            state = self.state
+        )
+    }
+
+    /// Waits for the API key and stops the server. Returns `None` when no key arrived within
+    /// `wait`.
+    pub async fn api_key(mut self, wait: Duration) -> Option<String> {
+        let api_key = timeout(wait, self.rx.recv()).await.ok().flatten();
+        for task in &self.tasks {
+            task.abort();
+        }
+        api_key
+    }
+}
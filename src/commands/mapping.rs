@@ -0,0 +1,372 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use serde_json::{Map, Value};
+use trieve_client::models::ChunkReqPayload;
+
+use super::dataset::DefaultError;
+
+pub const CHUNK_FIELDS: [&str; 9] = [
+    "chunk_html",
+    "link",
+    "tag_set",
+    "tracking_id",
+    "metadata",
+    "group_tracking_ids",
+    "num_value",
+    "time_stamp",
+    "weight",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MappedValue {
+    Text(String),
+    List(Vec<String>),
+}
+
+impl MappedValue {
+    fn map(self, f: impl Fn(&str) -> String) -> Self {
+        match self {
+            MappedValue::Text(text) => MappedValue::Text(f(&text)),
+            MappedValue::List(list) => MappedValue::List(list.iter().map(|s| f(s)).collect()),
+        }
+    }
+
+    fn into_text(self) -> Option<String> {
+        let text = match self {
+            MappedValue::Text(text) => text,
+            MappedValue::List(list) => list.join(","),
+        };
+        Some(text).filter(|t| !t.is_empty())
+    }
+
+    /// Lists default to `|` separated values, matching the format of the YC example CSV.
+    fn into_list(self) -> Option<Vec<String>> {
+        let list = match self {
+            MappedValue::Text(text) => text
+                .split('|')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            MappedValue::List(list) => list,
+        };
+        Some(list).filter(|l| !l.is_empty())
+    }
+
+    fn into_json(self) -> Value {
+        match self {
+            MappedValue::Text(text) => Value::String(text),
+            MappedValue::List(list) => Value::from(list),
+        }
+    }
+
+    pub fn from_json(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => None,
+            Value::String(s) => Some(MappedValue::Text(s.clone())),
+            Value::Array(values) => Some(MappedValue::List(
+                values
+                    .iter()
+                    .map(|v| v.as_str().map(|s| s.to_string()).unwrap_or(v.to_string()))
+                    .collect(),
+            )),
+            other => Some(MappedValue::Text(other.to_string())),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Transform {
+    Split(String),
+    Trim,
+    Lowercase,
+    Uppercase,
+}
+
+impl Transform {
+    fn parse(spec: &str) -> Result<Self, DefaultError> {
+        match spec {
+            "trim" => Ok(Transform::Trim),
+            "lowercase" => Ok(Transform::Lowercase),
+            "uppercase" => Ok(Transform::Uppercase),
+            s if s.starts_with("split(") && s.ends_with(')') && s.len() > "split()".len() => Ok(
+                Transform::Split(s["split(".len()..s.len() - 1].to_string()),
+            ),
+            _ => Err(DefaultError {
+                message: format!(
+                    "Unknown transform '{}', expected one of: split(<separator>), trim, lowercase, uppercase",
+                    spec
+                ),
+            }),
+        }
+    }
+
+    fn apply(&self, value: MappedValue) -> MappedValue {
+        match (self, value) {
+            (Transform::Split(separator), MappedValue::Text(text)) => MappedValue::List(
+                text.split(separator.as_str())
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+            ),
+            (Transform::Split(_), list) => list,
+            (Transform::Trim, value) => value.map(|s| s.trim().to_string()),
+            (Transform::Lowercase, value) => value.map(|s| s.to_lowercase()),
+            (Transform::Uppercase, value) => value.map(|s| s.to_uppercase()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldMapping {
+    pub field: String,
+    pub column: String,
+    pub transforms: Vec<Transform>,
+}
+
+/// Splits `column:transform:transform` on colons that are not inside a transform's parentheses.
+fn split_spec(spec: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (i, c) in spec.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ':' if depth == 0 => {
+                parts.push(&spec[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&spec[start..]);
+
+    parts
+}
+
+impl FieldMapping {
+    pub fn parse(field: &str, spec: &str) -> Result<Self, DefaultError> {
+        let is_metadata_key = field
+            .strip_prefix("metadata.")
+            .is_some_and(|key| !key.is_empty());
+
+        if !CHUNK_FIELDS.contains(&field) && !is_metadata_key {
+            return Err(DefaultError {
+                message: format!(
+                    "Unknown chunk field '{}', expected one of: {} or metadata.<key>",
+                    field,
+                    CHUNK_FIELDS.join(", ")
+                ),
+            });
+        }
+
+        let mut parts = split_spec(spec).into_iter();
+        let column = parts.next().unwrap_or_default().to_string();
+
+        if column.is_empty() {
+            return Err(DefaultError {
+                message: format!("Missing column for field '{}'", field),
+            });
+        }
+
+        Ok(FieldMapping {
+            field: field.to_string(),
+            column,
+            transforms: parts.map(Transform::parse).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ColumnMapping {
+    pub fields: Vec<FieldMapping>,
+}
+
+impl ColumnMapping {
+    /// Builds a mapping from an optional YAML mapping file and `--map <field>=<column>` flags.
+    /// Flags take precedence over entries in the file.
+    pub fn from_args(maps: &[String], mapping_file: Option<&Path>) -> Result<Self, DefaultError> {
+        let mut mapping = ColumnMapping::default();
+
+        if let Some(path) = mapping_file {
+            let contents = fs::read_to_string(path).map_err(|e| DefaultError {
+                message: format!("Could not read mapping file {}: {}", path.display(), e),
+            })?;
+            let entries: serde_yaml::Mapping =
+                serde_yaml::from_str(&contents).map_err(|e| DefaultError {
+                    message: format!("Invalid mapping file {}: {}", path.display(), e),
+                })?;
+
+            for (field, spec) in entries {
+                let field = field.as_str().ok_or_else(|| DefaultError {
+                    message: "Mapping file keys must be chunk field names".to_string(),
+                })?;
+                let spec = match spec {
+                    serde_yaml::Value::String(s) => s,
+                    serde_yaml::Value::Number(n) => n.to_string(),
+                    _ => {
+                        return Err(DefaultError {
+                            message: format!("Invalid column for field '{}'", field),
+                        })
+                    }
+                };
+                mapping.insert(FieldMapping::parse(field, &spec)?);
+            }
+        }
+
+        for map in maps {
+            let (field, spec) = map.split_once('=').ok_or_else(|| DefaultError {
+                message: format!("Invalid mapping '{}', expected <field>=<column>", map),
+            })?;
+            mapping.insert(FieldMapping::parse(field, spec)?);
+        }
+
+        Ok(mapping)
+    }
+
+    /// Maps the first columns to chunk fields in the order of `CHUNK_FIELDS`, which matches the YC example CSV.
+    pub fn positional(column_count: usize) -> Self {
+        ColumnMapping {
+            fields: CHUNK_FIELDS
+                .iter()
+                .take(column_count)
+                .enumerate()
+                .map(|(i, field)| FieldMapping {
+                    field: field.to_string(),
+                    column: i.to_string(),
+                    transforms: vec![],
+                })
+                .collect(),
+        }
+    }
+
+    /// Adds an identity mapping (`field=field`) for every chunk field that is not mapped yet.
+    /// A `tag_set` given as a string is split on commas, matching the JSON example datasets.
+    pub fn with_identity_defaults(mut self) -> Self {
+        for field in CHUNK_FIELDS {
+            if !self.fields.iter().any(|m| m.field == field) {
+                self.fields.push(FieldMapping {
+                    field: field.to_string(),
+                    column: field.to_string(),
+                    transforms: if field == "tag_set" {
+                        vec![Transform::Split(",".to_string())]
+                    } else {
+                        vec![]
+                    },
+                });
+            }
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    fn insert(&mut self, mapping: FieldMapping) {
+        self.fields.retain(|m| m.field != mapping.field);
+        self.fields.push(mapping);
+    }
+
+    /// Resolves every mapped column (an index or a header name) to its CSV column index.
+    pub fn resolve_csv_columns(
+        &self,
+        headers: &csv::StringRecord,
+    ) -> Result<HashMap<String, usize>, DefaultError> {
+        self.fields
+            .iter()
+            .map(|m| {
+                let index = m
+                    .column
+                    .parse::<usize>()
+                    .ok()
+                    .or_else(|| headers.iter().position(|h| h == m.column))
+                    .filter(|i| *i < headers.len())
+                    .ok_or_else(|| DefaultError {
+                        message: format!("Column '{}' not found in CSV headers", m.column),
+                    })?;
+                Ok((m.column.clone(), index))
+            })
+            .collect()
+    }
+
+    /// Builds a chunk by looking up every mapped column and applying its transforms.
+    pub fn chunk_from_row(
+        &self,
+        lookup: impl Fn(&str) -> Option<MappedValue>,
+    ) -> Result<ChunkReqPayload, DefaultError> {
+        let mut values: HashMap<&str, MappedValue> = HashMap::new();
+        let mut metadata_fields = Map::new();
+
+        for mapping in &self.fields {
+            let Some(value) = lookup(&mapping.column) else {
+                continue;
+            };
+            let value = mapping
+                .transforms
+                .iter()
+                .fold(value, |value, transform| transform.apply(value));
+
+            match mapping.field.strip_prefix("metadata.") {
+                Some(key) => {
+                    metadata_fields.insert(key.to_string(), value.into_json());
+                }
+                None => {
+                    values.insert(mapping.field.as_str(), value);
+                }
+            }
+        }
+
+        let mut text = |field: &str| values.remove(field).and_then(|v| v.into_text());
+        let number = |field: &str, value: Option<String>| {
+            value
+                .map(|v| v.parse::<f64>())
+                .transpose()
+                .map_err(|e| DefaultError {
+                    message: format!("Invalid number for {}: {}", field, e),
+                })
+        };
+
+        let mut metadata = text("metadata")
+            .map(|v| serde_json::from_str::<Value>(&v))
+            .transpose()
+            .map_err(|e| DefaultError {
+                message: format!("Invalid metadata JSON: {}", e),
+            })?;
+
+        if !metadata_fields.is_empty() {
+            let mut merged = match metadata {
+                Some(Value::Object(existing)) => existing,
+                _ => Map::new(),
+            };
+            merged.extend(metadata_fields);
+            metadata = Some(Value::Object(merged));
+        }
+
+        let chunk_html = text("chunk_html");
+        let link = text("link");
+        let tracking_id = text("tracking_id");
+        let time_stamp = text("time_stamp");
+        let num_value = number("num_value", text("num_value"))?;
+        let weight = number("weight", text("weight"))?;
+
+        Ok(ChunkReqPayload {
+            chunk_html: Some(chunk_html),
+            link: Some(link),
+            tag_set: Some(values.remove("tag_set").and_then(|v| v.into_list())),
+            tracking_id: Some(tracking_id),
+            metadata: Some(metadata),
+            group_tracking_ids: Some(
+                values
+                    .remove("group_tracking_ids")
+                    .and_then(|v| v.into_list()),
+            ),
+            num_value: Some(num_value),
+            time_stamp: Some(time_stamp),
+            weight: Some(weight),
+            upsert_by_tracking_id: Some(Some(true)),
+            ..Default::default()
+        })
+    }
+}
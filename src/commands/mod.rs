@@ -1,6 +1,62 @@
-pub mod api_key;
+pub mod alias;
+pub mod api;
+pub mod apikeys;
+pub mod apply;
+pub mod ask;
+pub mod audit;
+pub mod backup;
+pub mod bench;
+pub mod browser;
+pub mod chunk;
+pub mod chunking;
+pub mod client;
+pub mod config;
 pub mod configure;
 pub mod dataset;
+pub mod date;
+pub mod diff;
+pub mod eval;
+pub mod export;
+pub mod file;
+pub mod file_upload;
+pub mod filter;
+pub mod guard;
+pub mod http;
+pub mod i18n;
+pub mod ingest;
+pub mod interrupt;
+pub mod invitation;
+pub mod jobs;
 pub mod login_server;
+pub mod mapping;
+pub mod open;
 pub mod organization;
+pub mod output;
+pub mod pager;
+pub mod picker;
+pub mod pipeline;
 pub mod profile;
+pub mod rag;
+pub mod reembed;
+pub mod render;
+pub mod rest;
+pub mod saved_searches;
+pub mod search;
+pub mod session;
+pub mod shell;
+pub mod stats;
+pub mod style;
+pub mod suggest;
+pub mod synthetic;
+pub mod table;
+pub mod telemetry;
+pub mod templates;
+pub mod throughput;
+pub mod topic;
+pub mod update;
+pub mod upload;
+pub mod user;
+pub mod validate;
+pub mod version;
+pub mod webhook;
+pub mod word_list;
@@ -0,0 +1,131 @@
+use std::env;
+
+use reqwest::Url;
+
+use crate::cli::{OpenDataset, OpenOrganization};
+
+use super::{
+    browser,
+    configure::{require_login, TrieveConfiguration},
+    dataset::{require_dataset, DefaultError},
+    output::quiet,
+};
+
+/// A web app of Trieve, served next to the API.
+#[derive(Clone, Copy)]
+enum App {
+    Dashboard,
+    Search,
+}
+
+impl App {
+    /// The subdomain of the app on the host of the API, e.g. `dashboard.trieve.ai` for
+    /// `api.trieve.ai`.
+    fn subdomain(self) -> &'static str {
+        match self {
+            App::Dashboard => "dashboard",
+            App::Search => "search",
+        }
+    }
+
+    /// The port the app listens on in a local development setup.
+    fn local_port(self) -> u16 {
+        match self {
+            App::Dashboard => 5173,
+            App::Search => 5174,
+        }
+    }
+
+    /// The environment variable to set the URL of the app with, for servers whose hosts do not
+    /// follow the naming of the hosted service.
+    fn env(self) -> &'static str {
+        match self {
+            App::Dashboard => "TRIEVE_DASHBOARD_URL",
+            App::Search => "TRIEVE_SEARCH_URL",
+        }
+    }
+}
+
+/// The base URL of a web app of the server of the profile: the host of the API with `api.`
+/// replaced by the subdomain of the app, or the port of the app for a local server.
+fn app_url(settings: &TrieveConfiguration, app: App) -> Result<String, DefaultError> {
+    if let Some(url) = env::var(app.env()).ok().filter(|url| !url.is_empty()) {
+        return Ok(url.trim_end_matches('/').to_string());
+    }
+
+    let cannot_derive = || DefaultError {
+        message: format!(
+            "Cannot tell the {} URL from the API URL {}, set {} to it",
+            app.subdomain(),
+            settings.api_url,
+            app.env()
+        ),
+    };
+    let mut url = Url::parse(&settings.api_url).map_err(|_| cannot_derive())?;
+    let host = url.host_str().ok_or_else(cannot_derive)?.to_string();
+
+    if matches!(host.as_str(), "localhost" | "127.0.0.1" | "[::1]") {
+        url.set_port(Some(app.local_port()))
+            .map_err(|_| cannot_derive())?;
+    } else {
+        let domain = host.strip_prefix("api.").ok_or_else(cannot_derive)?;
+        url.set_host(Some(&format!("{}.{}", app.subdomain(), domain)))
+            .map_err(|_| cannot_derive())?;
+    }
+    url.set_path("");
+
+    Ok(url.as_str().trim_end_matches('/').to_string())
+}
+
+/// Opens `url` in the browser, or prints it with `--print`.
+fn open_url(url: &str, print: bool) -> Result<(), DefaultError> {
+    if print {
+        println!("{}", url);
+        return Ok(());
+    }
+
+    if !quiet() {
+        println!("Opening {}", url);
+    }
+    browser::from_env().open(url).map_err(|e| DefaultError {
+        message: format!("Could not open {}: {}", url, e),
+    })
+}
+
+pub fn open_dataset(settings: TrieveConfiguration, open: OpenDataset) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(open.dataset_id, &settings)?;
+    let url = format!(
+        "{}/dataset/{}",
+        app_url(&settings, App::Dashboard)?,
+        dataset_id
+    );
+    open_url(&url, open.print)
+}
+
+pub fn open_organization(
+    settings: TrieveConfiguration,
+    open: OpenOrganization,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let url = format!(
+        "{}/org?org={}",
+        app_url(&settings, App::Dashboard)?,
+        settings.organization_id
+    );
+    open_url(&url, open.print)
+}
+
+pub fn open_search(settings: TrieveConfiguration, open: OpenDataset) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(open.dataset_id, &settings)?;
+    let url = format!(
+        "{}/?dataset={}",
+        app_url(&settings, App::Search)?,
+        dataset_id
+    );
+    open_url(&url, open.print)
+}
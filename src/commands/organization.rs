@@ -1,22 +1,144 @@
-use trieve_client::{
-    apis::configuration::{ApiKey, Configuration},
-    models::CreateOrganizationReqPayload,
+use crate::{
+    cli::{
+        CreateOrganization, DeleteOrganization, RemoveOrganizationAlias, SetOrganizationAlias,
+        SwitchOrganization, UpdateOrganization,
+    },
+    commands::configure::{get_user, load_profiles, OrgDTO},
 };
 
-use crate::{
-    commands::configure::{get_user, OrgDTO, TrieveProfile},
-    CreateOrganization, DeleteOrganization, SwitchOrganization,
+use super::{
+    alias::update_aliases,
+    api::TrieveApi,
+    audit,
+    client::ApiClient,
+    config::profile_required,
+    configure::{require_login, TrieveConfiguration},
+    dataset::DefaultError,
+    export::export_organization_to_dir,
+    guard,
+    i18n::tr_args,
+    output::{ensure_interactive, quiet, success},
+    picker::{pick, pick_with_cursor},
+    rest::Rest,
+    style::id,
+    table::{self, Column},
 };
 
-use super::configure::{TrieveConfiguration, TrieveProfileInner};
+/// Returns the organization ID an alias of the profile stands for, or `organization` itself when
+/// it is not an alias.
+pub fn resolve_organization_alias(settings: &TrieveConfiguration, organization: &str) -> String {
+    settings
+        .organization_aliases
+        .get(organization)
+        .cloned()
+        .unwrap_or_else(|| organization.to_string())
+}
+
+/// How well an organization name matches what was typed, lower is better: the start of the name,
+/// another part of it, or its letters in order, such as `acp` for `Acme Production`.
+fn name_match(name: &str, query: &str) -> Option<u8> {
+    let name = name.to_lowercase();
+    let query = query.to_lowercase();
+
+    if name.starts_with(&query) {
+        return Some(0);
+    }
+    if name.contains(&query) {
+        return Some(1);
+    }
+    let mut letters = name.chars();
+    query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .all(|c| letters.any(|l| l == c))
+        .then_some(2)
+}
+
+/// Finds the organization `organization` stands for: an alias of the profile, an ID, or the name
+/// of one of the organizations of the user. A name that is not an exact match is matched fuzzily,
+/// and the user picks one when several organizations match equally well.
+pub async fn resolve_organization(
+    settings: &TrieveConfiguration,
+    organization: &str,
+) -> Result<uuid::Uuid, DefaultError> {
+    resolve_organization_with(&ApiClient::from(settings), settings, organization).await
+}
+
+/// Like `resolve_organization`, looking up the organizations of the user through `api`.
+pub async fn resolve_organization_with(
+    api: &impl TrieveApi,
+    settings: &TrieveConfiguration,
+    organization: &str,
+) -> Result<uuid::Uuid, DefaultError> {
+    let organization = resolve_organization_alias(settings, organization);
+    if let Ok(id) = organization.parse::<uuid::Uuid>() {
+        return Ok(id);
+    }
+
+    let orgs = api.me().await?.orgs;
+    let exact = orgs
+        .iter()
+        .filter(|org| org.name.to_lowercase() == organization.to_lowercase())
+        .collect::<Vec<_>>();
+    let matches = if exact.is_empty() {
+        let scores = orgs
+            .iter()
+            .filter_map(|org| name_match(&org.name, &organization).map(|score| (score, org)))
+            .collect::<Vec<_>>();
+        let best = scores.iter().map(|(score, _)| *score).min();
+        scores
+            .into_iter()
+            .filter(|(score, _)| Some(*score) == best)
+            .map(|(_, org)| org)
+            .collect()
+    } else {
+        exact
+    };
+
+    match matches.as_slice() {
+        [] => Err(DefaultError {
+            message: format!(
+                "No organization matches '{}'. Run `trieve organization switch` to pick one from a list.",
+                organization
+            ),
+        }),
+        [org] => Ok(org.id),
+        _ if quiet() => Err(DefaultError {
+            message: format!(
+                "'{}' matches several organizations: {}. Pass an ID or a longer part of the name.",
+                organization,
+                matches
+                    .iter()
+                    .map(|org| org.name.clone())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        }),
+        _ => {
+            let orgs = matches
+                .iter()
+                .map(|org| OrgDTO((*org).clone()))
+                .collect::<Vec<OrgDTO>>();
+            let selected = pick(
+                &format!("Several organizations match '{}':", organization),
+                "organizations",
+                orgs,
+            )?;
+            Ok(selected.0.id)
+        }
+    }
+}
 
 pub async fn switch_organization(
     organization_data: SwitchOrganization,
-    profiles: Vec<TrieveProfileInner>,
+    profile_name: Option<String>,
     settings: TrieveConfiguration,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let profile_name = profile_required(profile_name)?;
+
     let organization_id = if organization_data.organization_id.is_none() {
-        let user = get_user(settings.api_url.clone(), settings.api_key.clone()).await;
+        ensure_interactive("the organization ID")?;
+        let user = get_user(&settings).await?;
 
         let orgs = user
             .orgs
@@ -24,69 +146,48 @@ pub async fn switch_organization(
             .map(|org| OrgDTO(org.clone()))
             .collect::<Vec<OrgDTO>>();
 
-        let selected_organization =
-            inquire::Select::new("Select an organization to use:", orgs.clone())
-                .with_starting_cursor(
-                    orgs.iter()
-                        .position(|o| {
-                            o.0.id
-                                == profiles
-                                    .iter()
-                                    .find(|p| p.selected)
-                                    .map(|p| p.settings.organization_id)
-                                    .unwrap_or_default()
-                        })
-                        .unwrap_or(0),
-                )
-                .prompt()
-                .unwrap();
+        let current = orgs
+            .iter()
+            .position(|o| o.0.id == settings.organization_id)
+            .unwrap_or(0);
+        let selected_organization = pick_with_cursor(
+            "Select an organization to use:",
+            "organizations",
+            orgs,
+            current,
+        )?;
 
         selected_organization.0.id
     } else {
-        organization_data
-            .organization_id
-            .unwrap()
-            .parse()
-            .map_err(|e| {
-                eprintln!("Invalid organization ID: {:?}", e);
-                std::process::exit(1);
-            })
-            .unwrap()
+        resolve_organization(&settings, &organization_data.organization_id.unwrap())
+            .await
+            .map_err(|e| e.message)?
     };
 
-    let new_config = TrieveConfiguration {
-        api_key: settings.api_key.clone(),
-        organization_id,
-        api_url: settings.api_url.clone(),
-    };
+    // Only the profile of this command changes, so it is safe to switch the organization of one
+    // profile while other commands run with another.
+    let mut profiles = load_profiles()?;
+    let profile = profiles
+        .iter_mut()
+        .find(|p| p.name == profile_name)
+        .ok_or_else(|| format!("Profile '{}' not found.", profile_name))?;
+    if let Some(api_key) = &organization_data.api_key {
+        profile.api_keys.insert(organization_id, api_key.clone());
+    }
+    profile.settings.api_key = profile.api_key_for(organization_id);
+    profile.settings.organization_id = organization_id;
 
-    let profiles = profiles
-        .iter()
-        .map(|p| {
-            if p.settings == settings {
-                TrieveProfileInner {
-                    name: p.name.clone(),
-                    selected: true,
-                    settings: new_config.clone(),
-                }
-            } else {
-                TrieveProfileInner {
-                    name: p.name.clone(),
-                    selected: false,
-                    settings: p.settings.clone(),
-                }
-            }
-        })
-        .collect::<Vec<TrieveProfileInner>>();
-
-    confy::store("trieve", "profiles", TrieveProfile { inner: profiles })
-        .map_err(|e| {
-            eprintln!("Error saving configuration: {:?}", e);
-            std::process::exit(1);
-        })
-        .unwrap();
-
-    println!("Switched to organization '{}'.", organization_id);
+    confy::store("trieve", "profiles", profiles)
+        .map_err(|e| format!("Error saving configuration: {:?}", e))?;
+
+    if quiet() {
+        return Ok(());
+    }
+
+    success(&tr_args(
+        "organization-switched",
+        &[("organization", id(&organization_id.to_string()))],
+    ));
 
     Ok(())
 }
@@ -96,33 +197,148 @@ pub async fn create_organization(
     settings: TrieveConfiguration,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let name = if organization_data.name.is_none() {
+        ensure_interactive("--name")?;
         inquire::Text::new("Enter a name for this organization:")
             .prompt()
             .unwrap()
     } else {
         organization_data.name.unwrap()
     };
-    let configuration = Configuration {
-        base_path: settings.api_url.clone(),
-        api_key: Some(ApiKey {
-            prefix: None,
-            key: settings.api_key.clone(),
-        }),
-        ..Default::default()
+    let org = ApiClient::from(&settings)
+        .create_organization(name)
+        .await
+        .map_err(|e| format!("Error creating organization: {}", e.message))?;
+    audit::record(
+        &settings,
+        "organization create",
+        Some(&org.id.to_string()),
+        serde_json::json!({ "name": org.name }),
+    );
+
+    if quiet() {
+        println!("{}", org.id);
+        return Ok(());
+    }
+
+    success(&tr_args(
+        "organization-created",
+        &[("organization", id(&org.id.to_string()))],
+    ));
+    Ok(())
+}
+
+/// Parses `KEY=VALUE`, reading the value as JSON when it is valid JSON and as a string otherwise.
+fn parse_config_entry(entry: &str) -> Result<(String, serde_json::Value), String> {
+    let (key, value) = entry
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid setting '{}', expected KEY=VALUE", entry))?;
+    let value = serde_json::from_str(value)
+        .unwrap_or_else(|_| serde_json::Value::String(value.to_string()));
+
+    Ok((key.trim().to_string(), value))
+}
+
+pub async fn update_organization(
+    organization_data: UpdateOrganization,
+    settings: TrieveConfiguration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    require_login(&settings)?;
+
+    let organization_id = organization_data
+        .organization_id
+        .map(|id| resolve_organization_alias(&settings, &id))
+        .unwrap_or_else(|| settings.organization_id.to_string());
+    let organization_settings = TrieveConfiguration {
+        organization_id: organization_id.parse()?,
+        ..settings.clone()
     };
+    let rest = Rest::new(&organization_settings);
+
+    let organization: serde_json::Value = rest
+        .get(&format!("/api/organization/{}", organization_id))
+        .await
+        .map_err(|e| e.message)?;
+    let current_name = organization["name"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+
+    let mut config = serde_json::Map::new();
+    if let Some(path) = &organization_data.config_file {
+        let file = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        match serde_json::from_str(&file)? {
+            serde_json::Value::Object(entries) => config.extend(entries),
+            _ => return Err(format!("{} must contain a JSON object", path.display()).into()),
+        }
+    }
+    for entry in &organization_data.config {
+        let (key, value) = parse_config_entry(entry)?;
+        config.insert(key, value);
+    }
+
+    let interactive = organization_data.name.is_none() && config.is_empty();
+    if interactive {
+        ensure_interactive("--name or --config")?;
+    }
+    let name = match organization_data.name {
+        Some(name) => Some(name),
+        None if interactive => Some(
+            inquire::Text::new("Enter a new name for this organization:")
+                .with_default(&current_name)
+                .prompt()
+                .unwrap(),
+        ),
+        None => None,
+    };
+
+    let mut body = serde_json::json!({ "organization_id": organization_id });
+    if let Some(name) = name.filter(|name| *name != current_name) {
+        body["name"] = name.into();
+    }
+    if !config.is_empty() {
+        // The configuration is replaced as a whole, so merge the changes into the current one.
+        let mut partner_configuration = organization["partner_configuration"]
+            .as_object()
+            .cloned()
+            .unwrap_or_default();
+        partner_configuration.extend(config);
+        body["partner_configuration"] = partner_configuration.into();
+    }
+
+    if body.as_object().map_or(0, |body| body.len()) == 1 {
+        if !quiet() {
+            println!("Nothing to update.");
+        }
+        return Ok(());
+    }
+
+    let organization: serde_json::Value = rest
+        .put("/api/organization", &body)
+        .await
+        .map_err(|e| e.message)?;
+    audit::record(
+        &settings,
+        "organization update",
+        Some(&organization_id),
+        body,
+    );
+
+    if quiet() {
+        return Ok(());
+    }
+
+    success(&tr_args(
+        "organization-updated",
+        &[(
+            "organization",
+            organization["name"]
+                .as_str()
+                .unwrap_or(&organization_id)
+                .to_string(),
+        )],
+    ));
 
-    let org = trieve_client::apis::organization_api::create_organization(
-        &configuration,
-        CreateOrganizationReqPayload { name },
-    )
-    .await
-    .map_err(|e| {
-        eprintln!("Error getting user: {:?}", e);
-        std::process::exit(1);
-    })
-    .unwrap();
-
-    println!("Organization '{}' created.", org.id);
     Ok(())
 }
 
@@ -131,7 +347,8 @@ pub async fn delete_organization(
     settings: TrieveConfiguration,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let organization_id = if data.organization_id.is_none() {
-        let user = get_user(settings.api_url.clone(), settings.api_key.clone()).await;
+        ensure_interactive("the organization ID")?;
+        let user = get_user(&settings).await?;
 
         let orgs = user
             .orgs
@@ -140,35 +357,179 @@ pub async fn delete_organization(
             .collect::<Vec<OrgDTO>>();
 
         let selected_organization =
-            inquire::Select::new("Select an organization to delete:", orgs.clone())
-                .prompt()
-                .unwrap();
+            pick("Select an organization to delete:", "organizations", orgs)?;
 
         selected_organization.0.id.to_string()
     } else {
-        data.organization_id.unwrap()
+        resolve_organization_alias(&settings, &data.organization_id.unwrap())
     };
 
-    let configuration = Configuration {
-        base_path: settings.api_url.clone(),
-        api_key: Some(ApiKey {
-            prefix: None,
-            key: settings.api_key.clone(),
-        }),
-        ..Default::default()
-    };
+    guard::confirm_destructive(&format!("delete organization {}", organization_id))?;
+
+    if let Some(dir) = data.export_first {
+        let export_settings = TrieveConfiguration {
+            organization_id: organization_id
+                .parse()
+                .map_err(|e| format!("Invalid organization ID: {:?}", e))?,
+            ..settings.clone()
+        };
+
+        let manifest = export_organization_to_dir(export_settings, &dir)
+            .await
+            .map_err(|e| {
+                format!(
+                    "Error exporting organization, it was not deleted: {}",
+                    e.message
+                )
+            })?;
+
+        if !quiet() {
+            println!(
+                "Exported and verified {} datasets to {}",
+                manifest.datasets.len(),
+                dir.display()
+            );
+        }
+    }
+
+    ApiClient::from(&settings)
+        .delete_organization(&organization_id)
+        .await
+        .map_err(|e| format!("Error deleting organization: {}", e.message))?;
+    audit::record(
+        &settings,
+        "organization delete",
+        Some(&organization_id),
+        serde_json::json!({}),
+    );
+
+    Ok(())
+}
+
+pub async fn set_organization_alias(
+    settings: TrieveConfiguration,
+    profile_name: Option<String>,
+    set: SetOrganizationAlias,
+) -> Result<(), DefaultError> {
+    let profile_name =
+        profile_required(profile_name).map_err(|message| DefaultError { message })?;
+
+    if set.alias.parse::<uuid::Uuid>().is_ok() {
+        return Err(DefaultError {
+            message: format!(
+                "'{}' is an organization ID and cannot be an alias",
+                set.alias
+            ),
+        });
+    }
+
+    require_login(&settings)?;
+
+    let organization_id = resolve_organization(&settings, &set.organization).await?;
+    // Only organizations of the user can be switched to, so an alias for any other is a typo.
+    let organization = get_user(&settings)
+        .await?
+        .orgs
+        .into_iter()
+        .find(|org| org.id == organization_id)
+        .ok_or_else(|| DefaultError {
+            message: format!("You are not a member of organization {}", organization_id),
+        })?;
+
+    update_aliases(&profile_name, |settings| {
+        settings
+            .organization_aliases
+            .insert(set.alias.clone(), organization_id.to_string());
+        Ok(())
+    })?;
+    audit::record(
+        &settings,
+        "organization alias set",
+        Some(&set.alias),
+        serde_json::json!({ "organization_id": organization_id, "profile": profile_name }),
+    );
+
+    if !quiet() {
+        success(&format!(
+            "{} now stands for organization {} ({}) in profile {}.",
+            set.alias,
+            organization.name,
+            id(&organization_id.to_string()),
+            profile_name
+        ));
+    }
+
+    Ok(())
+}
+
+/// The columns of `organization alias list`.
+const ORGANIZATION_ALIAS_COLUMNS: [Column<(String, String)>; 2] = [
+    Column {
+        key: "alias",
+        header: "Alias",
+        value: |(alias, _)| alias.clone(),
+    },
+    Column {
+        key: "organization_id",
+        header: "Organization ID",
+        value: |(_, organization_id)| organization_id.clone(),
+    },
+];
+
+pub fn list_organization_aliases(settings: &TrieveConfiguration) -> Result<(), DefaultError> {
+    let aliases = settings
+        .organization_aliases
+        .clone()
+        .into_iter()
+        .collect::<Vec<_>>();
+    if quiet() {
+        return table::print_quiet(
+            &ORGANIZATION_ALIAS_COLUMNS,
+            &aliases,
+            &["alias", "organization_id"],
+        );
+    }
+
+    if settings.organization_aliases.is_empty() {
+        println!("No organization aliases. Add one with `trieve organization alias set <ALIAS> <ORGANIZATION>`.");
+        return Ok(());
+    }
+
+    println!("{}", table::render(&ORGANIZATION_ALIAS_COLUMNS, &aliases)?);
+
+    Ok(())
+}
+
+pub fn remove_organization_alias(
+    settings: &TrieveConfiguration,
+    profile_name: Option<String>,
+    remove: RemoveOrganizationAlias,
+) -> Result<(), DefaultError> {
+    let profile_name =
+        profile_required(profile_name).map_err(|message| DefaultError { message })?;
+
+    update_aliases(&profile_name, |settings| {
+        settings
+            .organization_aliases
+            .remove(&remove.alias)
+            .map(|_| ())
+            .ok_or_else(|| DefaultError {
+                message: format!(
+                    "No organization alias '{}' in profile {}",
+                    remove.alias, profile_name
+                ),
+            })
+    })?;
+    audit::record(
+        settings,
+        "organization alias rm",
+        Some(&remove.alias),
+        serde_json::json!({ "profile": profile_name }),
+    );
 
-    trieve_client::apis::organization_api::delete_organization(
-        &configuration,
-        &organization_id.clone(),
-        &organization_id.clone(),
-    )
-    .await
-    .map_err(|e| {
-        eprintln!("Error getting organization: {:?}", e);
-        std::process::exit(1);
-    })
-    .unwrap();
+    if !quiet() {
+        success(&format!("Removed organization alias {}.", remove.alias));
+    }
 
     Ok(())
 }
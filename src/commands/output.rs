@@ -0,0 +1,234 @@
+use std::{
+    fmt::Debug,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Mutex, OnceLock,
+    },
+};
+
+use reqwest::StatusCode;
+use serde_json::json;
+
+use crate::cli::OutputFormat;
+
+use super::{dataset::DefaultError, i18n::tr, style};
+
+/// The exit codes of the CLI, documented in the README so scripts can tell failures apart.
+pub const EXIT_ERROR: i32 = 1;
+/// The arguments were invalid. clap exits with it.
+pub const EXIT_USAGE: i32 = 2;
+/// No credentials are configured, or the API rejected them with 401 or 403.
+pub const EXIT_UNAUTHORIZED: i32 = 3;
+/// The API answered 404, e.g. for a dataset or chunk that does not exist.
+pub const EXIT_NOT_FOUND: i32 = 4;
+/// The API answered with any other error status.
+pub const EXIT_API_ERROR: i32 = 5;
+/// `--fail-on-empty` was passed and there were no results.
+pub const EXIT_EMPTY: i32 = 6;
+/// The command was interrupted with Ctrl-C.
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+/// Response headers the Trieve API and the proxies in front of it identify requests with.
+const REQUEST_ID_HEADERS: [&str; 2] = ["x-request-id", "request-id"];
+
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+static QUIET: OnceLock<bool> = OnceLock::new();
+
+/// The last API response that was not a success, reported along with the error a command fails
+/// with.
+static LAST_API_ERROR: Mutex<Option<ApiError>> = Mutex::new(None);
+
+/// The failure a command reported itself through `fail`. It is reported instead of the error the
+/// command fails with, which may have lost its message on the way, e.g. when the failure was
+/// reported for a request of the generated client.
+static FAILURE: Mutex<Option<Failure>> = Mutex::new(None);
+
+/// The exit code of a command that finished but wants its caller to know something, see
+/// `set_exit_code`.
+static EXIT_CODE: AtomicI32 = AtomicI32::new(0);
+
+#[derive(Clone)]
+struct Failure {
+    exit_code: i32,
+    code: &'static str,
+    message: String,
+}
+
+#[derive(Clone)]
+struct ApiError {
+    status: StatusCode,
+    request_id: Option<String>,
+}
+
+pub fn init(format: OutputFormat, quiet: bool) {
+    let _ = FORMAT.set(format);
+    let _ = QUIET.set(quiet);
+}
+
+pub fn format() -> OutputFormat {
+    FORMAT.get().copied().unwrap_or_default()
+}
+
+/// Whether `--quiet` was passed, in which case commands print only the identifiers they produce,
+/// one per line, and never prompt.
+pub fn quiet() -> bool {
+    QUIET.get().copied().unwrap_or_default()
+}
+
+/// Prints the confirmation that a change was made.
+pub fn success(message: &str) {
+    println!("{}", style::success(message));
+}
+
+/// Prints a warning to stderr.
+pub fn warn(message: &str) {
+    eprintln!("{}", style::warning(message));
+}
+
+/// Prints an error to stderr, for failures a command reports itself before exiting.
+pub fn error(message: &str) {
+    eprintln!("{}", style::error(message));
+}
+
+/// Called before prompting for `argument`. In quiet mode nothing can be prompted for, so the
+/// command fails and asks for the argument instead.
+pub fn ensure_interactive(argument: &str) -> Result<(), DefaultError> {
+    if quiet() {
+        return Err(fail(
+            EXIT_ERROR,
+            "cli_error",
+            format!("{} must be passed when --quiet is used", argument),
+        ));
+    }
+    Ok(())
+}
+
+/// Records a failed API response, so the error output can quote its status and request ID.
+pub fn record_api_error(status: StatusCode, headers: &[(String, String)]) {
+    let request_id = headers
+        .iter()
+        .find(|(name, _)| REQUEST_ID_HEADERS.contains(&name.to_ascii_lowercase().as_str()))
+        .map(|(_, value)| value.clone());
+
+    if let Ok(mut last) = LAST_API_ERROR.lock() {
+        *last = Some(ApiError { status, request_id });
+    }
+}
+
+/// An error the command reports itself, which the CLI prints as is and exits with `exit_code`
+/// for. `code` is the error code printed with `--output json`.
+pub fn fail(exit_code: i32, code: &'static str, message: String) -> DefaultError {
+    if let Ok(mut failure) = FAILURE.lock() {
+        *failure = Some(Failure {
+            exit_code,
+            code,
+            message: message.clone(),
+        });
+    }
+    DefaultError { message }
+}
+
+/// The error commands fail with when no credentials are configured, which makes the CLI exit with
+/// `EXIT_UNAUTHORIZED`.
+pub fn login_required() -> DefaultError {
+    fail(EXIT_UNAUTHORIZED, "login_required", tr("login-required"))
+}
+
+/// The error commands fail with when they stopped on Ctrl-C, which makes the CLI exit with
+/// `EXIT_INTERRUPTED`.
+pub fn interrupted(message: String) -> DefaultError {
+    fail(EXIT_INTERRUPTED, "interrupted", message)
+}
+
+/// The errors commands fail with, which are printed with their `Debug` representation as text
+/// and with their message as JSON.
+pub trait CommandError: Debug {
+    fn message(&self) -> String;
+}
+
+impl CommandError for DefaultError {
+    fn message(&self) -> String {
+        self.message.clone()
+    }
+}
+
+impl CommandError for Box<dyn std::error::Error> {
+    fn message(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl CommandError for String {
+    fn message(&self) -> String {
+        self.clone()
+    }
+}
+
+/// Sets the code the CLI exits with once a command finished without an error, e.g. when
+/// `--exit-code` was passed and there are changes. See `exit_code`.
+pub fn set_exit_code(code: i32) {
+    EXIT_CODE.store(code, Ordering::Relaxed);
+}
+
+/// The code set with `set_exit_code`, `0` unless the command set one.
+pub fn exit_code() -> i32 {
+    EXIT_CODE.load(Ordering::Relaxed)
+}
+
+/// Sets the exit code to `EXIT_EMPTY` when `--fail-on-empty` was passed and the command found
+/// nothing. The command still prints its output, so scripts see it either way.
+pub fn fail_if_empty(fail_on_empty: bool, empty: bool) {
+    if fail_on_empty && empty {
+        set_exit_code(EXIT_EMPTY);
+    }
+}
+
+/// Prints the error a command failed with and returns the code to exit with. With `--output json`
+/// the error is printed as `{"error": {"code", "message", "status", "request_id"}}`, where `code`
+/// is derived from the status of the failed API response, is the code of a failure reported
+/// through `fail`, such as `login_required` when no credentials are configured, or is `cli_error`
+/// when the failure did not come from the API. The exit code tells failed API responses apart
+/// from other errors, see `EXIT_ERROR`.
+pub fn report_error(context: &str, error: &impl CommandError) -> i32 {
+    let failure = FAILURE.lock().ok().and_then(|failure| failure.clone());
+    let api_error = LAST_API_ERROR.lock().ok().and_then(|last| last.clone());
+    let exit_code = match (&failure, api_error.as_ref().map(|e| e.status)) {
+        (Some(failure), _) => failure.exit_code,
+        (None, Some(StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN)) => EXIT_UNAUTHORIZED,
+        (None, Some(StatusCode::NOT_FOUND)) => EXIT_NOT_FOUND,
+        (None, Some(_)) => EXIT_API_ERROR,
+        (None, None) => EXIT_ERROR,
+    };
+
+    match (format(), &failure) {
+        (OutputFormat::Text, Some(failure)) => eprintln!("{}", style::error(&failure.message)),
+        (OutputFormat::Text, None) => eprintln!("{}: {:?}", style::error(context), error),
+        (OutputFormat::Json, _) => {
+            let (code, message) = match failure {
+                Some(failure) => (failure.code.to_string(), failure.message),
+                None => (
+                    api_error
+                        .as_ref()
+                        .and_then(|e| e.status.canonical_reason())
+                        .map(|reason| reason.to_ascii_lowercase().replace([' ', '-'], "_"))
+                        .unwrap_or_else(|| "cli_error".to_string()),
+                    error.message(),
+                ),
+            };
+
+            eprintln!(
+                "{}",
+                json!({
+                    "error": {
+                        "code": code,
+                        "message": format!("{}: {}", context, message),
+                        "status": api_error.as_ref().map(|e| e.status.as_u16()),
+                        "request_id": api_error.and_then(|e| e.request_id),
+                    }
+                })
+            );
+        }
+    }
+
+    exit_code
+}
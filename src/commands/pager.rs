@@ -0,0 +1,71 @@
+use std::{
+    env,
+    io::{stdout, IsTerminal, Write},
+    process::{Command, Stdio},
+    sync::OnceLock,
+};
+
+use super::output::quiet;
+
+static NO_PAGER: OnceLock<bool> = OnceLock::new();
+
+/// Turns the pager off for the whole run when `--no-pager` was passed.
+pub fn init(no_pager: bool) {
+    let _ = NO_PAGER.set(no_pager);
+}
+
+/// The pager command: `TRIEVE_PAGER`, then `PAGER`, then `less`. An empty command or `cat` turns
+/// paging off.
+fn pager_command() -> Option<String> {
+    let command = ["TRIEVE_PAGER", "PAGER"]
+        .iter()
+        .find_map(|var| env::var(var).ok())
+        .unwrap_or_else(|| "less".to_string());
+    let command = command.trim();
+
+    (!command.is_empty() && command != "cat").then(|| command.to_string())
+}
+
+/// Whether `text` is paged: on a terminal it is not tall enough for, unless `--no-pager` or
+/// `--quiet` was passed.
+fn should_page(text: &str) -> bool {
+    if NO_PAGER.get().copied().unwrap_or_default() || quiet() || !stdout().is_terminal() {
+        return false;
+    }
+
+    crossterm::terminal::size()
+        .map(|(_, rows)| text.lines().count() >= rows as usize)
+        .unwrap_or_default()
+}
+
+/// Writes `text` to the pager and waits for it to exit. Like git, `less` is told to quit when the
+/// text fits on one screen and to keep colors, unless `LESS` is set already.
+fn page(command: &str, text: &str) -> std::io::Result<()> {
+    let mut args = command.split_whitespace();
+    let program = args.next().unwrap_or("less");
+    let mut pager = Command::new(program);
+    pager.args(args).stdin(Stdio::piped());
+    if env::var_os("LESS").is_none() {
+        pager.env("LESS", "FRX");
+    }
+
+    let mut child = pager.spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager closes its input when it is quit before the end of the text.
+        let _ = writeln!(stdin, "{}", text);
+    }
+    child.wait().map(|_| ())
+}
+
+/// Prints a long listing such as a table, through the pager when it does not fit in the
+/// terminal. Without a pager the text is printed as is.
+pub fn print(text: &str) {
+    if should_page(text) {
+        if let Some(command) = pager_command() {
+            if page(&command, text).is_ok() {
+                return;
+            }
+        }
+    }
+    println!("{}", text);
+}
@@ -0,0 +1,61 @@
+use std::fmt::Display;
+
+use inquire::{InquireError, Select};
+
+use super::{dataset::DefaultError, output::interrupted};
+
+/// The most options shown at once, however tall the terminal is.
+const MAX_PAGE_SIZE: usize = 30;
+/// The lines the prompt, the filter and the help message take.
+const PROMPT_LINES: usize = 4;
+
+/// The number of options shown at once: as many as fit in the terminal, so long lists need less
+/// scrolling.
+fn page_size() -> usize {
+    crossterm::terminal::size()
+        .map(|(_, rows)| (rows as usize).saturating_sub(PROMPT_LINES))
+        .unwrap_or(Select::<String>::DEFAULT_PAGE_SIZE)
+        .clamp(Select::<String>::DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE)
+}
+
+/// Prompts for one of `options`, `noun` being what they are, e.g. `datasets`.
+pub fn pick<T: Display>(message: &str, noun: &str, options: Vec<T>) -> Result<T, DefaultError> {
+    pick_with_cursor(message, noun, options, 0)
+}
+
+/// Like `pick`, with the option at `cursor` selected at first, e.g. the current organization.
+///
+/// Typing filters the options fuzzily, so `prdocs` finds `prod-docs`, and the help line shows how
+/// many options there are. Escape and Ctrl-C cancel the command, which exits with
+/// `EXIT_INTERRUPTED`.
+pub fn pick_with_cursor<T: Display>(
+    message: &str,
+    noun: &str,
+    options: Vec<T>,
+    cursor: usize,
+) -> Result<T, DefaultError> {
+    if options.is_empty() {
+        return Err(DefaultError {
+            message: format!("There are no {} to select from", noun),
+        });
+    }
+
+    let options_len = options.len();
+    let help = format!(
+        "{} {}, type to filter, ↑↓ to move, enter to select",
+        options_len, noun
+    );
+    let prompt = Select::new(message, options)
+        .with_help_message(&help)
+        .with_page_size(page_size())
+        .with_starting_cursor(cursor.min(options_len - 1));
+
+    prompt.prompt().map_err(|e| match e {
+        InquireError::OperationCanceled | InquireError::OperationInterrupted => {
+            interrupted("Cancelled".to_string())
+        }
+        e => DefaultError {
+            message: e.to_string(),
+        },
+    })
+}
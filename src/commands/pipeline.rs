@@ -0,0 +1,258 @@
+use std::{
+    env, fs,
+    process::{Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+use tabled::{builder::Builder, settings::Style};
+
+use crate::cli::RunPipeline;
+
+use super::{
+    dataset::DefaultError,
+    output::{quiet, EXIT_USAGE},
+    style::{bold, dim},
+};
+
+/// The seconds waited before a failed step is retried, unless the pipeline sets `retry_delay`.
+const DEFAULT_RETRY_DELAY: u64 = 10;
+
+/// The command of a step: a command line such as `chunk retag --filter tag_set=stale`, or its
+/// arguments as a list.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StepCommand {
+    Line(String),
+    Args(Vec<String>),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Step {
+    name: Option<String>,
+    run: StepCommand,
+    retries: Option<u32>,
+    #[serde(default)]
+    continue_on_error: bool,
+}
+
+/// A pipeline file: trieve commands run one after another, e.g. from cron.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Pipeline {
+    name: Option<String>,
+    /// How often every step is retried, unless the step sets its own `retries`.
+    #[serde(default)]
+    retries: u32,
+    /// The seconds waited before a failed step is retried.
+    retry_delay: Option<u64>,
+    steps: Vec<Step>,
+}
+
+/// Splits a command line into arguments like a shell would, honoring single and double quotes
+/// and backslash escapes, but without expanding anything.
+fn split_command(line: &str) -> Result<Vec<String>, DefaultError> {
+    let mut args = vec![];
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('"') | None, '\\') => {
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+                in_arg = true;
+            }
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err(DefaultError {
+            message: format!("Unterminated quote in '{}'", line),
+        });
+    }
+    if in_arg {
+        args.push(current);
+    }
+
+    Ok(args)
+}
+
+impl Step {
+    fn args(&self) -> Result<Vec<String>, DefaultError> {
+        let args = match &self.run {
+            StepCommand::Line(line) => split_command(line)?,
+            StepCommand::Args(args) => args.clone(),
+        };
+
+        // Steps may be written as they would be typed, with the leading `trieve`.
+        match args.split_first() {
+            Some((first, rest)) if first == "trieve" => Ok(rest.to_vec()),
+            _ => Ok(args),
+        }
+    }
+}
+
+/// The outcome of a step, as shown in the summary.
+struct StepReport {
+    name: String,
+    status: String,
+    attempts: u32,
+    duration: Duration,
+}
+
+/// Runs the steps of a YAML pipeline in order, each as its own `trieve` process with the profile
+/// of this one. Failed steps are retried; the pipeline stops at the first step that still fails
+/// unless the step sets `continue_on_error`. A summary of every step is printed at the end.
+pub fn run_pipeline(run: RunPipeline, profile: Option<String>) -> Result<(), DefaultError> {
+    let contents = fs::read_to_string(&run.file).map_err(|e| DefaultError {
+        message: format!("Could not read {}: {}", run.file.display(), e),
+    })?;
+    let pipeline: Pipeline = serde_yaml::from_str(&contents).map_err(|e| DefaultError {
+        message: format!("Invalid pipeline {}: {}", run.file.display(), e),
+    })?;
+
+    // Every step is checked before the first one runs, so a typo does not fail the pipeline
+    // halfway through.
+    let steps = pipeline
+        .steps
+        .iter()
+        .map(|step| {
+            let args = step.args()?;
+            if args.is_empty() {
+                return Err(DefaultError {
+                    message: format!("A step of {} has an empty `run`", run.file.display()),
+                });
+            }
+            let name = step.name.clone().unwrap_or_else(|| args.join(" "));
+            Ok((step, name, args))
+        })
+        .collect::<Result<Vec<_>, DefaultError>>()?;
+
+    let exe = env::current_exe().map_err(|e| DefaultError {
+        message: e.to_string(),
+    })?;
+    let retry_delay = Duration::from_secs(pipeline.retry_delay.unwrap_or(DEFAULT_RETRY_DELAY));
+    let mut reports = vec![];
+    let mut failed = 0;
+
+    if !quiet() {
+        if let Some(name) = &pipeline.name {
+            println!("Running pipeline {}", bold(name));
+        }
+    }
+
+    for (index, (step, name, args)) in steps.iter().enumerate() {
+        if failed > 0 && !run.keep_going {
+            reports.push(StepReport {
+                name: name.clone(),
+                status: "skipped".to_string(),
+                attempts: 0,
+                duration: Duration::ZERO,
+            });
+            continue;
+        }
+
+        if !quiet() {
+            println!(
+                "{} {}",
+                bold(&format!("[{}/{}]", index + 1, steps.len())),
+                bold(name)
+            );
+            println!("{}", dim(&format!("trieve {}", args.join(" "))));
+        }
+
+        let retries = step.retries.unwrap_or(pipeline.retries);
+        let started = Instant::now();
+        let mut attempts = 0;
+        let status = loop {
+            attempts += 1;
+            let mut command = Command::new(&exe);
+            command.args(args).stdin(Stdio::null());
+            if let Some(profile) = profile.as_ref().filter(|p| !p.is_empty()) {
+                command.env("TRIEVE_PROFILE", profile);
+            }
+            let code = command
+                .status()
+                .map(|status| status.code().unwrap_or(-1))
+                .map_err(|e| DefaultError {
+                    message: format!("Could not run step '{}': {}", name, e),
+                })?;
+
+            if code == 0 {
+                break "succeeded".to_string();
+            }
+            // Invalid arguments fail the same way every time.
+            if attempts > retries || code == EXIT_USAGE {
+                break format!("failed ({})", code);
+            }
+            eprintln!(
+                "Step '{}' failed with exit code {}, retrying in {}s ({} of {})",
+                name,
+                code,
+                retry_delay.as_secs(),
+                attempts,
+                retries
+            );
+            thread::sleep(retry_delay);
+        };
+
+        if status != "succeeded" && !step.continue_on_error {
+            failed += 1;
+        }
+        reports.push(StepReport {
+            name: name.clone(),
+            status: if status != "succeeded" && step.continue_on_error {
+                format!("{}, ignored", status)
+            } else {
+                status
+            },
+            attempts,
+            duration: started.elapsed(),
+        });
+    }
+
+    if !quiet() {
+        let mut builder = Builder::default();
+        builder.push_record(["Step", "Status", "Attempts", "Duration"]);
+        for report in &reports {
+            builder.push_record([
+                report.name.clone(),
+                report.status.clone(),
+                report.attempts.to_string(),
+                format!("{:.1}s", report.duration.as_secs_f64()),
+            ]);
+        }
+        println!();
+        println!("{}", builder.build().with(Style::rounded()));
+    }
+
+    if failed > 0 {
+        return Err(DefaultError {
+            message: format!("{} of {} steps failed", failed, steps.len()),
+        });
+    }
+
+    Ok(())
+}
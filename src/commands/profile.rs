@@ -1,32 +1,38 @@
-use tabled::{builder::Builder, settings::Style};
+use crate::{
+    cli::{CurrentProfile, DeleteProfile, ProfileFormat, SwitchProfile},
+    commands::configure::TrieveProfile,
+};
 
-use crate::{commands::configure::TrieveProfile, DeleteProfile, SwitchProfile};
+use serde_json::json;
 
-use super::configure::TrieveProfileInner;
+use super::{
+    configure::{TrieveConfiguration, TrieveProfileInner},
+    i18n::{tr, tr_args},
+    output::{ensure_interactive, quiet, success},
+    picker::{pick, pick_with_cursor},
+    table::{self, Column},
+};
 
 pub fn switch_profile(
     profile_data: SwitchProfile,
     profiles: Vec<TrieveProfileInner>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let profile_name = profile_data.profile_name.unwrap_or_else(|| {
-        let profile_name = inquire::Select::new(
-            "Select a profile to switch to:",
-            profiles.iter().map(|p| p.name.clone()).collect(),
-        )
-        .with_starting_cursor(profiles.iter().position(|p| p.selected).unwrap_or(0))
-        .prompt()
-        .unwrap();
-        profile_name
-    });
-
-    profiles
-        .iter()
-        .find(|p| p.name == profile_name)
-        .ok_or_else(|| {
-            eprintln!("Profile '{}' not found.", profile_name);
-            std::process::exit(1);
-        })
-        .unwrap();
+    let profile_name = match profile_data.profile_name {
+        Some(profile_name) => profile_name,
+        None => {
+            ensure_interactive("--profile-name")?;
+            pick_with_cursor(
+                "Select a profile to switch to:",
+                "profiles",
+                profiles.iter().map(|p| p.name.clone()).collect(),
+                profiles.iter().position(|p| p.selected).unwrap_or(0),
+            )?
+        }
+    };
+
+    if !profiles.iter().any(|p| p.name == profile_name) {
+        return Err(tr_args("profile-not-found", &[("profile", profile_name)]).into());
+    }
 
     let profiles = profiles
         .iter()
@@ -36,52 +42,70 @@ pub fn switch_profile(
                     name: p.name.clone(),
                     selected: true,
                     settings: p.settings.clone(),
+                    api_keys: p.api_keys.clone(),
+                    protected: p.protected,
                 }
             } else {
                 TrieveProfileInner {
                     name: p.name.clone(),
                     selected: false,
                     settings: p.settings.clone(),
+                    api_keys: p.api_keys.clone(),
+                    protected: p.protected,
                 }
             }
         })
         .collect::<Vec<TrieveProfileInner>>();
 
-    confy::store("trieve", "profiles", TrieveProfile { inner: profiles })
-        .map_err(|e| {
-            eprintln!("Error saving configuration: {:?}", e);
-            std::process::exit(1);
-        })
-        .unwrap();
+    confy::store("trieve", "profiles", TrieveProfile::from(profiles))
+        .map_err(|e| format!("Error saving configuration: {:?}", e))?;
 
-    println!("Switched to profile '{}'.", profile_name);
+    if !quiet() {
+        success(&tr_args(
+            "profile-switched",
+            &[("profile", profile_name.clone())],
+        ));
+    }
 
     Ok(())
 }
 
+/// The columns of `profile list`.
+const PROFILE_COLUMNS: [Column<TrieveProfileInner>; 4] = [
+    Column {
+        key: "name",
+        header: "Name",
+        value: |p| p.name.clone(),
+    },
+    Column {
+        key: "api_url",
+        header: "API Url",
+        value: |p| p.settings.api_url.clone(),
+    },
+    Column {
+        key: "selected",
+        header: "Selected",
+        value: |p| if p.selected { "✔" } else { "" }.to_string(),
+    },
+    Column {
+        key: "protected",
+        header: "Protected",
+        value: |p| if p.protected { "✔" } else { "" }.to_string(),
+    },
+];
+
 pub fn list_profiles(
     mut profiles: Vec<TrieveProfileInner>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut builder = Builder::default();
-
     profiles.sort_by(|a, b| b.selected.cmp(&a.selected));
 
-    builder.push_record(["Name", "API Url", "Selected"]);
-
-    for profile in profiles {
-        builder.push_record([
-            profile.name,
-            profile.settings.api_url,
-            if profile.selected {
-                "✔".to_owned()
-            } else {
-                "".to_owned()
-            },
-        ]);
+    if quiet() {
+        table::print_quiet(&PROFILE_COLUMNS, &profiles, &["name"]).map_err(|e| e.message)?;
+        return Ok(());
     }
 
-    let table = builder.build().with(Style::rounded()).to_string();
-    println!("Profiles:");
+    let table = table::render(&PROFILE_COLUMNS, &profiles).map_err(|e| e.message)?;
+    println!("{}", tr("profile-list-header"));
     println!("{}", table);
 
     Ok(())
@@ -91,24 +115,22 @@ pub fn delete_profile(
     profile_data: DeleteProfile,
     profiles: Vec<TrieveProfileInner>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let profile_name = profile_data.profile_name.unwrap_or_else(|| {
-        let profile_name = inquire::Select::new(
-            "Select a profile to delete:",
-            profiles.iter().map(|p| p.name.clone()).collect(),
-        )
-        .prompt()
-        .unwrap();
-        profile_name
-    });
+    let profile_name = match profile_data.profile_name {
+        Some(profile_name) => profile_name,
+        None => {
+            ensure_interactive("--profile-name")?;
+            pick(
+                "Select a profile to delete:",
+                "profiles",
+                profiles.iter().map(|p| p.name.clone()).collect(),
+            )?
+        }
+    };
 
     let profile = profiles
         .iter()
         .find(|p| p.name == profile_name)
-        .ok_or_else(|| {
-            eprintln!("Profile '{}' not found.", profile_name);
-            std::process::exit(1);
-        })
-        .unwrap();
+        .ok_or_else(|| tr_args("profile-not-found", &[("profile", profile_name.clone())]))?;
 
     let mut profiles = profiles
         .iter()
@@ -118,21 +140,145 @@ pub fn delete_profile(
 
     if profile.selected {
         if profiles.is_empty() {
-            eprintln!("Cannot delete the last profile.");
-            std::process::exit(1);
+            return Err(tr("profile-delete-last").into());
         }
 
         profiles[0].selected = true;
     }
 
-    confy::store("trieve", "profiles", TrieveProfile { inner: profiles })
-        .map_err(|e| {
-            eprintln!("Error saving configuration: {:?}", e);
-            std::process::exit(1);
-        })
-        .unwrap();
+    confy::store("trieve", "profiles", TrieveProfile::from(profiles))
+        .map_err(|e| format!("Error saving configuration: {:?}", e))?;
+
+    if !quiet() {
+        success(&tr_args(
+            "profile-deleted",
+            &[("profile", profile_name.clone())],
+        ));
+    }
+
+    Ok(())
+}
+
+/// Marks a profile as protected, so destructive commands run with it must be confirmed by typing
+/// its name, or removes the mark.
+pub fn set_profile_protection(
+    profile_name: Option<String>,
+    protected: bool,
+    mut profiles: Vec<TrieveProfileInner>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let profile_name = match profile_name {
+        Some(profile_name) => profile_name,
+        None => {
+            ensure_interactive("the profile name")?;
+            pick(
+                if protected {
+                    "Select a profile to protect:"
+                } else {
+                    "Select a profile to unprotect:"
+                },
+                "profiles",
+                profiles.iter().map(|p| p.name.clone()).collect(),
+            )?
+        }
+    };
+
+    let profile = profiles
+        .iter_mut()
+        .find(|p| p.name == profile_name)
+        .ok_or_else(|| tr_args("profile-not-found", &[("profile", profile_name.clone())]))?;
+    profile.protected = protected;
+
+    confy::store("trieve", "profiles", TrieveProfile::from(profiles))
+        .map_err(|e| format!("Error saving configuration: {:?}", e))?;
 
-    println!("Deleted profile '{}'.", profile_name);
+    if !quiet() {
+        success(&tr_args(
+            if protected {
+                "profile-protected"
+            } else {
+                "profile-unprotected"
+            },
+            &[("profile", profile_name)],
+        ));
+    }
 
     Ok(())
 }
+
+/// A short name of the organization for prompts: its alias in the profile, or the start of its ID.
+fn organization_short_name(settings: &TrieveConfiguration) -> String {
+    let organization_id = settings.organization_id.to_string();
+    settings
+        .organization_aliases
+        .iter()
+        .find(|(_, id)| **id == organization_id)
+        .map(|(alias, _)| alias.clone())
+        .unwrap_or_else(|| organization_id.chars().take(8).collect())
+}
+
+/// Prints the profile the command runs with, which is the one passed with `--profile` or
+/// `TRIEVE_PROFILE`, or else the selected one, and the organization and server it uses. It reads
+/// the configuration only and makes no requests, as shell prompts run it all the time.
+pub fn current_profile(
+    profile_name: Option<String>,
+    from_flag: bool,
+    settings: &TrieveConfiguration,
+    current: &CurrentProfile,
+) {
+    let logged_in = !settings.organization_id.is_nil();
+
+    match current.format {
+        ProfileFormat::Starship => {
+            let organization =
+                logged_in.then(|| format!("({})", organization_short_name(settings)));
+            let segment = [profile_name, organization]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<String>>()
+                .join(" ");
+            println!("{}", segment);
+            return;
+        }
+        ProfileFormat::Json => {
+            println!(
+                "{}",
+                json!({
+                    "profile": profile_name,
+                    "organization_id": logged_in.then(|| settings.organization_id.to_string()),
+                    "organization": logged_in.then(|| organization_short_name(settings)),
+                    "api_url": settings.api_url,
+                })
+            );
+            return;
+        }
+        ProfileFormat::Text => {}
+    }
+
+    if quiet() {
+        println!("{}", profile_name.unwrap_or_default());
+        return;
+    }
+
+    let profile = match profile_name {
+        Some(name) if from_flag => format!("{} (from --profile or TRIEVE_PROFILE)", name),
+        Some(name) => format!("{} (selected)", name),
+        None => "none, TRIEVE_NO_PROFILE is set".to_string(),
+    };
+    let organization = if logged_in {
+        let organization_id = settings.organization_id.to_string();
+        match settings
+            .organization_aliases
+            .iter()
+            .find(|(_, id)| **id == organization_id)
+        {
+            Some((alias, _)) => format!("{} ({})", organization_id, alias),
+            None => organization_id,
+        }
+    } else {
+        "none, run `trieve login`".to_string()
+    };
+
+    println!("Profile:      {}", profile);
+    println!("Organization: {}", organization);
+    println!("API URL:      {}", settings.api_url);
+}
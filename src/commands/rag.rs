@@ -0,0 +1,442 @@
+use std::{fs, path::Path, time::Instant};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tabled::{builder::Builder, settings::Style};
+
+use crate::cli::{RagTest, SearchType};
+
+use super::{
+    ask::generate_answer,
+    audit,
+    configure::{require_login, TrieveConfiguration},
+    dataset::{require_dataset, update_server_configuration, DefaultError},
+    eval::search_type_name,
+    output::{quiet, warn},
+    rest::Rest,
+    search::search_dataset,
+    style::{bold, dim},
+};
+
+/// A prompt file: the prompts to ask, and the RAG configurations to ask each of them with.
+#[derive(Deserialize)]
+struct PromptFile {
+    prompts: Vec<String>,
+    #[serde(default)]
+    configurations: Vec<RagConfiguration>,
+}
+
+/// One way of answering a prompt. Fields left out use the defaults of `trieve ask`, or of the
+/// dataset for the model and the RAG prompt.
+#[derive(Deserialize, Serialize, Clone)]
+struct RagConfiguration {
+    #[serde(default)]
+    name: String,
+    #[serde(default = "default_n_retrievals")]
+    n_retrievals: u32,
+    #[serde(default = "default_search_type")]
+    search_type: SearchType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    /// The `LLM_DEFAULT_MODEL` of the dataset while this configuration runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    /// Replaces the `RAG_PROMPT` of the dataset, the instruction sent along with the chunks.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rag_prompt: Option<String>,
+}
+
+fn default_n_retrievals() -> u32 {
+    8
+}
+
+fn default_search_type() -> SearchType {
+    SearchType::Hybrid
+}
+
+impl RagConfiguration {
+    fn describe(&self) -> String {
+        let mut parts = vec![
+            format!("{} retrievals", self.n_retrievals),
+            search_type_name(self.search_type),
+        ];
+        if let Some(temperature) = self.temperature {
+            parts.push(format!("temperature {}", temperature));
+        }
+        if let Some(model) = &self.model {
+            parts.push(model.clone());
+        }
+        if self.rag_prompt.is_some() {
+            parts.push("custom RAG prompt".to_string());
+        }
+        parts.join(", ")
+    }
+}
+
+/// The answer to one prompt with one configuration.
+#[derive(Serialize)]
+struct RagAnswer {
+    answer: String,
+    /// The tracking IDs (or IDs) of the retrieved chunks, best match first.
+    chunks: Vec<String>,
+    latency_ms: f64,
+}
+
+#[derive(Serialize)]
+struct PromptReport {
+    prompt: String,
+    /// The answers in the order of the configurations.
+    answers: Vec<RagAnswer>,
+}
+
+#[derive(Serialize)]
+struct RagReport {
+    dataset_id: String,
+    configurations: Vec<RagConfiguration>,
+    prompts: Vec<PromptReport>,
+}
+
+fn read_prompt_file(path: &Path) -> Result<PromptFile, DefaultError> {
+    let read_error = |e: String| DefaultError {
+        message: format!("Could not read {}: {}", path.display(), e),
+    };
+    let file = fs::read_to_string(path).map_err(|e| read_error(e.to_string()))?;
+    let mut prompt_file: PromptFile =
+        serde_yaml::from_str(&file).map_err(|e| read_error(e.to_string()))?;
+
+    if prompt_file.prompts.is_empty() {
+        return Err(DefaultError {
+            message: format!("{} contains no prompts", path.display()),
+        });
+    }
+    if prompt_file.configurations.is_empty() {
+        prompt_file.configurations.push(RagConfiguration {
+            name: String::new(),
+            n_retrievals: default_n_retrievals(),
+            search_type: default_search_type(),
+            temperature: None,
+            model: None,
+            rag_prompt: None,
+        });
+    }
+    for (i, configuration) in prompt_file.configurations.iter_mut().enumerate() {
+        if configuration.name.is_empty() {
+            configuration.name = format!("config-{}", i + 1);
+        }
+    }
+
+    Ok(prompt_file)
+}
+
+/// Sets the `LLM_DEFAULT_MODEL` of the dataset, as the generate request has no model of its own.
+async fn set_model(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    model: &str,
+) -> Result<(), DefaultError> {
+    let changes = Map::from_iter([("LLM_DEFAULT_MODEL".to_string(), json!(model))]);
+    update_server_configuration(settings, dataset_id, changes).await?;
+    audit::record(
+        settings,
+        "dataset update",
+        Some(dataset_id),
+        json!({ "LLM_DEFAULT_MODEL": model }),
+    );
+    Ok(())
+}
+
+async fn answer_prompt(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    prompt: &str,
+    configuration: &RagConfiguration,
+) -> Result<RagAnswer, DefaultError> {
+    let started = Instant::now();
+    let hits = search_dataset(
+        settings,
+        dataset_id,
+        &json!({
+            "query": prompt,
+            "search_type": configuration.search_type,
+            "page_size": configuration.n_retrievals,
+        }),
+    )
+    .await?;
+
+    let mut options = json!({});
+    if let Some(temperature) = configuration.temperature {
+        options["temperature"] = json!(temperature);
+    }
+    if let Some(rag_prompt) = &configuration.rag_prompt {
+        options["prompt"] = json!(rag_prompt);
+    }
+    let answer = generate_answer(
+        settings,
+        dataset_id,
+        &hits,
+        vec![json!({ "role": "user", "content": prompt })],
+        options,
+    )
+    .await?;
+
+    Ok(RagAnswer {
+        answer,
+        chunks: hits.iter().map(|hit| hit.id().to_string()).collect(),
+        latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+    })
+}
+
+/// Answers every prompt with every configuration, switching the model of the dataset as needed.
+async fn run_configurations(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    prompt_file: &PromptFile,
+    original_model: &str,
+    current_model: &mut String,
+) -> Result<Vec<PromptReport>, DefaultError> {
+    let mut reports = prompt_file
+        .prompts
+        .iter()
+        .map(|prompt| PromptReport {
+            prompt: prompt.clone(),
+            answers: vec![],
+        })
+        .collect::<Vec<PromptReport>>();
+
+    for configuration in &prompt_file.configurations {
+        let model = configuration.model.as_deref().unwrap_or(original_model);
+        if model != current_model {
+            set_model(settings, dataset_id, model).await?;
+            *current_model = model.to_string();
+        }
+
+        if !quiet() {
+            eprintln!(
+                "Running {} ({})",
+                bold(&configuration.name),
+                configuration.describe()
+            );
+        }
+        for report in &mut reports {
+            let answer = answer_prompt(settings, dataset_id, &report.prompt, configuration).await?;
+            report.answers.push(answer);
+        }
+    }
+
+    Ok(reports)
+}
+
+impl RagReport {
+    fn print(&self) {
+        for (i, prompt) in self.prompts.iter().enumerate() {
+            println!("{}", bold(&format!("Prompt {}: {}", i + 1, prompt.prompt)));
+            for (configuration, answer) in self.configurations.iter().zip(&prompt.answers) {
+                println!();
+                println!(
+                    "  {} {}",
+                    bold(&configuration.name),
+                    dim(&format!("({:.0} ms)", answer.latency_ms))
+                );
+                for line in answer.answer.lines() {
+                    println!("    {}", line);
+                }
+                println!(
+                    "    {}",
+                    dim(&format!("Retrieved: {}", answer.chunks.join(", ")))
+                );
+            }
+            println!();
+        }
+    }
+
+    /// The number of chunks the configuration retrieved that the first configuration retrieved
+    /// too, over all prompts, as a share of the chunks it retrieved.
+    fn overlap_with_first(&self, configuration: usize) -> f64 {
+        let (shared, total) = self.prompts.iter().fold((0, 0), |(shared, total), prompt| {
+            let first = &prompt.answers[0].chunks;
+            let chunks = &prompt.answers[configuration].chunks;
+            (
+                shared + chunks.iter().filter(|id| first.contains(id)).count(),
+                total + chunks.len(),
+            )
+        });
+        if total == 0 {
+            0.0
+        } else {
+            shared as f64 / total as f64
+        }
+    }
+
+    fn summary(&self) -> Vec<[String; 5]> {
+        let count = self.prompts.len() as f64;
+        self.configurations
+            .iter()
+            .enumerate()
+            .map(|(i, configuration)| {
+                let answers = self.prompts.iter().map(|prompt| &prompt.answers[i]);
+                [
+                    configuration.name.clone(),
+                    configuration.describe(),
+                    format!(
+                        "{:.0}",
+                        answers
+                            .clone()
+                            .map(|answer| answer.answer.split_whitespace().count() as f64)
+                            .sum::<f64>()
+                            / count
+                    ),
+                    format!(
+                        "{:.0} ms",
+                        answers.map(|answer| answer.latency_ms).sum::<f64>() / count
+                    ),
+                    format!("{:.0}%", self.overlap_with_first(i) * 100.0),
+                ]
+            })
+            .collect()
+    }
+
+    const SUMMARY_HEADERS: [&'static str; 5] = [
+        "Configuration",
+        "Settings",
+        "Avg. words",
+        "Avg. latency",
+        "Chunks shared with first",
+    ];
+
+    fn print_summary(&self) {
+        let mut builder = Builder::default();
+        builder.push_record(Self::SUMMARY_HEADERS);
+        for row in self.summary() {
+            builder.push_record(row);
+        }
+        println!("{}", builder.build().with(Style::rounded()));
+    }
+
+    fn markdown(&self) -> String {
+        let cell = |text: &str| text.replace('|', "\\|").replace('\n', "<br>");
+        let mut markdown = format!("# RAG test of dataset {}\n\n", self.dataset_id);
+
+        markdown.push_str(&format!("| {} |\n", Self::SUMMARY_HEADERS.join(" | ")));
+        markdown.push_str(&format!(
+            "|{}\n",
+            " --- |".repeat(Self::SUMMARY_HEADERS.len())
+        ));
+        for row in self.summary() {
+            markdown.push_str(&format!(
+                "| {} |\n",
+                row.iter()
+                    .map(|c| cell(c))
+                    .collect::<Vec<String>>()
+                    .join(" | ")
+            ));
+        }
+
+        for (i, prompt) in self.prompts.iter().enumerate() {
+            markdown.push_str(&format!("\n## Prompt {}: {}\n", i + 1, prompt.prompt));
+            for (configuration, answer) in self.configurations.iter().zip(&prompt.answers) {
+                markdown.push_str(&format!(
+                    "\n### {}\n\n_{}, {:.0} ms_\n\n{}\n\nRetrieved: {}\n",
+                    configuration.name,
+                    configuration.describe(),
+                    answer.latency_ms,
+                    answer.answer,
+                    answer
+                        .chunks
+                        .iter()
+                        .map(|id| format!("`{}`", id))
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                ));
+            }
+        }
+
+        markdown
+    }
+
+    /// Writes the report to `path`: as JSON when it ends in `.json`, as Markdown otherwise, or
+    /// as Markdown to stdout when `path` is `-`.
+    fn write(&self, path: &Path) -> Result<(), DefaultError> {
+        let is_json = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+        let report = if is_json {
+            serde_json::to_string_pretty(self).map_err(|e| DefaultError {
+                message: e.to_string(),
+            })?
+        } else {
+            self.markdown()
+        };
+
+        if path.as_os_str() == "-" {
+            println!("{}", report);
+            return Ok(());
+        }
+
+        fs::write(path, report).map_err(|e| DefaultError {
+            message: format!("Could not write {}: {}", path.display(), e),
+        })
+    }
+}
+
+pub async fn rag_test(settings: TrieveConfiguration, test: RagTest) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(test.dataset_id, &settings)?;
+    let prompt_file = read_prompt_file(&test.prompt_file)?;
+
+    // Configurations with a model switch the model of the dataset, which is switched back after.
+    let original_model = if prompt_file
+        .configurations
+        .iter()
+        .any(|configuration| configuration.model.is_some())
+    {
+        let dataset: Value = Rest::new(&settings)
+            .dataset(&dataset_id)
+            .get(&format!("/api/dataset/{}", dataset_id))
+            .await?;
+        let model = dataset["server_configuration"]["LLM_DEFAULT_MODEL"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        warn(&format!(
+            "The model of dataset {} is switched while the configurations run, which affects \
+             everyone using it. It is set back to '{}' afterwards.",
+            dataset_id, model
+        ));
+        Some(model)
+    } else {
+        None
+    };
+
+    let dataset_model = original_model.clone().unwrap_or_default();
+    let mut current_model = dataset_model.clone();
+    let result = run_configurations(
+        &settings,
+        &dataset_id,
+        &prompt_file,
+        &dataset_model,
+        &mut current_model,
+    )
+    .await;
+    if let Some(original_model) = original_model.filter(|model| *model != current_model) {
+        set_model(&settings, &dataset_id, &original_model).await?;
+    }
+
+    let report = RagReport {
+        dataset_id,
+        configurations: prompt_file.configurations,
+        prompts: result?,
+    };
+    match &test.report {
+        Some(path) if path.as_os_str() == "-" => return report.write(path),
+        Some(path) => report.write(path)?,
+        None => {}
+    }
+
+    if !quiet() {
+        report.print();
+    }
+    report.print_summary();
+
+    Ok(())
+}
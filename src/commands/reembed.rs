@@ -0,0 +1,323 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::cli::ReembedDataset;
+
+use super::{
+    alias::resolve_dataset,
+    audit,
+    configure::{config_subdir, require_login, TrieveConfiguration},
+    dataset::DefaultError,
+    export::export_dataset,
+    jobs,
+    mapping::ColumnMapping,
+    output::{fail, interrupted, quiet, EXIT_ERROR},
+    rest::Rest,
+    style::bold,
+    upload::{chunk_records, open_source, upload_chunk_stream, Checkpoint},
+};
+
+const MIGRATION_FILE: &str = "migration.json";
+const CHUNKS_FILE: &str = "chunks.jsonl";
+
+/// The embedding models the size and server of which are known, so only the model has to be
+/// passed. Other models need `--embedding-size` and `--embedding-base-url`.
+const KNOWN_EMBEDDING_MODELS: [(&str, u64, &str); 5] = [
+    ("jina-base-en", 768, "https://embedding.trieve.ai"),
+    (
+        "jina-embeddings-v2-base-code",
+        768,
+        "https://embedding.trieve.ai/jina-code",
+    ),
+    ("bge-m3", 1024, "https://embedding.trieve.ai/bge-m3"),
+    ("text-embedding-3-small", 1536, "https://api.openai.com/v1"),
+    ("text-embedding-3-large", 3072, "https://api.openai.com/v1"),
+];
+
+/// The progress of a migration, saved next to the exported chunks so an interrupted migration
+/// continues where it stopped when the command is run again.
+#[derive(Serialize, Deserialize)]
+struct Migration {
+    from: String,
+    /// The dataset the chunks are uploaded to.
+    to: String,
+    embedding_model: String,
+    /// The number of chunks exported, once the export finished.
+    exported: Option<usize>,
+    started_at: DateTime<Utc>,
+}
+
+impl Migration {
+    fn load(path: &Path) -> Result<Option<Migration>, DefaultError> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = fs::read_to_string(path).map_err(|e| DefaultError {
+            message: format!("Could not read {}: {}", path.display(), e),
+        })?;
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| DefaultError {
+                message: format!("Invalid migration state {}: {}", path.display(), e),
+            })
+    }
+
+    fn store(&self, path: &Path) -> Result<(), DefaultError> {
+        let contents = serde_json::to_string_pretty(self).map_err(|e| DefaultError {
+            message: e.to_string(),
+        })?;
+        fs::write(path, contents).map_err(|e| DefaultError {
+            message: format!("Could not write {}: {}", path.display(), e),
+        })
+    }
+}
+
+/// The directory holding the state and exported chunks of the migration of a dataset to a model.
+fn migration_dir(from: &str, model: &str) -> Result<PathBuf, DefaultError> {
+    let model = model
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>();
+
+    config_subdir("migrations")
+        .map(|dir| dir.join(format!("{}-{}", from, model)))
+        .map_err(|e| DefaultError {
+            message: e.to_string(),
+        })
+}
+
+/// The `server_configuration` of the new dataset: the one of the old dataset with the embedding
+/// model, size and server replaced.
+fn embedding_configuration(old: &Value, reembed: &ReembedDataset) -> Result<Value, DefaultError> {
+    let known = KNOWN_EMBEDDING_MODELS
+        .iter()
+        .find(|(model, _, _)| *model == reembed.embedding_model);
+    let size = reembed
+        .embedding_size
+        .or(known.map(|(_, size, _)| *size))
+        .ok_or_else(|| DefaultError {
+            message: format!(
+                "Unknown embedding model {}, pass its dimensions with --embedding-size",
+                reembed.embedding_model
+            ),
+        })?;
+    let base_url = reembed
+        .embedding_base_url
+        .clone()
+        .or(known.map(|(_, _, url)| url.to_string()))
+        .ok_or_else(|| DefaultError {
+            message: format!(
+                "Unknown embedding model {}, pass the URL of its server with --embedding-base-url",
+                reembed.embedding_model
+            ),
+        })?;
+
+    let mut configuration = old["server_configuration"].clone();
+    if !configuration.is_object() {
+        configuration = json!({});
+    }
+    configuration["EMBEDDING_MODEL_NAME"] = json!(reembed.embedding_model);
+    configuration["EMBEDDING_SIZE"] = json!(size);
+    configuration["EMBEDDING_BASE_URL"] = json!(base_url);
+    Ok(configuration)
+}
+
+/// Moves the tracking ID of the old dataset to the new one. Tracking IDs are unique, so the old
+/// dataset keeps it with a `-previous` suffix.
+async fn swap_tracking_id(
+    settings: &TrieveConfiguration,
+    old: &Value,
+    new_id: &str,
+) -> Result<(), DefaultError> {
+    let Some(tracking_id) = old["tracking_id"].as_str() else {
+        if !quiet() {
+            println!("The old dataset has no tracking ID, nothing to swap.");
+        }
+        return Ok(());
+    };
+    let old_id = old["id"].as_str().unwrap_or_default();
+    let previous = format!("{}-previous", tracking_id);
+
+    for (dataset_id, new_tracking_id) in [(old_id, previous.as_str()), (new_id, tracking_id)] {
+        let body = json!({ "dataset_id": dataset_id, "new_tracking_id": new_tracking_id });
+        let _: Value = Rest::new(settings)
+            .dataset(dataset_id)
+            .put("/api/dataset", &body)
+            .await?;
+        audit::record(settings, "dataset update", Some(dataset_id), body);
+    }
+
+    if !quiet() {
+        println!(
+            "Moved tracking ID {} to the new dataset, the old one is now {}.",
+            tracking_id, previous
+        );
+    }
+
+    Ok(())
+}
+
+fn step(number: usize, steps: usize, text: &str) {
+    if !quiet() {
+        println!("{} {}", bold(&format!("[{}/{}]", number, steps)), text);
+    }
+}
+
+/// Copies the chunks of a dataset into a new dataset that embeds them with another model. The
+/// chunks are exported to the configuration directory first, then uploaded with a checkpoint, so
+/// running the command again after an interruption or failed batches continues the migration.
+pub async fn reembed_dataset(
+    settings: TrieveConfiguration,
+    mut reembed: ReembedDataset,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+    reembed.from = resolve_dataset(&settings, &reembed.from);
+
+    let old: Value = Rest::new(&settings)
+        .dataset(&reembed.from)
+        .get(&format!("/api/dataset/{}", reembed.from))
+        .await?;
+    let configuration = embedding_configuration(&old, &reembed)?;
+
+    if reembed.detach {
+        return jobs::detach();
+    }
+
+    let dir = migration_dir(&reembed.from, &reembed.embedding_model)?;
+    if reembed.restart && dir.exists() {
+        fs::remove_dir_all(&dir).map_err(|e| DefaultError {
+            message: format!("Could not remove {}: {}", dir.display(), e),
+        })?;
+    }
+    fs::create_dir_all(&dir).map_err(|e| DefaultError {
+        message: format!("Could not create {}: {}", dir.display(), e),
+    })?;
+    let state_path = dir.join(MIGRATION_FILE);
+    let chunks_path = dir.join(CHUNKS_FILE);
+    let steps = if reembed.swap { 4 } else { 3 };
+
+    let old_name = old["name"].as_str().unwrap_or(&reembed.from).to_string();
+    let mut migration = match Migration::load(&state_path)? {
+        Some(migration) => {
+            step(
+                1,
+                steps,
+                &format!("Resuming the migration to dataset {}", migration.to),
+            );
+            migration
+        }
+        None => {
+            let name = reembed
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("{} ({})", old_name, reembed.embedding_model));
+            step(1, steps, &format!("Creating dataset {}", name));
+            let created: Value = Rest::new(&settings)
+                .post(
+                    "/api/dataset",
+                    &json!({
+                        "dataset_name": name,
+                        "organization_id": settings.organization_id,
+                        "server_configuration": configuration,
+                    }),
+                )
+                .await?;
+            let migration = Migration {
+                from: reembed.from.clone(),
+                to: created["id"].as_str().unwrap_or_default().to_string(),
+                embedding_model: reembed.embedding_model.clone(),
+                exported: None,
+                started_at: Utc::now(),
+            };
+            migration.store(&state_path)?;
+            audit::record(
+                &settings,
+                "dataset create",
+                Some(&migration.to),
+                json!({ "name": name, "reembed_from": reembed.from }),
+            );
+            migration
+        }
+    };
+
+    let exported = match migration.exported {
+        Some(exported) => exported,
+        None => {
+            step(2, steps, &format!("Exporting the chunks of {}", old_name));
+            let exported = export_dataset(&settings, &reembed.from, &chunks_path).await?;
+            migration.exported = Some(exported);
+            migration.store(&state_path)?;
+            exported
+        }
+    };
+
+    step(
+        3,
+        steps,
+        &format!("Uploading {} chunks to dataset {}", exported, migration.to),
+    );
+    let (reader, format) = open_source(None, Some(&chunks_path), None).await?;
+    let records = chunk_records(reader, format, &ColumnMapping::from_args(&[], None)?)?;
+    let checkpoint = Checkpoint::open(&chunks_path, &migration.to, true)?;
+    let summary = upload_chunk_stream(
+        settings.clone(),
+        migration.to.clone(),
+        records,
+        Some(checkpoint),
+        None,
+        None,
+    )
+    .await?;
+
+    if summary.interrupted || summary.failed_batches > 0 {
+        let message = format!(
+            "The migration did not finish, {} of {} chunks were uploaded. Run the same command again to continue it.",
+            summary.skipped + summary.uploaded,
+            exported
+        );
+        return Err(if summary.interrupted {
+            interrupted(message)
+        } else {
+            fail(EXIT_ERROR, "migration_incomplete", message)
+        });
+    }
+    if !quiet() {
+        println!("Uploaded {} chunks.", summary.skipped + summary.uploaded);
+    }
+
+    if reembed.swap {
+        step(4, steps, "Swapping tracking IDs");
+        swap_tracking_id(&settings, &old, &migration.to).await?;
+    }
+
+    audit::record(
+        &settings,
+        "dataset reembed",
+        Some(&migration.to),
+        json!({
+            "from": reembed.from,
+            "embedding_model": reembed.embedding_model,
+            "chunks": exported,
+            "swap": reembed.swap,
+        }),
+    );
+    let _ = fs::remove_dir_all(&dir);
+
+    if quiet() {
+        println!("{}", migration.to);
+    } else {
+        println!(
+            "Dataset {} now holds the chunks of {} embedded with {}. The old dataset was kept; delete it with `trieve dataset delete {}` once the new one is verified.",
+            migration.to, old_name, reembed.embedding_model, reembed.from
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,329 @@
+use std::io::{stdout, IsTerminal};
+
+use scraper::{ElementRef, Html, Node};
+
+use super::style::{bold, dim};
+
+/// Text is wrapped at this width at most, however wide the terminal is, as long lines are hard to
+/// read.
+const MAX_WIDTH: usize = 100;
+/// The width text is wrapped at when stdout is not a terminal.
+const DEFAULT_WIDTH: usize = 80;
+/// Lines are not wrapped narrower than this, however deep a list is nested.
+const MIN_WIDTH: usize = 20;
+
+/// Elements whose content is not shown.
+const SKIPPED: [&str; 7] = [
+    "head", "script", "style", "noscript", "template", "svg", "iframe",
+];
+/// Elements that are part of the text around them.
+const INLINE: [&str; 20] = [
+    "span", "strong", "b", "em", "i", "u", "mark", "small", "sub", "sup", "abbr", "cite", "q", "s",
+    "del", "ins", "kbd", "var", "time", "label",
+];
+
+/// The width to wrap rendered text at: the width of the terminal, up to `MAX_WIDTH`.
+pub fn terminal_width() -> usize {
+    if !stdout().is_terminal() {
+        return DEFAULT_WIDTH;
+    }
+    crossterm::terminal::size()
+        .map(|(columns, _)| columns as usize)
+        .unwrap_or(DEFAULT_WIDTH)
+        .min(MAX_WIDTH)
+}
+
+/// A paragraph of rendered text.
+struct Block {
+    /// The lines of the paragraph, which are wrapped unless it is preformatted.
+    text: String,
+    /// Printed before the first line, e.g. the indentation with the bullet of a list item.
+    first_prefix: String,
+    /// Printed before the other lines, e.g. the indentation of nested lists and quotes.
+    prefix: String,
+    preformatted: bool,
+    heading: bool,
+    /// The outermost list the block is in, whose items follow each other without a blank line.
+    list: Option<usize>,
+}
+
+/// Walks the chunk HTML in order, collecting the paragraphs to print and the links to list
+/// below them.
+#[derive(Default)]
+struct Renderer {
+    blocks: Vec<Block>,
+    /// Inline text of the paragraph being read, with a newline for every `<br>`.
+    pending: String,
+    /// The indentation of the current position, from the enclosing lists and quotes.
+    indent: String,
+    /// The bullet or number of the list item whose first paragraph has not been pushed yet.
+    marker: Option<String>,
+    /// The depth of lists at the current position.
+    lists: usize,
+    /// The number of outermost lists read so far.
+    list_count: usize,
+    links: Vec<String>,
+}
+
+impl Renderer {
+    fn walk(&mut self, element: ElementRef) {
+        for child in element.children() {
+            match child.value() {
+                Node::Text(text) => self.pending.push_str(text),
+                Node::Element(_) => {
+                    let Some(child) = ElementRef::wrap(child) else {
+                        continue;
+                    };
+                    self.element(child);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn element(&mut self, element: ElementRef) {
+        let name = element.value().name();
+        match name {
+            _ if SKIPPED.contains(&name) => {}
+            _ if INLINE.contains(&name) => self.walk(element),
+            "br" => self.pending.push('\n'),
+            "a" => {
+                self.walk(element);
+                self.link(element);
+            }
+            "code" => {
+                self.pending.push('`');
+                self.walk(element);
+                self.pending.push('`');
+            }
+            "img" => {
+                let alt = element.value().attr("alt").unwrap_or_default().trim();
+                if !alt.is_empty() {
+                    self.pending.push_str(&format!("[image: {}]", alt));
+                }
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.flush();
+                let level = name[1..].parse().unwrap_or(1);
+                let text = collapse(&element.text().collect::<String>());
+                if !text.is_empty() {
+                    self.push(format!("{} {}", "#".repeat(level), text), false, true);
+                }
+            }
+            "pre" => {
+                self.flush();
+                let text = element.text().collect::<String>();
+                let text = text.trim_matches('\n').trim_end();
+                if !text.is_empty() {
+                    self.push(text.to_string(), true, false);
+                }
+            }
+            "ul" | "ol" => self.list(element, name == "ol"),
+            "blockquote" => {
+                self.flush();
+                let outer = self.indent.clone();
+                self.indent = format!("{}│ ", outer);
+                self.walk(element);
+                self.flush();
+                self.indent = outer;
+            }
+            "tr" => {
+                self.flush();
+                let cells = element
+                    .children()
+                    .filter_map(ElementRef::wrap)
+                    .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+                    .map(|cell| collapse(&cell.text().collect::<String>()))
+                    .collect::<Vec<String>>();
+                if cells.iter().any(|cell| !cell.is_empty()) {
+                    self.push(cells.join(" | "), false, false);
+                }
+            }
+            _ => {
+                self.flush();
+                self.walk(element);
+                self.flush();
+            }
+        }
+    }
+
+    /// Numbers a link after its text, to be listed with its URL below the chunk. Links to anchors
+    /// and links whose text is their URL are left alone.
+    fn link(&mut self, element: ElementRef) {
+        let Some(href) = element.value().attr("href").map(str::trim) else {
+            return;
+        };
+        if href.is_empty()
+            || href.starts_with('#')
+            || href.starts_with("javascript:")
+            || collapse(&element.text().collect::<String>()) == href
+        {
+            return;
+        }
+
+        let number = match self.links.iter().position(|link| link == href) {
+            Some(index) => index + 1,
+            None => {
+                self.links.push(href.to_string());
+                self.links.len()
+            }
+        };
+        self.pending.push_str(&format!(" [{}]", number));
+    }
+
+    fn list(&mut self, list: ElementRef, ordered: bool) {
+        self.flush();
+        let mut number = list
+            .value()
+            .attr("start")
+            .and_then(|start| start.parse::<usize>().ok())
+            .unwrap_or(1);
+        if self.lists == 0 {
+            self.list_count += 1;
+        }
+
+        for item in list.children().filter_map(ElementRef::wrap) {
+            if item.value().name() != "li" {
+                self.element(item);
+                continue;
+            }
+
+            let marker = if ordered {
+                number += 1;
+                format!("{}. ", number - 1)
+            } else {
+                "• ".to_string()
+            };
+            let outer = self.indent.clone();
+            self.indent = format!("{}{}", outer, " ".repeat(marker.chars().count()));
+            self.marker = Some(marker);
+            self.lists += 1;
+            self.walk(item);
+            self.flush();
+            self.lists -= 1;
+            self.marker = None;
+            self.indent = outer;
+        }
+    }
+
+    /// Ends the paragraph being read.
+    fn flush(&mut self) {
+        let pending = std::mem::take(&mut self.pending);
+        let lines = pending
+            .split('\n')
+            .map(collapse)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<String>>();
+        if !lines.is_empty() {
+            self.push(lines.join("\n"), false, false);
+        }
+    }
+
+    fn push(&mut self, text: String, preformatted: bool, heading: bool) {
+        let prefix = self.indent.clone();
+        // The bullet takes the place of the indentation of the item on the first line.
+        let first_prefix = match self.marker.take() {
+            Some(marker) => format!(
+                "{}{}",
+                &prefix[..prefix.len() - marker.chars().count()],
+                marker
+            ),
+            None => prefix.clone(),
+        };
+        self.blocks.push(Block {
+            text,
+            first_prefix,
+            prefix,
+            preformatted,
+            heading,
+            list: (self.lists > 0).then_some(self.list_count),
+        });
+    }
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// Breaks a line into lines of at most `width` characters, between words. A word longer than
+/// `width` gets a line of its own.
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut current = String::new();
+    for word in line.split(' ') {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    lines.push(current);
+    lines
+}
+
+/// Renders chunk HTML as text to read in a terminal: paragraphs wrapped at `width` and separated
+/// by blank lines, headings marked with `#`, bulleted and numbered lists, quotes, preformatted
+/// text as is, and the URLs of links numbered below the text.
+pub fn render_html(html: &str, width: usize) -> String {
+    let fragment = Html::parse_fragment(html);
+    let mut renderer = Renderer::default();
+    renderer.walk(fragment.root_element());
+    renderer.flush();
+
+    let mut rendered = String::new();
+    let mut previous_list = None;
+    for (index, block) in renderer.blocks.iter().enumerate() {
+        if index > 0 {
+            rendered.push_str(if previous_list.is_some() && previous_list == block.list {
+                "\n"
+            } else {
+                "\n\n"
+            });
+        }
+        previous_list = block.list;
+
+        let lines = if block.preformatted {
+            block
+                .text
+                .lines()
+                .map(|line| format!("    {}", line))
+                .collect()
+        } else {
+            let width = width
+                .saturating_sub(block.prefix.chars().count())
+                .max(MIN_WIDTH);
+            block
+                .text
+                .split('\n')
+                .flat_map(|line| wrap(line, width))
+                .collect::<Vec<String>>()
+        };
+        for (number, line) in lines.iter().enumerate() {
+            if number > 0 {
+                rendered.push('\n');
+            }
+            rendered.push_str(if number == 0 {
+                &block.first_prefix
+            } else {
+                &block.prefix
+            });
+            rendered.push_str(&if block.heading {
+                bold(line)
+            } else {
+                line.clone()
+            });
+        }
+    }
+
+    if !renderer.links.is_empty() {
+        rendered.push('\n');
+        for (index, link) in renderer.links.iter().enumerate() {
+            rendered.push('\n');
+            rendered.push_str(&dim(&format!("[{}] {}", index + 1, link)));
+        }
+    }
+
+    rendered
+}
@@ -0,0 +1,115 @@
+use std::{env, fmt::Debug, future::Future};
+
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::{
+    configure::TrieveConfiguration,
+    dataset::DefaultError,
+    http::{self, Response},
+};
+
+/// A thin serde_json based client for the Trieve REST API, for endpoints the generated
+/// `trieve_client` does not have yet or whose models do not match the server.
+pub struct Rest<'a> {
+    settings: &'a TrieveConfiguration,
+    dataset_id: Option<&'a str>,
+}
+
+impl<'a> Rest<'a> {
+    pub fn new(settings: &'a TrieveConfiguration) -> Self {
+        Rest {
+            settings,
+            dataset_id: None,
+        }
+    }
+
+    /// Scopes the requests to a dataset by setting the `TR-Dataset` header.
+    pub fn dataset(mut self, dataset_id: &'a str) -> Self {
+        self.dataset_id = Some(dataset_id);
+        self
+    }
+
+    pub async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<Response, DefaultError> {
+        let mut request = http::api_request(self.settings, method, path);
+        if !self.settings.organization_id.is_nil() {
+            request = request.header("TR-Organization", self.settings.organization_id.to_string());
+        }
+        if let Some(dataset_id) = self.dataset_id {
+            request = request.header("TR-Dataset", dataset_id);
+        }
+
+        http::send(request, body).await
+    }
+
+    async fn send_json<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&Value>,
+    ) -> Result<T, DefaultError> {
+        let response = self.send(method, path, body).await?;
+        if response.body.trim().is_empty() {
+            // Endpoints that return `204 No Content` deserialize like `null`.
+            return serde_json::from_value(Value::Null).map_err(|e| DefaultError {
+                message: e.to_string(),
+            });
+        }
+
+        response.json()
+    }
+
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, DefaultError> {
+        self.send_json(Method::GET, path, None).await
+    }
+
+    pub async fn post<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &Value,
+    ) -> Result<T, DefaultError> {
+        self.send_json(Method::POST, path, Some(body)).await
+    }
+
+    pub async fn put<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &Value,
+    ) -> Result<T, DefaultError> {
+        self.send_json(Method::PUT, path, Some(body)).await
+    }
+
+    pub async fn delete(&self, path: &str) -> Result<(), DefaultError> {
+        self.send(Method::DELETE, path, None).await.map(|_| ())
+    }
+}
+
+/// Runs a read through the generated client, falling back to the REST layer when the client
+/// cannot deserialize the response. Setting `TRIEVE_FORCE_REST=true` skips the generated client.
+///
+/// Only use this for requests without side effects: by the time deserialization fails the
+/// server has already handled the request.
+pub async fn or_rest<T, E, F, R>(typed: F, rest: impl FnOnce() -> R) -> Result<T, DefaultError>
+where
+    E: Debug,
+    F: Future<Output = Result<T, trieve_client::apis::Error<E>>>,
+    R: Future<Output = Result<T, DefaultError>>,
+{
+    if env::var("TRIEVE_FORCE_REST").unwrap_or_default() == "true" {
+        return rest().await;
+    }
+
+    match typed.await {
+        Ok(value) => Ok(value),
+        Err(trieve_client::apis::Error::Serde(_)) => rest().await,
+        Err(e) => Err(DefaultError {
+            message: e.to_string(),
+        }),
+    }
+}
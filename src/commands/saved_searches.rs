@@ -0,0 +1,151 @@
+use std::{fs, path::PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tabled::{builder::Builder, settings::Style};
+
+use crate::cli::{DeleteSearch, RunSearch, SaveSearch, SearchOptions, SearchQuery};
+
+use super::{
+    configure::{config_subdir, TrieveConfiguration},
+    dataset::DefaultError,
+    date::format_time,
+    output::quiet,
+    search::search,
+};
+
+/// A search saved under a name with all of its options, so it can be run again with
+/// `trieve search run <NAME>`.
+#[derive(Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+    pub saved_at: DateTime<Utc>,
+    pub options: SearchOptions,
+}
+
+fn saved_search_path(name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid search name '{}'. Use letters, numbers, '-' and '_' only.",
+            name
+        )
+        .into());
+    }
+
+    Ok(config_subdir("searches")?.join(format!("{}.json", name)))
+}
+
+pub fn load_saved_search(name: &str) -> Result<SavedSearch, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(saved_search_path(name)?).map_err(|_| {
+        format!(
+            "Saved search '{}' not found. Run `trieve search list` to see the saved searches.",
+            name
+        )
+    })?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+pub fn save_search(save: SaveSearch) -> Result<(), Box<dyn std::error::Error>> {
+    let path = saved_search_path(&save.name)?;
+    if path.exists() && !save.force {
+        return Err(format!(
+            "Saved search '{}' already exists. Pass --force to overwrite it.",
+            save.name
+        )
+        .into());
+    }
+
+    let saved = SavedSearch {
+        name: save.name.clone(),
+        query: save.query,
+        saved_at: Utc::now(),
+        options: save.options,
+    };
+    fs::write(&path, serde_json::to_string_pretty(&saved)?)?;
+    if quiet() {
+        println!("{}", save.name);
+        return Ok(());
+    }
+
+    println!("Saved search '{}'.", save.name);
+    println!("Run it with `trieve search run {}`.", save.name);
+
+    Ok(())
+}
+
+pub async fn run_search(settings: TrieveConfiguration, run: RunSearch) -> Result<(), DefaultError> {
+    let mut saved = load_saved_search(&run.name).map_err(|e| DefaultError {
+        message: e.to_string(),
+    })?;
+    if run.dataset_id.is_some() {
+        saved.options.dataset_id = run.dataset_id;
+        saved.options.all_datasets = false;
+    }
+
+    search(
+        settings,
+        SearchQuery {
+            query: Some(saved.query),
+            options: saved.options,
+        },
+    )
+    .await
+}
+
+pub fn list_searches() -> Result<(), Box<dyn std::error::Error>> {
+    let mut searches = fs::read_dir(config_subdir("searches")?)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|e| e == "json"))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|contents| serde_json::from_str::<SavedSearch>(&contents).ok())
+        .collect::<Vec<SavedSearch>>();
+
+    searches.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if quiet() {
+        for search in searches {
+            println!("{}", search.name);
+        }
+        return Ok(());
+    }
+
+    let mut builder = Builder::default();
+
+    builder.push_record(["Name", "Query", "Dataset", "Filters", "Saved At"]);
+
+    for search in searches {
+        builder.push_record([
+            search.name.clone(),
+            search.query.clone(),
+            if search.options.all_datasets {
+                "(all)".to_string()
+            } else {
+                search.options.dataset_id.clone().unwrap_or_default()
+            },
+            search.options.filters.join(" "),
+            format_time(search.saved_at),
+        ]);
+    }
+
+    let table = builder.build().with(Style::rounded()).to_string();
+    println!("Saved searches:");
+    println!("{}", table);
+
+    Ok(())
+}
+
+pub fn delete_search(delete: DeleteSearch) -> Result<(), Box<dyn std::error::Error>> {
+    fs::remove_file(saved_search_path(&delete.name)?)
+        .map_err(|_| format!("Saved search '{}' not found.", delete.name))?;
+
+    if !quiet() {
+        println!("Deleted saved search '{}'.", delete.name);
+    }
+
+    Ok(())
+}
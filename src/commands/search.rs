@@ -0,0 +1,932 @@
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tabled::{builder::Builder, settings::Style};
+use tokio::task::JoinSet;
+
+use crate::cli::{
+    BatchSearch, CompareSearch, GroupSearch, InputFormat, SearchOptions, SearchQuery, SortArgs,
+};
+
+use super::{
+    configure::{require_login, TrieveConfiguration},
+    dataset::{dataset_or_default, get_datasets_from_org, select_dataset_id, DefaultError},
+    filter::parse_filters,
+    output::{ensure_interactive, fail_if_empty, quiet},
+    rest::Rest,
+    style::{bold, dim, highlight, link},
+    upload::MAX_CONCURRENT_BATCHES,
+};
+
+/// A search result, from either the `score_chunks` (v1) or `chunks` (v2) response shape.
+pub struct SearchHit {
+    pub chunk: Value,
+    pub score: f64,
+    /// Matched phrases returned next to the chunk (v2). v1 marks them inside `chunk_html`.
+    pub highlights: Vec<String>,
+    /// The name of the dataset the chunk is in, set when several datasets were searched.
+    pub dataset: Option<String>,
+}
+
+impl SearchHit {
+    /// The tracking ID of the chunk, or its ID when it has none.
+    pub fn id(&self) -> &str {
+        self.chunk["tracking_id"]
+            .as_str()
+            .filter(|id| !id.is_empty())
+            .or(self.chunk["id"].as_str())
+            .unwrap_or_default()
+    }
+}
+
+pub fn search_hits(response: &Value) -> Vec<SearchHit> {
+    if let Some(score_chunks) = response["score_chunks"].as_array() {
+        return score_chunks
+            .iter()
+            .filter_map(|hit| {
+                Some(SearchHit {
+                    chunk: hit["metadata"].get(0).cloned()?,
+                    score: hit["score"].as_f64().unwrap_or_default(),
+                    highlights: vec![],
+                    dataset: None,
+                })
+            })
+            .collect();
+    }
+
+    response["chunks"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|hit| SearchHit {
+            chunk: hit["chunk"].clone(),
+            score: hit["score"].as_f64().unwrap_or_default(),
+            highlights: hit["highlights"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|highlight| highlight.as_str().map(|h| html_to_text(h).text()))
+                .collect(),
+            dataset: None,
+        })
+        .collect()
+}
+
+/// A chunk group returned by a search over groups, with its best matching chunks.
+pub struct GroupHit {
+    pub group: Value,
+    pub hits: Vec<SearchHit>,
+}
+
+impl GroupHit {
+    /// The tracking ID of the group, or its ID when it has none.
+    pub fn id(&self) -> &str {
+        self.group["tracking_id"]
+            .as_str()
+            .filter(|id| !id.is_empty())
+            .or(self.group["id"].as_str())
+            .unwrap_or_default()
+    }
+}
+
+/// The groups of a search over groups, from either the `group_chunks` (v1) or `results` (v2)
+/// response shape.
+pub fn group_hits(response: &Value) -> Vec<GroupHit> {
+    if let Some(group_chunks) = response["group_chunks"].as_array() {
+        return group_chunks
+            .iter()
+            .map(|group| GroupHit {
+                group: group["group"].clone(),
+                hits: search_hits(&json!({ "score_chunks": group["metadata"] })),
+            })
+            .collect();
+    }
+
+    response["results"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|group| GroupHit {
+            group: group["group"].clone(),
+            hits: search_hits(&json!({ "chunks": group["chunks"] })),
+        })
+        .collect()
+}
+
+/// Plain text with a flag per character telling whether it is part of a highlighted match.
+#[derive(Default)]
+pub struct MarkedText(Vec<(char, bool)>);
+
+impl MarkedText {
+    fn push(&mut self, c: char, marked: bool) {
+        // Collapse runs of whitespace, including the line breaks left by block tags.
+        if c.is_whitespace() {
+            if self.0.last().is_none_or(|(last, _)| last.is_whitespace()) {
+                return;
+            }
+            self.0.push((' ', false));
+        } else {
+            self.0.push((c, marked));
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.0
+            .iter()
+            .map(|(c, _)| c)
+            .collect::<String>()
+            .trim()
+            .to_string()
+    }
+
+    fn has_marks(&self) -> bool {
+        self.0.iter().any(|(_, marked)| *marked)
+    }
+
+    /// Marks every case-insensitive occurrence of `phrase`.
+    fn mark_phrase(&mut self, phrase: &str) {
+        let phrase = phrase.to_lowercase().chars().collect::<Vec<char>>();
+        if phrase.is_empty() || phrase.len() > self.0.len() {
+            return;
+        }
+
+        for start in 0..=self.0.len() - phrase.len() {
+            let matches = self.0[start..start + phrase.len()]
+                .iter()
+                .zip(&phrase)
+                .all(|((c, _), p)| c.to_lowercase().eq(p.to_lowercase()));
+            if matches {
+                for (_, marked) in &mut self.0[start..start + phrase.len()] {
+                    *marked = true;
+                }
+            }
+        }
+    }
+
+    /// Cuts the text down to about `max_len` characters around the first highlighted match.
+    fn snippet(&self, max_len: usize) -> MarkedText {
+        let text = self.0.as_slice();
+        let text = {
+            let start = text
+                .iter()
+                .position(|(c, _)| !c.is_whitespace())
+                .unwrap_or(0);
+            let end = text
+                .iter()
+                .rposition(|(c, _)| !c.is_whitespace())
+                .map_or(start, |end| end + 1);
+            &text[start..end]
+        };
+        if text.len() <= max_len {
+            return MarkedText(text.to_vec());
+        }
+
+        let first_mark = text.iter().position(|(_, marked)| *marked).unwrap_or(0);
+        let mut start = first_mark.saturating_sub(max_len / 3);
+        let end = (start + max_len).min(text.len());
+        start = end.saturating_sub(max_len);
+
+        // Do not cut words in half.
+        while start > 0 && start < end && !text[start - 1].0.is_whitespace() {
+            start += 1;
+        }
+        let mut cut_end = end;
+        while cut_end < text.len() && cut_end > start && !text[cut_end].0.is_whitespace() {
+            cut_end -= 1;
+        }
+        if cut_end == start {
+            cut_end = end;
+        }
+
+        let mut snippet = vec![];
+        if start > 0 {
+            snippet.push(('…', false));
+        }
+        snippet.extend_from_slice(&text[start..cut_end]);
+        if cut_end < text.len() {
+            snippet.push(('…', false));
+        }
+        MarkedText(snippet)
+    }
+
+    /// Renders the text with the highlighted matches styled for the terminal.
+    fn render(&self) -> String {
+        let mut rendered = String::new();
+        let mut run = String::new();
+        let mut run_marked = false;
+
+        for (c, marked) in &self.0 {
+            if *marked != run_marked && !run.is_empty() {
+                rendered.push_str(&if run_marked {
+                    highlight(&run)
+                } else {
+                    run.clone()
+                });
+                run.clear();
+            }
+            run_marked = *marked;
+            run.push(*c);
+        }
+        rendered.push_str(&if run_marked { highlight(&run) } else { run });
+
+        rendered
+    }
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => {
+            let code = entity.strip_prefix('#')?;
+            let code = match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+            char::from_u32(code)
+        }
+    }
+}
+
+/// Strips the tags from chunk HTML, keeping track of the `<mark>` tags the search highlighter
+/// wraps matches in.
+pub fn html_to_text(html: &str) -> MarkedText {
+    let mut text = MarkedText::default();
+    let mut marks = 0usize;
+    let mut chars = html.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '<' => {
+                let mut tag = String::new();
+                for c in chars.by_ref() {
+                    if c == '>' {
+                        break;
+                    }
+                    tag.push(c);
+                }
+
+                let closing = tag.starts_with('/');
+                let name = tag
+                    .trim_start_matches('/')
+                    .split(|c: char| c.is_whitespace() || c == '/')
+                    .next()
+                    .unwrap_or_default()
+                    .to_lowercase();
+                match name.as_str() {
+                    "mark" if closing => marks = marks.saturating_sub(1),
+                    "mark" => marks += 1,
+                    "br" | "p" | "div" | "li" | "tr" | "td" | "th" | "h1" | "h2" | "h3" | "h4"
+                    | "h5" | "h6" => text.push(' ', false),
+                    _ => {}
+                }
+            }
+            '&' => {
+                let mut entity = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == ';' || entity.len() > 8 {
+                        break;
+                    }
+                    entity.push(c);
+                    chars.next();
+                }
+                match (chars.peek() == Some(&';'), decode_entity(&entity)) {
+                    (true, Some(decoded)) => {
+                        chars.next();
+                        text.push(decoded, marks > 0);
+                    }
+                    _ => {
+                        text.push('&', marks > 0);
+                        entity.chars().for_each(|c| text.push(c, marks > 0));
+                    }
+                }
+            }
+            c => text.push(c, marks > 0),
+        }
+    }
+
+    text
+}
+
+/// Builds the `sort_options` of a search request from the sorting and re-ranking flags, or `None`
+/// when none were passed.
+pub fn sort_options(sort: &SortArgs) -> Result<Option<Value>, DefaultError> {
+    let mut options = serde_json::Map::new();
+
+    if let Some(rerank) = sort.rerank {
+        options.insert("sort_by".to_string(), json!({ "rerank_type": rerank }));
+    }
+    if let Some(sort_by) = &sort.sort_by {
+        let (field, direction) = match sort_by.rsplit_once(':') {
+            Some((field, direction @ ("asc" | "desc"))) => (field, direction),
+            _ => (sort_by.as_str(), "desc"),
+        };
+        options.insert(
+            "sort_by".to_string(),
+            json!({ "field": field, "direction": direction }),
+        );
+    }
+    if let Some(recency_bias) = sort.recency_bias {
+        if !(0.0..=1.0).contains(&recency_bias) {
+            return Err(DefaultError {
+                message: "--recency-bias must be between 0 and 1".to_string(),
+            });
+        }
+        options.insert("recency_bias".to_string(), json!(recency_bias));
+    }
+    if !sort.tag_weights.is_empty() {
+        let mut tag_weights = serde_json::Map::new();
+        for tag_weight in &sort.tag_weights {
+            let weight = tag_weight
+                .split_once('=')
+                .and_then(|(tag, weight)| Some((tag, weight.trim().parse::<f32>().ok()?)));
+            let Some((tag, weight)) = weight else {
+                return Err(DefaultError {
+                    message: format!("Invalid --tag-weight '{}', expected TAG=WEIGHT", tag_weight),
+                });
+            };
+            tag_weights.insert(tag.to_string(), json!(weight));
+        }
+        options.insert("tag_weights".to_string(), Value::Object(tag_weights));
+    }
+
+    Ok((!options.is_empty()).then_some(Value::Object(options)))
+}
+
+/// Runs a chunk search against a dataset. `body` is the search request payload.
+pub async fn search_dataset(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    body: &Value,
+) -> Result<Vec<SearchHit>, DefaultError> {
+    let response: Value = Rest::new(settings)
+        .dataset(dataset_id)
+        .post("/api/chunk/search", body)
+        .await?;
+
+    Ok(search_hits(&response))
+}
+
+/// Runs a chunk search against every dataset of the organization, a few datasets at a time, and
+/// merges the hits by score. Every dataset is asked for the hits up to the end of `page`, so the
+/// page can be taken from the merged hits. Datasets that cannot be searched are reported and
+/// skipped.
+async fn search_all_datasets(
+    settings: &TrieveConfiguration,
+    body: &Value,
+    page: u32,
+    page_size: u32,
+) -> Result<Vec<SearchHit>, DefaultError> {
+    let datasets = get_datasets_from_org(settings.clone()).await?;
+    let mut body = body.clone();
+    body["page"] = json!(1);
+    body["page_size"] = json!(page * page_size);
+
+    let mut hits = vec![];
+    let mut collect = |(name, result): (String, Result<Vec<SearchHit>, DefaultError>)| match result
+    {
+        Ok(found) => hits.extend(found.into_iter().map(|hit| SearchHit {
+            dataset: Some(name.clone()),
+            ..hit
+        })),
+        Err(e) => eprintln!("Could not search dataset {}: {}", name, e.message),
+    };
+
+    let mut in_flight = JoinSet::new();
+    for dataset in datasets {
+        while in_flight.len() >= MAX_CONCURRENT_BATCHES {
+            if let Some(finished) = in_flight.join_next().await {
+                collect(finished.unwrap());
+            }
+        }
+
+        let settings = settings.clone();
+        let body = body.clone();
+        in_flight.spawn(async move {
+            let dataset_id = dataset.dataset.id.to_string();
+            let result = search_dataset(&settings, &dataset_id, &body).await;
+            (dataset.dataset.name, result)
+        });
+    }
+    while let Some(finished) = in_flight.join_next().await {
+        collect(finished.unwrap());
+    }
+
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    Ok(hits
+        .into_iter()
+        .skip(((page - 1) * page_size) as usize)
+        .take(page_size as usize)
+        .collect())
+}
+
+/// Prints a search hit as a readable snippet with its score, link and tags, indented by `indent`
+/// spaces.
+pub fn print_hit(index: usize, hit: &SearchHit, snippet_length: usize, indent: usize) {
+    let chunk = &hit.chunk;
+    let mut text = html_to_text(chunk["chunk_html"].as_str().unwrap_or_default());
+    if !text.has_marks() {
+        for phrase in &hit.highlights {
+            text.mark_phrase(phrase);
+        }
+    }
+
+    let indent = " ".repeat(indent);
+    let id = hit.id();
+    println!(
+        "{}{} {} {}",
+        indent,
+        bold(&format!("{}.", index + 1)),
+        bold(&format!("{:.4}", hit.score)),
+        dim(id)
+    );
+    println!("{}   {}", indent, text.snippet(snippet_length).render());
+
+    if let Some(dataset) = &hit.dataset {
+        println!("{}   {} {}", indent, dim("dataset:"), dataset);
+    }
+    if let Some(url) = chunk["link"].as_str().filter(|url| !url.is_empty()) {
+        println!("{}   {} {}", indent, dim("link:"), link(url));
+    }
+    let tags = chunk["tag_set"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|tag| tag.as_str())
+        .filter(|tag| !tag.is_empty())
+        .collect::<Vec<&str>>();
+    if !tags.is_empty() {
+        println!("{}   {} {}", indent, dim("tags:"), tags.join(", "));
+    }
+    println!();
+}
+
+/// Prints search hits as snippets, or with `full` as the JSON of their chunks.
+pub fn print_hits(hits: &[SearchHit], full: bool, snippet_length: usize, indent: usize) {
+    for (index, hit) in hits.iter().enumerate() {
+        if full {
+            println!(
+                "{}{} {}",
+                " ".repeat(indent),
+                bold(&format!("{}.", index + 1)),
+                bold(&format!("{:.4}", hit.score))
+            );
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&hit.chunk).unwrap_or_default()
+            );
+            println!();
+        } else {
+            print_hit(index, hit, snippet_length, indent);
+        }
+    }
+}
+
+/// The query to run, prompted for when it was not passed.
+fn query_or_prompt(query: Option<String>) -> Result<String, DefaultError> {
+    match query {
+        Some(query) => Ok(query),
+        None => {
+            ensure_interactive("the search query")?;
+            Ok(inquire::Text::new("Search query:").prompt().unwrap())
+        }
+    }
+}
+
+pub async fn search(
+    settings: TrieveConfiguration,
+    search: SearchQuery,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let sort_options = sort_options(&search.options.sort)?;
+    let filters = Some(&search.options.filters)
+        .filter(|filters| !filters.is_empty())
+        .map(|filters| parse_filters(filters))
+        .transpose()?;
+    let query = query_or_prompt(search.query)?;
+    let page = search.options.page.max(1);
+    let body = json!({
+        "query": query,
+        "search_type": search.options.search_type,
+        "page": page,
+        "page_size": search.options.page_size,
+        "filters": filters,
+        "sort_options": sort_options,
+        "highlight_options": {
+            "highlight_results": true,
+        },
+    });
+
+    if search.options.all_datasets {
+        let hits = search_all_datasets(&settings, &body, page, search.options.page_size).await?;
+        return print_search_results(&query, &hits, &search.options);
+    }
+
+    let dataset_id = match dataset_or_default(search.options.dataset_id.clone(), &settings) {
+        Some(dataset_id) => dataset_id,
+        None => select_dataset_id(&settings, "Select a dataset to search:").await?,
+    };
+
+    if search.options.group_by {
+        let response: Value = Rest::new(&settings)
+            .dataset(&dataset_id)
+            .post(
+                "/api/chunk_group/group_oriented_search",
+                &json!({
+                    "query": query,
+                    "search_type": search.options.search_type,
+                    "page": page,
+                    "page_size": search.options.page_size,
+                    "group_size": search.options.group_size,
+                    "filters": filters,
+                    "sort_options": sort_options,
+                    "highlight_options": {
+                        "highlight_results": true,
+                    },
+                }),
+            )
+            .await?;
+        let groups = group_hits(&response);
+
+        if quiet() {
+            for group in &groups {
+                println!("{}", group.id());
+            }
+        } else if groups.is_empty() {
+            println!("No results for '{}'.", query);
+        }
+        fail_if_empty(search.options.fail_on_empty, groups.is_empty());
+        if quiet() {
+            return Ok(());
+        }
+
+        for (index, group) in groups.iter().enumerate() {
+            println!(
+                "{} {} {}",
+                bold(&format!("{}.", index + 1)),
+                bold(group.group["name"].as_str().unwrap_or("(unnamed group)")),
+                dim(group.id())
+            );
+            println!();
+            print_hits(
+                &group.hits,
+                search.options.full,
+                search.options.snippet_length,
+                3,
+            );
+        }
+
+        return Ok(());
+    }
+
+    let hits = search_dataset(&settings, &dataset_id, &body).await?;
+    print_search_results(&query, &hits, &search.options)
+}
+
+/// Prints the hits of a search: their IDs in quiet mode, prefixed by their dataset when several
+/// datasets were searched, and otherwise as snippets.
+fn print_search_results(
+    query: &str,
+    hits: &[SearchHit],
+    options: &SearchOptions,
+) -> Result<(), DefaultError> {
+    if quiet() {
+        for hit in hits {
+            match &hit.dataset {
+                Some(dataset) => println!("{}\t{}", dataset, hit.id()),
+                None => println!("{}", hit.id()),
+            }
+        }
+    } else if hits.is_empty() {
+        println!("No results for '{}'.", query);
+    } else {
+        print_hits(hits, options.full, options.snippet_length, 0);
+    }
+    fail_if_empty(options.fail_on_empty, hits.is_empty());
+
+    Ok(())
+}
+
+/// Searches the chunks of a single group.
+pub async fn search_group(
+    settings: TrieveConfiguration,
+    search: GroupSearch,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let sort_options = sort_options(&search.sort)?;
+    let query = query_or_prompt(search.query)?;
+    let dataset_id = match dataset_or_default(search.dataset_id, &settings) {
+        Some(dataset_id) => dataset_id,
+        None => select_dataset_id(&settings, "Select the dataset of the group:").await?,
+    };
+
+    let response: Value = Rest::new(&settings)
+        .dataset(&dataset_id)
+        .post(
+            "/api/chunk_group/search",
+            &json!({
+                "query": query,
+                "group_id": search.group_id,
+                "search_type": search.search_type,
+                "page_size": search.page_size,
+                "sort_options": sort_options,
+                "highlight_options": {
+                    "highlight_results": true,
+                },
+            }),
+        )
+        .await?;
+    // v1 returns the chunks of the group as `bookmarks`.
+    let hits = match response["bookmarks"].as_array() {
+        Some(bookmarks) => search_hits(&json!({ "score_chunks": bookmarks })),
+        None => search_hits(&response),
+    };
+
+    if quiet() {
+        for hit in &hits {
+            println!("{}", hit.id());
+        }
+    } else if hits.is_empty() {
+        println!("No results for '{}' in group {}.", query, search.group_id);
+    } else {
+        print_hits(&hits, search.full, search.snippet_length, 0);
+    }
+    fail_if_empty(search.fail_on_empty, hits.is_empty());
+
+    Ok(())
+}
+
+/// Reads one query per line, skipping blank lines and `#` comments.
+fn read_query_file(path: &Path) -> Result<Vec<String>, DefaultError> {
+    let queries = fs::read_to_string(path).map_err(|e| DefaultError {
+        message: format!("Could not read {}: {}", path.display(), e),
+    })?;
+
+    Ok(queries
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// A short, single line label for a hit in the comparison table.
+fn compare_label(hit: &SearchHit, shared: &HashSet<String>) -> String {
+    let mut text = html_to_text(hit.chunk["chunk_html"].as_str().unwrap_or_default()).text();
+    if text.chars().count() > 60 {
+        text = format!("{}…", text.chars().take(60).collect::<String>());
+    }
+
+    format!(
+        "{}{} ({:.3})\n{}",
+        if shared.contains(hit.id()) { "= " } else { "" },
+        hit.id(),
+        hit.score,
+        text
+    )
+}
+
+pub async fn compare(
+    settings: TrieveConfiguration,
+    compare: CompareSearch,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let queries = read_query_file(&compare.query_file)?;
+    if queries.is_empty() {
+        return Err(DefaultError {
+            message: format!("{} contains no queries", compare.query_file.display()),
+        });
+    }
+
+    let mut total_overlap = 0.0;
+    let mut same_top_hit = 0;
+
+    for query in &queries {
+        let body = json!({
+            "query": query,
+            "search_type": compare.search_type,
+            "page_size": compare.page_size,
+        });
+        let hits_a = search_dataset(&settings, &compare.dataset_a, &body).await?;
+        let hits_b = search_dataset(&settings, &compare.dataset_b, &body).await?;
+
+        let ids_a = hits_a
+            .iter()
+            .map(|hit| hit.id().to_string())
+            .collect::<HashSet<String>>();
+        let ids_b = hits_b
+            .iter()
+            .map(|hit| hit.id().to_string())
+            .collect::<HashSet<String>>();
+        let shared = ids_a
+            .intersection(&ids_b)
+            .cloned()
+            .collect::<HashSet<String>>();
+        let union = ids_a.union(&ids_b).count();
+        let overlap = if union == 0 {
+            1.0
+        } else {
+            shared.len() as f64 / union as f64
+        };
+        total_overlap += overlap;
+        if hits_a.first().map(|hit| hit.id()) == hits_b.first().map(|hit| hit.id()) {
+            same_top_hit += 1;
+        }
+
+        let mut builder = Builder::default();
+        builder.push_record([
+            "#".to_string(),
+            format!("A: {}", compare.dataset_a),
+            format!("B: {}", compare.dataset_b),
+        ]);
+        for rank in 0..hits_a.len().max(hits_b.len()) {
+            builder.push_record([
+                (rank + 1).to_string(),
+                hits_a
+                    .get(rank)
+                    .map(|hit| compare_label(hit, &shared))
+                    .unwrap_or_default(),
+                hits_b
+                    .get(rank)
+                    .map(|hit| compare_label(hit, &shared))
+                    .unwrap_or_default(),
+            ]);
+        }
+
+        println!("{} {}", bold("Query:"), query);
+        println!("{}", builder.build().with(Style::rounded()));
+        println!(
+            "{} {} of {} results shared, overlap {:.0}%",
+            dim("Overlap:"),
+            shared.len(),
+            union,
+            overlap * 100.0
+        );
+        println!();
+    }
+
+    println!("{}", bold("Summary"));
+    println!("Queries:            {}", queries.len());
+    println!(
+        "Average overlap:    {:.0}%",
+        total_overlap * 100.0 / queries.len() as f64
+    );
+    println!("Same top result:    {} of {}", same_top_hit, queries.len());
+    println!("Results marked with = appear in both datasets.");
+
+    Ok(())
+}
+
+/// One result of one query in the file written by `search batch`.
+#[derive(Serialize)]
+struct BatchResult {
+    query: String,
+    rank: usize,
+    chunk_id: String,
+    tracking_id: String,
+    score: f64,
+    link: String,
+}
+
+/// Runs every query of a file against a dataset and writes the top results as flat rows for
+/// offline analysis.
+pub async fn batch(settings: TrieveConfiguration, batch: BatchSearch) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let queries = read_query_file(&batch.query_file)?;
+    if queries.is_empty() {
+        return Err(DefaultError {
+            message: format!("{} contains no queries", batch.query_file.display()),
+        });
+    }
+
+    let out = batch.out.display().to_string();
+    let format = match batch.format {
+        Some(format) => format,
+        None if out.ends_with(".csv") => InputFormat::Csv,
+        None if out.ends_with(".json") => InputFormat::Json,
+        None if out.ends_with(".jsonl") || out.ends_with(".ndjson") => InputFormat::Jsonl,
+        None => {
+            return Err(DefaultError {
+                message: format!(
+                    "Could not infer the format of {}, pass --format csv, json or jsonl",
+                    out
+                ),
+            })
+        }
+    };
+    let sort_options = sort_options(&batch.sort)?;
+    let filters = Some(&batch.filters)
+        .filter(|filters| !filters.is_empty())
+        .map(|filters| parse_filters(filters))
+        .transpose()?;
+    let dataset_id = match dataset_or_default(batch.dataset_id, &settings) {
+        Some(dataset_id) => dataset_id,
+        None => select_dataset_id(&settings, "Select a dataset to search:").await?,
+    };
+
+    let mut results = vec![];
+    for (index, query) in queries.iter().enumerate() {
+        let hits = search_dataset(
+            &settings,
+            &dataset_id,
+            &json!({
+                "query": query,
+                "search_type": batch.search_type,
+                "page_size": batch.page_size,
+                "filters": filters,
+                "sort_options": sort_options,
+            }),
+        )
+        .await?;
+
+        if !quiet() {
+            println!(
+                "[{}/{}] {} results for '{}'",
+                index + 1,
+                queries.len(),
+                hits.len(),
+                query
+            );
+        }
+
+        results.extend(hits.iter().enumerate().map(|(rank, hit)| {
+            BatchResult {
+                query: query.clone(),
+                rank: rank + 1,
+                chunk_id: hit.chunk["id"].as_str().unwrap_or_default().to_string(),
+                tracking_id: hit.chunk["tracking_id"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                score: hit.score,
+                link: hit.chunk["link"].as_str().unwrap_or_default().to_string(),
+            }
+        }));
+    }
+
+    write_batch_results(&batch.out, format, &results)?;
+
+    if quiet() {
+        println!("{}", out);
+    } else {
+        println!(
+            "Wrote {} results for {} queries to {}",
+            results.len(),
+            queries.len(),
+            out
+        );
+    }
+
+    Ok(())
+}
+
+fn write_batch_results(
+    path: &Path,
+    format: InputFormat,
+    results: &[BatchResult],
+) -> Result<(), DefaultError> {
+    let file = File::create(path).map_err(|e| DefaultError {
+        message: format!("Could not create {}: {}", path.display(), e),
+    })?;
+    let to_error = |e: &dyn std::fmt::Display| DefaultError {
+        message: format!("Could not write {}: {}", path.display(), e),
+    };
+
+    match format {
+        InputFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(file);
+            for result in results {
+                writer.serialize(result).map_err(|e| to_error(&e))?;
+            }
+            writer.flush().map_err(|e| to_error(&e))?;
+        }
+        InputFormat::Json => {
+            serde_json::to_writer_pretty(BufWriter::new(file), results)
+                .map_err(|e| to_error(&e))?;
+        }
+        InputFormat::Jsonl => {
+            let mut writer = BufWriter::new(file);
+            for result in results {
+                serde_json::to_writer(&mut writer, result).map_err(|e| to_error(&e))?;
+                writeln!(writer).map_err(|e| to_error(&e))?;
+            }
+            writer.flush().map_err(|e| to_error(&e))?;
+        }
+    }
+
+    Ok(())
+}
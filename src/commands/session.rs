@@ -0,0 +1,121 @@
+use std::{
+    io::{stdin, IsTerminal},
+    sync::OnceLock,
+};
+
+use inquire::Confirm;
+use reqwest::{Method, StatusCode};
+use tokio::sync::Mutex;
+
+use super::{
+    configure::{relogin, TrieveConfiguration},
+    dataset::DefaultError,
+    http,
+    i18n::tr_args,
+    output::{self, quiet},
+};
+use crate::cli::OutputFormat;
+
+/// The profile the current command runs with.
+struct Session {
+    profile_name: String,
+    settings: TrieveConfiguration,
+}
+
+static SESSION: OnceLock<Session> = OnceLock::new();
+
+/// Held while a rejected request is being handled, so concurrent requests failing at the same time
+/// do not prompt more than once.
+static HANDLING: Mutex<()> = Mutex::const_new(());
+
+/// The report of the invalid credentials, so requests rejected at the same time fail with it too
+/// without checking the credentials again.
+static REJECTED: OnceLock<String> = OnceLock::new();
+
+tokio::task_local! {
+    /// Set while logging in again, whose own requests must not be handled again.
+    static RELOGGING_IN: ();
+}
+
+/// Records the profile the current command runs with, so rejected credentials can be reported
+/// against it and refreshed.
+pub fn init(profile_name: String, settings: TrieveConfiguration) {
+    let _ = SESSION.set(Session {
+        profile_name,
+        settings,
+    });
+}
+
+/// Whether the stored API key itself is rejected, as opposed to it lacking access to a single
+/// organization or dataset.
+async fn credentials_invalid(settings: &TrieveConfiguration) -> bool {
+    match http::api_request(settings, Method::GET, "/api/auth/me")
+        .send()
+        .await
+    {
+        Ok(response) => matches!(
+            response.status(),
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+        ),
+        Err(_) => false,
+    }
+}
+
+/// Fails the request rejected with `status` with the report of the invalid credentials, which
+/// makes the CLI exit with `EXIT_UNAUTHORIZED`.
+fn unauthorized(status: StatusCode, message: String) -> DefaultError {
+    output::record_api_error(status, &[]);
+    output::fail(output::EXIT_UNAUTHORIZED, "unauthorized", message)
+}
+
+/// Called for every `401` and `403` response. When the API key of the profile has been revoked or
+/// has expired, it offers to log in again, keeping the rest of the profile, and fails the request
+/// with what to do next. Otherwise the error is handled by the command as usual.
+pub async fn on_unauthorized(status: StatusCode) -> Result<(), DefaultError> {
+    if !matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+        return Ok(());
+    }
+    let Some(session) = SESSION.get() else {
+        return Ok(());
+    };
+    if RELOGGING_IN.try_with(|_| ()).is_ok() {
+        return Ok(());
+    }
+
+    let _handling = HANDLING.lock().await;
+    if let Some(message) = REJECTED.get() {
+        return Err(unauthorized(status, message.clone()));
+    }
+    if !credentials_invalid(&session.settings).await {
+        return Ok(());
+    }
+
+    let profile = [("profile", session.profile_name.clone())];
+    let invalid = tr_args("credentials-invalid", &profile);
+    let login_again =
+        matches!(output::format(), OutputFormat::Text) && stdin().is_terminal() && !quiet() && {
+            eprintln!("{}", invalid);
+            Confirm::new("Would you like to log in again now?")
+                .with_default(true)
+                .prompt()
+                .unwrap_or(false)
+        };
+
+    let message = if login_again {
+        RELOGGING_IN
+            .scope(
+                (),
+                Box::pin(relogin(&session.profile_name, &session.settings)),
+            )
+            .await?;
+        tr_args("credentials-updated", &profile)
+    } else {
+        format!(
+            "{}\n{}",
+            invalid,
+            tr_args("credentials-login-hint", &profile)
+        )
+    };
+    let _ = REJECTED.set(message.clone());
+    Err(unauthorized(status, message))
+}
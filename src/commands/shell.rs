@@ -0,0 +1,266 @@
+use rustyline::{
+    completion::{Completer, Pair},
+    error::ReadlineError,
+    highlight::Highlighter,
+    hint::Hinter,
+    history::DefaultHistory,
+    validate::Validator,
+    Context, Editor, Helper,
+};
+use serde_json::json;
+
+use super::{
+    alias::resolve_dataset,
+    chunk::fetch_chunk,
+    configure::{config_subdir, require_login, TrieveConfiguration},
+    dataset::{get_datasets_from_org, DefaultError},
+    search::{print_hits, search_dataset},
+};
+
+/// The commands of the shell, with their usage and description as shown by `help`.
+const COMMANDS: [(&str, &str, &str); 6] = [
+    ("search", "search <QUERY>", "Search the current dataset"),
+    ("get", "get <ID>", "Print a chunk by its ID or tracking ID"),
+    (
+        "use",
+        "use dataset <ID|NAME|ALIAS>",
+        "Switch to another dataset",
+    ),
+    (
+        "datasets",
+        "datasets",
+        "List the datasets of the organization",
+    ),
+    ("help", "help", "Show this help"),
+    ("exit", "exit", "Leave the shell (or press Ctrl-D)"),
+];
+
+/// Completes command names, and dataset IDs and names after `use dataset`.
+struct ShellHelper {
+    /// The ID and name of every dataset of the organization, loaded when the shell starts.
+    datasets: Vec<(String, String)>,
+}
+
+impl Helper for ShellHelper {}
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+impl Highlighter for ShellHelper {}
+impl Validator for ShellHelper {}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        let start = line.rfind(' ').map_or(0, |i| i + 1);
+        let word = &line[start..];
+        let words = line[..start].split_whitespace().collect::<Vec<&str>>();
+
+        let candidates = match words.as_slice() {
+            [] => COMMANDS
+                .iter()
+                .filter(|(name, _, _)| name.starts_with(word))
+                .map(|(name, _, _)| Pair {
+                    display: name.to_string(),
+                    replacement: format!("{} ", name),
+                })
+                .collect(),
+            ["use"] if "dataset".starts_with(word) => vec![Pair {
+                display: "dataset".to_string(),
+                replacement: "dataset ".to_string(),
+            }],
+            ["use", "dataset"] => self
+                .datasets
+                .iter()
+                .filter(|(id, name)| id.starts_with(word) || name.starts_with(word))
+                .map(|(id, name)| Pair {
+                    display: format!("{} ({})", name, id),
+                    replacement: id.clone(),
+                })
+                .collect(),
+            _ => vec![],
+        };
+
+        Ok((start, candidates))
+    }
+}
+
+/// The context commands run in, kept between commands for the whole session.
+struct Shell {
+    settings: TrieveConfiguration,
+    dataset_id: Option<String>,
+}
+
+impl Shell {
+    fn prompt(&self) -> String {
+        match &self.dataset_id {
+            Some(dataset_id) => format!("trieve ({})> ", dataset_id),
+            None => "trieve> ".to_string(),
+        }
+    }
+
+    fn dataset_id(&self) -> Result<&str, DefaultError> {
+        self.dataset_id.as_deref().ok_or_else(|| DefaultError {
+            message: "No dataset selected. Run `use dataset <ID>` first.".to_string(),
+        })
+    }
+
+    /// Runs one line of input and returns whether the shell should keep running.
+    async fn run(&mut self, line: &str, helper: &ShellHelper) -> Result<bool, DefaultError> {
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+
+        match command {
+            "search" if !rest.is_empty() => {
+                let hits = search_dataset(
+                    &self.settings,
+                    self.dataset_id()?,
+                    &json!({
+                        "query": rest,
+                        "search_type": "hybrid",
+                        "page_size": 10,
+                        "highlight_options": {
+                            "highlight_results": true,
+                        },
+                    }),
+                )
+                .await?;
+                if hits.is_empty() {
+                    println!("No results for '{}'.", rest);
+                }
+                print_hits(&hits, false, 300, 0);
+            }
+            "get" if !rest.is_empty() => {
+                let chunk = fetch_chunk(&self.settings, self.dataset_id()?, rest).await?;
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&chunk).unwrap_or_default()
+                );
+            }
+            "search" | "get" => {
+                let usage = COMMANDS
+                    .iter()
+                    .find(|(name, _, _)| *name == command)
+                    .map(|(_, usage, _)| *usage)
+                    .unwrap_or_default();
+                return Err(DefaultError {
+                    message: format!("Usage: {}", usage),
+                });
+            }
+            "use" => {
+                let Some(dataset) = rest.strip_prefix("dataset").map(str::trim) else {
+                    return Err(DefaultError {
+                        message: "Usage: use dataset <ID|NAME|ALIAS>".to_string(),
+                    });
+                };
+                let dataset = &resolve_dataset(&self.settings, dataset);
+                let dataset_id = helper
+                    .datasets
+                    .iter()
+                    .find(|(id, name)| id == dataset || name == dataset)
+                    .map(|(id, _)| id.clone())
+                    .or_else(|| {
+                        dataset
+                            .parse::<uuid::Uuid>()
+                            .ok()
+                            .map(|_| dataset.to_string())
+                    })
+                    .ok_or_else(|| DefaultError {
+                        message: format!("Dataset '{}' not found", dataset),
+                    })?;
+                self.dataset_id = Some(dataset_id);
+            }
+            "datasets" => {
+                for (id, name) in &helper.datasets {
+                    println!("{}  {}", id, name);
+                }
+            }
+            "help" => {
+                for (_, usage, description) in COMMANDS {
+                    println!("  {:<24}{}", usage, description);
+                }
+            }
+            "exit" | "quit" => return Ok(false),
+            _ => {
+                return Err(DefaultError {
+                    message: format!("Unknown command '{}'. Type `help` for a list.", line),
+                })
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+/// An interactive shell that keeps the profile and the current dataset in memory between
+/// commands, with history and tab completion.
+pub async fn shell(settings: TrieveConfiguration) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let datasets = get_datasets_from_org(settings.clone())
+        .await?
+        .into_iter()
+        .map(|dataset| (dataset.dataset.id.to_string(), dataset.dataset.name))
+        .collect::<Vec<(String, String)>>();
+
+    let mut editor: Editor<ShellHelper, DefaultHistory> =
+        Editor::new().map_err(|e| DefaultError {
+            message: e.to_string(),
+        })?;
+    editor.set_helper(Some(ShellHelper { datasets }));
+
+    let history = config_subdir("shell")
+        .map(|dir| dir.join("history.txt"))
+        .ok();
+    if let Some(history) = &history {
+        let _ = editor.load_history(history);
+    }
+
+    let mut shell = Shell {
+        dataset_id: settings
+            .default_dataset_id
+            .as_ref()
+            .map(|dataset_id| resolve_dataset(&settings, dataset_id)),
+        settings,
+    };
+    println!("Trieve shell. Type `help` for the commands, `exit` or Ctrl-D to leave.");
+
+    loop {
+        let line = match editor.readline(&shell.prompt()) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                return Err(DefaultError {
+                    message: e.to_string(),
+                })
+            }
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+
+        let Some(helper) = editor.helper() else {
+            break;
+        };
+        match shell.run(line, helper).await {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) => eprintln!("Error: {}", e.message),
+        }
+    }
+
+    if let Some(history) = &history {
+        let _ = editor.save_history(history);
+    }
+
+    Ok(())
+}
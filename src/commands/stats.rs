@@ -0,0 +1,303 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::{stdout, IsTerminal, Write},
+    time::{Duration, Instant},
+};
+
+use chrono::Local;
+use serde_json::Value;
+use tabled::{builder::Builder, settings::Style};
+
+use crate::cli::{DatasetStats, DatasetUsage};
+
+use super::{
+    configure::{require_login, TrieveConfiguration},
+    dataset::{
+        dataset_or_default, get_datasets_from_org, select_dataset_id, DatasetAndUsageDTO,
+        DefaultError,
+    },
+    export::scroll_chunks,
+    output::{ensure_interactive, fail_if_empty, quiet},
+    picker::pick,
+    rest::Rest,
+    style::{bold, dim},
+};
+
+/// The number of groups and files the listing endpoints return per page.
+const LISTING_PAGE_SIZE: u64 = 10;
+const TOP_TAGS: usize = 10;
+
+/// Counts the items of a paginated listing, such as `/api/dataset/groups/{id}`, by reading only
+/// the first and the last page.
+async fn count_listing(rest: &Rest<'_>, path: &str, items_key: &str) -> Result<u64, DefaultError> {
+    let first: Value = rest.get(&format!("{}/1", path)).await?;
+    let total_pages = first["total_pages"].as_u64().unwrap_or(1).max(1);
+    let last = if total_pages == 1 {
+        first
+    } else {
+        rest.get(&format!("{}/{}", path, total_pages)).await?
+    };
+
+    let last_page_len = last[items_key].as_array().map(Vec::len).unwrap_or(0) as u64;
+    Ok((total_pages - 1) * LISTING_PAGE_SIZE + last_page_len)
+}
+
+/// Aggregates computed from a sample of the chunks in a dataset.
+#[derive(Default)]
+struct ChunkSample {
+    chunks: usize,
+    total_length: usize,
+    with_link: usize,
+    with_metadata: usize,
+    tags: HashMap<String, usize>,
+}
+
+impl ChunkSample {
+    fn add(&mut self, chunk: &Value) {
+        self.chunks += 1;
+        self.total_length += chunk["chunk_html"]
+            .as_str()
+            .map(|html| html.chars().count())
+            .unwrap_or(0);
+        if chunk["link"].as_str().is_some_and(|link| !link.is_empty()) {
+            self.with_link += 1;
+        }
+        if chunk["metadata"]
+            .as_object()
+            .is_some_and(|metadata| !metadata.is_empty())
+        {
+            self.with_metadata += 1;
+        }
+        for tag in chunk["tag_set"].as_array().into_iter().flatten() {
+            if let Some(tag) = tag.as_str().filter(|tag| !tag.is_empty()) {
+                *self.tags.entry(tag.to_string()).or_default() += 1;
+            }
+        }
+    }
+
+    fn percentage(&self, count: usize) -> String {
+        if self.chunks == 0 {
+            return "-".to_string();
+        }
+        format!("{:.1}%", count as f64 * 100.0 / self.chunks as f64)
+    }
+}
+
+/// Scrolls through the dataset until `size` chunks have been sampled or the dataset ends.
+async fn sample_chunks(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    size: usize,
+) -> Result<ChunkSample, DefaultError> {
+    let mut sample = ChunkSample::default();
+    let mut seen = HashSet::new();
+    let mut offset: Option<String> = None;
+
+    while sample.chunks < size {
+        let chunks = scroll_chunks(settings, dataset_id, offset.as_deref()).await?;
+        let mut new_chunks = 0;
+
+        for chunk in &chunks {
+            let id = chunk["id"].as_str().unwrap_or_default().to_string();
+            // The offset chunk may be returned again at the start of the next page.
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            new_chunks += 1;
+            sample.add(chunk);
+            offset = Some(id);
+            if sample.chunks >= size {
+                break;
+            }
+        }
+
+        if new_chunks == 0 {
+            break;
+        }
+    }
+
+    Ok(sample)
+}
+
+pub async fn dataset_stats(
+    settings: TrieveConfiguration,
+    stats: DatasetStats,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let datasets = get_datasets_from_org(settings.clone()).await?;
+    let dataset = match dataset_or_default(stats.dataset_id, &settings) {
+        Some(dataset_id) => datasets
+            .into_iter()
+            .find(|d| d.dataset.id.to_string() == dataset_id)
+            .ok_or_else(|| DefaultError {
+                message: format!(
+                    "Dataset {} not found in organization {}",
+                    dataset_id, settings.organization_id
+                ),
+            })?,
+        None => {
+            let datasets = datasets
+                .into_iter()
+                .map(DatasetAndUsageDTO)
+                .collect::<Vec<_>>();
+
+            ensure_interactive("--dataset-id")?;
+            pick("Select a dataset:", "datasets", datasets)?.0
+        }
+    };
+    let dataset_id = dataset.dataset.id.to_string();
+    let chunk_count = dataset.dataset_usage.chunk_count;
+
+    let rest = Rest::new(&settings).dataset(&dataset_id);
+    let groups = count_listing(
+        &rest,
+        &format!("/api/dataset/groups/{}", dataset_id),
+        "groups",
+    )
+    .await?;
+    let files = count_listing(
+        &rest,
+        &format!("/api/dataset/files/{}", dataset_id),
+        "file_and_group_ids",
+    )
+    .await?;
+    let sample = sample_chunks(&settings, &dataset_id, stats.sample_size).await?;
+
+    // Chunks are counted when they are queued, but only show up in a scroll once indexed.
+    let index_status = match (chunk_count, sample.chunks) {
+        (0, 0) => "empty",
+        (_, 0) => "indexing",
+        _ if (sample.chunks as u64) < (chunk_count as u64).min(stats.sample_size as u64) => {
+            "partially indexed"
+        }
+        _ => "ready",
+    };
+
+    let mut builder = Builder::default();
+    builder.push_record(["Stat", "Value"]);
+    builder.push_record(["Dataset".to_string(), dataset.dataset.name.clone()]);
+    builder.push_record(["Chunks".to_string(), chunk_count.to_string()]);
+    builder.push_record(["Groups".to_string(), groups.to_string()]);
+    builder.push_record(["Files".to_string(), files.to_string()]);
+    builder.push_record(["Index status".to_string(), index_status.to_string()]);
+    builder.push_record(["Sampled chunks".to_string(), sample.chunks.to_string()]);
+    builder.push_record([
+        "Average chunk length".to_string(),
+        sample
+            .total_length
+            .checked_div(sample.chunks)
+            .map(|length| format!("{} characters", length))
+            .unwrap_or("-".to_string()),
+    ]);
+    builder.push_record([
+        "Chunks with a link".to_string(),
+        sample.percentage(sample.with_link),
+    ]);
+    builder.push_record([
+        "Chunks with metadata".to_string(),
+        sample.percentage(sample.with_metadata),
+    ]);
+    println!("{}", builder.build().with(Style::rounded()));
+
+    if !sample.tags.is_empty() {
+        let mut tags = sample.tags.iter().collect::<Vec<_>>();
+        tags.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+        let mut builder = Builder::default();
+        builder.push_record(["Tag", "Chunks", "Share"]);
+        for (tag, count) in tags.iter().take(TOP_TAGS) {
+            builder.push_record([
+                tag.to_string(),
+                count.to_string(),
+                sample.percentage(**count),
+            ]);
+        }
+
+        println!();
+        println!(
+            "Top tags ({} distinct in the sample of {} chunks):",
+            tags.len(),
+            sample.chunks
+        );
+        println!("{}", builder.build().with(Style::rounded()));
+    }
+
+    Ok(())
+}
+
+async fn chunk_count(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+) -> Result<u64, DefaultError> {
+    let usage: Value = Rest::new(settings)
+        .dataset(dataset_id)
+        .get(&format!("/api/dataset/usage/{}", dataset_id))
+        .await?;
+    Ok(usage["chunk_count"].as_u64().unwrap_or(0))
+}
+
+/// Prints the chunk count of a dataset, or with `--watch` polls it and keeps a single line up to
+/// date with the count, the chunks added since watching started and the current rate. The API
+/// does not report how many chunks are still queued, so the rate is what shows whether a
+/// background ingestion is still being indexed.
+pub async fn dataset_usage(
+    settings: TrieveConfiguration,
+    usage: DatasetUsage,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = match dataset_or_default(usage.dataset_id, &settings) {
+        Some(dataset_id) => dataset_id,
+        None => select_dataset_id(&settings, "Select a dataset:").await?,
+    };
+
+    let first = chunk_count(&settings, &dataset_id).await?;
+    if !usage.watch {
+        if quiet() {
+            println!("{}", first);
+        } else {
+            println!("{} {}", dim("Chunks:"), first);
+        }
+        fail_if_empty(usage.fail_on_empty, first == 0);
+        return Ok(());
+    }
+
+    // On a terminal the line is redrawn in place, otherwise every poll is printed on a line.
+    let redraw = stdout().is_terminal() && !quiet();
+    let interval = Duration::from_secs(usage.interval.max(1));
+    let (mut last, mut last_time) = (first, Instant::now());
+    let mut result: Result<u64, DefaultError> = Ok(first);
+
+    loop {
+        let time = Local::now().format("%H:%M:%S").to_string();
+        let line = match result {
+            Ok(count) if quiet() => format!("{}\t{}", time, count),
+            Ok(count) => {
+                let rate = count.saturating_sub(last) as f64 / last_time.elapsed().as_secs_f64();
+                let state = if count > last { "indexing" } else { "idle" };
+                last = count;
+                last_time = Instant::now();
+                format!(
+                    "{} {} {} {:.1}/s {}",
+                    dim(&time),
+                    bold(&format!("{} chunks", count)),
+                    dim(&format!("(+{} since start)", count.saturating_sub(first))),
+                    rate,
+                    dim(state)
+                )
+            }
+            Err(e) => format!("{} {}", dim(&time), e.message),
+        };
+
+        if redraw {
+            print!("\r\x1b[2K{}", line);
+            let _ = stdout().flush();
+        } else {
+            println!("{}", line);
+        }
+
+        tokio::time::sleep(interval).await;
+        result = chunk_count(&settings, &dataset_id).await;
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    env,
+    io::{stderr, stdout, IsTerminal},
+    sync::OnceLock,
+};
+
+static NO_COLOR: OnceLock<bool> = OnceLock::new();
+static COLORS: OnceLock<bool> = OnceLock::new();
+static STDERR_COLORS: OnceLock<bool> = OnceLock::new();
+
+/// Turns styling off for the whole run when `--no-color` was passed.
+pub fn init(no_color: bool) {
+    let _ = NO_COLOR.set(no_color);
+}
+
+/// Whether styling is allowed at all: not with `--no-color`, nor when `NO_COLOR` is set to a
+/// non-empty value (https://no-color.org).
+fn colors_allowed() -> bool {
+    !NO_COLOR.get().copied().unwrap_or_default()
+        && env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+}
+
+/// Whether ANSI styling is used on stdout. It is off when stdout is not a terminal, so piped
+/// output stays plain.
+pub fn colors_enabled() -> bool {
+    *COLORS.get_or_init(|| colors_allowed() && stdout().is_terminal())
+}
+
+/// Like `colors_enabled`, for messages printed to stderr.
+fn stderr_colors_enabled() -> bool {
+    *STDERR_COLORS.get_or_init(|| colors_allowed() && stderr().is_terminal())
+}
+
+/// Only the 16 basic colors are used, which terminal themes remap, so the output stays readable
+/// on light and dark backgrounds.
+fn paint_if(enabled: bool, text: &str, code: &str) -> String {
+    if enabled {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text.to_string()
+    }
+}
+
+fn paint(text: &str, code: &str) -> String {
+    paint_if(colors_enabled(), text, code)
+}
+
+pub fn bold(text: &str) -> String {
+    paint(text, "1")
+}
+
+pub fn dim(text: &str) -> String {
+    paint(text, "2")
+}
+
+/// Bold yellow, used for the parts of a result that matched the query.
+pub fn highlight(text: &str) -> String {
+    paint(text, "1;33")
+}
+
+pub fn link(text: &str) -> String {
+    paint(text, "4;36")
+}
+
+/// Green, used for added lines in diffs.
+pub fn added(text: &str) -> String {
+    paint(text, "32")
+}
+
+/// Red, used for removed lines in diffs.
+pub fn removed(text: &str) -> String {
+    paint(text, "31")
+}
+
+/// Cyan, used for the `@@` headers in diffs.
+pub fn hunk(text: &str) -> String {
+    paint(text, "36")
+}
+
+/// Green, used for confirmations that a change was made.
+pub fn success(text: &str) -> String {
+    paint(text, "32")
+}
+
+/// Dim, used for UUIDs next to the names they belong to.
+pub fn id(text: &str) -> String {
+    paint(text, "2")
+}
+
+/// Yellow, used for warnings on stderr.
+pub fn warning(text: &str) -> String {
+    paint_if(stderr_colors_enabled(), text, "33")
+}
+
+/// Bold red, used for errors on stderr.
+pub fn error(text: &str) -> String {
+    paint_if(stderr_colors_enabled(), text, "1;31")
+}
@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use serde_json::{json, Value};
+
+use crate::cli::{SuggestQueries, SuggestionType};
+
+use super::{
+    configure::{require_login, TrieveConfiguration},
+    dataset::{require_dataset, DefaultError},
+    output::{fail_if_empty, quiet},
+    rest::Rest,
+};
+
+/// The most queries asked for in one request, as the server generates them in a single LLM call.
+const QUERIES_PER_REQUEST: usize = 10;
+
+/// Asks for queries until `count` different ones were generated, or a request brings no new ones.
+async fn generate_queries(
+    rest: &Rest<'_>,
+    suggest: &SuggestQueries,
+) -> Result<Vec<String>, DefaultError> {
+    let suggestion_type = match suggest.suggestion_type {
+        SuggestionType::Question => "question",
+        SuggestionType::Keyword => "keyword",
+        SuggestionType::Semantic => "semantic",
+    };
+    let mut seen = HashSet::new();
+    let mut queries = vec![];
+
+    while queries.len() < suggest.count {
+        let response: Value = rest
+            .post(
+                "/api/chunk/suggestions",
+                &json!({
+                    "query": suggest.query,
+                    "context": suggest.context,
+                    "suggestion_type": suggestion_type,
+                    "suggestions_to_create": (suggest.count - queries.len()).min(QUERIES_PER_REQUEST),
+                }),
+            )
+            .await?;
+
+        let before = queries.len();
+        for query in response["queries"].as_array().into_iter().flatten() {
+            let Some(query) = query.as_str().map(str::trim).filter(|q| !q.is_empty()) else {
+                continue;
+            };
+            if queries.len() < suggest.count && seen.insert(query.to_lowercase()) {
+                queries.push(query.to_string());
+            }
+        }
+        if queries.len() == before {
+            break;
+        }
+    }
+
+    Ok(queries)
+}
+
+pub async fn suggest_queries(
+    settings: TrieveConfiguration,
+    suggest: SuggestQueries,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(suggest.dataset_id.clone(), &settings)?;
+    let rest = Rest::new(&settings).dataset(&dataset_id);
+    let queries = generate_queries(&rest, &suggest).await?;
+
+    if quiet() {
+        for query in &queries {
+            println!("{}", query);
+        }
+    } else if queries.is_empty() {
+        println!("No queries were generated for dataset {}.", dataset_id);
+    } else {
+        println!("Suggested queries for dataset {}:", dataset_id);
+        for (i, query) in queries.iter().enumerate() {
+            println!("{:>3}. {}", i + 1, query);
+        }
+    }
+
+    fail_if_empty(suggest.fail_on_empty, queries.is_empty());
+    Ok(())
+}
@@ -0,0 +1,362 @@
+use std::io::{stdout, ErrorKind, Write};
+
+use chrono::{Duration, NaiveDate};
+use serde_json::json;
+use trieve_client::models::ChunkReqPayload;
+
+use crate::cli::{Distribution, GenerateChunks};
+
+use super::{
+    chunk::report_upload,
+    configure::{require_login, TrieveConfiguration},
+    dataset::{require_dataset, DefaultError},
+    output::quiet,
+    upload::{upload_chunk_stream, ChunkRecords},
+};
+
+/// The tag every generated chunk is created with, so generated data is easy to find and delete.
+const GENERATED_TAG: &str = "trieve-generated";
+
+const LOREM: &str = "lorem ipsum dolor sit amet consectetur adipiscing elit sed do eiusmod tempor \
+    incididunt ut labore et dolore magna aliqua enim ad minim veniam quis nostrud exercitation \
+    ullamco laboris nisi aliquip ex ea commodo consequat duis aute irure in reprehenderit \
+    voluptate velit esse cillum fugiat nulla pariatur excepteur sint occaecat cupidatat non \
+    proident sunt culpa qui officia deserunt mollit anim id est laborum";
+
+/// The categories chunks are tagged with, so filters and facets have something to work on.
+const TOPICS: [&str; 8] = [
+    "billing",
+    "authentication",
+    "search",
+    "ingestion",
+    "analytics",
+    "security",
+    "deployment",
+    "integrations",
+];
+const AUTHORS: [&str; 6] = [
+    "Ada Lovelace",
+    "Grace Hopper",
+    "Alan Turing",
+    "Katherine Johnson",
+    "Linus Torvalds",
+    "Margaret Hamilton",
+];
+const NOUNS: [&str; 14] = [
+    "dataset",
+    "API key",
+    "webhook",
+    "index",
+    "chunk",
+    "organization",
+    "invoice",
+    "user",
+    "file",
+    "group",
+    "filter",
+    "report",
+    "dashboard",
+    "session",
+];
+/// Verbs with their past participle.
+const VERBS: [(&str, &str); 10] = [
+    ("create", "created"),
+    ("update", "updated"),
+    ("delete", "deleted"),
+    ("configure", "configured"),
+    ("export", "exported"),
+    ("import", "imported"),
+    ("rotate", "rotated"),
+    ("share", "shared"),
+    ("schedule", "scheduled"),
+    ("sync", "synced"),
+];
+const ADJECTIVES: [&str; 9] = [
+    "default", "archived", "shared", "private", "recent", "large", "empty", "primary", "custom",
+];
+
+/// A small deterministic random number generator, so the same seed generates the same chunks.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.state >> 33
+    }
+
+    /// A number in `range`.
+    pub fn between(&mut self, range: std::ops::RangeInclusive<usize>) -> usize {
+        range.start() + self.next_u64() as usize % (range.end() - range.start() + 1)
+    }
+
+    pub fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.next_u64() as usize % items.len()]
+    }
+}
+
+/// `word` with `a` or `an` in front of it.
+fn article(word: &str) -> String {
+    if word.starts_with(['a', 'e', 'i', 'o', 'A', 'E', 'I', 'O']) {
+        format!("an {}", word)
+    } else {
+        format!("a {}", word)
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+struct Generator {
+    rng: Rng,
+    seed: u64,
+    distribution: Distribution,
+    lorem: Vec<&'static str>,
+}
+
+impl Generator {
+    fn new(seed: u64, distribution: Distribution) -> Self {
+        Generator {
+            rng: Rng::new(seed),
+            seed,
+            distribution,
+            lorem: LOREM.split_whitespace().collect(),
+        }
+    }
+
+    fn lorem_sentence(&mut self) -> String {
+        let words = (0..self.rng.between(6..=14))
+            .map(|_| self.rng.pick(&self.lorem))
+            .collect::<Vec<&str>>()
+            .join(" ");
+        format!("{}.", capitalize(&words))
+    }
+
+    /// A sentence about the product, such as a help center or documentation page would have.
+    fn product_sentence(&mut self) -> String {
+        let noun = self.rng.between(0..=NOUNS.len() - 1);
+        // Any noun but the first one.
+        let other = NOUNS[(noun + self.rng.between(1..=NOUNS.len() - 1)) % NOUNS.len()];
+        let noun = NOUNS[noun];
+        let (verb, past) = self.rng.pick(&VERBS);
+        let adjective = self.rng.pick(&ADJECTIVES);
+
+        match self.rng.next_u64() % 6 {
+            0 => format!(
+                "You can {} {} {} from the settings page.",
+                verb,
+                article(adjective),
+                noun
+            ),
+            1 => format!("Each {} belongs to exactly one {}.", noun, other),
+            2 => format!(
+                "When {} is {}, every {} {} is updated within a few seconds.",
+                article(noun),
+                past,
+                adjective,
+                other
+            ),
+            3 => format!(
+                "Only admins can {} {} that is {}.",
+                verb,
+                article(noun),
+                adjective
+            ),
+            4 => format!("The {} limits depend on your plan.", noun),
+            _ => format!(
+                "{} {} stays available until it is {} by its owner.",
+                capitalize(&article(adjective)),
+                noun,
+                past
+            ),
+        }
+    }
+
+    fn product_paragraph(&mut self) -> String {
+        (0..self.rng.between(2..=5))
+            .map(|_| self.product_sentence())
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    fn lorem_html(&mut self) -> String {
+        (0..self.rng.between(1..=3))
+            .map(|_| {
+                let sentences = (0..self.rng.between(3..=7))
+                    .map(|_| self.lorem_sentence())
+                    .collect::<Vec<String>>()
+                    .join(" ");
+                format!("<p>{}</p>", sentences)
+            })
+            .collect()
+    }
+
+    fn markdown_html(&mut self, topic: &str) -> String {
+        let (verb, _) = self.rng.pick(&VERBS);
+        let noun = self.rng.pick(&NOUNS);
+        let mut html = format!(
+            "<h2>{} {}</h2><p>{}</p>",
+            capitalize(verb),
+            article(noun),
+            self.product_paragraph()
+        );
+
+        html.push_str("<ul>");
+        for _ in 0..self.rng.between(2..=4) {
+            html.push_str(&format!("<li>{}</li>", self.product_sentence()));
+        }
+        html.push_str("</ul>");
+
+        if self.rng.next_u64().is_multiple_of(2) {
+            html.push_str(&format!(
+                "<pre><code>trieve {} {} --{}</code></pre>",
+                topic,
+                verb,
+                self.rng.pick(&ADJECTIVES)
+            ));
+        }
+
+        html
+    }
+
+    fn faq_html(&mut self) -> (String, String) {
+        let (verb, past) = self.rng.pick(&VERBS);
+        let noun = self.rng.pick(&NOUNS);
+        let question = match self.rng.next_u64() % 4 {
+            0 => format!("How do I {} {}?", verb, article(noun)),
+            1 => format!(
+                "Can I {} {} {}?",
+                verb,
+                article(self.rng.pick(&ADJECTIVES)),
+                noun
+            ),
+            2 => format!("Why can't I {} my {}?", verb, noun),
+            _ => format!("What happens when {} is {}?", article(noun), past),
+        };
+
+        (
+            format!("<h3>{}</h3><p>{}</p>", question, self.product_paragraph()),
+            question,
+        )
+    }
+
+    fn chunk(&mut self, index: usize) -> ChunkReqPayload {
+        let topic = self.rng.pick(&TOPICS);
+        let distribution = match self.distribution {
+            Distribution::Lorem => "lorem",
+            Distribution::Markdown => "markdown",
+            Distribution::Faq => "faq",
+        };
+        let mut metadata = json!({
+            "source": GENERATED_TAG,
+            "distribution": distribution,
+            "category": topic,
+            "author": self.rng.pick(&AUTHORS),
+            "rating": self.rng.between(1..=5),
+            "index": index,
+        });
+
+        let html = match self.distribution {
+            Distribution::Lorem => self.lorem_html(),
+            Distribution::Markdown => self.markdown_html(topic),
+            Distribution::Faq => {
+                let (html, question) = self.faq_html();
+                metadata["question"] = json!(question);
+                html
+            }
+        };
+
+        let mut tags = vec![GENERATED_TAG.to_string(), topic.to_string()];
+        let other_topic = self.rng.pick(&TOPICS);
+        if self.rng.next_u64().is_multiple_of(3) && other_topic != topic {
+            tags.push(other_topic.to_string());
+        }
+
+        // Most chunks get few views and some get many, like real content does.
+        let views = (self.rng.next_u64() % 100).pow(3) / 100;
+        let published = NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap_or_default()
+            .and_hms_opt(0, 0, 0)
+            .unwrap_or_default()
+            + Duration::minutes((self.rng.next_u64() % (365 * 24 * 60)) as i64);
+
+        ChunkReqPayload {
+            chunk_html: Some(Some(html)),
+            link: Some(Some(format!(
+                "https://example.com/{}/{}/{}",
+                distribution, topic, index
+            ))),
+            tag_set: Some(Some(tags)),
+            num_value: Some(Some(views as f64)),
+            metadata: Some(Some(metadata)),
+            time_stamp: Some(Some(published.format("%Y-%m-%dT%H:%M:%S").to_string())),
+            tracking_id: Some(Some(format!("{}-{}-{}", GENERATED_TAG, self.seed, index))),
+            upsert_by_tracking_id: Some(Some(true)),
+            ..Default::default()
+        }
+    }
+}
+
+/// Generates `count` chunks of the `distribution` from `seed`.
+pub fn generated_chunks(count: usize, distribution: Distribution, seed: u64) -> ChunkRecords {
+    let mut generator = Generator::new(seed, distribution);
+    Box::new((0..count).map(move |index| Ok(generator.chunk(index))))
+}
+
+pub async fn generate_chunks(
+    settings: TrieveConfiguration,
+    generate: GenerateChunks,
+) -> Result<(), DefaultError> {
+    let seed = generate
+        .seed
+        .unwrap_or_else(|| uuid::Uuid::new_v4().as_u64_pair().0);
+    let chunks = generated_chunks(generate.count, generate.distribution, seed);
+
+    if generate.dry_run {
+        let mut out = stdout().lock();
+        for chunk in chunks {
+            let line = serde_json::to_string(&chunk?).map_err(|e| DefaultError {
+                message: e.to_string(),
+            })?;
+            match writeln!(out, "{}", line) {
+                Ok(()) => {}
+                // The reader, such as `head`, has seen enough.
+                Err(e) if e.kind() == ErrorKind::BrokenPipe => return Ok(()),
+                Err(e) => {
+                    return Err(DefaultError {
+                        message: e.to_string(),
+                    })
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    require_login(&settings)?;
+    let dataset_id = require_dataset(generate.dataset_id, &settings)?;
+
+    if !quiet() {
+        println!(
+            "Generating {} chunks with seed {}. Pass `--seed {}` to generate them again.",
+            generate.count, seed, seed
+        );
+    }
+    let summary =
+        upload_chunk_stream(settings, dataset_id.clone(), chunks, None, None, None).await?;
+    report_upload(&summary, &dataset_id, false)?;
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use std::sync::OnceLock;
+
+use tabled::{builder::Builder, settings::Style};
+
+use super::dataset::DefaultError;
+
+/// The columns passed with `--columns`, empty when it is not given.
+static COLUMNS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// A column of a list table. The keys and the order of the columns of a table are kept stable, and
+/// new columns are only ever added at the end, so scripts reading the output keep working.
+pub struct Column<T> {
+    /// The name `--columns` selects the column by, e.g. `chunks`.
+    pub key: &'static str,
+    pub header: &'static str,
+    pub value: fn(&T) -> String,
+}
+
+pub fn init(columns: Vec<String>) {
+    let _ = COLUMNS.set(
+        columns
+            .iter()
+            .map(|column| column.trim().to_lowercase())
+            .filter(|column| !column.is_empty())
+            .collect(),
+    );
+}
+
+/// The columns picked with `--columns` in the order given, or `default`, or else all of them.
+fn selected<'a, T>(
+    columns: &'a [Column<T>],
+    default: Option<&[&str]>,
+) -> Result<Vec<&'a Column<T>>, DefaultError> {
+    let keys = match COLUMNS.get().filter(|keys| !keys.is_empty()) {
+        Some(keys) => keys.clone(),
+        None => match default {
+            Some(keys) => keys.iter().map(|key| key.to_string()).collect(),
+            None => return Ok(columns.iter().collect()),
+        },
+    };
+
+    keys.iter()
+        .map(|key| {
+            columns
+                .iter()
+                .find(|column| column.key == key)
+                .ok_or_else(|| DefaultError {
+                    message: format!(
+                        "Unknown column '{}'. The columns are: {}",
+                        key,
+                        columns
+                            .iter()
+                            .map(|column| column.key)
+                            .collect::<Vec<&str>>()
+                            .join(", ")
+                    ),
+                })
+        })
+        .collect()
+}
+
+/// Renders `rows` as a table of the columns picked with `--columns`, all of them by default.
+pub fn render<T>(columns: &[Column<T>], rows: &[T]) -> Result<String, DefaultError> {
+    let columns = selected(columns, None)?;
+
+    let mut builder = Builder::default();
+    builder.push_record(columns.iter().map(|column| column.header));
+    for row in rows {
+        builder.push_record(columns.iter().map(|column| (column.value)(row)));
+    }
+
+    Ok(builder.build().with(Style::rounded()).to_string())
+}
+
+/// Prints `rows` for `--quiet`: one line per row, without a header, with the columns picked with
+/// `--columns` separated by tabs. Without `--columns` the `default` columns are printed.
+pub fn print_quiet<T>(
+    columns: &[Column<T>],
+    rows: &[T],
+    default: &[&str],
+) -> Result<(), DefaultError> {
+    let columns = selected(columns, Some(default))?;
+
+    for row in rows {
+        println!(
+            "{}",
+            columns
+                .iter()
+                .map(|column| (column.value)(row))
+                .collect::<Vec<String>>()
+                .join("\t")
+        );
+    }
+
+    Ok(())
+}
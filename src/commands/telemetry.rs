@@ -0,0 +1,240 @@
+use std::{
+    collections::BTreeMap,
+    env,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde_json::{json, Value};
+
+use super::http;
+
+/// How often metrics are exported while a command runs. They are exported once more when an
+/// upload finishes, since commands may exit right after it.
+const EXPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Upper bounds, in milliseconds, of the buckets of the batch latency histogram.
+const LATENCY_BOUNDS_MS: [f64; 11] = [
+    50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0, 30000.0, 60000.0, 120000.0,
+];
+
+static EXPORTER: OnceLock<Exporter> = OnceLock::new();
+
+/// The upload metrics of one dataset, cumulative since the command started.
+#[derive(Default)]
+struct Series {
+    batches: u64,
+    failed_batches: u64,
+    chunks: u64,
+    latency_counts: [u64; LATENCY_BOUNDS_MS.len() + 1],
+    latency_sum: f64,
+    latency_min: Option<f64>,
+    latency_max: Option<f64>,
+}
+
+/// Sends metrics to an OpenTelemetry collector over OTLP/HTTP with JSON encoding, so ingestion
+/// jobs can be watched next to the server metrics.
+struct Exporter {
+    url: String,
+    headers: Vec<(String, String)>,
+    service_name: String,
+    started: SystemTime,
+    series: Mutex<BTreeMap<String, Series>>,
+    /// Set after the first failed export, so a collector that is down is reported only once.
+    warned: AtomicBool,
+}
+
+/// Enables exporting metrics to the OTLP endpoint, e.g. `http://localhost:4318`. The
+/// `OTEL_EXPORTER_OTLP_HEADERS` and `OTEL_SERVICE_NAME` environment variables are honored.
+pub fn init(endpoint: Option<String>) {
+    let Some(endpoint) = endpoint.filter(|endpoint| !endpoint.is_empty()) else {
+        return;
+    };
+
+    let headers = env::var("OTEL_EXPORTER_OTLP_HEADERS")
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|header| header.split_once('='))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+
+    let exporter = Exporter {
+        url: format!("{}/v1/metrics", endpoint.trim_end_matches('/')),
+        headers,
+        service_name: env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| "trieve-cli".to_string()),
+        started: SystemTime::now(),
+        series: Mutex::new(BTreeMap::new()),
+        warned: AtomicBool::new(false),
+    };
+    if EXPORTER.set(exporter).is_ok() {
+        tokio::spawn(async {
+            let mut interval = tokio::time::interval(EXPORT_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                export().await;
+            }
+        });
+    }
+}
+
+/// Records an uploaded batch of `chunks` chunks that took `latency` and succeeded or failed.
+pub fn record_batch(dataset_id: &str, chunks: usize, latency: Duration, ok: bool) {
+    let Some(exporter) = EXPORTER.get() else {
+        return;
+    };
+    let Ok(mut series) = exporter.series.lock() else {
+        return;
+    };
+
+    let series = series.entry(dataset_id.to_string()).or_default();
+    let latency = latency.as_secs_f64() * 1000.0;
+    series.batches += 1;
+    if ok {
+        series.chunks += chunks as u64;
+    } else {
+        series.failed_batches += 1;
+    }
+    let bucket = LATENCY_BOUNDS_MS
+        .iter()
+        .position(|bound| latency <= *bound)
+        .unwrap_or(LATENCY_BOUNDS_MS.len());
+    series.latency_counts[bucket] += 1;
+    series.latency_sum += latency;
+    series.latency_min = Some(series.latency_min.map_or(latency, |min| min.min(latency)));
+    series.latency_max = Some(series.latency_max.map_or(latency, |max| max.max(latency)));
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+fn attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+impl Exporter {
+    /// Builds an `ExportMetricsServiceRequest` in the OTLP JSON encoding, with cumulative sums and
+    /// histograms per dataset.
+    fn request(&self) -> Option<Value> {
+        let series = self.series.lock().ok()?;
+        if series.is_empty() {
+            return None;
+        }
+
+        let start = unix_nanos(self.started);
+        let now = unix_nanos(SystemTime::now());
+        let counter = |name: &str, unit: &str, description: &str, value: fn(&Series) -> u64| {
+            json!({
+                "name": name,
+                "unit": unit,
+                "description": description,
+                "sum": {
+                    "aggregationTemporality": 2,
+                    "isMonotonic": true,
+                    "dataPoints": series.iter().map(|(dataset_id, series)| json!({
+                        "attributes": [attribute("trieve.dataset.id", dataset_id)],
+                        "startTimeUnixNano": start,
+                        "timeUnixNano": now,
+                        "asInt": value(series).to_string(),
+                    })).collect::<Vec<Value>>(),
+                },
+            })
+        };
+
+        let metrics = vec![
+            counter(
+                "trieve.upload.batches",
+                "{batch}",
+                "Batches of chunks sent",
+                |series| series.batches,
+            ),
+            counter(
+                "trieve.upload.batch.failures",
+                "{batch}",
+                "Batches of chunks that failed",
+                |series| series.failed_batches,
+            ),
+            counter(
+                "trieve.upload.chunks",
+                "{chunk}",
+                "Chunks uploaded",
+                |series| series.chunks,
+            ),
+            json!({
+                "name": "trieve.upload.batch.duration",
+                "unit": "ms",
+                "description": "Time taken to upload a batch of chunks",
+                "histogram": {
+                    "aggregationTemporality": 2,
+                    "dataPoints": series.iter().map(|(dataset_id, series)| json!({
+                        "attributes": [attribute("trieve.dataset.id", dataset_id)],
+                        "startTimeUnixNano": start,
+                        "timeUnixNano": now,
+                        "count": series.batches.to_string(),
+                        "sum": series.latency_sum,
+                        "min": series.latency_min,
+                        "max": series.latency_max,
+                        "bucketCounts": series
+                            .latency_counts
+                            .iter()
+                            .map(u64::to_string)
+                            .collect::<Vec<String>>(),
+                        "explicitBounds": LATENCY_BOUNDS_MS,
+                    })).collect::<Vec<Value>>(),
+                },
+            }),
+        ];
+
+        Some(json!({
+            "resourceMetrics": [{
+                "resource": {
+                    "attributes": [
+                        attribute("service.name", &self.service_name),
+                        attribute("service.version", env!("CARGO_PKG_VERSION")),
+                    ],
+                },
+                "scopeMetrics": [{
+                    "scope": { "name": "trieve-cli", "version": env!("CARGO_PKG_VERSION") },
+                    "metrics": metrics,
+                }],
+            }],
+        }))
+    }
+}
+
+/// Sends the current metrics to the collector. Failures are reported once and never fail the
+/// command.
+pub async fn export() {
+    let Some(exporter) = EXPORTER.get() else {
+        return;
+    };
+    let Some(body) = exporter.request() else {
+        return;
+    };
+
+    let mut request = http::client().post(&exporter.url).json(&body);
+    for (name, value) in &exporter.headers {
+        request = request.header(name, value);
+    }
+    let result = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+
+    if let Err(e) = result {
+        if !exporter.warned.swap(true, Ordering::SeqCst) {
+            eprintln!(
+                "Could not export metrics to {}: {}",
+                exporter.url,
+                e.without_url()
+            );
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::{fs, path::PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tabled::{builder::Builder, settings::Style};
+
+use crate::cli::{DeleteTemplate, SaveTemplate};
+
+use super::{
+    alias::resolve_dataset,
+    configure::{config_subdir, require_login, TrieveConfiguration},
+    dataset::select_dataset_id,
+    date::format_time,
+    output::{ensure_interactive, quiet},
+    rest::Rest,
+};
+
+/// A dataset `server_configuration` saved under a name, so new datasets can be created with the
+/// same embedding model, RAG prompt and other settings.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DatasetTemplate {
+    pub name: String,
+    pub source_dataset_id: String,
+    pub saved_at: DateTime<Utc>,
+    pub server_configuration: Value,
+}
+
+fn template_path(name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Invalid template name '{}'. Use letters, numbers, '-' and '_' only.",
+            name
+        )
+        .into());
+    }
+
+    Ok(config_subdir("templates")?.join(format!("{}.json", name)))
+}
+
+pub fn load_template(name: &str) -> Result<DatasetTemplate, Box<dyn std::error::Error>> {
+    let contents = fs::read_to_string(template_path(name)?).map_err(|_| {
+        format!(
+            "Template '{}' not found. Run `trieve dataset template list` to see the saved templates.",
+            name
+        )
+    })?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+pub async fn save_template(
+    settings: TrieveConfiguration,
+    save: SaveTemplate,
+) -> Result<(), Box<dyn std::error::Error>> {
+    require_login(&settings)?;
+
+    let dataset_id = match save.dataset_id {
+        Some(dataset_id) => resolve_dataset(&settings, &dataset_id),
+        None => select_dataset_id(&settings, "Select a dataset to save as a template:")
+            .await
+            .map_err(|e| e.message)?,
+    };
+    let name = match save.name {
+        Some(name) => name,
+        None => {
+            ensure_interactive("--name")?;
+            inquire::Text::new("Template Name: ").prompt()?
+        }
+    };
+    let path = template_path(&name)?;
+
+    if path.exists() && !save.force {
+        return Err(format!(
+            "Template '{}' already exists. Pass --force to overwrite it.",
+            name
+        )
+        .into());
+    }
+
+    let dataset: Value = Rest::new(&settings)
+        .dataset(&dataset_id)
+        .get(&format!("/api/dataset/{}", dataset_id))
+        .await
+        .map_err(|e| e.message)?;
+
+    let template = DatasetTemplate {
+        name: name.clone(),
+        source_dataset_id: dataset_id.clone(),
+        saved_at: Utc::now(),
+        server_configuration: dataset["server_configuration"].clone(),
+    };
+    fs::write(&path, serde_json::to_string_pretty(&template)?)?;
+    if quiet() {
+        println!("{}", name);
+        return Ok(());
+    }
+
+    println!(
+        "Saved the configuration of dataset {} as template '{}'.",
+        dataset_id, name
+    );
+    println!(
+        "Create a dataset from it with `trieve dataset create --template {}`.",
+        name
+    );
+
+    Ok(())
+}
+
+pub fn list_templates() -> Result<(), Box<dyn std::error::Error>> {
+    let mut templates = fs::read_dir(config_subdir("templates")?)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|e| e == "json"))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|contents| serde_json::from_str::<DatasetTemplate>(&contents).ok())
+        .collect::<Vec<DatasetTemplate>>();
+
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+
+    if quiet() {
+        for template in templates {
+            println!("{}", template.name);
+        }
+        return Ok(());
+    }
+
+    let mut builder = Builder::default();
+
+    builder.push_record(["Name", "Source Dataset", "Saved At", "Embedding Model"]);
+
+    for template in templates {
+        builder.push_record([
+            template.name.clone(),
+            template.source_dataset_id.clone(),
+            format_time(template.saved_at),
+            template.server_configuration["EMBEDDING_MODEL_NAME"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        ]);
+    }
+
+    let table = builder.build().with(Style::rounded()).to_string();
+    println!("Dataset templates:");
+    println!("{}", table);
+
+    Ok(())
+}
+
+pub fn delete_template(delete: DeleteTemplate) -> Result<(), Box<dyn std::error::Error>> {
+    fs::remove_file(template_path(&delete.name)?)
+        .map_err(|_| format!("Template '{}' not found.", delete.name))?;
+
+    if !quiet() {
+        println!("Deleted template '{}'.", delete.name);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use std::time::{Duration, Instant};
+
+use super::{configure::TrieveConfiguration, http, output::quiet, upload::MAX_CONCURRENT_BATCHES};
+
+/// The most batches uploaded at once when the concurrency adapts to the server.
+const MAX_ADAPTIVE_CONCURRENCY: usize = 32;
+
+/// A batch counts as fast when it took at most this many times as long as the fastest batch.
+const FAST_BATCH_FACTOR: u32 = 2;
+
+/// Keeps the number of batches uploaded at once near what the server sustains. The limit is
+/// halved when the server pushes back, with 429 or 5xx responses or failed batches, and grows by
+/// one after a full round of fast batches, so large imports run as fast as cloud and small
+/// self-hosted servers allow. A `concurrency` set in the configuration is used as is.
+pub struct Throughput {
+    limit: usize,
+    adaptive: bool,
+    fastest: Option<Duration>,
+    /// The fast batches since the limit last changed.
+    fast_batches: usize,
+    /// The pushback responses seen so far, as counted by `http::overloaded_responses`.
+    overloaded: u64,
+    last_decrease: Option<Instant>,
+}
+
+impl Throughput {
+    pub fn new(settings: &TrieveConfiguration) -> Self {
+        Throughput {
+            limit: settings
+                .upload_concurrency
+                .unwrap_or(MAX_CONCURRENT_BATCHES)
+                .max(1),
+            adaptive: settings.upload_concurrency.is_none(),
+            fastest: None,
+            fast_batches: 0,
+            overloaded: http::overloaded_responses(),
+            last_decrease: None,
+        }
+    }
+
+    /// The number of batches that may be in flight.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Adjusts the limit after a batch finished in `latency`.
+    pub fn record(&mut self, latency: Duration, ok: bool) {
+        if !self.adaptive {
+            return;
+        }
+
+        let overloaded = http::overloaded_responses();
+        let pushed_back = !ok || overloaded > self.overloaded;
+        self.overloaded = overloaded;
+
+        if pushed_back {
+            // Batches in flight together see the same pushback, so it lowers the limit once.
+            if self
+                .last_decrease
+                .is_some_and(|decreased| decreased.elapsed() < latency)
+            {
+                return;
+            }
+
+            self.last_decrease = Some(Instant::now());
+            self.fast_batches = 0;
+            if self.limit > 1 {
+                self.limit /= 2;
+                if !quiet() {
+                    eprintln!(
+                        "The server is pushing back, uploading {} batches at once",
+                        self.limit
+                    );
+                }
+            }
+            return;
+        }
+
+        let fastest = *self.fastest.get_or_insert(latency);
+        self.fastest = Some(fastest.min(latency));
+        if latency > fastest * FAST_BATCH_FACTOR {
+            return;
+        }
+
+        self.fast_batches += 1;
+        if self.fast_batches >= self.limit && self.limit < MAX_ADAPTIVE_CONCURRENCY {
+            self.fast_batches = 0;
+            self.limit += 1;
+        }
+    }
+}
@@ -0,0 +1,164 @@
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{stdout, BufWriter, Write},
+};
+
+use serde_json::{json, Value};
+
+use crate::cli::ExportTopics;
+
+use super::{
+    configure::{require_login, TrieveConfiguration},
+    dataset::{require_dataset, DefaultError},
+    output::{fail_if_empty, quiet},
+    rest::Rest,
+};
+
+/// The IDs of the topics and of their owners found in the RAG analytics of the dataset, as topics
+/// can only be listed per owner.
+async fn topics_from_analytics(
+    rest: &Rest<'_>,
+) -> Result<(Vec<String>, Vec<String>), DefaultError> {
+    let mut topic_ids = vec![];
+    let mut owner_ids = vec![];
+    let mut seen = HashSet::new();
+
+    for page in 1.. {
+        let response: Value = rest
+            .post(
+                "/api/analytics/rag",
+                &json!({ "type": "rag_queries", "page": page }),
+            )
+            .await?;
+        let queries = response["queries"].as_array().cloned().unwrap_or_default();
+        if queries.is_empty() {
+            break;
+        }
+
+        for query in queries {
+            for (field, ids) in [("topic_id", &mut topic_ids), ("user_id", &mut owner_ids)] {
+                let Some(id) = query[field].as_str().filter(|id| !id.is_empty()) else {
+                    continue;
+                };
+                if id != uuid::Uuid::nil().to_string() && seen.insert(id.to_string()) {
+                    ids.push(id.to_string());
+                }
+            }
+        }
+    }
+
+    Ok((topic_ids, owner_ids))
+}
+
+/// Splits the chunks an assistant message cites, which are stored as a JSON list before or after
+/// `||`, from its content.
+fn split_citations(content: &str) -> (String, Vec<Value>) {
+    let citations = |text: &str| {
+        serde_json::from_str::<Vec<Value>>(text.trim())
+            .ok()
+            .map(|chunks| {
+                chunks
+                    .iter()
+                    .map(|chunk| {
+                        json!({
+                            "id": chunk["id"],
+                            "tracking_id": chunk["tracking_id"],
+                            "link": chunk["link"],
+                        })
+                    })
+                    .collect()
+            })
+    };
+
+    match content.split_once("||") {
+        Some((before, after)) => match (citations(before), citations(after)) {
+            (Some(cited), _) => (after.trim().to_string(), cited),
+            (None, Some(cited)) => (before.trim().to_string(), cited),
+            (None, None) => (content.to_string(), vec![]),
+        },
+        None => (content.to_string(), vec![]),
+    }
+}
+
+fn exported_message(message: &Value) -> Value {
+    let (content, citations) = split_citations(message["content"].as_str().unwrap_or_default());
+    json!({
+        "id": message["id"],
+        "sort_order": message["sort_order"],
+        "role": message["role"],
+        "content": content,
+        "citations": citations,
+        "created_at": message["created_at"],
+    })
+}
+
+pub async fn export_topics(
+    settings: TrieveConfiguration,
+    export: ExportTopics,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(export.dataset_id, &settings)?;
+    let rest = Rest::new(&settings).dataset(&dataset_id);
+
+    let (mut topic_ids, owner_ids) = if export.owner_id.is_empty() {
+        topics_from_analytics(&rest).await?
+    } else {
+        (vec![], export.owner_id)
+    };
+
+    let mut topics = vec![];
+    for owner_id in &owner_ids {
+        let owned: Vec<Value> = rest.get(&format!("/api/topic/owner/{}", owner_id)).await?;
+        topics.extend(owned);
+    }
+    // Topics seen in the analytics whose owner could not be found are exported without their
+    // name and owner.
+    topic_ids.retain(|id| !topics.iter().any(|topic| topic["id"] == *id));
+    topics.extend(topic_ids.into_iter().map(|id| json!({ "id": id })));
+
+    let mut out: Box<dyn Write> = if export.out.as_os_str() == "-" {
+        Box::new(stdout().lock())
+    } else {
+        Box::new(BufWriter::new(File::create(&export.out).map_err(|e| {
+            DefaultError {
+                message: format!("Could not create {}: {}", export.out.display(), e),
+            }
+        })?))
+    };
+    let write_error = |e: std::io::Error| DefaultError {
+        message: format!("Could not write {}: {}", export.out.display(), e),
+    };
+
+    let mut message_count = 0;
+    for topic in &topics {
+        let topic_id = topic["id"].as_str().unwrap_or_default();
+        let messages: Vec<Value> = rest.get(&format!("/api/messages/{}", topic_id)).await?;
+        message_count += messages.len();
+
+        let line = json!({
+            "id": topic_id,
+            "name": topic["name"],
+            "owner_id": topic["owner_id"],
+            "created_at": topic["created_at"],
+            "updated_at": topic["updated_at"],
+            "messages": messages.iter().map(exported_message).collect::<Vec<Value>>(),
+        });
+        writeln!(out, "{}", line).map_err(write_error)?;
+    }
+    out.flush().map_err(write_error)?;
+
+    if !quiet() && export.out.as_os_str() != "-" {
+        println!(
+            "Exported {} topics with {} messages from dataset {} to {}",
+            topics.len(),
+            message_count,
+            dataset_id,
+            export.out.display()
+        );
+    }
+
+    fail_if_empty(export.fail_on_empty, topics.is_empty());
+    Ok(())
+}
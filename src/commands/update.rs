@@ -0,0 +1,223 @@
+use std::{env, fs, io::Read, path::Path};
+
+use reqwest::Method;
+use semver::Version;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::cli::Update;
+
+use super::{dataset::DefaultError, http};
+
+const RELEASES_URL: &str = "https://api.github.com/repos/devflowinc/trieve-CLI/releases/latest";
+
+#[derive(Deserialize, Debug)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+    assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl Release {
+    fn version(&self) -> Result<Version, DefaultError> {
+        parse_version(&self.tag_name)
+    }
+
+    fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+fn update_error(message: impl Into<String>) -> DefaultError {
+    DefaultError {
+        message: message.into(),
+    }
+}
+
+/// Parses a version such as `0.5.3` or a release tag such as `v0.5.3`.
+pub fn parse_version(version: &str) -> Result<Version, DefaultError> {
+    Version::parse(version.trim_start_matches('v'))
+        .map_err(|e| update_error(format!("Invalid version '{}': {}", version, e)))
+}
+
+pub fn current_version() -> Version {
+    Version::parse(env!("CARGO_PKG_VERSION")).expect("CARGO_PKG_VERSION is a valid version")
+}
+
+/// The target triple release binaries are published for, or `None` on unsupported platforms.
+fn release_target() -> Option<&'static str> {
+    match (env::consts::OS, env::consts::ARCH) {
+        ("linux", "x86_64") => Some("x86_64-unknown-linux-gnu"),
+        ("linux", "aarch64") => Some("aarch64-unknown-linux-gnu"),
+        ("macos", "x86_64") => Some("x86_64-apple-darwin"),
+        ("macos", "aarch64") => Some("aarch64-apple-darwin"),
+        ("windows", "x86_64") => Some("x86_64-pc-windows-msvc"),
+        _ => None,
+    }
+}
+
+fn binary_name(target: &str) -> String {
+    format!("trieve-{}{}", target, env::consts::EXE_SUFFIX)
+}
+
+async fn latest_release() -> Result<Release, DefaultError> {
+    let request =
+        http::request(Method::GET, RELEASES_URL).header("Accept", "application/vnd.github+json");
+
+    http::send(request, None).await?.json()
+}
+
+async fn download_bytes(url: &str) -> Result<Vec<u8>, DefaultError> {
+    let mut bytes = vec![];
+    http::download(url)
+        .await?
+        .read_to_end(&mut bytes)
+        .map_err(|e| update_error(e.to_string()))?;
+
+    Ok(bytes)
+}
+
+/// Looks up the SHA-256 of `binary` in the release, either from a `<binary>.sha256` file or from a
+/// `SHA256SUMS` file in the `sha256sum` format.
+async fn expected_checksum(release: &Release, binary: &str) -> Result<String, DefaultError> {
+    if let Some(asset) = release.asset(&format!("{}.sha256", binary)) {
+        let checksum = String::from_utf8_lossy(&download_bytes(&asset.browser_download_url).await?)
+            .split_whitespace()
+            .next()
+            .map(|checksum| checksum.to_lowercase());
+        if let Some(checksum) = checksum {
+            return Ok(checksum);
+        }
+    }
+
+    if let Some(asset) = release.asset("SHA256SUMS") {
+        let sums = String::from_utf8_lossy(&download_bytes(&asset.browser_download_url).await?)
+            .into_owned();
+        let checksum = sums.lines().find_map(|line| {
+            let (checksum, name) = line.split_once(char::is_whitespace)?;
+            (name.trim().trim_start_matches('*') == binary).then(|| checksum.to_lowercase())
+        });
+        if let Some(checksum) = checksum {
+            return Ok(checksum);
+        }
+    }
+
+    Err(update_error(format!(
+        "Release {} does not publish a checksum for {}, refusing to install it",
+        release.tag_name, binary
+    )))
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Replaces the running executable with `binary`. The new binary is written next to the current
+/// one and renamed over it, so a failed write never leaves a broken install behind. Windows does
+/// not allow replacing a running executable, so it is moved aside first.
+fn replace_executable(binary: &[u8]) -> Result<(), DefaultError> {
+    let io_error = |e: std::io::Error| update_error(e.to_string());
+
+    let current = env::current_exe().map_err(io_error)?;
+    let current = current.canonicalize().unwrap_or(current);
+    let directory = current
+        .parent()
+        .ok_or_else(|| update_error("Could not find the directory of the trieve executable"))?;
+    let staged = directory.join(format!(".trieve-update-{}", uuid::Uuid::new_v4()));
+
+    fs::write(&staged, binary).map_err(io_error)?;
+    copy_permissions(&current, &staged).map_err(|e| {
+        let _ = fs::remove_file(&staged);
+        io_error(e)
+    })?;
+
+    if cfg!(windows) {
+        let old = current.with_extension("old.exe");
+        let _ = fs::remove_file(&old);
+        fs::rename(&current, &old).map_err(io_error)?;
+    }
+
+    fs::rename(&staged, &current).map_err(|e| {
+        let _ = fs::remove_file(&staged);
+        update_error(format!(
+            "Could not replace {}: {}. You may need to rerun the update with elevated permissions.",
+            current.display(),
+            e
+        ))
+    })
+}
+
+#[cfg(unix)]
+fn copy_permissions(from: &Path, to: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(from)?.permissions().mode();
+    fs::set_permissions(to, fs::Permissions::from_mode(mode | 0o111))
+}
+
+#[cfg(not(unix))]
+fn copy_permissions(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::set_permissions(to, fs::metadata(from)?.permissions())
+}
+
+pub async fn update(update: Update) -> Result<(), DefaultError> {
+    let current = current_version();
+    let release = latest_release().await?;
+    let latest = release.version()?;
+
+    if latest <= current {
+        println!("trieve {} is the latest version.", current);
+        return Ok(());
+    }
+
+    println!(
+        "A new version of trieve is available: {} -> {}",
+        current, latest
+    );
+    println!("Release notes: {}", release.html_url);
+    if update.check_only {
+        println!("Run `trieve update` to install it.");
+        return Ok(());
+    }
+
+    let target = release_target().ok_or_else(|| {
+        update_error(format!(
+            "No release binaries are published for {}-{}. Install the update with `cargo install trieve`.",
+            env::consts::OS,
+            env::consts::ARCH
+        ))
+    })?;
+    let binary_name = binary_name(target);
+    let asset = release.asset(&binary_name).ok_or_else(|| {
+        update_error(format!(
+            "Release {} has no binary for {}. Install the update with `cargo install trieve`.",
+            release.tag_name, target
+        ))
+    })?;
+
+    let expected = expected_checksum(&release, &binary_name).await?;
+    println!("Downloading {}...", asset.name);
+    let binary = download_bytes(&asset.browser_download_url).await?;
+
+    let actual = sha256_hex(&binary);
+    if actual != expected {
+        return Err(update_error(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            binary_name, expected, actual
+        )));
+    }
+
+    replace_executable(&binary)?;
+    println!("Updated trieve to {}.", latest);
+
+    Ok(())
+}
@@ -0,0 +1,761 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, HashSet},
+    fs::{self, File},
+    hash::{Hash, Hasher},
+    io::{BufRead, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinSet;
+use trieve_client::models::{
+    ChunkReqPayload, CreateChunkGroupReqPayloadEnum, CreateSingleChunkGroupReqPayload,
+};
+
+use crate::cli::{DedupeArgs, InputFormat};
+
+use super::{
+    api::TrieveApi,
+    audit,
+    client::ApiClient,
+    configure::{config_subdir, TrieveConfiguration},
+    dataset::DefaultError,
+    export::scroll_chunks,
+    http, interrupt,
+    mapping::{ColumnMapping, MappedValue, CHUNK_FIELDS},
+    output::quiet,
+    rest::Rest,
+    telemetry,
+    throughput::Throughput,
+};
+
+pub const CHUNKS_PER_BATCH: usize = 120;
+/// The file name standing for stdin, which is read as JSONL unless `--format` says otherwise.
+pub const STDIN: &str = "-";
+pub const MAX_CONCURRENT_BATCHES: usize = 8;
+/// How long to wait for in-flight batches after Ctrl-C before giving up on them.
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The key the error of a failed chunk is written under in an `--errors-out` file. It is not a
+/// chunk field, so the file can be uploaded again as it is.
+pub const ERROR_KEY: &str = "_error";
+
+pub type ChunkRecords = Box<dyn Iterator<Item = Result<ChunkReqPayload, DefaultError>>>;
+
+/// A batch that finished uploading.
+struct FinishedBatch {
+    index: usize,
+    chunks: Vec<ChunkReqPayload>,
+    /// The number of input records the batch was read from.
+    records: usize,
+    result: Result<(), DefaultError>,
+    latency: Duration,
+}
+
+pub async fn upload_batch(
+    settings: TrieveConfiguration,
+    dataset_id: String,
+    chunks: &[ChunkReqPayload],
+) -> Result<(), DefaultError> {
+    let body = serde_json::to_value(chunks).map_err(|e| DefaultError {
+        message: e.to_string(),
+    })?;
+
+    Rest::new(&settings)
+        .dataset(&dataset_id)
+        .send(Method::POST, "/api/chunk", Some(&body))
+        .await?;
+
+    Ok(())
+}
+
+/// Creates a group for every group tracking id referenced by the chunks that is not in `created` yet.
+pub async fn create_groups_for_chunks(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    chunk_datas: &[ChunkReqPayload],
+    created: &mut HashSet<String>,
+) -> Result<(), DefaultError> {
+    let config = ApiClient::from(settings);
+
+    let group_tracking_ids: HashSet<String> = chunk_datas
+        .iter()
+        .filter_map(|chunk| chunk.group_tracking_ids.clone().flatten())
+        .flatten()
+        .filter(|tracking_id| !created.contains(tracking_id))
+        .collect();
+
+    for tracking_id in group_tracking_ids {
+        let group_data = CreateChunkGroupReqPayloadEnum::CreateSingleChunkGroupReqPayload(
+            Box::new(CreateSingleChunkGroupReqPayload {
+                name: Some(Some(tracking_id.clone())),
+                tracking_id: Some(Some(tracking_id.clone())),
+                ..Default::default()
+            }),
+        );
+
+        config.create_chunk_group(dataset_id, group_data).await?;
+
+        created.insert(tracking_id);
+    }
+
+    Ok(())
+}
+
+/// Opens a URL, local file or stdin (`-`) of chunks, inferring the format from the extension when
+/// it is not given.
+pub async fn open_source(
+    url: Option<&str>,
+    file: Option<&Path>,
+    format: Option<InputFormat>,
+) -> Result<(Box<dyn Read + Send + Sync>, InputFormat), DefaultError> {
+    let (location, reader): (String, Box<dyn Read + Send + Sync>) = match (url, file) {
+        (Some(url), _) => (url.to_string(), http::download(url).await?),
+        (None, Some(file)) if file == Path::new(STDIN) => {
+            (format!("{}.jsonl", STDIN), Box::new(std::io::stdin()))
+        }
+        (None, Some(file)) => {
+            let reader = File::open(file).map_err(|e| DefaultError {
+                message: format!("Could not open {}: {}", file.display(), e),
+            })?;
+            (file.display().to_string(), Box::new(reader))
+        }
+        (None, None) => {
+            return Err(DefaultError {
+                message: "No URL or file provided".to_string(),
+            })
+        }
+    };
+
+    let location = location.to_lowercase();
+    let format = match format {
+        Some(format) => format,
+        None if location.ends_with(".csv") => InputFormat::Csv,
+        None if location.ends_with(".json") => InputFormat::Json,
+        None if location.ends_with(".jsonl") || location.ends_with(".ndjson") => InputFormat::Jsonl,
+        None => {
+            return Err(DefaultError {
+                message: format!(
+                    "Could not infer the format of {}, please pass `--format csv|json|jsonl`",
+                    location
+                ),
+            })
+        }
+    };
+
+    Ok((reader, format))
+}
+
+fn json_record(
+    mapping: &ColumnMapping,
+    value: serde_json::Value,
+    line: Option<usize>,
+) -> Result<ChunkReqPayload, DefaultError> {
+    let chunk = value.as_object().ok_or_else(|| DefaultError {
+        message: match line {
+            Some(line) => format!("Line {} is not a JSON object", line),
+            None => "Expected every chunk to be a JSON object".to_string(),
+        },
+    })?;
+
+    mapping.chunk_from_row(|key| chunk.get(key).and_then(MappedValue::from_json))
+}
+
+/// Turns a CSV, JSON or JSONL reader into an iterator of chunks. CSV and JSONL input is read
+/// lazily, so only the JSON format needs to fit in memory.
+pub fn chunk_records(
+    reader: Box<dyn Read + Send + Sync>,
+    format: InputFormat,
+    mapping: &ColumnMapping,
+) -> Result<ChunkRecords, DefaultError> {
+    match format {
+        InputFormat::Csv => {
+            let mut rdr = csv::Reader::from_reader(reader);
+            let headers = rdr
+                .headers()
+                .map_err(|e| DefaultError {
+                    message: e.to_string(),
+                })?
+                .clone();
+
+            let mapping = if mapping.is_empty() {
+                ColumnMapping::positional(headers.len())
+            } else {
+                mapping.clone()
+            };
+            let columns = mapping.resolve_csv_columns(&headers)?;
+
+            Ok(Box::new(rdr.into_records().map(move |record| {
+                let record = record.map_err(|e| DefaultError {
+                    message: e.to_string(),
+                })?;
+                mapping.chunk_from_row(|column| {
+                    columns
+                        .get(column)
+                        .and_then(|i| record.get(*i))
+                        .map(|v| MappedValue::Text(v.to_string()))
+                })
+            })))
+        }
+        InputFormat::Json => {
+            let mapping = mapping.clone().with_identity_defaults();
+            let chunks: serde_json::Value =
+                serde_json::from_reader(reader).map_err(|e| DefaultError {
+                    message: e.to_string(),
+                })?;
+            let serde_json::Value::Array(chunks) = chunks else {
+                return Err(DefaultError {
+                    message: "Expected a JSON array of chunks".to_string(),
+                });
+            };
+
+            Ok(Box::new(
+                chunks
+                    .into_iter()
+                    .map(move |chunk| json_record(&mapping, chunk, None)),
+            ))
+        }
+        InputFormat::Jsonl => {
+            let mapping = mapping.clone().with_identity_defaults();
+
+            Ok(Box::new(
+                BufReader::new(reader)
+                    .lines()
+                    .enumerate()
+                    .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+                    .map(move |(i, line)| {
+                        let line = line.map_err(|e| DefaultError {
+                            message: e.to_string(),
+                        })?;
+                        let value = serde_json::from_str(&line).map_err(|e| DefaultError {
+                            message: format!("Invalid JSON on line {}: {}", i + 1, e),
+                        })?;
+                        json_record(&mapping, value, Some(i + 1))
+                    }),
+            ))
+        }
+    }
+}
+
+/// Records how many input records have been uploaded so an interrupted upload can be resumed.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Checkpoint {
+    pub source: String,
+    pub dataset_id: String,
+    pub records_completed: usize,
+    pub updated_at: DateTime<Utc>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl Checkpoint {
+    pub fn new(source: &Path, dataset_id: &str) -> Result<Self, DefaultError> {
+        let source = fs::canonicalize(source)
+            .unwrap_or(source.to_path_buf())
+            .display()
+            .to_string();
+
+        let mut hasher = DefaultHasher::new();
+        (&source, dataset_id).hash(&mut hasher);
+
+        let dir = config_subdir("checkpoints").map_err(|e| DefaultError {
+            message: e.to_string(),
+        })?;
+
+        Ok(Checkpoint {
+            source,
+            dataset_id: dataset_id.to_string(),
+            records_completed: 0,
+            updated_at: Utc::now(),
+            path: dir.join(format!("{:016x}.json", hasher.finish())),
+        })
+    }
+
+    /// Creates the checkpoint for an upload, loading the saved progress when `resume` is set and
+    /// otherwise pointing out that a previous upload of the same source can be resumed.
+    pub fn open(source: &Path, dataset_id: &str, resume: bool) -> Result<Self, DefaultError> {
+        let mut checkpoint = Checkpoint::new(source, dataset_id)?;
+
+        if resume {
+            checkpoint.load()?;
+            if checkpoint.records_completed > 0 && !quiet() {
+                println!(
+                    "Resuming upload after {} records (checkpoint from {})",
+                    checkpoint.records_completed,
+                    checkpoint.updated_at.format("%Y-%m-%d %H:%M:%S")
+                );
+            }
+        } else if checkpoint.exists() && !quiet() {
+            println!("Found a checkpoint from a previous upload of this source. Starting over; pass `--resume` to continue from it instead.");
+        }
+
+        Ok(checkpoint)
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// Loads the progress saved by a previous run, if any.
+    pub fn load(&mut self) -> Result<(), DefaultError> {
+        if !self.exists() {
+            return Ok(());
+        }
+
+        let contents = fs::read_to_string(&self.path).map_err(|e| DefaultError {
+            message: e.to_string(),
+        })?;
+        let saved: Checkpoint = serde_json::from_str(&contents).map_err(|e| DefaultError {
+            message: format!("Invalid checkpoint {}: {}", self.path.display(), e),
+        })?;
+
+        self.records_completed = saved.records_completed;
+        self.updated_at = saved.updated_at;
+        Ok(())
+    }
+
+    fn store(&mut self, records_completed: usize) -> Result<(), DefaultError> {
+        self.records_completed = records_completed;
+        self.updated_at = Utc::now();
+
+        let contents = serde_json::to_string_pretty(self).map_err(|e| DefaultError {
+            message: e.to_string(),
+        })?;
+        fs::write(&self.path, contents).map_err(|e| DefaultError {
+            message: format!("Could not write checkpoint {}: {}", self.path.display(), e),
+        })
+    }
+
+    pub fn clear(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Writes the chunks of failed batches to a JSONL file, in the format `chunk upload` reads and with
+/// the error of their batch, so only the failures have to be retried.
+pub struct FailureLog {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    failed: usize,
+}
+
+impl FailureLog {
+    pub fn create(path: &Path) -> Result<Self, DefaultError> {
+        let file = File::create(path).map_err(|e| DefaultError {
+            message: format!("Could not create {}: {}", path.display(), e),
+        })?;
+
+        Ok(FailureLog {
+            path: path.to_path_buf(),
+            writer: BufWriter::new(file),
+            failed: 0,
+        })
+    }
+
+    fn write(&mut self, chunks: &[ChunkReqPayload], error: &str) -> Result<(), DefaultError> {
+        let write_error = |e: std::io::Error| DefaultError {
+            message: format!("Could not write {}: {}", self.path.display(), e),
+        };
+
+        for chunk in chunks {
+            let mut record = match serde_json::to_value(chunk) {
+                Ok(serde_json::Value::Object(record)) => record,
+                _ => serde_json::Map::new(),
+            };
+            record.retain(|key, value| CHUNK_FIELDS.contains(&key.as_str()) && !value.is_null());
+            record.insert(ERROR_KEY.to_string(), error.into());
+
+            writeln!(self.writer, "{}", serde_json::Value::Object(record)).map_err(write_error)?;
+        }
+        self.failed += chunks.len();
+
+        self.writer.flush().map_err(write_error)
+    }
+
+    fn finish(&self) {
+        if self.failed > 0 {
+            eprintln!(
+                "Wrote the {} chunks of the failed batches to {}. Retry them with `trieve chunk upload --file {}`.",
+                self.failed,
+                self.path.display(),
+                self.path.display()
+            );
+        }
+    }
+}
+
+/// Drops chunks whose text was already seen during the upload, and optionally chunks whose tracking
+/// ID already exists in the dataset.
+#[derive(Debug, Default)]
+pub struct Dedupe {
+    seen: HashSet<u64>,
+    existing_tracking_ids: HashSet<String>,
+    pub dropped: usize,
+}
+
+impl Dedupe {
+    /// Sets up deduplication as requested by `--dedupe` and `--dedupe-existing`, fetching the
+    /// tracking IDs of the dataset for the latter.
+    pub async fn from_args(
+        args: &DedupeArgs,
+        settings: &TrieveConfiguration,
+        dataset_id: &str,
+    ) -> Result<Option<Self>, DefaultError> {
+        if !args.dedupe && !args.dedupe_existing {
+            return Ok(None);
+        }
+
+        let mut dedupe = Dedupe::default();
+        if args.dedupe_existing {
+            dedupe.existing_tracking_ids = existing_tracking_ids(settings, dataset_id).await?;
+            if !quiet() {
+                println!(
+                    "Found {} tracking IDs in dataset {}",
+                    dedupe.existing_tracking_ids.len(),
+                    dataset_id
+                );
+            }
+        }
+
+        Ok(Some(dedupe))
+    }
+
+    /// Whether the chunk is a duplicate, in which case it is counted as dropped.
+    fn is_duplicate(&mut self, chunk: &ChunkReqPayload) -> bool {
+        let existing = chunk
+            .tracking_id
+            .clone()
+            .flatten()
+            .is_some_and(|tracking_id| self.existing_tracking_ids.contains(&tracking_id));
+
+        let mut hasher = DefaultHasher::new();
+        normalized_text(chunk.chunk_html.as_ref().and_then(|html| html.as_deref()))
+            .hash(&mut hasher);
+        let duplicate = existing || !self.seen.insert(hasher.finish());
+
+        if duplicate {
+            self.dropped += 1;
+        }
+        duplicate
+    }
+}
+
+/// The text of a chunk without markup, case and whitespace differences, so chunks that only differ
+/// in those count as duplicates.
+fn normalized_text(html: Option<&str>) -> String {
+    let mut text = String::new();
+    let mut in_tag = false;
+
+    for c in html.unwrap_or_default().chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.extend(c.to_lowercase()),
+            _ => {}
+        }
+    }
+
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// The tracking IDs of every chunk in a dataset.
+async fn existing_tracking_ids(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+) -> Result<HashSet<String>, DefaultError> {
+    let mut tracking_ids = HashSet::new();
+    let mut seen = HashSet::new();
+    let mut offset: Option<String> = None;
+
+    loop {
+        let chunks = scroll_chunks(settings, dataset_id, offset.as_deref()).await?;
+        let mut new_chunks = 0;
+
+        for chunk in chunks {
+            let id = chunk["id"].as_str().unwrap_or_default().to_string();
+            // The offset chunk may be returned again at the start of the next page.
+            if !seen.insert(id.clone()) {
+                continue;
+            }
+            if let Some(tracking_id) = chunk["tracking_id"].as_str() {
+                tracking_ids.insert(tracking_id.to_string());
+            }
+            offset = Some(id);
+            new_chunks += 1;
+        }
+
+        if new_chunks == 0 {
+            break;
+        }
+    }
+
+    Ok(tracking_ids)
+}
+
+#[derive(Debug, Default)]
+pub struct UploadSummary {
+    pub uploaded: usize,
+    pub skipped: usize,
+    pub failed_batches: usize,
+    /// The number of chunks dropped by `--dedupe`.
+    pub duplicates: usize,
+    /// Set when the upload was stopped early with Ctrl-C.
+    pub interrupted: bool,
+}
+
+/// Tracks finished batches and advances the checkpoint over the contiguous run of successful ones,
+/// so a resumed upload restarts at the first batch that did not complete.
+struct UploadProgress {
+    summary: UploadSummary,
+    finished: BTreeMap<usize, (usize, bool)>,
+    next_batch: usize,
+    records_completed: usize,
+    checkpoint: Option<Checkpoint>,
+    failures: Option<FailureLog>,
+}
+
+impl UploadProgress {
+    /// Records a finished batch of `len` chunks, read from `records` input records, which is more
+    /// than `len` when duplicates were dropped.
+    fn finish_batch(
+        &mut self,
+        index: usize,
+        chunks: &[ChunkReqPayload],
+        records: usize,
+        result: Result<(), DefaultError>,
+    ) -> Result<(), DefaultError> {
+        let len = chunks.len();
+        match &result {
+            Ok(()) => {
+                self.summary.uploaded += len;
+                if !quiet() && len > 0 {
+                    println!(
+                        "Batch {}: uploaded {} chunks ({} total)",
+                        index + 1,
+                        len,
+                        self.summary.uploaded
+                    );
+                }
+            }
+            Err(e) => {
+                self.summary.failed_batches += 1;
+                eprintln!("Error uploading batch {}: {}", index + 1, e.message);
+
+                let tracking_ids = chunks
+                    .iter()
+                    .filter_map(|chunk| chunk.tracking_id.clone().flatten())
+                    .collect::<Vec<String>>();
+                if !tracking_ids.is_empty() {
+                    eprintln!(
+                        "  Tracking IDs: {}{}",
+                        tracking_ids[..tracking_ids.len().min(5)].join(", "),
+                        if tracking_ids.len() > 5 {
+                            format!(" and {} more", tracking_ids.len() - 5)
+                        } else {
+                            String::new()
+                        }
+                    );
+                }
+                if let Some(failures) = self.failures.as_mut() {
+                    failures.write(chunks, &e.message)?;
+                }
+            }
+        }
+        self.finished.insert(index, (records, result.is_ok()));
+
+        let previous = self.records_completed;
+        while let Some((len, true)) = self.finished.get(&self.next_batch).copied() {
+            self.finished.remove(&self.next_batch);
+            self.records_completed += len;
+            self.next_batch += 1;
+        }
+
+        if self.records_completed != previous {
+            if let Some(checkpoint) = self.checkpoint.as_mut() {
+                checkpoint.store(self.records_completed)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Uploads chunks in batches of `CHUNKS_PER_BATCH`, keeping as many requests in flight as the
+/// server sustains (see `Throughput`), or the `upload_concurrency` of the profile. With a checkpoint, records
+/// completed by a previous run are skipped and progress is saved after every batch; the checkpoint
+/// is removed once everything succeeded. With `dedupe`, duplicate chunks are dropped before they
+/// are batched, and with `failures`, the chunks of failed batches are written to a file.
+///
+/// Once stopping is requested through `interrupt::request`, which the CLI does on Ctrl-C, no new
+/// batches are scheduled and the in-flight ones are given `DRAIN_TIMEOUT` to finish, so the
+/// checkpoint reflects exactly what reached the server.
+pub async fn upload_chunk_stream(
+    settings: TrieveConfiguration,
+    dataset_id: String,
+    records: ChunkRecords,
+    checkpoint: Option<Checkpoint>,
+    mut dedupe: Option<Dedupe>,
+    failures: Option<FailureLog>,
+) -> Result<UploadSummary, DefaultError> {
+    let skip = checkpoint
+        .as_ref()
+        .map(|c| c.records_completed)
+        .unwrap_or(0);
+
+    let mut progress = UploadProgress {
+        summary: UploadSummary {
+            skipped: skip,
+            ..Default::default()
+        },
+        finished: BTreeMap::new(),
+        next_batch: 0,
+        records_completed: skip,
+        checkpoint,
+        failures,
+    };
+
+    let watch = interrupt::watch();
+
+    let mut throughput = Throughput::new(&settings);
+    let mut created_groups: HashSet<String> = HashSet::new();
+    let mut in_flight: JoinSet<FinishedBatch> = JoinSet::new();
+    let mut batch: Vec<ChunkReqPayload> = Vec::with_capacity(CHUNKS_PER_BATCH);
+    let mut batch_index = 0;
+    let mut records = records.skip(skip).peekable();
+
+    // The number of input records read into the current batch, including dropped duplicates.
+    let mut batch_records = 0;
+
+    while !interrupt::requested() {
+        let Some(record) = records.next() else {
+            break;
+        };
+        let record = record?;
+        batch_records += 1;
+
+        if !dedupe
+            .as_mut()
+            .is_some_and(|dedupe| dedupe.is_duplicate(&record))
+        {
+            batch.push(record);
+        }
+
+        if batch.len() < CHUNKS_PER_BATCH && records.peek().is_some() {
+            continue;
+        }
+
+        if batch.is_empty() {
+            // Only duplicates were read since the last batch.
+            progress.finish_batch(batch_index, &[], batch_records, Ok(()))?;
+            batch_records = 0;
+            batch_index += 1;
+            continue;
+        }
+
+        create_groups_for_chunks(&settings, &dataset_id, &batch, &mut created_groups).await?;
+
+        while in_flight.len() >= throughput.limit() {
+            if let Some(finished) = in_flight.join_next().await {
+                let finished = finished.unwrap();
+                throughput.record(finished.latency, finished.result.is_ok());
+                progress.finish_batch(
+                    finished.index,
+                    &finished.chunks,
+                    finished.records,
+                    finished.result,
+                )?;
+            }
+        }
+
+        if interrupt::requested() {
+            break;
+        }
+
+        let chunks = std::mem::take(&mut batch);
+        let settings = settings.clone();
+        let dataset_id = dataset_id.clone();
+        let index = batch_index;
+        let records = std::mem::take(&mut batch_records);
+        in_flight.spawn(async move {
+            let started = Instant::now();
+            let result = upload_batch(settings, dataset_id.clone(), &chunks).await;
+            let latency = started.elapsed();
+            telemetry::record_batch(&dataset_id, chunks.len(), latency, result.is_ok());
+            FinishedBatch {
+                index,
+                chunks,
+                records,
+                result,
+                latency,
+            }
+        });
+        batch_index += 1;
+    }
+
+    let interrupted = interrupt::requested();
+    let drain = async {
+        while let Some(finished) = in_flight.join_next().await {
+            let finished = finished.unwrap();
+            progress.finish_batch(
+                finished.index,
+                &finished.chunks,
+                finished.records,
+                finished.result,
+            )?;
+        }
+        Ok::<(), DefaultError>(())
+    };
+
+    if interrupted {
+        if let Ok(result) = tokio::time::timeout(DRAIN_TIMEOUT, drain).await {
+            result?;
+        } else {
+            eprintln!(
+                "Timed out after {}s waiting for {} in-flight batches, they may or may not have been uploaded.",
+                DRAIN_TIMEOUT.as_secs(),
+                in_flight.len()
+            );
+            in_flight.abort_all();
+        }
+    } else {
+        drain.await?;
+    }
+    drop(watch);
+    telemetry::export().await;
+    if let Some(failures) = progress.failures.as_ref() {
+        failures.finish();
+    }
+
+    progress.summary.interrupted = interrupted;
+    progress.summary.duplicates = dedupe.map_or(0, |dedupe| dedupe.dropped);
+
+    audit::record(
+        &settings,
+        "chunk upload",
+        Some(&dataset_id),
+        serde_json::json!({
+            "uploaded": progress.summary.uploaded,
+            "failed_batches": progress.summary.failed_batches,
+            "interrupted": interrupted,
+        }),
+    );
+
+    if interrupted {
+        eprintln!(
+            "Upload stopped after {} chunks, the first {} records are confirmed uploaded.",
+            progress.summary.uploaded, progress.records_completed
+        );
+    } else if progress.summary.failed_batches == 0 {
+        if let Some(checkpoint) = progress.checkpoint.as_ref() {
+            checkpoint.clear();
+        }
+    }
+
+    Ok(progress.summary)
+}
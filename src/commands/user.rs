@@ -0,0 +1,130 @@
+use std::fmt;
+
+use serde_json::{json, Value};
+
+use crate::cli::{SetUserRole, UserRole};
+
+use super::{
+    audit,
+    configure::{require_login, TrieveConfiguration},
+    dataset::DefaultError,
+    output::{ensure_interactive, quiet, success},
+    picker::pick,
+    rest::Rest,
+    style::id,
+};
+
+/// The role level the API stores for `role`.
+pub fn role_level(role: UserRole) -> i64 {
+    match role {
+        UserRole::User => 0,
+        UserRole::Admin => 1,
+        UserRole::Owner => 2,
+    }
+}
+
+pub fn role_name(level: i64) -> &'static str {
+    match level {
+        0 => "user",
+        1 => "admin",
+        2 => "owner",
+        _ => "unknown",
+    }
+}
+
+/// A user with their role in the current organization.
+struct Member {
+    id: String,
+    email: String,
+    role: i64,
+}
+
+impl fmt::Display for Member {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({}) - {}", self.email, role_name(self.role), self.id)
+    }
+}
+
+async fn get_members(settings: &TrieveConfiguration) -> Result<Vec<Member>, DefaultError> {
+    let organization_id = settings.organization_id.to_string();
+    let users: Vec<Value> = Rest::new(settings)
+        .get(&format!("/api/user/organization/{}", organization_id))
+        .await?;
+
+    Ok(users
+        .iter()
+        .map(|user| Member {
+            id: user["id"].as_str().unwrap_or_default().to_string(),
+            email: user["email"].as_str().unwrap_or_default().to_string(),
+            role: user["user_orgs"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|user_org| user_org["organization_id"] == organization_id.as_str())
+                .and_then(|user_org| user_org["role"].as_i64())
+                .unwrap_or_default(),
+        })
+        .collect())
+}
+
+pub async fn set_user_role(
+    settings: TrieveConfiguration,
+    set_role: SetUserRole,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let members = get_members(&settings).await?;
+    let member = match &set_role.user {
+        Some(user) => members
+            .into_iter()
+            .find(|member| member.id == *user || member.email.eq_ignore_ascii_case(user))
+            .ok_or_else(|| DefaultError {
+                message: format!(
+                    "{} is not a member of organization {}",
+                    user, settings.organization_id
+                ),
+            })?,
+        None => {
+            ensure_interactive("the user")?;
+            pick("Select a member:", "members", members)?
+        }
+    };
+
+    let role = role_level(set_role.role);
+    let _: Value = Rest::new(&settings)
+        .put(
+            "/api/user",
+            &json!({
+                "user_id": member.id,
+                "role": role,
+            }),
+        )
+        .await?;
+    audit::record(
+        &settings,
+        "organization user set-role",
+        Some(&member.id),
+        json!({
+            "organization_id": settings.organization_id,
+            "email": member.email,
+            "from": role_name(member.role),
+            "to": role_name(role),
+        }),
+    );
+
+    if !quiet() {
+        success(&format!(
+            "{} ({}) is now {} of organization {}.",
+            member.email,
+            id(&member.id),
+            match set_role.role {
+                UserRole::Admin => "an admin",
+                UserRole::Owner => "an owner",
+                UserRole::User => "a user",
+            },
+            settings.organization_id
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,339 @@
+use std::{
+    collections::HashSet,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use serde_json::Value;
+
+use crate::cli::{InputFormat, ValidateChunks};
+
+use super::{
+    dataset::DefaultError,
+    mapping::{ColumnMapping, FieldMapping},
+    output::quiet,
+    upload::{open_source, ERROR_KEY},
+};
+
+/// The number of invalid records printed before the rest are only counted.
+const MAX_REPORTED_ERRORS: usize = 20;
+
+/// A problem with one input record, found before anything is uploaded.
+pub struct RecordError {
+    /// `line 12` for CSV and JSONL input, `record 12` for a JSON array.
+    pub location: String,
+    pub message: String,
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+/// Checks the value of one mapped field against the type the chunk field expects. CSV cells are
+/// checked as strings.
+fn check_field(mapping: &FieldMapping, value: &Value) -> Option<String> {
+    let name = if mapping.field == mapping.column {
+        mapping.field.clone()
+    } else {
+        format!("{} (column '{}')", mapping.field, mapping.column)
+    };
+    let invalid = |expected: &str| {
+        Some(format!(
+            "{} must be {}, found {}",
+            name,
+            expected,
+            type_name(value)
+        ))
+    };
+
+    match (mapping.field.as_str(), value) {
+        (_, Value::Null) => None,
+        (field, _) if field.starts_with("metadata.") => None,
+        (
+            "chunk_html" | "link" | "tracking_id" | "time_stamp",
+            Value::String(_) | Value::Number(_),
+        ) => None,
+        ("chunk_html" | "link" | "tracking_id" | "time_stamp", _) => invalid("a string"),
+        ("tag_set" | "group_tracking_ids", Value::String(_)) => None,
+        ("tag_set" | "group_tracking_ids", Value::Array(items)) => {
+            items.iter().find(|item| !item.is_string()).map(|item| {
+                format!(
+                    "{} must be a list of strings, found {} in the list",
+                    name,
+                    type_name(item)
+                )
+            })
+        }
+        ("tag_set" | "group_tracking_ids", _) => invalid("a list of strings"),
+        ("num_value" | "weight", Value::Number(_)) => None,
+        ("num_value" | "weight", Value::String(s)) if s.trim().is_empty() => None,
+        ("num_value" | "weight", Value::String(s)) => s
+            .trim()
+            .parse::<f64>()
+            .err()
+            .map(|_| format!("{} must be a number, found '{}'", name, s)),
+        ("num_value" | "weight", _) => invalid("a number"),
+        ("metadata", Value::Object(_)) => None,
+        ("metadata", Value::String(s)) if s.trim().is_empty() => None,
+        ("metadata", Value::String(s)) => match serde_json::from_str::<Value>(s) {
+            Ok(Value::Object(_)) => None,
+            Ok(other) => Some(format!(
+                "{} must be a JSON object, found {}",
+                name,
+                type_name(&other)
+            )),
+            Err(e) => Some(format!("{} is not valid JSON: {}", name, e)),
+        },
+        ("metadata", _) => invalid("an object"),
+        _ => None,
+    }
+}
+
+fn is_blank(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => true,
+        Some(Value::String(s)) => s.trim().is_empty(),
+        Some(_) => false,
+    }
+}
+
+/// Checks one record against the chunk schema: every field must have the expected type and
+/// `chunk_html` must be set. With `known_keys`, keys of JSON objects that are not mapped to a
+/// chunk field are reported too, since they would be dropped silently.
+fn check_record(
+    mapping: &ColumnMapping,
+    lookup: impl Fn(&str) -> Option<Value>,
+    keys: Option<Vec<&String>>,
+    known_keys: &HashSet<&str>,
+) -> Vec<String> {
+    let mut errors = vec![];
+
+    if let Some(keys) = keys {
+        let mut unknown = keys
+            .into_iter()
+            .filter(|key| !known_keys.contains(key.as_str()))
+            .map(|key| format!("'{}'", key))
+            .collect::<Vec<String>>();
+        unknown.sort();
+        if !unknown.is_empty() {
+            errors.push(format!(
+                "unknown field{} {}",
+                if unknown.len() == 1 { "" } else { "s" },
+                unknown.join(", ")
+            ));
+        }
+    }
+
+    for field in &mapping.fields {
+        let value = lookup(&field.column);
+        if field.field == "chunk_html" && is_blank(value.as_ref()) {
+            errors.push(if field.column == "chunk_html" {
+                "missing chunk_html".to_string()
+            } else {
+                format!("missing chunk_html (column '{}')", field.column)
+            });
+            continue;
+        }
+        if let Some(error) = value.and_then(|value| check_field(field, &value)) {
+            errors.push(error);
+        }
+    }
+
+    if !mapping
+        .fields
+        .iter()
+        .any(|field| field.field == "chunk_html")
+    {
+        errors.push("missing chunk_html, no column is mapped to it".to_string());
+    }
+
+    errors
+}
+
+/// Checks JSON records, as read from a JSON array or JSONL file, with the same defaults the
+/// upload uses. Keys that are not mapped to a chunk field are reported with `report_unknown`.
+pub fn check_json(value: &Value, mapping: &ColumnMapping, report_unknown: bool) -> Vec<String> {
+    let mapping = mapping.clone().with_identity_defaults();
+    let known_keys = mapping
+        .fields
+        .iter()
+        .map(|field| field.column.as_str())
+        .chain([ERROR_KEY])
+        .collect::<HashSet<&str>>();
+
+    let Some(record) = value.as_object() else {
+        return vec![format!(
+            "expected a JSON object, found {}",
+            type_name(value)
+        )];
+    };
+
+    check_record(
+        &mapping,
+        |column| record.get(column).cloned(),
+        report_unknown.then(|| record.keys().collect()),
+        &known_keys,
+    )
+}
+
+/// Reads a whole CSV, JSON or JSONL file and returns the problems of every invalid record, with
+/// its line (or position in a JSON array), and the number of records read.
+pub async fn validate_file(
+    path: &Path,
+    format: Option<InputFormat>,
+    mapping: &ColumnMapping,
+) -> Result<(Vec<RecordError>, usize), DefaultError> {
+    let (reader, format) = open_source(None, Some(path), format).await?;
+    let mut errors = vec![];
+    let mut count = 0;
+    let mut report = |location: String, messages: Vec<String>| {
+        errors.extend(messages.into_iter().map(|message| RecordError {
+            location: location.clone(),
+            message,
+        }));
+    };
+
+    match format {
+        InputFormat::Csv => {
+            let mut rdr = csv::Reader::from_reader(reader);
+            let headers = rdr
+                .headers()
+                .map_err(|e| DefaultError {
+                    message: e.to_string(),
+                })?
+                .clone();
+            let mapping = if mapping.is_empty() {
+                ColumnMapping::positional(headers.len())
+            } else {
+                mapping.clone()
+            };
+            let columns = mapping.resolve_csv_columns(&headers)?;
+
+            for record in rdr.into_records() {
+                count += 1;
+                match record {
+                    Ok(record) => {
+                        let line = record.position().map_or(count + 1, |p| p.line() as usize);
+                        let messages = check_record(
+                            &mapping,
+                            |column| {
+                                columns
+                                    .get(column)
+                                    .and_then(|i| record.get(*i))
+                                    .map(|cell| Value::String(cell.to_string()))
+                            },
+                            None,
+                            &HashSet::new(),
+                        );
+                        report(format!("line {}", line), messages);
+                    }
+                    Err(e) => {
+                        let line = e.position().map_or(count + 1, |p| p.line() as usize);
+                        report(format!("line {}", line), vec![e.to_string()]);
+                    }
+                }
+            }
+        }
+        InputFormat::Json => {
+            let records: Value = serde_json::from_reader(reader).map_err(|e| DefaultError {
+                message: format!("Invalid JSON in {}: {}", path.display(), e),
+            })?;
+            let Value::Array(records) = records else {
+                return Err(DefaultError {
+                    message: format!("Expected a JSON array of chunks in {}", path.display()),
+                });
+            };
+
+            for (i, record) in records.iter().enumerate() {
+                count += 1;
+                report(
+                    format!("record {}", i + 1),
+                    check_json(record, mapping, true),
+                );
+            }
+        }
+        InputFormat::Jsonl => {
+            for (i, line) in BufReader::new(reader).lines().enumerate() {
+                let line = line.map_err(|e| DefaultError {
+                    message: e.to_string(),
+                })?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                count += 1;
+                let messages = match serde_json::from_str::<Value>(&line) {
+                    Ok(record) => check_json(&record, mapping, true),
+                    Err(e) => vec![format!("invalid JSON: {}", e)],
+                };
+                report(format!("line {}", i + 1), messages);
+            }
+        }
+    }
+
+    Ok((errors, count))
+}
+
+/// Prints the first `MAX_REPORTED_ERRORS` problems to stderr and how many more there are.
+pub fn print_errors(source: &str, errors: &[RecordError]) {
+    for error in errors.iter().take(MAX_REPORTED_ERRORS) {
+        eprintln!("{}:{}: {}", source, error.location, error.message);
+    }
+    if errors.len() > MAX_REPORTED_ERRORS {
+        eprintln!("... and {} more", errors.len() - MAX_REPORTED_ERRORS);
+    }
+}
+
+/// Validates a file before it is uploaded, so a malformed record does not stop a long upload
+/// halfway through.
+pub async fn validate_before_upload(
+    path: &Path,
+    format: Option<InputFormat>,
+    mapping: &ColumnMapping,
+) -> Result<(), DefaultError> {
+    let (errors, _) = validate_file(path, format, mapping).await?;
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    print_errors(&path.display().to_string(), &errors);
+    Err(DefaultError {
+        message: format!(
+            "{} problems found in {}, nothing was uploaded. Fix them, or pass --no-validate to skip this check",
+            errors.len(),
+            path.display()
+        ),
+    })
+}
+
+pub async fn validate_chunks(validate: ValidateChunks) -> Result<(), DefaultError> {
+    let mapping = ColumnMapping::from_args(&validate.map, validate.mapping_file.as_deref())?;
+    let (errors, count) = validate_file(&validate.file, validate.format, &mapping).await?;
+
+    if errors.is_empty() {
+        if !quiet() {
+            println!(
+                "All {} records in {} are valid.",
+                count,
+                validate.file.display()
+            );
+        }
+        return Ok(());
+    }
+
+    print_errors(&validate.file.display().to_string(), &errors);
+    let invalid = errors
+        .iter()
+        .map(|error| error.location.as_str())
+        .collect::<HashSet<&str>>()
+        .len();
+    Err(DefaultError {
+        message: format!("{} of {} records are invalid", invalid, count),
+    })
+}
@@ -0,0 +1,219 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tabled::{builder::Builder, settings::Style};
+
+use super::{
+    api::TrieveApi,
+    client::{ApiClient, TRIEVE_CLIENT_VERSION},
+    configure::{config_subdir, TrieveConfiguration},
+    dataset::DefaultError,
+    http,
+    output::{quiet, warn},
+    update::{current_version, parse_version},
+};
+
+/// How long a server version is cached before it is checked again on startup.
+const CHECK_INTERVAL_HOURS: i64 = 24;
+/// The startup check must never noticeably slow down a command.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How the API version of a server relates to the version the bundled client was generated from.
+#[derive(Debug, PartialEq)]
+pub enum VersionSkew {
+    Compatible,
+    /// The server is older, so some commands may call routes it does not have.
+    ServerOlder,
+    /// The server is newer, so responses may contain fields the client does not know yet.
+    ServerNewer,
+}
+
+impl VersionSkew {
+    pub fn between(client: &Version, server: &Version) -> Self {
+        match (server.major, server.minor).cmp(&(client.major, client.minor)) {
+            std::cmp::Ordering::Less => VersionSkew::ServerOlder,
+            std::cmp::Ordering::Equal => VersionSkew::Compatible,
+            std::cmp::Ordering::Greater => VersionSkew::ServerNewer,
+        }
+    }
+
+    fn warning(&self, api_url: &str, server: &Version) -> Option<String> {
+        match self {
+            VersionSkew::Compatible => None,
+            VersionSkew::ServerOlder => Some(format!(
+                "Warning: the Trieve server at {} runs API version {}, which is older than the {} this CLI was built for. Some commands may not exist on this server.",
+                api_url, server, TRIEVE_CLIENT_VERSION
+            )),
+            VersionSkew::ServerNewer => Some(format!(
+                "Warning: the Trieve server at {} runs API version {}, which is newer than the {} this CLI was built for. Run `trieve update` if commands fail unexpectedly.",
+                api_url, server, TRIEVE_CLIENT_VERSION
+            )),
+        }
+    }
+}
+
+fn client_version() -> Version {
+    parse_version(TRIEVE_CLIENT_VERSION).expect("TRIEVE_CLIENT_VERSION is a valid version")
+}
+
+/// Reads the API version from the OpenAPI document the server publishes.
+pub async fn server_version(settings: &TrieveConfiguration) -> Result<Version, DefaultError> {
+    let request = http::request(
+        Method::GET,
+        &format!("{}/api-docs/openapi.json", settings.api_url),
+    );
+    let spec: Value = http::send(request, None).await?.json()?;
+
+    let version = spec["info"]["version"]
+        .as_str()
+        .ok_or_else(|| DefaultError {
+            message: "The server's OpenAPI document has no info.version".to_string(),
+        })?;
+    parse_version(version)
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct VersionCache {
+    servers: HashMap<String, CachedVersion>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedVersion {
+    /// `None` when the version could not be looked up, so a server that is down or does not
+    /// publish its version is not asked again on every command.
+    #[serde(default)]
+    version: Option<String>,
+    checked_at: DateTime<Utc>,
+}
+
+fn cache_path() -> Option<PathBuf> {
+    config_subdir("cache")
+        .ok()
+        .map(|dir| dir.join("server-versions.json"))
+}
+
+fn load_cache(path: &Path) -> VersionCache {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|cache| serde_json::from_str(&cache).ok())
+        .unwrap_or_default()
+}
+
+/// Warns on stderr when the server API version differs from the bundled client's. The server
+/// version is cached for a day per API URL, as is a failed or timed out lookup, and any failure is
+/// ignored, so this never gets in the way of the command being run. Set `TRIEVE_NO_VERSION_CHECK=true` to skip it.
+pub async fn warn_on_skew(settings: &TrieveConfiguration) {
+    if settings.api_key.is_empty()
+        || quiet()
+        || env::var("TRIEVE_NO_VERSION_CHECK").unwrap_or_default() == "true"
+    {
+        return;
+    }
+    let Some(path) = cache_path() else {
+        return;
+    };
+
+    let mut cache = load_cache(&path);
+    let cached = cache.servers.get(&settings.api_url).filter(|cached| {
+        Utc::now() - cached.checked_at < chrono::Duration::hours(CHECK_INTERVAL_HOURS)
+    });
+
+    let server = match cached {
+        Some(cached) => cached
+            .version
+            .as_deref()
+            .and_then(|version| parse_version(version).ok()),
+        None => {
+            let server = tokio::time::timeout(CHECK_TIMEOUT, server_version(settings))
+                .await
+                .ok()
+                .and_then(Result::ok);
+            cache.servers.insert(
+                settings.api_url.clone(),
+                CachedVersion {
+                    version: server.as_ref().map(Version::to_string),
+                    checked_at: Utc::now(),
+                },
+            );
+            if let Ok(cache) = serde_json::to_string_pretty(&cache) {
+                let _ = fs::write(&path, cache);
+            }
+            server
+        }
+    };
+    let Some(server) = server else {
+        return;
+    };
+
+    if let Some(warning) =
+        VersionSkew::between(&client_version(), &server).warning(&settings.api_url, &server)
+    {
+        warn(&warning);
+    }
+}
+
+/// Checks the CLI installation and the connection to the server of the current profile.
+pub async fn doctor(settings: TrieveConfiguration) -> Result<(), DefaultError> {
+    let mut builder = Builder::default();
+    builder.push_record(["Check", "Result"]);
+    builder.push_record(["CLI version".to_string(), current_version().to_string()]);
+    builder.push_record([
+        "Client API version".to_string(),
+        TRIEVE_CLIENT_VERSION.to_string(),
+    ]);
+    builder.push_record(["API URL".to_string(), settings.api_url.clone()]);
+
+    let mut warnings = vec![];
+    match server_version(&settings).await {
+        Ok(server) => {
+            builder.push_record(["Server API version".to_string(), server.to_string()]);
+            let skew = VersionSkew::between(&client_version(), &server);
+            builder.push_record([
+                "Version skew".to_string(),
+                match skew {
+                    VersionSkew::Compatible => "none".to_string(),
+                    VersionSkew::ServerOlder => "server is older".to_string(),
+                    VersionSkew::ServerNewer => "server is newer".to_string(),
+                },
+            ]);
+            warnings.extend(skew.warning(&settings.api_url, &server));
+        }
+        Err(e) => {
+            builder.push_record(["Server API version".to_string(), "unreachable".to_string()]);
+            warnings.push(format!(
+                "Could not reach {}: {}",
+                settings.api_url, e.message
+            ));
+        }
+    }
+
+    let credentials = if settings.api_key.is_empty() {
+        warnings.push("No API key configured. Run `trieve login` to get started.".to_string());
+        "missing".to_string()
+    } else {
+        match ApiClient::from(&settings).me().await {
+            Ok(user) => format!("ok ({})", user.email.flatten().unwrap_or_default()),
+            Err(e) => {
+                warnings.push(format!("Could not verify the API key: {}", e.message));
+                "failed".to_string()
+            }
+        }
+    };
+    builder.push_record(["Credentials".to_string(), credentials]);
+
+    println!("{}", builder.build().with(Style::rounded()));
+    for warning in &warnings {
+        println!("{}", warning);
+    }
+
+    Ok(())
+}
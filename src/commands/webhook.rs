@@ -0,0 +1,264 @@
+use std::{collections::HashSet, time::Duration};
+
+use chrono::Local;
+use serde_json::{json, Value};
+use tokio::{
+    io::{self, AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    time::timeout,
+};
+
+use crate::cli::WebhookListen;
+
+use super::{
+    configure::{require_login, TrieveConfiguration},
+    dataset::{dataset_or_default, require_dataset, DefaultError},
+    output::quiet,
+    rest::Rest,
+    style::{bold, dim},
+};
+
+/// The largest webhook request accepted, head and body.
+const MAX_REQUEST_SIZE: usize = 10 * 1024 * 1024;
+
+/// How long a connection may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The headers printed with every request, since they identify the sender and the delivery.
+const SHOWN_HEADERS: [&str; 4] = [
+    "content-type",
+    "user-agent",
+    "x-request-id",
+    "x-trieve-event",
+];
+
+struct WebhookRequest {
+    method: String,
+    path: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+/// Reads a whole HTTP request, with its body as long as `Content-Length` says.
+async fn read_request(stream: &mut TcpStream) -> io::Result<WebhookRequest> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let mut data = Vec::new();
+    let mut buf = [0u8; 8 * 1024];
+
+    let head_end = loop {
+        if let Some(end) = data.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end + 4;
+        }
+        if data.len() >= MAX_REQUEST_SIZE {
+            return Err(invalid("request too large"));
+        }
+        let read = stream.read(&mut buf).await?;
+        if read == 0 {
+            return Err(invalid("connection closed before the request was complete"));
+        }
+        data.extend_from_slice(&buf[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&data[..head_end]).into_owned();
+    let mut lines = head.lines();
+    let mut request_line = lines.next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(path)) = (request_line.next(), request_line.next()) else {
+        return Err(invalid("malformed request line"));
+    };
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect::<Vec<(String, String)>>();
+
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name == "content-length")
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    if head_end + content_length > MAX_REQUEST_SIZE {
+        return Err(invalid("request too large"));
+    }
+    while data.len() < head_end + content_length {
+        let read = stream.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        data.extend_from_slice(&buf[..read]);
+    }
+    data.truncate(head_end + content_length);
+
+    Ok(WebhookRequest {
+        method: method.to_string(),
+        path: path.to_string(),
+        headers,
+        body: data.split_off(head_end),
+    })
+}
+
+async fn respond(stream: &mut TcpStream, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Prints a webhook delivery: its JSON on one line in quiet mode, otherwise a header line, the
+/// identifying headers and the pretty-printed payload.
+fn print_request(request: &WebhookRequest, payload: Option<&Value>) {
+    let body = String::from_utf8_lossy(&request.body);
+    if quiet() {
+        match payload {
+            Some(payload) => println!("{}", payload),
+            None => println!("{}", json!(body)),
+        }
+        return;
+    }
+
+    println!(
+        "{} {} {} {}",
+        dim(&Local::now().format("%H:%M:%S").to_string()),
+        bold(&request.method),
+        request.path,
+        dim(&format!("({} bytes)", request.body.len()))
+    );
+    for (name, value) in &request.headers {
+        if SHOWN_HEADERS.contains(&name.as_str()) {
+            println!("  {} {}", dim(&format!("{}:", name)), value);
+        }
+    }
+    match payload {
+        Some(payload) => println!(
+            "{}",
+            serde_json::to_string_pretty(payload).unwrap_or_default()
+        ),
+        None if !body.is_empty() => println!("{}", body),
+        None => {}
+    }
+    println!();
+}
+
+/// Prints every request sent to the listener, answering each with `200 OK`. With a dataset, JSON
+/// payloads of other datasets are acknowledged but not printed.
+async fn listen(listener: TcpListener, dataset_id: Option<String>) -> io::Result<()> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let request = match timeout(READ_TIMEOUT, read_request(&mut stream)).await {
+            Ok(Ok(request)) => request,
+            Ok(Err(e)) => {
+                eprintln!("Ignored a malformed request: {}", e);
+                respond(&mut stream, "400 Bad Request", r#"{"ok":false}"#).await;
+                continue;
+            }
+            Err(_) => {
+                respond(&mut stream, "408 Request Timeout", r#"{"ok":false}"#).await;
+                continue;
+            }
+        };
+        respond(&mut stream, "200 OK", r#"{"ok":true}"#).await;
+
+        let payload = serde_json::from_slice::<Value>(&request.body).ok();
+        let other_dataset = dataset_id.as_ref().is_some_and(|dataset_id| {
+            payload
+                .as_ref()
+                .and_then(|payload| payload["dataset_id"].as_str())
+                .is_some_and(|id| id != dataset_id)
+        });
+        if !other_dataset {
+            print_request(&request, payload.as_ref());
+        }
+    }
+}
+
+/// Polls the events of a dataset and prints the events created since polling started.
+async fn poll_events(
+    settings: &TrieveConfiguration,
+    dataset_id: &str,
+    interval: Duration,
+) -> Result<(), DefaultError> {
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut first = true;
+
+    loop {
+        let response: Value = Rest::new(settings)
+            .dataset(dataset_id)
+            .post("/api/events", &json!({ "page": 1, "page_size": 100 }))
+            .await?;
+        let events = response["events"].as_array().cloned().unwrap_or_default();
+
+        // Events are returned newest first, so print them in reverse to keep them in order.
+        for event in events.iter().rev() {
+            let id = event["id"].as_str().unwrap_or_default().to_string();
+            if !seen.insert(id) || first {
+                continue;
+            }
+
+            if quiet() {
+                println!("{}", event);
+                continue;
+            }
+            println!(
+                "{} {} {}",
+                dim(event["created_at"].as_str().unwrap_or_default()),
+                bold(event["event_type"].as_str().unwrap_or("event")),
+                dim(event["id"].as_str().unwrap_or_default())
+            );
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&event["event_data"]).unwrap_or_default()
+            );
+            println!();
+        }
+        first = false;
+
+        tokio::time::sleep(interval).await;
+    }
+}
+
+pub async fn webhook_listen(
+    settings: TrieveConfiguration,
+    listen_args: WebhookListen,
+) -> Result<(), DefaultError> {
+    if listen_args.poll {
+        require_login(&settings)?;
+
+        let dataset_id = require_dataset(listen_args.dataset_id, &settings)?;
+        if !quiet() {
+            println!(
+                "Polling the events of dataset {} every {}s. Press Ctrl-C to stop.",
+                dataset_id, listen_args.interval
+            );
+        }
+        return poll_events(
+            &settings,
+            &dataset_id,
+            Duration::from_secs(listen_args.interval.max(1)),
+        )
+        .await;
+    }
+
+    let dataset_id = dataset_or_default(listen_args.dataset_id, &settings);
+    let listener = TcpListener::bind((listen_args.host.as_str(), listen_args.port))
+        .await
+        .map_err(|e| DefaultError {
+            message: format!(
+                "Could not listen on {}:{}: {}",
+                listen_args.host, listen_args.port, e
+            ),
+        })?;
+    if !quiet() {
+        println!(
+            "Listening for webhooks on http://{}:{}. Press Ctrl-C to stop.",
+            listen_args.host, listen_args.port
+        );
+    }
+
+    listen(listener, dataset_id)
+        .await
+        .map_err(|e| DefaultError {
+            message: e.to_string(),
+        })
+}
@@ -0,0 +1,380 @@
+use std::{collections::HashMap, io::stdout, path::Path};
+
+use csv::{ReaderBuilder, Trim, WriterBuilder};
+use serde_json::{json, Map, Value};
+
+use crate::cli::{ClearWords, ImportWords, ListWords};
+
+use super::{
+    audit,
+    configure::{require_login, TrieveConfiguration},
+    dataset::{require_dataset, update_server_configuration, DefaultError},
+    guard,
+    output::{quiet, success, warn},
+    rest::Rest,
+};
+
+/// At most this many problems of an imported file are printed.
+const MAX_REPORTED_PROBLEMS: usize = 20;
+
+/// A list of words stored in the `server_configuration` of a dataset.
+#[derive(Clone, Copy)]
+pub enum WordList {
+    /// Groups of terms that match each other in searches, stored as a list of lists.
+    Synonyms,
+    /// Words left out of full-text searches, stored as a list.
+    StopWords,
+}
+
+impl WordList {
+    /// The field of the `server_configuration` the list is stored in.
+    fn key(self) -> &'static str {
+        match self {
+            WordList::Synonyms => "SYNONYMS",
+            WordList::StopWords => "STOP_WORDS",
+        }
+    }
+
+    /// The subcommand of `dataset` that manages the list.
+    fn command(self) -> &'static str {
+        match self {
+            WordList::Synonyms => "synonyms",
+            WordList::StopWords => "stopwords",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            WordList::Synonyms => "synonym groups",
+            WordList::StopWords => "stop words",
+        }
+    }
+
+    /// The entries of the list in a `server_configuration`. A stop word is an entry of one word.
+    fn entries(self, server_configuration: &Value) -> Vec<Vec<String>> {
+        let strings = |value: &Value| {
+            value
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|word| word.as_str().map(str::to_string))
+                .collect::<Vec<String>>()
+        };
+        let list = &server_configuration[self.key()];
+
+        match self {
+            WordList::Synonyms => list.as_array().into_iter().flatten().map(strings).collect(),
+            WordList::StopWords => strings(list).into_iter().map(|word| vec![word]).collect(),
+        }
+    }
+
+    fn to_value(self, entries: &[Vec<String>]) -> Value {
+        match self {
+            WordList::Synonyms => json!(entries),
+            WordList::StopWords => json!(entries.concat()),
+        }
+    }
+}
+
+/// Reads the terms of a word list file with the line each is on. Every line of synonyms is a
+/// group, and every word of a stop word file is an entry of its own.
+fn read_file(list: WordList, path: &Path) -> Result<Vec<(u64, Vec<String>)>, DefaultError> {
+    let read_error = |e: csv::Error| DefaultError {
+        message: format!("Could not read {}: {}", path.display(), e),
+    };
+
+    let mut reader = ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .comment(Some(b'#'))
+        .trim(Trim::All)
+        .from_path(path)
+        .map_err(read_error)?;
+
+    let mut entries = vec![];
+    for record in reader.records() {
+        let record = record.map_err(read_error)?;
+        let line = record
+            .position()
+            .map(|position| position.line())
+            .unwrap_or_default();
+        let terms = record
+            .iter()
+            .filter(|term| !term.is_empty())
+            .map(str::to_lowercase)
+            .collect::<Vec<String>>();
+
+        match list {
+            WordList::Synonyms if !terms.is_empty() => entries.push((line, terms)),
+            WordList::Synonyms => {}
+            WordList::StopWords => entries.extend(terms.into_iter().map(|term| (line, vec![term]))),
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Checks the entries read from a file and appends them to `existing`. Every problem is reported
+/// with its line, so a file can be fixed in one go.
+fn validate(
+    list: WordList,
+    path: &Path,
+    existing: Vec<Vec<String>>,
+    entries: Vec<(u64, Vec<String>)>,
+) -> Result<Vec<Vec<String>>, DefaultError> {
+    // The line each term was first seen on, 0 for the terms already in the dataset.
+    let mut seen: HashMap<String, u64> = existing
+        .iter()
+        .flatten()
+        .map(|term| (term.clone(), 0))
+        .collect();
+    let mut result = existing;
+    let mut problems = vec![];
+
+    for (line, terms) in entries {
+        let terms = terms
+            .into_iter()
+            .fold(vec![], |mut unique: Vec<String>, term| {
+                if !unique.contains(&term) {
+                    unique.push(term);
+                }
+                unique
+            });
+        match list {
+            WordList::Synonyms => {
+                if terms.len() < 2 {
+                    problems.push(format!(
+                        "line {}: a synonym group needs at least two terms",
+                        line
+                    ));
+                    continue;
+                }
+                let mut valid = true;
+                for term in &terms {
+                    match seen.get(term) {
+                        Some(0) => problems.push(format!(
+                            "line {}: '{}' is already in a synonym group of the dataset",
+                            line, term
+                        )),
+                        Some(other) => problems.push(format!(
+                            "line {}: '{}' is already in the group on line {}",
+                            line, term, other
+                        )),
+                        None => continue,
+                    }
+                    valid = false;
+                }
+                if valid {
+                    seen.extend(terms.iter().map(|term| (term.clone(), line)));
+                    result.push(terms);
+                }
+            }
+            WordList::StopWords => {
+                let word = &terms[0];
+                if word.split_whitespace().count() > 1 {
+                    problems.push(format!("line {}: '{}' is not a single word", line, word));
+                } else if seen.insert(word.clone(), line).is_none() {
+                    result.push(terms);
+                }
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        return Ok(result);
+    }
+
+    let mut message = format!("{} has {} problems:", path.display(), problems.len());
+    for problem in problems.iter().take(MAX_REPORTED_PROBLEMS) {
+        message.push_str("\n  ");
+        message.push_str(problem);
+    }
+    if problems.len() > MAX_REPORTED_PROBLEMS {
+        message.push_str(&format!(
+            "\n  and {} more",
+            problems.len() - MAX_REPORTED_PROBLEMS
+        ));
+    }
+    Err(DefaultError { message })
+}
+
+/// Prints the entries as CSV, one per line, so the output can be imported again.
+fn print_entries(entries: &[Vec<String>]) -> Result<(), DefaultError> {
+    let mut writer = WriterBuilder::new().flexible(true).from_writer(stdout());
+    for entry in entries {
+        writer.write_record(entry).map_err(|e| DefaultError {
+            message: format!("Could not print the list: {}", e),
+        })?;
+    }
+    writer.flush().map_err(|e| DefaultError {
+        message: format!("Could not print the list: {}", e),
+    })
+}
+
+async fn get_entries(
+    settings: &TrieveConfiguration,
+    list: WordList,
+    dataset_id: &str,
+) -> Result<Vec<Vec<String>>, DefaultError> {
+    let dataset: Value = Rest::new(settings)
+        .dataset(dataset_id)
+        .get(&format!("/api/dataset/{}", dataset_id))
+        .await?;
+    Ok(list.entries(&dataset["server_configuration"]))
+}
+
+/// Stores the list in the dataset and warns when the server drops it, as older servers ignore
+/// configuration fields they do not know.
+async fn set_entries(
+    settings: &TrieveConfiguration,
+    list: WordList,
+    dataset_id: &str,
+    entries: &[Vec<String>],
+) -> Result<(), DefaultError> {
+    let changes = Map::from_iter([(list.key().to_string(), list.to_value(entries))]);
+    let dataset = update_server_configuration(settings, dataset_id, changes).await?;
+    if dataset["server_configuration"].get(list.key()).is_none() {
+        warn(&format!(
+            "The server did not keep {}, it may not support {} yet.",
+            list.key(),
+            list.name()
+        ));
+    }
+    Ok(())
+}
+
+pub async fn import_words(
+    settings: TrieveConfiguration,
+    list: WordList,
+    import: ImportWords,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let entries = read_file(list, &import.file)?;
+    let dataset_id = require_dataset(import.dataset_id, &settings)?;
+    let existing = if import.append {
+        get_entries(&settings, list, &dataset_id).await?
+    } else {
+        vec![]
+    };
+    let existing_count = existing.len();
+    let result = validate(list, &import.file, existing, entries)?;
+
+    if import.dry_run {
+        print_entries(&result)?;
+        if !quiet() {
+            eprintln!(
+                "{} {} would be set for dataset {}, nothing was changed.",
+                result.len(),
+                list.name(),
+                dataset_id
+            );
+        }
+        return Ok(());
+    }
+
+    if !import.append {
+        let replaced = get_entries(&settings, list, &dataset_id).await?;
+        if !replaced.is_empty() {
+            guard::confirm_destructive(&format!(
+                "replace the {} {} of dataset {}",
+                replaced.len(),
+                list.name(),
+                dataset_id
+            ))?;
+        }
+    }
+
+    set_entries(&settings, list, &dataset_id, &result).await?;
+    audit::record(
+        &settings,
+        &format!("dataset {} import", list.command()),
+        Some(&dataset_id),
+        json!({
+            "file": import.file,
+            "append": import.append,
+            "count": result.len(),
+        }),
+    );
+
+    if !quiet() {
+        if import.append {
+            success(&format!(
+                "Added {} {} to dataset {}, which has {} now.",
+                result.len() - existing_count,
+                list.name(),
+                dataset_id,
+                result.len()
+            ));
+        } else {
+            success(&format!(
+                "Imported {} {} into dataset {}.",
+                result.len(),
+                list.name(),
+                dataset_id
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn list_words(
+    settings: TrieveConfiguration,
+    list: WordList,
+    list_words: ListWords,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(list_words.dataset_id, &settings)?;
+    let entries = get_entries(&settings, list, &dataset_id).await?;
+
+    if entries.is_empty() && !quiet() {
+        println!("Dataset {} has no {}.", dataset_id, list.name());
+        return Ok(());
+    }
+
+    print_entries(&entries)
+}
+
+pub async fn clear_words(
+    settings: TrieveConfiguration,
+    list: WordList,
+    clear: ClearWords,
+) -> Result<(), DefaultError> {
+    require_login(&settings)?;
+
+    let dataset_id = require_dataset(clear.dataset_id, &settings)?;
+    let entries = get_entries(&settings, list, &dataset_id).await?;
+    if entries.is_empty() {
+        if !quiet() {
+            println!("Dataset {} has no {}.", dataset_id, list.name());
+        }
+        return Ok(());
+    }
+
+    guard::confirm_destructive(&format!(
+        "clear the {} of dataset {}",
+        list.name(),
+        dataset_id
+    ))?;
+
+    set_entries(&settings, list, &dataset_id, &[]).await?;
+    audit::record(
+        &settings,
+        &format!("dataset {} clear", list.command()),
+        Some(&dataset_id),
+        json!({ "count": entries.len() }),
+    );
+
+    if !quiet() {
+        success(&format!(
+            "Cleared the {} {} of dataset {}.",
+            entries.len(),
+            list.name(),
+            dataset_id
+        ));
+    }
+
+    Ok(())
+}
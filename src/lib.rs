@@ -0,0 +1,42 @@
+//! The commands of the Trieve CLI, for Rust tools that want to run them without the `trieve`
+//! binary. The binary is a thin clap front-end over this crate: [`cli`] holds its arguments and
+//! [`commands`] the operations behind them.
+//!
+//! The building blocks, such as [`commands::export::export_dataset`] and
+//! [`commands::upload::upload_chunk_stream`], return their errors instead of exiting. Call
+//! [`commands::output::init`] with `quiet` set first, so they do not print progress.
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use trieve_cli_core::{
+//!     cli::OutputFormat,
+//!     commands::{
+//!         configure::TrieveConfiguration, dataset::DefaultError, export::export_dataset, output,
+//!     },
+//! };
+//!
+//! async fn backup(settings: &TrieveConfiguration, dataset_id: &str) -> Result<usize, DefaultError> {
+//!     output::init(OutputFormat::Text, true);
+//!     export_dataset(settings, dataset_id, Path::new("chunks.jsonl")).await
+//! }
+//! ```
+//!
+//! The browser login is put together from a [`commands::login_server::CallbackServer`] and a
+//! [`commands::browser::Browser`]:
+//!
+//! ```no_run
+//! use trieve_cli_core::commands::{
+//!     browser::{self, Browser},
+//!     login_server::{CallbackServer, LOGIN_TIMEOUT},
+//! };
+//!
+//! async fn login(api_url: &str) -> std::io::Result<Option<String>> {
+//!     let callback = CallbackServer::start(None).await?;
+//!     browser::from_env().open(&callback.login_url(api_url))?;
+//!     Ok(callback.api_key(LOGIN_TIMEOUT).await)
+//! }
+//! ```
+
+pub mod cli;
+pub mod commands;
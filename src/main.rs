@@ -1,182 +1,31 @@
-use crate::commands::configure::TrieveConfiguration;
-use clap::{Args, Parser, Subcommand};
-use commands::configure::TrieveProfile;
-use std::env;
-
-mod commands;
-
-#[derive(Parser)]
-#[command(author, version)]
-#[command(
-    name = "trieve",
-    about = "Trieve CLI - CLI for Trieve Search Product",
-    long_about = "Trieve CLI is a CLI for the Trieve Search Product. 
-    
-    It allows you to interact with the Trieve Search Product from the command line by adding data."
-)]
-#[command(arg_required_else_help(true))]
-struct Cli {
-    #[command(subcommand)]
-    command: Option<Commands>,
-    /// The name of the profile to use
-    #[arg(short, long, env = "TRIEVE_PROFILE")]
-    profile: Option<String>,
-}
-
-#[derive(Subcommand)]
-enum Commands {
-    /// Configures the Trieve CLI with your API key
-    Login(Login),
-    /// Commands for interacting with datasets in the Trieve service
-    #[command(subcommand)]
-    Dataset(DatasetCommands),
-    #[command(subcommand, about = "Commands for managing API Keys")]
-    ApiKey(ApiKeyCommands),
-    /// Command to manage profiles
-    #[command(subcommand)]
-    Profile(Profile),
-    /// Command to interact with organizations
-    #[command(subcommand)]
-    Organization(Organization),
-}
-
-#[derive(Subcommand)]
-enum Profile {
-    /// Switch to a different profile
-    Switch(SwitchProfile),
-    /// Delete a profile
-    Delete(DeleteProfile),
-    /// List all profiles
-    List(ListProfile),
-}
-
-#[derive(Subcommand)]
-enum Organization {
-    /// Switch to a different organization
-    Switch(SwitchOrganization),
-    /// Create an organization
-    Create(CreateOrganization),
-    /// Delete an organization
-    Delete(DeleteOrganization),
-}
-
-#[derive(Subcommand)]
-enum ApiKeyCommands {
-    /// Generate a new API Key
-    Generate(ApiKeyData),
-    //TODO: List API Keys, Delete API Key
-}
-
-#[derive(Subcommand)]
-enum DatasetCommands {
-    /// Create a dataset in the Trieve service
-    Create(CreateDataset),
-    /// List all datasets in the Trieve service
-    List(ListDatasets),
-    /// Delete a dataset in the Trieve service
-    Delete(DeleteDataset),
-    /// Add seed data to a dataset in the Trieve service
-    Example(AddSeedData),
-}
-
-#[derive(Args)]
-struct Login {
-    /// API Key from the Trieve dashboard (https://dashboard.trieve.ai)
-    #[arg(short, long, env = "TRIEVE_API_KEY")]
-    api_key: Option<String>,
-    /// The URL of the Trieve server if you are using a self-hosted version of Trieve
-    #[arg(long, required = false)]
-    api_url: Option<String>,
-    /// Name the profile you are configuring
-    #[arg(long, required = false)]
-    profile_name: Option<String>,
-}
-
-#[derive(Args)]
-struct CreateDataset {
-    /// The name of the dataset
-    #[arg(short, long)]
-    name: Option<String>,
-}
-
-#[derive(Args)]
-struct ListDatasets;
-
-#[derive(Args)]
-struct DeleteDataset {
-    /// The ID of the dataset to delete
-    dataset_id: Option<String>,
-}
-
-#[derive(Args)]
-struct AddSeedData {
-    /// The ID of the dataset to add seed data to
-    dataset_id: Option<String>,
-}
-
-#[derive(Args)]
-struct ApiKeyData {
-    /// The name of the API Key
-    #[arg(short, long)]
-    name: Option<String>,
-    /// The role of the API Key
-    #[arg(short, long)]
-    role: Option<String>,
-}
-
-#[derive(Args)]
-struct SwitchProfile {
-    /// The name of the profile to switch to
-    profile_name: Option<String>,
-}
-
-#[derive(Args)]
-struct DeleteProfile {
-    /// The name of the profile to delete
-    profile_name: Option<String>,
-}
-
-#[derive(Args)]
-struct ListProfile;
-
-#[derive(Args)]
-struct SwitchOrganization {
-    /// The ID of the organization to switch to
-    organization_id: Option<String>,
-}
-
-#[derive(Args)]
-struct CreateOrganization {
-    /// The name of the organization to create
-    name: Option<String>,
-}
-
-#[derive(Args)]
-struct DeleteOrganization {
-    /// The ID of the organization to delete
-    organization_id: Option<String>,
-}
+use clap::Parser;
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+use trieve_cli_core::{cli::*, commands};
 
 #[tokio::main]
 async fn main() {
     let args = Cli::parse();
-    let no_profile =
-        env::var("TRIEVE_NO_PROFILE").unwrap_or_else(|_| String::new()) == "true";
+    commands::i18n::init(args.lang.clone());
+    commands::output::init(args.output, args.quiet);
+    commands::style::init(args.no_color);
+    commands::table::init(args.columns.clone());
+    commands::date::init(args.date_format);
+    commands::pager::init(args.no_pager);
+    commands::telemetry::init(args.otlp_endpoint.clone());
+    let started = Instant::now();
+    let print_stats = args.stats;
 
-    let profiles: TrieveProfile = confy::load("trieve", "profiles")
-        .map_err(|e| {
-            eprintln!("Error loading configuration: {:?}", e);
-        })
-        .unwrap_or_default();
+    let no_profile = env::var("TRIEVE_NO_PROFILE").unwrap_or_else(|_| String::new()) == "true";
 
-    let settings = if no_profile {
-        TrieveConfiguration::from_env().unwrap_or_else(|e| {
-            eprintln!(
-                "Error creating configuration from environment variables: {:?}",
-                e
-            );
-            std::process::exit(1);
-        })
+    let profiles = commands::configure::load_profiles()
+        .unwrap_or_else(|e| exit_with_error("Error reading configuration", &e));
+    let profile_from_flag = args.profile.is_some();
+
+    let (profile_name, profile_settings) = if no_profile {
+        (None, None)
     } else if args.profile.is_some() {
         let profile_name = args.profile.unwrap();
         let profile = profiles
@@ -189,125 +38,672 @@ async fn main() {
             })
             .unwrap();
 
-        profile.settings.clone()
+        (Some(profile.name.clone()), Some(profile.settings.clone()))
     } else {
-        profiles
+        let profile = profiles
             .inner
             .iter()
             .find(|p| p.selected)
             .cloned()
-            .unwrap_or_default()
-            .settings
+            .unwrap_or_default();
+
+        (Some(profile.name), Some(profile.settings))
     };
+    commands::audit::init(profile_name.clone());
+    commands::guard::init(
+        profiles
+            .inner
+            .iter()
+            .find(|p| p.protected && Some(&p.name) == profile_name.as_ref())
+            .map(|p| p.name.clone()),
+    );
+
+    let flags = args
+        .timeout
+        .map(|timeout| ("timeout", timeout.to_string()))
+        .into_iter()
+        .collect::<Vec<_>>();
+    let settings = commands::config::resolve(profile_settings.as_ref(), &flags)
+        .map_err(|e| {
+            eprintln!("Error reading configuration: {}", e);
+            std::process::exit(1);
+        })
+        .unwrap();
+
+    // Shell prompts run `profile current` before every prompt, so it is answered from the
+    // configuration file alone, before the HTTP client is set up.
+    if let Some(Commands::Profile(Profile::Current(current))) = &args.command {
+        commands::profile::current_profile(profile_name, profile_from_flag, &settings, current);
+        return;
+    }
+
+    commands::http::configure(commands::http::NetworkOptions {
+        proxy: args.proxy.clone(),
+        ca_cert: args.ca_cert.clone(),
+        no_verify_tls: args.no_verify_tls,
+        timeout: settings.timeout_secs.map(Duration::from_secs),
+    })
+    .unwrap_or_else(|e| exit_with_error("Error configuring the HTTP client", &e));
+
+    if matches!(
+        args.command,
+        Some(
+            Commands::Dataset(_)
+                | Commands::ApiKey(_)
+                | Commands::Organization(_)
+                | Commands::Search(_)
+                | Commands::Ask(_)
+                | Commands::Eval(_)
+                | Commands::Topic(_)
+                | Commands::Rag(_)
+                | Commands::Bench(_)
+                | Commands::Chunk(_)
+                | Commands::Ingest(_)
+                | Commands::Group(_)
+                | Commands::File(_)
+                | Commands::Apply(_)
+                | Commands::Diff(_)
+                | Commands::Shell
+        )
+    ) {
+        commands::version::warn_on_skew(&settings).await;
+    }
+
+    tokio::spawn(handle_interrupts());
+
+    // Commands run with a profile offer to log in again when its API key has been revoked.
+    if let Some(profile_name) = profile_name
+        .clone()
+        .filter(|_| commands::config::source_of("api_key") == commands::config::Source::Profile)
+    {
+        if !matches!(args.command, Some(Commands::Login(_))) {
+            commands::session::init(profile_name, settings.clone());
+        }
+    }
 
     match args.command {
         Some(Commands::Login(login)) => {
-            commands::configure::login(login, settings).await;
+            commands::configure::login(login, settings)
+                .await
+                .unwrap_or_else(|e| exit_with_error("Error logging in", &e));
         }
         Some(Commands::Dataset(dataset)) => match dataset {
-            DatasetCommands::List(_) => commands::dataset::list_datasets(settings)
+            DatasetCommands::List(list) => commands::dataset::list_datasets(settings, list)
                 .await
-                .map_err(|e| {
-                    eprintln!("Error listing datasets: {:?}", e);
-                    std::process::exit(1);
-                })
-                .unwrap(),
+                .unwrap_or_else(|e| exit_with_error("Error listing datasets", &e)),
             DatasetCommands::Create(create) => {
                 commands::dataset::create_trieve_dataset(settings, create)
                     .await
-                    .map_err(|e| {
-                        eprintln!("Error creating dataset: {:?}", e);
-                        std::process::exit(1);
-                    })
-                    .unwrap();
+                    .unwrap_or_else(|e| exit_with_error("Error creating dataset", &e));
+            }
+            DatasetCommands::Update(update) => {
+                commands::dataset::update_trieve_dataset(settings, update)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error updating dataset", &e));
             }
             DatasetCommands::Delete(delete) => {
                 commands::dataset::delete_trieve_dataset(settings, delete)
                     .await
-                    .map_err(|e| {
-                        eprintln!("Error deleting dataset: {:?}", e);
-                        std::process::exit(1);
-                    })
-                    .unwrap();
+                    .unwrap_or_else(|e| exit_with_error("Error deleting dataset", &e));
+            }
+            DatasetCommands::Restore(restore) => {
+                commands::backup::restore_dataset(settings, restore)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error restoring dataset", &e));
+            }
+            DatasetCommands::Reembed(reembed) => {
+                commands::reembed::reembed_dataset(settings, reembed)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error re-embedding dataset", &e));
+            }
+            DatasetCommands::Use(use_dataset) => {
+                commands::dataset::use_dataset(settings, profile_name, use_dataset)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error setting the default dataset", &e));
+            }
+            DatasetCommands::Export(export) => {
+                commands::export::export_dataset_to_file(settings, export)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error exporting dataset", &e));
+            }
+            DatasetCommands::Limits(limits) => {
+                commands::dataset::dataset_limits(settings, limits)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error getting dataset limits", &e));
+            }
+            DatasetCommands::Stats(stats) => {
+                commands::stats::dataset_stats(settings, stats)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error getting dataset stats", &e));
+            }
+            DatasetCommands::FilesStatus(status) => {
+                commands::file::files_status(settings, status)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error getting file statuses", &e));
+            }
+            DatasetCommands::Usage(usage) => {
+                commands::stats::dataset_usage(settings, usage)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error getting dataset usage", &e));
+            }
+            DatasetCommands::SuggestQueries(suggest) => {
+                commands::suggest::suggest_queries(settings, suggest)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error suggesting queries", &e));
             }
+            DatasetCommands::Template(template) => match template {
+                TemplateCommands::Save(save) => {
+                    commands::templates::save_template(settings, save)
+                        .await
+                        .unwrap_or_else(|e| exit_with_error("Error saving template", &e));
+                }
+                TemplateCommands::List => {
+                    commands::templates::list_templates()
+                        .unwrap_or_else(|e| exit_with_error("Error listing templates", &e));
+                }
+                TemplateCommands::Delete(delete) => {
+                    commands::templates::delete_template(delete)
+                        .unwrap_or_else(|e| exit_with_error("Error deleting template", &e));
+                }
+            },
+            DatasetCommands::Synonyms(command) => match command {
+                WordListCommands::Import(import) => {
+                    commands::word_list::import_words(
+                        settings,
+                        commands::word_list::WordList::Synonyms,
+                        import,
+                    )
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error importing synonyms", &e));
+                }
+                WordListCommands::List(list) => {
+                    commands::word_list::list_words(
+                        settings,
+                        commands::word_list::WordList::Synonyms,
+                        list,
+                    )
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error listing synonyms", &e));
+                }
+                WordListCommands::Clear(clear) => {
+                    commands::word_list::clear_words(
+                        settings,
+                        commands::word_list::WordList::Synonyms,
+                        clear,
+                    )
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error clearing synonyms", &e));
+                }
+            },
+            DatasetCommands::Stopwords(command) => match command {
+                WordListCommands::Import(import) => {
+                    commands::word_list::import_words(
+                        settings,
+                        commands::word_list::WordList::StopWords,
+                        import,
+                    )
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error importing stop words", &e));
+                }
+                WordListCommands::List(list) => {
+                    commands::word_list::list_words(
+                        settings,
+                        commands::word_list::WordList::StopWords,
+                        list,
+                    )
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error listing stop words", &e));
+                }
+                WordListCommands::Clear(clear) => {
+                    commands::word_list::clear_words(
+                        settings,
+                        commands::word_list::WordList::StopWords,
+                        clear,
+                    )
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error clearing stop words", &e));
+                }
+            },
             DatasetCommands::Example(seed_data) => {
                 commands::dataset::add_seed_data(settings, seed_data)
                     .await
-                    .map_err(|e| {
-                        eprintln!("Error adding seed data: {:?}", e);
-                        std::process::exit(1);
-                    })
-                    .unwrap();
+                    .unwrap_or_else(|e| exit_with_error("Error adding seed data", &e));
+            }
+            DatasetCommands::Demo(demo) => {
+                commands::dataset::install_demo(settings, demo)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error installing demo datasets", &e));
+            }
+        },
+        Some(Commands::Alias(alias)) => match alias {
+            AliasCommands::Set(set) => {
+                commands::alias::set_alias(settings, profile_name, set)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error setting alias", &e));
+            }
+            AliasCommands::List(_) => {
+                commands::alias::list_aliases(&settings)
+                    .unwrap_or_else(|e| exit_with_error("Error listing aliases", &e));
+            }
+            AliasCommands::Rm(remove) => {
+                commands::alias::remove_alias(&settings, profile_name, remove)
+                    .unwrap_or_else(|e| exit_with_error("Error removing alias", &e));
             }
         },
         Some(Commands::ApiKey(api_key)) => match api_key {
             ApiKeyCommands::Generate(api_key_data) => {
-                commands::api_key::generate_api_key(settings, api_key_data)
+                commands::apikeys::generate_api_key(settings, api_key_data)
                     .await
-                    .map_err(|e| {
-                        eprintln!("Error generating API Key: {:?}", e);
-                        std::process::exit(1);
-                    })
-                    .unwrap();
+                    .unwrap_or_else(|e| exit_with_error("Error generating API Key", &e));
             }
         },
         Some(Commands::Profile(profile)) => match profile {
             Profile::Switch(switch) => {
                 commands::profile::switch_profile(switch, profiles.to_vec())
-                    .map_err(|e| {
-                        eprintln!("Error switching profile: {:?}", e);
-                        std::process::exit(1);
-                    })
-                    .unwrap();
+                    .unwrap_or_else(|e| exit_with_error("Error switching profile", &e));
             }
             Profile::Delete(delete) => {
                 commands::profile::delete_profile(delete, profiles.to_vec())
-                    .map_err(|e| {
-                        eprintln!("Error deleting profile: {:?}", e);
-                        std::process::exit(1);
-                    })
-                    .unwrap();
+                    .unwrap_or_else(|e| exit_with_error("Error deleting profile", &e));
             }
             Profile::List(_) => {
                 commands::profile::list_profiles(profiles.to_vec())
-                    .map_err(|e| {
-                        eprintln!("Error listing profiles: {:?}", e);
-                        std::process::exit(1);
-                    })
-                    .unwrap();
+                    .unwrap_or_else(|e| exit_with_error("Error listing profiles", &e));
+            }
+            // Answered before the HTTP client is set up.
+            Profile::Current(_) => {}
+            Profile::Protect(protect) => {
+                commands::profile::set_profile_protection(
+                    protect.profile_name,
+                    true,
+                    profiles.to_vec(),
+                )
+                .unwrap_or_else(|e| exit_with_error("Error protecting profile", &e));
+            }
+            Profile::Unprotect(unprotect) => {
+                commands::profile::set_profile_protection(
+                    unprotect.profile_name,
+                    false,
+                    profiles.to_vec(),
+                )
+                .unwrap_or_else(|e| exit_with_error("Error unprotecting profile", &e));
+            }
+        },
+        Some(Commands::Config(config)) => match config {
+            ConfigCommands::Get(get) => {
+                commands::config::get_config(get, &settings)
+                    .unwrap_or_else(|e| exit_with_error("Error reading setting", &e));
+            }
+            ConfigCommands::Set(set) => {
+                commands::config::set_config(set, profile_name)
+                    .unwrap_or_else(|e| exit_with_error("Error changing setting", &e));
+            }
+            ConfigCommands::Unset(unset) => {
+                commands::config::unset_config(unset, profile_name)
+                    .unwrap_or_else(|e| exit_with_error("Error changing setting", &e));
+            }
+            ConfigCommands::List => {
+                commands::config::list_config(&settings, profile_name)
+                    .unwrap_or_else(|e| exit_with_error("Error listing settings", &e));
             }
         },
         Some(Commands::Organization(organization)) => match organization {
             Organization::Switch(switch) => {
-                commands::organization::switch_organization(switch, profiles.to_vec(), settings)
+                commands::organization::switch_organization(switch, profile_name, settings)
                     .await
-                    .map_err(|e| {
-                        eprintln!("Error switching organization: {:?}", e);
-                        std::process::exit(1);
-                    })
-                    .unwrap();
+                    .unwrap_or_else(|e| exit_with_error("Error switching organization", &e));
             }
             Organization::Create(create) => {
                 commands::organization::create_organization(create, settings)
                     .await
-                    .map_err(|e| {
-                        eprintln!("Error creating organization: {:?}", e);
-                        std::process::exit(1);
-                    })
-                    .unwrap();
+                    .unwrap_or_else(|e| exit_with_error("Error creating organization", &e));
+            }
+            Organization::Update(update) => {
+                commands::organization::update_organization(update, settings)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error updating organization", &e));
+            }
+            Organization::Export(export) => {
+                commands::export::export_organization(export, settings)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error exporting organization", &e));
             }
             Organization::Delete(delete) => {
                 commands::organization::delete_organization(delete, settings)
                     .await
-                    .map_err(|e| {
-                        eprintln!("Error deleting organization: {:?}", e);
-                        std::process::exit(1);
-                    })
-                    .unwrap();
+                    .unwrap_or_else(|e| exit_with_error("Error deleting organization", &e));
+            }
+            Organization::Invitations(invitations) => match invitations {
+                InvitationCommands::List(list) => {
+                    commands::invitation::list_invitations(settings, list)
+                        .await
+                        .unwrap_or_else(|e| exit_with_error("Error listing invitations", &e));
+                }
+                InvitationCommands::Create(create) => {
+                    commands::invitation::create_invitations(settings, create)
+                        .await
+                        .unwrap_or_else(|e| exit_with_error("Error creating invitations", &e));
+                }
+                InvitationCommands::Revoke(revoke) => {
+                    commands::invitation::revoke_invitations(settings, revoke)
+                        .await
+                        .unwrap_or_else(|e| exit_with_error("Error revoking invitations", &e));
+                }
+                InvitationCommands::Resend(resend) => {
+                    commands::invitation::resend_invitations(settings, resend)
+                        .await
+                        .unwrap_or_else(|e| exit_with_error("Error resending invitations", &e));
+                }
+            },
+            Organization::Users(users) => match users {
+                UserCommands::SetRole(set_role) => {
+                    commands::user::set_user_role(settings, set_role)
+                        .await
+                        .unwrap_or_else(|e| exit_with_error("Error setting role", &e));
+                }
+            },
+            Organization::Alias(alias) => match alias {
+                OrganizationAliasCommands::Set(set) => {
+                    commands::organization::set_organization_alias(settings, profile_name, set)
+                        .await
+                        .unwrap_or_else(|e| exit_with_error("Error setting alias", &e));
+                }
+                OrganizationAliasCommands::List(_) => {
+                    commands::organization::list_organization_aliases(&settings)
+                        .unwrap_or_else(|e| exit_with_error("Error listing aliases", &e));
+                }
+                OrganizationAliasCommands::Rm(remove) => {
+                    commands::organization::remove_organization_alias(
+                        &settings,
+                        profile_name,
+                        remove,
+                    )
+                    .unwrap_or_else(|e| exit_with_error("Error removing alias", &e));
+                }
+            },
+        },
+        Some(Commands::Search(search)) => match search.command {
+            Some(SearchCommands::Compare(compare)) => {
+                commands::search::compare(settings, compare)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error comparing searches", &e));
+            }
+            Some(SearchCommands::Save(save)) => {
+                commands::saved_searches::save_search(save)
+                    .unwrap_or_else(|e| exit_with_error("Error saving search", &e));
+            }
+            Some(SearchCommands::Run(run)) => {
+                commands::saved_searches::run_search(settings, run)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error running saved search", &e));
+            }
+            Some(SearchCommands::List) => {
+                commands::saved_searches::list_searches()
+                    .unwrap_or_else(|e| exit_with_error("Error listing saved searches", &e));
+            }
+            Some(SearchCommands::Delete(delete)) => {
+                commands::saved_searches::delete_search(delete)
+                    .unwrap_or_else(|e| exit_with_error("Error deleting saved search", &e));
+            }
+            Some(SearchCommands::Batch(batch)) => {
+                commands::search::batch(settings, batch)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error running batch search", &e));
+            }
+            None => {
+                commands::search::search(settings, search.query)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error searching dataset", &e));
+            }
+        },
+        Some(Commands::Ask(ask)) => {
+            commands::ask::ask(settings, ask)
+                .await
+                .unwrap_or_else(|e| exit_with_error("Error answering question", &e));
+        }
+        Some(Commands::Eval(eval)) => {
+            commands::eval::eval(settings, eval)
+                .await
+                .unwrap_or_else(|e| exit_with_error("Error evaluating search", &e));
+        }
+        Some(Commands::Topic(topic)) => match topic {
+            TopicCommands::Export(export) => {
+                commands::topic::export_topics(settings, export)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error exporting topics", &e));
+            }
+        },
+        Some(Commands::Rag(rag)) => match rag {
+            RagCommands::Test(test) => {
+                commands::rag::rag_test(settings, test)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error testing RAG prompts", &e));
+            }
+        },
+        Some(Commands::Bench(bench)) => match bench {
+            BenchCommands::Ingest(ingest) => {
+                commands::bench::bench_ingest(settings, ingest)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error running ingest benchmark", &e));
+            }
+            BenchCommands::Search(search) => {
+                commands::bench::bench_search(settings, search)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error running search benchmark", &e));
+            }
+        },
+        Some(Commands::Chunk(chunk)) => match chunk {
+            ChunkCommands::Upload(upload) => {
+                commands::chunk::upload_chunks(settings, upload)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error uploading chunks", &e));
+            }
+            ChunkCommands::Validate(validate) => {
+                commands::validate::validate_chunks(validate)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error validating chunks", &e));
+            }
+            ChunkCommands::Get(get) => {
+                commands::chunk::get_chunk(settings, get)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error getting chunk", &e));
+            }
+            ChunkCommands::Create(create) => {
+                commands::chunk::create_chunks(settings, create)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error creating chunks", &e));
+            }
+            ChunkCommands::Generate(generate) => {
+                commands::synthetic::generate_chunks(settings, generate)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error generating chunks", &e));
+            }
+            ChunkCommands::Split(split) => {
+                commands::chunk::split_chunks(settings, split)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error splitting chunks", &e));
+            }
+            ChunkCommands::Retag(retag) => {
+                commands::chunk::retag_chunks(settings, retag)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error retagging chunks", &e));
+            }
+            ChunkCommands::SetMetadata(set_metadata) => {
+                commands::chunk::set_chunk_metadata(settings, set_metadata)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error updating chunk metadata", &e));
+            }
+            ChunkCommands::Boost(boost) => {
+                commands::chunk::boost_chunks(settings, boost)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error boosting chunks", &e));
+            }
+        },
+        Some(Commands::Ingest(ingest)) => match ingest {
+            IngestCommands::Pdf(pdf) => {
+                commands::ingest::ingest_pdf(settings, pdf)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error ingesting PDF files", &e));
+            }
+            IngestCommands::Git(git) => {
+                commands::ingest::ingest_git(settings, git)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error ingesting repository", &e));
+            }
+            IngestCommands::NotionExport(export) => {
+                commands::ingest::ingest_notion_export(settings, export)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error ingesting Notion export", &e));
+            }
+            IngestCommands::ConfluenceExport(export) => {
+                commands::ingest::ingest_confluence_export(settings, export)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error ingesting Confluence export", &e));
+            }
+            IngestCommands::Rss(rss) => {
+                commands::ingest::ingest_rss(settings, rss)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error ingesting feed", &e));
+            }
+            IngestCommands::OpenApi(openapi) => {
+                commands::ingest::ingest_openapi(settings, openapi)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error ingesting OpenAPI spec", &e));
+            }
+        },
+        Some(Commands::Webhook(webhook)) => match webhook {
+            WebhookCommands::Listen(listen) => {
+                commands::webhook::webhook_listen(settings, listen)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error listening for webhooks", &e));
             }
         },
+        Some(Commands::Shell) => {
+            commands::shell::shell(settings)
+                .await
+                .unwrap_or_else(|e| exit_with_error("Error in shell", &e));
+        }
+        Some(Commands::Group(group)) => match group {
+            GroupCommands::Search(search) => {
+                commands::search::search_group(settings, search)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error searching group", &e));
+            }
+        },
+        Some(Commands::File(file)) => match file {
+            FileCommands::Upload(upload) => {
+                commands::file_upload::upload_files(settings, upload)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error uploading files", &e));
+            }
+            FileCommands::List(list) => {
+                commands::file::list_files(settings, list)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error listing files", &e));
+            }
+            FileCommands::Chunks(chunks) => {
+                commands::file::list_file_chunks(settings, chunks)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error listing file chunks", &e));
+            }
+            FileCommands::Get(get) => {
+                commands::file::get_file(settings, get)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error downloading file", &e));
+            }
+            FileCommands::Delete(delete) => {
+                commands::file::delete_file(settings, delete)
+                    .await
+                    .unwrap_or_else(|e| exit_with_error("Error deleting file", &e));
+            }
+        },
+        Some(Commands::Apply(apply)) => {
+            commands::apply::apply(settings, apply)
+                .await
+                .unwrap_or_else(|e| exit_with_error("Error applying manifest", &e));
+        }
+        Some(Commands::Diff(diff)) => {
+            commands::diff::diff(settings, diff)
+                .await
+                .unwrap_or_else(|e| exit_with_error("Error comparing manifest", &e));
+        }
+        Some(Commands::Jobs(jobs)) => match jobs {
+            JobsCommands::List(_) => {
+                commands::jobs::list_jobs()
+                    .unwrap_or_else(|e| exit_with_error("Error listing jobs", &e));
+            }
+            JobsCommands::Logs(logs) => {
+                commands::jobs::job_logs(logs)
+                    .unwrap_or_else(|e| exit_with_error("Error reading job logs", &e));
+            }
+            JobsCommands::Pipeline(pipeline) => {
+                commands::pipeline::run_pipeline(pipeline, profile_name)
+                    .unwrap_or_else(|e| exit_with_error("Error running pipeline", &e));
+            }
+            JobsCommands::Run(run) => {
+                commands::jobs::run_job(run)
+                    .unwrap_or_else(|e| exit_with_error("Error running job", &e));
+            }
+        },
+        Some(Commands::Audit(audit)) => match audit {
+            AuditCommands::List(list) => {
+                commands::audit::list_audit(list)
+                    .unwrap_or_else(|e| exit_with_error("Error reading the audit log", &e));
+            }
+        },
+        Some(Commands::Open(open)) => match open {
+            OpenCommands::Dataset(open) => {
+                commands::open::open_dataset(settings, open)
+                    .unwrap_or_else(|e| exit_with_error("Error opening the dataset", &e));
+            }
+            OpenCommands::Org(open) => {
+                commands::open::open_organization(settings, open)
+                    .unwrap_or_else(|e| exit_with_error("Error opening the organization", &e));
+            }
+            OpenCommands::Search(open) => {
+                commands::open::open_search(settings, open)
+                    .unwrap_or_else(|e| exit_with_error("Error opening the search UI", &e));
+            }
+        },
+        Some(Commands::Update(update)) => {
+            commands::update::update(update)
+                .await
+                .unwrap_or_else(|e| exit_with_error("Error updating the Trieve CLI", &e));
+        }
+        Some(Commands::Doctor) => {
+            commands::version::doctor(settings)
+                .await
+                .unwrap_or_else(|e| exit_with_error("Error running checks", &e));
+        }
         _ => {
             println!("Command not implemented yet");
         }
     }
+
+    if print_stats {
+        commands::http::print_stats(started.elapsed());
+    }
+
+    let code = commands::output::exit_code();
+    if code != 0 {
+        std::process::exit(code);
+    }
+}
+
+/// Reports a failed command and exits with the code it maps to.
+fn exit_with_error(context: &str, error: &impl commands::output::CommandError) -> ! {
+    std::process::exit(commands::output::report_error(context, error))
+}
+
+/// Exits on Ctrl-C, unless an operation that stops cleanly is running, such as an upload, which
+/// is asked to stop instead. A second Ctrl-C exits immediately; progress up to the last finished
+/// batch is already in the checkpoint at that point.
+async fn handle_interrupts() {
+    while tokio::signal::ctrl_c().await.is_ok() {
+        if commands::interrupt::requested() || !commands::interrupt::request() {
+            std::process::exit(commands::output::EXIT_INTERRUPTED);
+        }
+
+        eprintln!("\nInterrupted, waiting for in-flight requests to finish. Press Ctrl-C again to exit immediately.");
+    }
 }
@@ -0,0 +1,171 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{stderr, stdout, Trieve, API_KEY, DATASET_ID};
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const OTHER_DATASET_ID: &str = "00000000-0000-0000-0000-0000000000d2";
+
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/user/api_key"))
+        .and(header("Authorization", API_KEY))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_json(json!({ "api_key": "tr-generated" })),
+        )
+        .mount(&server)
+        .await;
+    server
+}
+
+/// Runs `api-key generate` with `args` and returns the body of the key request it sent.
+async fn generated_key_request(args: &[&str]) -> Value {
+    let server = mock_server().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["api-key", "generate", "--name", "ci", "--quiet"])
+        .args(args)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "tr-generated\n");
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    requests[0].body_json().unwrap()
+}
+
+#[tokio::test]
+async fn api_key_generate_sends_the_role_without_restrictions() {
+    for (role, value) in [
+        ("read-write", 1),
+        ("Read + Write", 1),
+        ("rw", 1),
+        ("1", 1),
+        ("read", 0),
+        ("Read", 0),
+        ("0", 0),
+    ] {
+        assert_eq!(
+            generated_key_request(&["--role", role]).await,
+            json!({ "name": "ci", "role": value }),
+            "role {}",
+            role
+        );
+    }
+}
+
+#[tokio::test]
+async fn api_key_generate_restricts_the_key_to_datasets() {
+    assert_eq!(
+        generated_key_request(&[
+            "--role",
+            "read",
+            "--dataset-id",
+            DATASET_ID,
+            "--dataset-id",
+            OTHER_DATASET_ID,
+        ])
+        .await,
+        json!({
+            "name": "ci",
+            "role": 0,
+            "dataset_ids": [DATASET_ID, OTHER_DATASET_ID],
+        })
+    );
+}
+
+#[tokio::test]
+async fn api_key_generate_restricts_the_key_to_scopes() {
+    assert_eq!(
+        generated_key_request(&[
+            "--role",
+            "read-write",
+            "--scope",
+            "chunk.create",
+            "--scope",
+            "search",
+        ])
+        .await,
+        json!({
+            "name": "ci",
+            "role": 1,
+            "scopes": ["chunk.create", "search"],
+        })
+    );
+}
+
+#[tokio::test]
+async fn api_key_generate_restricts_the_key_to_datasets_and_scopes() {
+    assert_eq!(
+        generated_key_request(&[
+            "--role",
+            "read",
+            "--dataset-id",
+            DATASET_ID,
+            "--scope",
+            "search",
+        ])
+        .await,
+        json!({
+            "name": "ci",
+            "role": 0,
+            "dataset_ids": [DATASET_ID],
+            "scopes": ["search"],
+        })
+    );
+}
+
+#[tokio::test]
+async fn api_key_generate_rejects_an_unknown_role_without_a_request() {
+    let server = mock_server().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["api-key", "generate", "--name", "ci", "--role", "admin"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("Invalid role: admin"),
+        "{}",
+        stderr(&output)
+    );
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn api_key_generate_rejects_an_invalid_dataset_id_without_a_request() {
+    let server = mock_server().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args([
+            "api-key",
+            "generate",
+            "--name",
+            "ci",
+            "--role",
+            "read",
+            "--dataset-id",
+            "not-a-dataset",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("Invalid dataset ID not-a-dataset"),
+        "{}",
+        stderr(&output)
+    );
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
@@ -0,0 +1,536 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{fs, io::Write, os::unix::fs::PermissionsExt, process::Stdio};
+
+use common::{run_typing, stderr, stdout, Trieve, DATASET_ID};
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+async fn mock_create_chunk(server: &MockServer) {
+    Mock::given(method("POST"))
+        .and(path("/api/chunk"))
+        .and(header("TR-Dataset", DATASET_ID))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "chunk_metadata": { "id": "00000000-0000-0000-0000-0000000000c1" }
+        })))
+        .mount(server)
+        .await;
+}
+
+/// The bodies of the `POST /api/chunk` requests the server received, in order.
+async fn created_chunks(server: &MockServer) -> Vec<Value> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.url.path() == "/api/chunk")
+        .map(|request| request.body_json().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn chunk_create_sends_the_chunk_and_prints_its_id() {
+    let server = MockServer::start().await;
+    mock_create_chunk(&server).await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args([
+            "chunk",
+            "create",
+            "--dataset-id",
+            DATASET_ID,
+            "--html",
+            "<p>Hello</p>",
+            "--tag",
+            "greeting",
+            "--tracking-id",
+            "hello",
+            "--quiet",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "00000000-0000-0000-0000-0000000000c1\n");
+
+    let chunks = created_chunks(&server).await;
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0]["chunk_html"], "<p>Hello</p>");
+    assert_eq!(chunks[0]["tag_set"], json!(["greeting"]));
+    assert_eq!(chunks[0]["tracking_id"], "hello");
+    assert_eq!(chunks[0]["upsert_by_tracking_id"], true);
+}
+
+#[tokio::test]
+async fn chunk_create_uploads_the_records_of_a_jsonl_file() {
+    let server = MockServer::start().await;
+    mock_create_chunk(&server).await;
+
+    let trieve = Trieve::new();
+    let file = trieve.home.path().join("chunks.jsonl");
+    fs::write(
+        &file,
+        "{\"chunk_html\": \"one\", \"tracking_id\": \"1\"}\n{\"chunk_html\": \"two\", \"tracking_id\": \"2\"}\n",
+    )
+    .unwrap();
+
+    let output = trieve
+        .logged_in(&server)
+        .args(["chunk", "create", "--dataset-id", DATASET_ID, "--quiet"])
+        .arg(&file)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let chunks = created_chunks(&server)
+        .await
+        .into_iter()
+        .flat_map(|body| body.as_array().cloned().unwrap_or_else(|| vec![body]))
+        .map(|chunk| chunk["chunk_html"].as_str().unwrap_or_default().to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(chunks, ["one", "two"]);
+}
+
+#[tokio::test]
+async fn chunk_create_reads_jsonl_from_stdin() {
+    let server = MockServer::start().await;
+    mock_create_chunk(&server).await;
+
+    let mut child = Trieve::new()
+        .logged_in(&server)
+        .args([
+            "chunk",
+            "create",
+            "-",
+            "--dataset-id",
+            DATASET_ID,
+            "--quiet",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"{\"chunk_html\": \"from stdin\"}\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let chunks = created_chunks(&server).await;
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0][0]["chunk_html"], "from stdin");
+}
+
+#[tokio::test]
+async fn chunk_generate_uploads_the_generated_chunks_in_batches() {
+    let server = MockServer::start().await;
+    mock_create_chunk(&server).await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args([
+            "chunk",
+            "generate",
+            "--dataset-id",
+            DATASET_ID,
+            "--count",
+            "250",
+            "--distribution",
+            "faq",
+            "--quiet",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let batches = created_chunks(&server).await;
+    assert_eq!(batches.len(), 3);
+    let chunks = batches
+        .iter()
+        .flat_map(|batch| batch.as_array().unwrap().clone())
+        .collect::<Vec<Value>>();
+    assert_eq!(chunks.len(), 250);
+    assert!(chunks.iter().all(|chunk| {
+        chunk["tag_set"][0] == "trieve-generated"
+            && chunk["metadata"]["distribution"] == "faq"
+            && chunk["chunk_html"].as_str().unwrap().starts_with("<h3>")
+    }));
+}
+
+#[test]
+fn chunk_generate_is_repeatable_with_a_seed() {
+    let trieve = Trieve::new();
+    let generate = |seed: &str| {
+        let output = trieve
+            .command()
+            .args([
+                "chunk",
+                "generate",
+                "--count",
+                "20",
+                "--dry-run",
+                "--seed",
+                seed,
+            ])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", stderr(&output));
+        stdout(&output)
+    };
+
+    let chunks = generate("42");
+    assert_eq!(chunks.lines().count(), 20);
+    assert_eq!(chunks, generate("42"));
+    assert_ne!(chunks, generate("43"));
+}
+
+/// The bodies of the `PUT /api/chunk` requests the server received, sorted by chunk.
+async fn updated_chunks(server: &MockServer) -> Vec<Value> {
+    let mut updates = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.method.as_str() == "PUT" && request.url.path() == "/api/chunk")
+        .map(|request| request.body_json().unwrap())
+        .collect::<Vec<Value>>();
+    updates.sort_by_key(|update: &Value| update["tracking_id"].to_string());
+    updates
+}
+
+#[tokio::test]
+async fn chunk_boost_sends_the_fulltext_and_semantic_boosts() {
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .and(path("/api/chunk"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["chunk", "boost", "pricing-page", "--dataset-id", DATASET_ID])
+        .args(["--fulltext", "2.0", "--semantic-phrase", "pricing"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        updated_chunks(&server).await,
+        [json!({
+            "tracking_id": "pricing-page",
+            "fulltext_boost": { "phrase": "pricing", "boost_factor": 2.0 },
+            "semantic_boost": { "phrase": "pricing", "distance_factor": 0.5 },
+        })]
+    );
+}
+
+#[tokio::test]
+async fn chunk_boost_from_a_csv_file_boosts_every_row() {
+    let server = MockServer::start().await;
+    Mock::given(method("PUT"))
+        .and(path("/api/chunk"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+    let trieve = Trieve::new();
+    let boosts = trieve.home.path().join("boosts.csv");
+    fs::write(
+        &boosts,
+        "id,fulltext,fulltext_phrase,semantic,semantic_phrase\n\
+         pricing-page,3,plans,,\n\
+         enterprise-page,,,0.8,enterprise pricing\n",
+    )
+    .unwrap();
+
+    let output = trieve
+        .logged_in(&server)
+        .args([
+            "chunk",
+            "boost",
+            "--dataset-id",
+            DATASET_ID,
+            "--quiet",
+            "--file",
+        ])
+        .arg(&boosts)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "2\n");
+    assert_eq!(
+        updated_chunks(&server).await,
+        [
+            json!({
+                "tracking_id": "enterprise-page",
+                "semantic_boost": { "phrase": "enterprise pricing", "distance_factor": 0.8 },
+            }),
+            json!({
+                "tracking_id": "pricing-page",
+                "fulltext_boost": { "phrase": "plans", "boost_factor": 3.0 },
+            }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn chunk_boost_from_a_csv_file_with_an_invalid_row_changes_nothing() {
+    let server = MockServer::start().await;
+    let trieve = Trieve::new();
+    let boosts = trieve.home.path().join("boosts.csv");
+    fs::write(
+        &boosts,
+        "id,fulltext,fulltext_phrase,semantic,semantic_phrase\n\
+         pricing-page,3,plans,,\n\
+         enterprise-page,2,,,\n",
+    )
+    .unwrap();
+
+    let output = trieve
+        .logged_in(&server)
+        .args(["chunk", "boost", "--dataset-id", DATASET_ID, "--file"])
+        .arg(&boosts)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("line 3"), "{}", stderr(&output));
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+async fn mock_get_chunk(server: &MockServer, chunk: Value) {
+    Mock::given(method("GET"))
+        .and(path("/api/chunk/tracking_id/pricing-page"))
+        .and(header("TR-Dataset", DATASET_ID))
+        .respond_with(ResponseTemplate::new(200).set_body_json(chunk))
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/chunk_group/chunks"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn chunk_get_render_prints_the_html_as_text() {
+    let server = MockServer::start().await;
+    mock_get_chunk(
+        &server,
+        json!({
+            "id": "00000000-0000-0000-0000-0000000000c1",
+            "tracking_id": "pricing-page",
+            "tag_set": ["pricing"],
+            "chunk_html": "<h2>Plans &amp; pricing</h2>\
+                <p>Every plan includes  <b>unlimited</b> seats.<br>See the \
+                <a href=\"https://example.com/faq\">FAQ</a>.</p>\
+                <ul><li>Free</li><li>Pro, <code>$20</code> a month</li></ul>\
+                <ol start=\"3\"><li>Enterprise</li></ol>\
+                <script>track()</script>",
+        }),
+    )
+    .await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args([
+            "chunk",
+            "get",
+            "pricing-page",
+            "--dataset-id",
+            DATASET_ID,
+            "--render",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "id:       00000000-0000-0000-0000-0000000000c1\n\
+         tracking: pricing-page\n\
+         tags:     pricing\n\
+         \n\
+         ## Plans & pricing\n\
+         \n\
+         Every plan includes unlimited seats.\n\
+         See the FAQ [1].\n\
+         \n\
+         • Free\n\
+         • Pro, `$20` a month\n\
+         \n\
+         3. Enterprise\n\
+         \n\
+         [1] https://example.com/faq\n\
+         \n\
+         source: not in any group\n"
+    );
+}
+
+#[tokio::test]
+async fn chunk_get_web_opens_the_link_of_the_chunk() {
+    let server = MockServer::start().await;
+    mock_get_chunk(
+        &server,
+        json!({
+            "id": "00000000-0000-0000-0000-0000000000c1",
+            "link": "https://example.com/pricing",
+            "chunk_html": "<p>Plans</p>",
+        }),
+    )
+    .await;
+    let trieve = Trieve::new();
+    let opened = trieve.home.path().join("opened");
+    let browser = trieve.home.path().join("browser");
+    fs::write(
+        &browser,
+        format!("#!/bin/sh\necho \"$1\" > {}\n", opened.display()),
+    )
+    .unwrap();
+    fs::set_permissions(&browser, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output = trieve
+        .logged_in(&server)
+        .env("BROWSER", &browser)
+        .args([
+            "chunk",
+            "get",
+            "pricing-page",
+            "--dataset-id",
+            DATASET_ID,
+            "--render",
+            "--web",
+            "--quiet",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "Plans\n");
+    // The browser is not waited for.
+    for _ in 0..50 {
+        if opened.exists() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(
+        fs::read_to_string(&opened).unwrap(),
+        "https://example.com/pricing\n"
+    );
+}
+
+/// A server with one chunk tagged `draft`, which accepts chunk updates.
+async fn mock_chunk_updates() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/chunks/scroll"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "chunks": [{ "id": "c1", "tag_set": ["draft"], "metadata": { "source": "docs" } }],
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/api/chunk"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+    server
+}
+
+/// Runs a bulk update with a protected profile, first typing the wrong name and then the right
+/// one, and checks that only the second run changed chunks.
+async fn assert_protected(server: &MockServer, args: &[&str], action: &str) {
+    let trieve = Trieve::new();
+    trieve.protected(server);
+
+    let output = run_typing(trieve.command().args(args), "yes");
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains(action), "{}", stderr(&output));
+    assert!(stderr(&output).contains("did not match profile 'production'"));
+    assert!(updated_chunks(server).await.is_empty());
+
+    let output = run_typing(trieve.command().args(args), "production");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!updated_chunks(server).await.is_empty());
+}
+
+#[tokio::test]
+async fn chunk_retag_with_a_protected_profile_requires_typing_its_name() {
+    let server = mock_chunk_updates().await;
+
+    assert_protected(
+        &server,
+        &[
+            "chunk",
+            "retag",
+            "--dataset-id",
+            DATASET_ID,
+            "--filter",
+            "tag_set=draft",
+            "--remove-tag",
+            "draft",
+        ],
+        "retag the chunks of dataset",
+    )
+    .await;
+    assert_eq!(updated_chunks(&server).await[0]["tag_set"], json!([]));
+}
+
+#[tokio::test]
+async fn chunk_set_metadata_with_a_protected_profile_requires_typing_its_name() {
+    let server = mock_chunk_updates().await;
+
+    assert_protected(
+        &server,
+        &[
+            "chunk",
+            "set-metadata",
+            "--dataset-id",
+            DATASET_ID,
+            "--filter",
+            "tag_set=draft",
+            "--set",
+            "reviewed=true",
+        ],
+        "change the metadata of the chunks of dataset",
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn chunk_boost_from_a_file_with_a_protected_profile_requires_typing_its_name() {
+    let server = mock_chunk_updates().await;
+    let boosts = tempfile::NamedTempFile::new().unwrap();
+    fs::write(
+        boosts.path(),
+        "id,fulltext,fulltext_phrase,semantic,semantic_phrase\npricing-page,3,plans,,\n",
+    )
+    .unwrap();
+
+    assert_protected(
+        &server,
+        &[
+            "chunk",
+            "boost",
+            "--dataset-id",
+            DATASET_ID,
+            "--file",
+            boosts.path().to_str().unwrap(),
+        ],
+        "boost 1 chunks of dataset",
+    )
+    .await;
+}
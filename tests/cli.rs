@@ -0,0 +1,9 @@
+use clap::CommandFactory;
+use trieve_cli_core::cli::Cli;
+
+/// Catches conflicting flags, such as a subcommand reusing the short flag of a global option,
+/// which clap only reports when the command is built.
+#[test]
+fn cli_definition_is_valid() {
+    Cli::command().debug_assert();
+}
@@ -0,0 +1,156 @@
+//! Runs the `trieve` binary against a wiremock server, with a home directory of its own so the
+//! profiles of the machine running the tests are neither read nor changed. Windows looks the config
+//! directory up by its known folder rather than the environment, so the tests only run on Unix.
+
+#![allow(dead_code)]
+
+use std::{
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+use tempfile::TempDir;
+use wiremock::MockServer;
+
+pub const ORGANIZATION_ID: &str = "00000000-0000-0000-0000-0000000000a1";
+pub const DATASET_ID: &str = "00000000-0000-0000-0000-0000000000d1";
+pub const API_KEY: &str = "tr-test";
+
+pub struct Trieve {
+    pub home: TempDir,
+}
+
+impl Trieve {
+    pub fn new() -> Self {
+        Trieve {
+            home: TempDir::new().expect("could not create a temporary home"),
+        }
+    }
+
+    /// Where the profiles are kept when the home is `self.home`.
+    pub fn config_dir(&self) -> PathBuf {
+        if cfg!(target_os = "macos") {
+            self.home
+                .path()
+                .join("Library/Application Support/rs.trieve")
+        } else {
+            self.home.path().join(".config/trieve")
+        }
+    }
+
+    /// Writes `profiles.toml`, with one profile per `(name, api_url)` and the first selected.
+    pub fn write_profiles(&self, profiles: &[(&str, &str)]) {
+        let profiles = profiles
+            .iter()
+            .enumerate()
+            .map(|(i, (name, api_url))| {
+                format!(
+                    "[[inner]]\nname = \"{}\"\nselected = {}\n\n[inner.settings]\napi_key = \"{}\"\norganization_id = \"{}\"\napi_url = \"{}\"\n",
+                    name,
+                    i == 0,
+                    API_KEY,
+                    ORGANIZATION_ID,
+                    api_url
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        fs::create_dir_all(self.config_dir()).unwrap();
+        fs::write(self.profiles_path(), format!("version = 2\n\n{}", profiles)).unwrap();
+    }
+
+    pub fn profiles_path(&self) -> PathBuf {
+        self.config_dir().join("profiles.toml")
+    }
+
+    /// A `trieve` command using the profiles in the temporary home.
+    pub fn command(&self) -> Command {
+        let home = self.home.path();
+        let mut command = Command::new(env!("CARGO_BIN_EXE_trieve"));
+        command
+            .env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .env("HOME", home)
+            .env("XDG_CONFIG_HOME", home.join(".config"))
+            .env("XDG_DATA_HOME", home.join(".local/share"))
+            .env("LANG", "en_US.UTF-8")
+            .env("TRIEVE_NO_VERSION_CHECK", "true")
+            .env("NO_COLOR", "1");
+        if let Some(force_rest) = std::env::var_os("TRIEVE_FORCE_REST") {
+            command.env("TRIEVE_FORCE_REST", force_rest);
+        }
+        command
+    }
+
+    /// Writes a single profile named `production` for `server` and protects it, so destructive
+    /// commands ask for its name.
+    pub fn protected(&self, server: &MockServer) {
+        self.write_profiles(&[("production", &server.uri())]);
+        let output = self
+            .command()
+            .args(["profile", "protect", "production"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", stderr(&output));
+    }
+
+    /// A `trieve` command logged in to `server` through the environment instead of a profile.
+    pub fn logged_in(&self, server: &MockServer) -> Command {
+        let mut command = self.command();
+        command
+            .env("TRIEVE_NO_PROFILE", "true")
+            .env("TRIEVE_API_KEY", API_KEY)
+            .env("TRIEVE_ORGANIZATION_ID", ORGANIZATION_ID)
+            .env("TRIEVE_API_URL", server.uri());
+        command
+    }
+}
+
+/// Runs `command`, typing `typed` when it asks for confirmation.
+pub fn run_typing(command: &mut Command, typed: &str) -> Output {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(format!("{}\n", typed).as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).to_string()
+}
+
+/// A dataset as `GET /api/dataset/organization/{organization_id}` returns it.
+pub fn dataset_and_usage(id: &str, name: &str, chunk_count: i32) -> serde_json::Value {
+    serde_json::json!({
+        "dataset": {
+            "id": id,
+            "name": name,
+            "organization_id": ORGANIZATION_ID,
+            "tracking_id": null,
+            "created_at": "2024-05-01T12:00:00",
+            "updated_at": "2024-05-01T12:00:00",
+            "server_configuration": {},
+            "deleted": 0
+        },
+        "dataset_usage": {
+            "id": "00000000-0000-0000-0000-0000000000e1",
+            "dataset_id": id,
+            "chunk_count": chunk_count
+        }
+    })
+}
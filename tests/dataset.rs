@@ -0,0 +1,551 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+
+use common::{dataset_and_usage, stderr, stdout, Trieve, DATASET_ID, ORGANIZATION_ID};
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+#[tokio::test]
+async fn dataset_list_prints_the_ids_of_the_datasets_when_quiet() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(format!(
+            "/api/dataset/organization/{}",
+            ORGANIZATION_ID
+        )))
+        .and(header("TR-Organization", ORGANIZATION_ID))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            dataset_and_usage(DATASET_ID, "docs", 12),
+            dataset_and_usage("00000000-0000-0000-0000-0000000000d2", "blog", 3),
+        ]))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["dataset", "list", "--quiet"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!("{}\n00000000-0000-0000-0000-0000000000d2\n", DATASET_ID)
+    );
+}
+
+async fn mock_datasets(server: &MockServer) {
+    Mock::given(method("GET"))
+        .and(path(format!(
+            "/api/dataset/organization/{}",
+            ORGANIZATION_ID
+        )))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            dataset_and_usage(DATASET_ID, "docs", 12),
+            dataset_and_usage("00000000-0000-0000-0000-0000000000d2", "blog", 3),
+        ]))
+        .mount(server)
+        .await;
+}
+
+#[tokio::test]
+async fn dataset_list_table() {
+    let server = MockServer::start().await;
+    mock_datasets(&server).await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["dataset", "list"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    insta::assert_snapshot!(stdout(&output));
+}
+
+#[tokio::test]
+async fn dataset_list_table_with_selected_columns() {
+    let server = MockServer::start().await;
+    mock_datasets(&server).await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["dataset", "list", "--columns", "name,chunks,id"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    insta::assert_snapshot!(stdout(&output));
+}
+
+#[tokio::test]
+async fn dataset_list_is_not_paged_when_stdout_is_not_a_terminal() {
+    let server = MockServer::start().await;
+    mock_datasets(&server).await;
+    let trieve = Trieve::new();
+    let paged = trieve.home.path().join("paged");
+
+    let output = trieve
+        .logged_in(&server)
+        .env("PAGER", format!("tee {}", paged.display()))
+        .args(["dataset", "list"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("docs"), "{}", stdout(&output));
+    assert!(!paged.exists());
+}
+
+#[tokio::test]
+async fn dataset_list_formats_any_timestamp_with_the_date_format() {
+    let server = MockServer::start().await;
+    let mut dataset = dataset_and_usage(DATASET_ID, "docs", 12);
+    dataset["dataset"]["created_at"] = json!("2024-05-01T12:00:00.123456");
+    dataset["dataset"]["updated_at"] = json!("2024-05-02T08:30:00+02:00");
+    let mut undated = dataset_and_usage("00000000-0000-0000-0000-0000000000d2", "blog", 3);
+    undated["dataset"]["created_at"] = json!("unknown");
+    Mock::given(method("GET"))
+        .and(path(format!(
+            "/api/dataset/organization/{}",
+            ORGANIZATION_ID
+        )))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![dataset, undated]))
+        .mount(&server)
+        .await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args([
+            "dataset",
+            "list",
+            "--quiet",
+            "--columns",
+            "name,created,updated",
+            "--date-format",
+            "iso",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "docs\t2024-05-01T12:00:00Z\t2024-05-02T06:30:00Z\n\
+         blog\tunknown\t2024-05-01T12:00:00Z\n"
+    );
+}
+
+#[tokio::test]
+async fn dataset_list_prints_the_selected_columns_tab_separated_when_quiet() {
+    let server = MockServer::start().await;
+    mock_datasets(&server).await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["dataset", "list", "--quiet", "--columns", "id,name,chunks"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "{}\tdocs\t12\n00000000-0000-0000-0000-0000000000d2\tblog\t3\n",
+            DATASET_ID
+        )
+    );
+}
+
+#[tokio::test]
+async fn dataset_list_rejects_unknown_columns() {
+    let server = MockServer::start().await;
+    mock_datasets(&server).await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["dataset", "list", "--columns", "id,size"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output)
+            .contains("Unknown column 'size'. The columns are: id, name, created, updated, chunks"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[tokio::test]
+async fn dataset_list_filters_by_name() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(format!(
+            "/api/dataset/organization/{}",
+            ORGANIZATION_ID
+        )))
+        .respond_with(ResponseTemplate::new(200).set_body_json(vec![
+            dataset_and_usage(DATASET_ID, "docs", 12),
+            dataset_and_usage("00000000-0000-0000-0000-0000000000d2", "blog", 3),
+        ]))
+        .mount(&server)
+        .await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["dataset", "list", "--quiet", "--filter-name", "blo"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "00000000-0000-0000-0000-0000000000d2\n");
+}
+
+#[tokio::test]
+async fn rejected_credentials_exit_with_the_unauthorized_code() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(serde_json::json!({
+            "message": "Unauthorized"
+        })))
+        .mount(&server)
+        .await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["dataset", "list", "--quiet"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(3), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "");
+}
+
+#[test]
+fn commands_without_credentials_exit_with_the_unauthorized_code() {
+    let output = Trieve::new()
+        .command()
+        .args(["dataset", "list", "--output", "json"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(3), "{}", stderr(&output));
+    let error: serde_json::Value = serde_json::from_str(stderr(&output).trim()).unwrap();
+    assert_eq!(error["error"]["code"], "login_required");
+    assert!(error["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("Run `trieve login`"));
+}
+
+#[tokio::test]
+async fn revoked_profile_credentials_exit_with_the_unauthorized_code() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+            "message": "Unauthorized"
+        })))
+        .mount(&server)
+        .await;
+    let trieve = Trieve::new();
+    trieve.write_profiles(&[("default", &server.uri())]);
+
+    let output = trieve.command().args(["dataset", "list"]).output().unwrap();
+
+    assert_eq!(output.status.code(), Some(3), "{}", stderr(&output));
+    assert!(
+        stderr(&output).contains("Run `trieve login --profile-name default`"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[tokio::test]
+async fn revoked_profile_credentials_are_reported_as_json() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+            "message": "Unauthorized"
+        })))
+        .mount(&server)
+        .await;
+    let trieve = Trieve::new();
+    trieve.write_profiles(&[("default", &server.uri())]);
+
+    let output = trieve
+        .command()
+        .args(["dataset", "list", "--output", "json"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(3), "{}", stderr(&output));
+    let error: serde_json::Value = serde_json::from_str(stderr(&output).trim()).unwrap();
+    assert_eq!(error["error"]["code"], "unauthorized");
+    assert_eq!(error["error"]["status"], 401);
+    assert!(error["error"]["message"]
+        .as_str()
+        .unwrap()
+        .contains("profile 'default' are invalid"));
+}
+
+#[tokio::test]
+async fn suggest_queries_asks_until_enough_different_queries_were_generated() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/chunk/suggestions"))
+        .and(header("TR-Dataset", DATASET_ID))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "queries": (1..=10).map(|i| format!("query {}", i)).collect::<Vec<String>>()
+        })))
+        .up_to_n_times(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/chunk/suggestions"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "queries": ["Query 1", "query 11", "query 12"]
+        })))
+        .mount(&server)
+        .await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args([
+            "dataset",
+            "suggest-queries",
+            "--dataset-id",
+            DATASET_ID,
+            "--count",
+            "12",
+            "--quiet",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let queries = stdout(&output);
+    assert_eq!(queries.lines().count(), 12);
+    assert_eq!(queries.lines().last(), Some("query 12"));
+
+    let requests = server.received_requests().await.unwrap();
+    let sizes = requests
+        .iter()
+        .map(|request| {
+            request.body_json::<serde_json::Value>().unwrap()["suggestions_to_create"].clone()
+        })
+        .collect::<Vec<serde_json::Value>>();
+    assert_eq!(sizes, [10, 2]);
+}
+
+/// A server reporting a dataset with a maximum chunk size and a plan with a chunk quota.
+async fn mock_limits() -> MockServer {
+    mock_limits_on(MockServer::start().await).await
+}
+
+async fn mock_limits_on(server: MockServer) -> MockServer {
+    Mock::given(method("GET"))
+        .and(path(format!("/api/dataset/{}", DATASET_ID)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": DATASET_ID,
+            "name": "docs",
+            "server_configuration": { "MAX_CHUNK_SIZE": 8000 },
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/api/organization/{}", ORGANIZATION_ID)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "plan": { "name": "Free", "chunk_count": 1000 },
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/api/organization/usage/{}", ORGANIZATION_ID)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "chunk_count": 12 })))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn dataset_limits_shows_only_limits_the_server_reports() {
+    let server = mock_limits().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["dataset", "limits", DATASET_ID])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let rows = stdout(&output)
+        .lines()
+        .map(|line| {
+            line.split('│')
+                .map(str::trim)
+                .filter(|cell| !cell.is_empty())
+                .collect::<Vec<&str>>()
+                .join(" | ")
+        })
+        .collect::<Vec<String>>();
+    for row in [
+        "Max chunk size | 8000 | dataset configuration",
+        "Max file size | not reported",
+        "Chunks per batch request | 120 | cli default",
+    ] {
+        assert!(rows.iter().any(|line| line == row), "{}", stdout(&output));
+    }
+}
+
+#[tokio::test]
+async fn requests_are_retried_while_the_server_is_unavailable() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(format!("/api/dataset/{}", DATASET_ID)))
+        .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "0"))
+        .up_to_n_times(2)
+        .with_priority(1)
+        .mount(&server)
+        .await;
+    let server = mock_limits_on(server).await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["dataset", "limits", DATASET_ID, "--quiet"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(
+        requests
+            .iter()
+            .filter(|request| request.url.path() == format!("/api/dataset/{}", DATASET_ID))
+            .count(),
+        3
+    );
+}
+
+#[tokio::test]
+async fn dataset_limits_prints_json_with_output_json() {
+    let server = mock_limits().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["--output", "json", "dataset", "limits", DATASET_ID])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let limits: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(limits["name"], "docs");
+    assert_eq!(
+        limits["limits"][0],
+        json!({ "limit": "Chunks", "value": 1000, "usage": 12, "source": "plan (Free)" })
+    );
+    assert_eq!(limits["limits"][1]["value"], Value::Null);
+}
+
+#[tokio::test]
+async fn dataset_limits_prints_only_the_limits_with_quiet() {
+    let server = mock_limits().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["dataset", "limits", DATASET_ID, "--quiet"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let lines = stdout(&output);
+    assert!(lines.starts_with("Chunks\t1000\n"), "{}", lines);
+    assert!(!lines.contains("Limits for dataset"));
+}
+
+#[tokio::test]
+async fn dataset_export_writes_the_chunks_as_json_lines() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/chunks/scroll"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "chunks": [
+                { "id": "00000000-0000-0000-0000-0000000000c1", "chunk_html": "one" },
+                { "id": "00000000-0000-0000-0000-0000000000c2", "chunk_html": "two" },
+            ],
+        })))
+        .mount(&server)
+        .await;
+    let trieve = Trieve::new();
+    let file = trieve.home.path().join("chunks.jsonl");
+
+    let output = trieve
+        .logged_in(&server)
+        .args(["dataset", "export", DATASET_ID, "--quiet", "--file"])
+        .arg(&file)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output).trim(), file.display().to_string());
+    let chunks = fs::read_to_string(&file)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap()["chunk_html"].clone())
+        .collect::<Vec<Value>>();
+    assert_eq!(chunks, ["one", "two"]);
+}
+
+#[tokio::test]
+async fn organization_export_requires_a_dir_when_detached() {
+    let server = MockServer::start().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["organization", "export", "--detach"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(stderr(&output).contains("--dir"), "{}", stderr(&output));
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn organization_export_fails_when_the_file_holds_fewer_chunks_than_the_server_reports() {
+    let server = MockServer::start().await;
+    mock_datasets(&server).await;
+    Mock::given(method("POST"))
+        .and(path("/api/chunks/scroll"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "chunks": [
+                { "id": "00000000-0000-0000-0000-0000000000c1", "chunk_html": "one" },
+                { "id": "00000000-0000-0000-0000-0000000000c2", "chunk_html": "two" },
+            ],
+        })))
+        .mount(&server)
+        .await;
+    let trieve = Trieve::new();
+    let dir = trieve.home.path().join("export");
+
+    let output = trieve
+        .logged_in(&server)
+        .args(["organization", "export", "--dir"])
+        .arg(&dir)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("the server reports 12 chunks"),
+        "{}",
+        stderr(&output)
+    );
+}
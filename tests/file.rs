@@ -0,0 +1,194 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+
+use common::{run_typing, stderr, stdout, Trieve, DATASET_ID};
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+fn file(id: &str, name: &str, group_id: Option<&str>) -> Value {
+    json!({
+        "file": { "id": id, "file_name": name, "size": 1024, "created_at": "2024-05-01T10:00:00" },
+        "group_id": group_id,
+    })
+}
+
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(format!("/api/dataset/files/{}/1", DATASET_ID)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "file_and_group_ids": [
+                file("f1", "guide.pdf", Some("g1")),
+                file("f2", "retried.pdf", Some("g2")),
+                file("f3", "pending.pdf", None),
+                file("f4", "broken.pdf", None),
+            ],
+            "total_pages": 1,
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/events"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "events": [
+                {
+                    "id": "e1",
+                    "event_type": "file_upload_failed",
+                    "created_at": "2024-05-01T10:05:00",
+                    "event_data": { "file_id": "f4", "error": "Could not extract text" },
+                },
+                {
+                    "id": "e2",
+                    "event_type": "file_upload_failed",
+                    "created_at": "2024-05-01T10:01:00",
+                    "event_data": "{\"file_id\":\"f2\",\"error\":\"Timed out\"}",
+                },
+            ],
+            "page_count": 1,
+        })))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn files_status_counts_the_files_by_status_with_the_latest_errors() {
+    let server = mock_server().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["dataset", "files-status", "--dataset-id", DATASET_ID])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let status = stdout(&output);
+    assert!(status.contains("4 files in dataset"));
+    assert!(status.contains("chunked       2"));
+    assert!(status.contains("queued        1"));
+    assert!(status.contains("failed        1"));
+    assert!(status.contains("broken.pdf"));
+    assert!(status.contains("Could not extract text"));
+    // The file was chunked after it failed once.
+    assert!(!status.contains("Timed out"));
+}
+
+#[tokio::test]
+async fn files_status_with_exit_code_fails_while_files_are_not_chunked() {
+    let server = mock_server().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["dataset", "files-status", "--dataset-id", DATASET_ID])
+        .args(["--quiet", "--exit-code"])
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "chunked\t2\nqueued\t1\nfailed\t1\n");
+}
+
+#[tokio::test]
+async fn file_upload_skips_ignored_binary_and_unsupported_files() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/file"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "file_metadata": { "id": "00000000-0000-0000-0000-0000000000f1" }
+        })))
+        .mount(&server)
+        .await;
+    let trieve = Trieve::new();
+    let corpus = trieve.home.path().join("corpus");
+    fs::create_dir_all(corpus.join("guides/drafts")).unwrap();
+    fs::write(
+        corpus.join(".trieveignore"),
+        "# work in progress\ndrafts/\n*.log\n",
+    )
+    .unwrap();
+    fs::write(corpus.join("readme.md"), "# Corpus").unwrap();
+    fs::write(corpus.join("notes"), "plain text without an extension").unwrap();
+    fs::write(corpus.join("build.log"), "ignored").unwrap();
+    fs::write(corpus.join("logo.png"), [0x89, b'P', b'N', b'G', 0, 0]).unwrap();
+    fs::write(corpus.join("data.bin"), [0, 1, 2, 3]).unwrap();
+    fs::write(corpus.join("guides/setup.html"), "<p>Setup</p>").unwrap();
+    fs::write(corpus.join("guides/drafts/next.md"), "ignored").unwrap();
+
+    let output = trieve
+        .logged_in(&server)
+        .args([
+            "file",
+            "upload",
+            "-r",
+            "--dataset-id",
+            DATASET_ID,
+            "--quiet",
+        ])
+        .arg(&corpus)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let mut statuses = stdout(&output)
+        .lines()
+        .map(|line| {
+            let fields = line.split('\t').collect::<Vec<&str>>();
+            let name = fields[1].trim_end_matches('/').rsplit('/').next().unwrap();
+            format!("{} {} {}", fields[0], name, fields[2])
+        })
+        .collect::<Vec<String>>();
+    statuses.sort();
+    assert_eq!(
+        statuses,
+        [
+            "skipped build.log ignored",
+            "skipped data.bin binary",
+            "skipped drafts ignored",
+            "skipped logo.png unsupported type image/png",
+            "uploaded notes 00000000-0000-0000-0000-0000000000f1",
+            "uploaded readme.md 00000000-0000-0000-0000-0000000000f1",
+            "uploaded setup.html 00000000-0000-0000-0000-0000000000f1",
+        ]
+    );
+
+    let mut file_names = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .map(|request| request.body_json::<Value>().unwrap()["file_name"].clone())
+        .collect::<Vec<Value>>();
+    file_names.sort_by_key(|name| name.to_string());
+    assert_eq!(file_names, ["notes", "readme.md", "setup.html"]);
+}
+
+#[tokio::test]
+async fn file_delete_keeping_the_chunks_with_a_protected_profile_requires_typing_its_name() {
+    let file_id = "00000000-0000-0000-0000-0000000000f1";
+    let server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .and(path(format!("/api/file/{}", file_id)))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+    let trieve = Trieve::new();
+    trieve.protected(&server);
+    let args = ["file", "delete", file_id, "--dataset-id", DATASET_ID];
+
+    let output = run_typing(trieve.command().args(args), "yes");
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains(&format!("delete file {}:", file_id)));
+    assert!(server.received_requests().await.unwrap().is_empty());
+
+    let output = run_typing(trieve.command().args(args), "production");
+    assert!(output.status.success(), "{}", stderr(&output));
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].url.query(), Some("delete_chunks=false"));
+}
@@ -0,0 +1,169 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{run_typing, stderr, stdout, Trieve, ORGANIZATION_ID};
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const PENDING_ID: &str = "00000000-0000-0000-0000-0000000000f1";
+const ACCEPTED_ID: &str = "00000000-0000-0000-0000-0000000000f2";
+
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(format!("/api/invitations/{}", ORGANIZATION_ID)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            {
+                "id": PENDING_ID,
+                "email": "ada@example.com",
+                "organization_id": ORGANIZATION_ID,
+                "used": false,
+                "role": 1,
+                "created_at": "2024-03-01T09:00:00",
+            },
+            {
+                "id": ACCEPTED_ID,
+                "email": "grace@example.com",
+                "organization_id": ORGANIZATION_ID,
+                "used": true,
+                "role": 0,
+                "created_at": "2024-02-01T09:00:00",
+            },
+        ])))
+        .mount(&server)
+        .await;
+    Mock::given(method("DELETE"))
+        .and(path(format!("/api/invitation/{}", PENDING_ID)))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/invitation"))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn invitations_list_prints_the_pending_invitations_with_the_selected_columns() {
+    let server = mock_server().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args([
+            "organization",
+            "invitations",
+            "list",
+            "--pending",
+            "--quiet",
+        ])
+        .args(["--columns", "email,role,status"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "ada@example.com\tadmin\tpending\n");
+}
+
+#[tokio::test]
+async fn invitations_resend_replaces_the_invitation_with_the_same_email_and_role() {
+    let server = mock_server().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["organization", "invitations", "resend", PENDING_ID])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let requests = server.received_requests().await.unwrap();
+    let methods = requests
+        .iter()
+        .map(|request| request.method.to_string())
+        .collect::<Vec<String>>();
+    assert_eq!(methods, ["GET", "DELETE", "POST"]);
+    let invitation = requests[2].body_json::<Value>().unwrap();
+    assert_eq!(invitation["email"], "ada@example.com");
+    assert_eq!(invitation["user_role"], 1);
+    assert_eq!(invitation["organization_id"], ORGANIZATION_ID);
+}
+
+#[tokio::test]
+async fn invitations_resend_refuses_accepted_invitations_before_changing_any() {
+    let server = mock_server().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args([
+            "organization",
+            "invitations",
+            "resend",
+            PENDING_ID,
+            ACCEPTED_ID,
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("grace@example.com"));
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests
+        .iter()
+        .all(|request| request.method.as_str() == "GET"));
+}
+
+/// Runs `organization invitations <command>` for the pending invitation with a protected profile,
+/// first typing the wrong name and then the right one, and checks that only the second run
+/// changed the invitations.
+async fn assert_protected(command: &str, action: &str) -> MockServer {
+    let server = mock_server().await;
+    let trieve = Trieve::new();
+    trieve.protected(&server);
+    let args = ["organization", "invitations", command, PENDING_ID];
+
+    let output = run_typing(trieve.command().args(args), "yes");
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains(action), "{}", stderr(&output));
+    assert!(stderr(&output).contains("did not match profile 'production'"));
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests
+        .iter()
+        .all(|request| request.method.as_str() == "GET"));
+
+    let output = run_typing(trieve.command().args(args), "production");
+    assert!(output.status.success(), "{}", stderr(&output));
+    server
+}
+
+#[tokio::test]
+async fn invitations_revoke_with_a_protected_profile_requires_typing_its_name() {
+    let server = assert_protected("revoke", "revoke the invitations of ada@example.com").await;
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(
+        requests
+            .iter()
+            .filter(|request| request.method.as_str() == "DELETE")
+            .count(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn invitations_resend_with_a_protected_profile_requires_typing_its_name() {
+    let server = assert_protected("resend", "replace the invitations of ada@example.com").await;
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(
+        requests
+            .iter()
+            .filter(|request| request.method.as_str() == "POST")
+            .count(),
+        1
+    );
+}
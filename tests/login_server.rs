@@ -0,0 +1,80 @@
+#![cfg(unix)]
+
+use std::{fs, os::unix::fs::PermissionsExt, path::Path, time::Duration};
+
+use tempfile::TempDir;
+use tokio::{net::TcpListener, sync::Mutex};
+use trieve_cli_core::commands::{
+    browser,
+    login_server::{CallbackServer, DEFAULT_CALLBACK_PORT, LOGIN_TIMEOUT},
+};
+
+/// The tests bind the callback ports, so they take turns.
+static PORTS: Mutex<()> = Mutex::const_new(());
+
+/// A `BROWSER` that plays the part of the login page: it takes the port and state from the login
+/// URL and requests the callback with an API key, as the page redirecting back does.
+const BROWSER_STUB: &str = r#"#!/bin/bash
+port=$(printf '%s' "$1" | sed -n 's/.*%26port=\([0-9]*\).*/\1/p')
+state=$(printf '%s' "$1" | sed -n 's/.*%26state=\([0-9a-f]*\).*/\1/p')
+exec 3<>"/dev/tcp/127.0.0.1/$port"
+printf 'GET /?apiKey=tr-stub&state=%s HTTP/1.1\r\nHost: localhost\r\n\r\n' "$state" >&3
+cat <&3 >/dev/null
+"#;
+
+fn install_browser_stub(dir: &Path) {
+    let path = dir.join("browser");
+    fs::write(&path, BROWSER_STUB).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    std::env::set_var("BROWSER", &path);
+}
+
+async fn login_through_browser_stub(callback: CallbackServer) -> Option<String> {
+    browser::from_env()
+        .open(&callback.login_url("http://localhost:8090"))
+        .unwrap();
+    callback.api_key(LOGIN_TIMEOUT).await
+}
+
+#[tokio::test]
+async fn the_browser_redirect_hands_over_the_api_key() {
+    let _ports = PORTS.lock().await;
+    let dir = TempDir::new().unwrap();
+    install_browser_stub(dir.path());
+
+    let callback = CallbackServer::start(None).await.unwrap();
+
+    assert_eq!(
+        login_through_browser_stub(callback).await.as_deref(),
+        Some("tr-stub")
+    );
+}
+
+#[tokio::test]
+async fn a_taken_default_port_falls_back_to_another_one() {
+    let _ports = PORTS.lock().await;
+    let dir = TempDir::new().unwrap();
+    install_browser_stub(dir.path());
+
+    // Another program may hold the default port already, which is just as good.
+    let _taken = TcpListener::bind(("127.0.0.1", DEFAULT_CALLBACK_PORT)).await;
+    let callback = CallbackServer::start(None).await.unwrap();
+
+    assert_ne!(callback.port(), DEFAULT_CALLBACK_PORT);
+    assert!(callback
+        .login_url("http://localhost:8090")
+        .contains(&format!("%26port={}%26", callback.port())));
+    assert_eq!(
+        login_through_browser_stub(callback).await.as_deref(),
+        Some("tr-stub")
+    );
+}
+
+#[tokio::test]
+async fn no_api_key_is_returned_when_the_browser_never_redirects() {
+    let _ports = PORTS.lock().await;
+
+    let callback = CallbackServer::start(None).await.unwrap();
+
+    assert_eq!(callback.api_key(Duration::from_millis(200)).await, None);
+}
@@ -0,0 +1,77 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{stderr, stdout, Trieve, DATASET_ID, ORGANIZATION_ID};
+use wiremock::MockServer;
+
+#[tokio::test]
+async fn open_derives_the_dashboard_and_search_urls_from_the_api_url() {
+    let server = MockServer::start().await;
+    let trieve = Trieve::new();
+
+    for (args, expected) in [
+        (
+            vec!["open", "dataset", DATASET_ID, "--print"],
+            format!("https://dashboard.example.com/dataset/{}\n", DATASET_ID),
+        ),
+        (
+            vec!["open", "org", "--print"],
+            format!(
+                "https://dashboard.example.com/org?org={}\n",
+                ORGANIZATION_ID
+            ),
+        ),
+        (
+            vec!["open", "search", DATASET_ID, "--print"],
+            format!("https://search.example.com/?dataset={}\n", DATASET_ID),
+        ),
+    ] {
+        let output = trieve
+            .logged_in(&server)
+            .env("TRIEVE_API_URL", "https://api.example.com")
+            .args(&args)
+            .output()
+            .unwrap();
+
+        assert!(output.status.success(), "{}", stderr(&output));
+        assert_eq!(stdout(&output), expected);
+    }
+}
+
+#[tokio::test]
+async fn open_uses_the_local_ports_for_a_local_server() {
+    let server = MockServer::start().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .env("TRIEVE_API_URL", "http://localhost:8090")
+        .args(["open", "dataset", DATASET_ID, "--print"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!("http://localhost:5173/dataset/{}\n", DATASET_ID)
+    );
+}
+
+#[tokio::test]
+async fn open_fails_when_the_dashboard_cannot_be_derived() {
+    let server = MockServer::start().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .env("TRIEVE_API_URL", "https://trieve.internal.example.com")
+        .args(["open", "org", "--print"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("TRIEVE_DASHBOARD_URL"),
+        "{}",
+        stderr(&output)
+    );
+}
@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+
+use trieve_cli_core::cli::OutputFormat;
+use trieve_cli_core::commands::{
+    api::TrieveApi, configure::TrieveConfiguration, dataset::DefaultError,
+    organization::resolve_organization_with, output,
+};
+use trieve_client::models::{
+    CreateChunkGroupReqPayloadEnum, CreateDatasetRequest, Dataset, DatasetAndUsage, Organization,
+    SetUserApiKeyRequest, SlimUser,
+};
+use uuid::Uuid;
+
+/// A user belonging to a fixed set of organizations, for the calls that only read the user.
+struct FakeApi {
+    orgs: Vec<Organization>,
+}
+
+fn unsupported<T>() -> Result<T, DefaultError> {
+    Err(DefaultError {
+        message: "not supported by the fake API".to_string(),
+    })
+}
+
+impl TrieveApi for FakeApi {
+    async fn me(&self) -> Result<SlimUser, DefaultError> {
+        Ok(SlimUser {
+            orgs: self.orgs.clone(),
+            ..Default::default()
+        })
+    }
+
+    async fn datasets(
+        &self,
+        _limit: Option<i64>,
+        _offset: Option<i64>,
+    ) -> Result<Vec<DatasetAndUsage>, DefaultError> {
+        Ok(vec![])
+    }
+
+    async fn create_dataset(
+        &self,
+        _request: CreateDatasetRequest,
+    ) -> Result<Dataset, DefaultError> {
+        unsupported()
+    }
+
+    async fn delete_dataset(&self, _dataset_id: &str) -> Result<(), DefaultError> {
+        unsupported()
+    }
+
+    async fn create_chunk_group(
+        &self,
+        _dataset_id: &str,
+        _group: CreateChunkGroupReqPayloadEnum,
+    ) -> Result<(), DefaultError> {
+        unsupported()
+    }
+
+    async fn create_api_key(&self, _request: SetUserApiKeyRequest) -> Result<String, DefaultError> {
+        unsupported()
+    }
+
+    async fn create_organization(&self, _name: String) -> Result<Organization, DefaultError> {
+        unsupported()
+    }
+
+    async fn delete_organization(&self, _organization_id: &str) -> Result<(), DefaultError> {
+        unsupported()
+    }
+}
+
+fn organization(id: u128, name: &str) -> Organization {
+    Organization {
+        id: Uuid::from_u128(id),
+        name: name.to_string(),
+        ..Default::default()
+    }
+}
+
+fn api() -> FakeApi {
+    FakeApi {
+        orgs: vec![
+            organization(1, "Acme Production"),
+            organization(2, "Acme Staging"),
+            organization(3, "Globex"),
+        ],
+    }
+}
+
+/// Resolves `query` in quiet mode, so several matches are an error rather than a prompt.
+async fn resolve(settings: &TrieveConfiguration, query: &str) -> Result<Uuid, DefaultError> {
+    output::init(OutputFormat::Text, true);
+    resolve_organization_with(&api(), settings, query).await
+}
+
+#[tokio::test]
+async fn resolves_an_exact_name_ignoring_case() {
+    let settings = TrieveConfiguration::default();
+    assert_eq!(
+        resolve(&settings, "globex").await.unwrap(),
+        Uuid::from_u128(3)
+    );
+}
+
+#[tokio::test]
+async fn resolves_the_letters_of_a_name_in_order() {
+    let settings = TrieveConfiguration::default();
+    assert_eq!(resolve(&settings, "acp").await.unwrap(), Uuid::from_u128(1));
+}
+
+#[tokio::test]
+async fn resolves_an_id_without_looking_it_up() {
+    let settings = TrieveConfiguration::default();
+    let id = Uuid::from_u128(42);
+    assert_eq!(resolve(&settings, &id.to_string()).await.unwrap(), id);
+}
+
+#[tokio::test]
+async fn resolves_an_alias_of_the_profile() {
+    let settings = TrieveConfiguration {
+        organization_aliases: BTreeMap::from([(
+            "prod".to_string(),
+            Uuid::from_u128(1).to_string(),
+        )]),
+        ..Default::default()
+    };
+    assert_eq!(
+        resolve(&settings, "prod").await.unwrap(),
+        Uuid::from_u128(1)
+    );
+}
+
+#[tokio::test]
+async fn names_every_match_of_an_ambiguous_name() {
+    let settings = TrieveConfiguration::default();
+    let error = resolve(&settings, "acme").await.unwrap_err();
+    assert!(
+        error.message.contains("Acme Production"),
+        "{}",
+        error.message
+    );
+    assert!(error.message.contains("Acme Staging"), "{}", error.message);
+}
+
+#[tokio::test]
+async fn fails_when_no_organization_matches() {
+    let settings = TrieveConfiguration::default();
+    let error = resolve(&settings, "initech").await.unwrap_err();
+    assert!(error.message.contains("No organization matches 'initech'"));
+}
@@ -0,0 +1,324 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::{
+    io::Write,
+    process::{Output, Stdio},
+};
+
+use common::{dataset_and_usage, stderr, stdout, Trieve, DATASET_ID, ORGANIZATION_ID};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+#[test]
+fn profile_list_prints_the_selected_profile_first_when_quiet() {
+    let trieve = Trieve::new();
+    trieve.write_profiles(&[
+        ("staging", "http://127.0.0.1:1"),
+        ("production", "http://127.0.0.1:2"),
+    ]);
+
+    let output = trieve
+        .command()
+        .args(["profile", "list", "--quiet"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "staging\nproduction\n");
+}
+
+#[test]
+fn profile_list_table() {
+    let trieve = Trieve::new();
+    trieve.write_profiles(&[
+        ("staging", "https://staging.trieve.ai"),
+        ("production", "https://api.trieve.ai"),
+    ]);
+
+    let output = trieve.command().args(["profile", "list"]).output().unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    insta::assert_snapshot!(stdout(&output));
+}
+
+#[test]
+fn profile_list_prints_the_selected_columns_when_quiet() {
+    let trieve = Trieve::new();
+    trieve.write_profiles(&[
+        ("staging", "https://staging.trieve.ai"),
+        ("production", "https://api.trieve.ai"),
+    ]);
+
+    let output = trieve
+        .command()
+        .args(["profile", "list", "--quiet", "--columns", "name,api_url"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        "staging\thttps://staging.trieve.ai\nproduction\thttps://api.trieve.ai\n"
+    );
+}
+
+#[test]
+fn profile_switch_selects_the_profile() {
+    let trieve = Trieve::new();
+    trieve.write_profiles(&[
+        ("staging", "http://127.0.0.1:1"),
+        ("production", "http://127.0.0.1:2"),
+    ]);
+
+    let output = trieve
+        .command()
+        .args(["profile", "switch", "production"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = trieve
+        .command()
+        .args(["profile", "list", "--quiet"])
+        .output()
+        .unwrap();
+    assert_eq!(stdout(&output), "production\nstaging\n");
+}
+
+#[test]
+fn profile_switch_to_an_unknown_profile_fails() {
+    let trieve = Trieve::new();
+    trieve.write_profiles(&[("staging", "http://127.0.0.1:1")]);
+
+    let output = trieve
+        .command()
+        .args(["profile", "switch", "nope"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+}
+
+#[tokio::test]
+async fn profile_flag_after_the_subcommand_uses_that_profile_for_the_command_only() {
+    let staging = MockServer::start().await;
+    let production = MockServer::start().await;
+    for (server, name) in [(&staging, "staging-docs"), (&production, "production-docs")] {
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/api/dataset/organization/{}",
+                ORGANIZATION_ID
+            )))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(vec![dataset_and_usage(DATASET_ID, name, 1)]),
+            )
+            .mount(server)
+            .await;
+    }
+    let trieve = Trieve::new();
+    trieve.write_profiles(&[
+        ("production", &production.uri()),
+        ("staging", &staging.uri()),
+    ]);
+
+    let output = trieve
+        .command()
+        .args([
+            "dataset",
+            "list",
+            "--quiet",
+            "--columns",
+            "name",
+            "-p",
+            "staging",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "staging-docs\n");
+
+    let output = trieve
+        .command()
+        .args(["profile", "current", "--quiet"])
+        .output()
+        .unwrap();
+    assert_eq!(stdout(&output), "production\n");
+}
+
+#[test]
+fn profile_current_prints_the_profile_organization_and_server() {
+    let trieve = Trieve::new();
+    trieve.write_profiles(&[
+        ("production", "https://api.trieve.ai"),
+        ("staging", "https://staging.trieve.ai"),
+    ]);
+
+    let output = trieve
+        .command()
+        .args(["profile", "current", "--profile", "staging"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let current = stdout(&output);
+    assert!(current.contains("staging (from --profile or TRIEVE_PROFILE)"));
+    assert!(current.contains(ORGANIZATION_ID));
+    assert!(current.contains("https://staging.trieve.ai"));
+}
+
+#[test]
+fn profile_current_prints_a_prompt_segment_for_starship() {
+    let trieve = Trieve::new();
+    trieve.write_profiles(&[
+        ("production", "http://127.0.0.1:1"),
+        ("staging", "http://127.0.0.1:2"),
+    ]);
+
+    let output = trieve
+        .command()
+        .args(["profile", "current", "--format", "starship"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        stdout(&output),
+        format!("production ({})\n", &ORGANIZATION_ID[..8])
+    );
+}
+
+#[test]
+fn organization_switch_with_a_profile_flag_leaves_the_selected_profile_alone() {
+    let trieve = Trieve::new();
+    trieve.write_profiles(&[
+        ("production", "http://127.0.0.1:1"),
+        ("staging", "http://127.0.0.1:2"),
+    ]);
+    let organization_id = "00000000-0000-0000-0000-0000000000a2";
+
+    let output = trieve
+        .command()
+        .args(["-p", "staging", "organization", "switch", organization_id])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let current = |profile: &str| {
+        let output = trieve
+            .command()
+            .args(["profile", "current", "-p", profile])
+            .output()
+            .unwrap();
+        stdout(&output)
+    };
+    assert!(current("staging").contains(organization_id));
+    assert!(current("production").contains(ORGANIZATION_ID));
+    let output = trieve
+        .command()
+        .args(["profile", "current", "--quiet"])
+        .output()
+        .unwrap();
+    assert_eq!(stdout(&output), "production\n");
+}
+
+const FILE_ID: &str = "00000000-0000-0000-0000-0000000000f1";
+
+/// Deletes a file with its chunks, typing `typed` when asked for confirmation.
+fn delete_file_typing(trieve: &Trieve, typed: &str) -> Output {
+    let mut child = trieve
+        .command()
+        .args(["file", "delete", FILE_ID, "--delete-chunks", "--quiet"])
+        .args(["--dataset-id", DATASET_ID])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(format!("{}\n", typed).as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[tokio::test]
+async fn destructive_commands_with_a_protected_profile_require_typing_its_name() {
+    let server = MockServer::start().await;
+    Mock::given(method("DELETE"))
+        .and(path(format!("/api/file/{}", FILE_ID)))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+    let trieve = Trieve::new();
+    trieve.write_profiles(&[("production", &server.uri())]);
+
+    let output = trieve
+        .command()
+        .args(["profile", "protect", "production"])
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let output = delete_file_typing(&trieve, "yes");
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("did not match profile 'production'"));
+    assert!(server.received_requests().await.unwrap().is_empty());
+
+    let output = delete_file_typing(&trieve, "production");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(server.received_requests().await.unwrap().len(), 1);
+}
+
+#[test]
+fn profile_unprotect_removes_the_protection() {
+    let trieve = Trieve::new();
+    trieve.write_profiles(&[("production", "http://127.0.0.1:1")]);
+
+    for command in ["protect", "unprotect"] {
+        let output = trieve
+            .command()
+            .args(["profile", command, "production"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", stderr(&output));
+    }
+
+    let output = trieve
+        .command()
+        .args(["profile", "list", "--quiet", "--columns", "protected"])
+        .output()
+        .unwrap();
+    assert_eq!(stdout(&output).trim(), "");
+}
+
+#[test]
+fn config_set_api_key_replaces_the_key_stored_for_the_organization() {
+    const OTHER_ORGANIZATION_ID: &str = "00000000-0000-0000-0000-0000000000a2";
+    let trieve = Trieve::new();
+    trieve.write_profiles(&[("production", "http://127.0.0.1:1")]);
+    let mut profiles = std::fs::read_to_string(trieve.profiles_path()).unwrap();
+    profiles.push_str(&format!(
+        "\n[inner.api_keys]\n\"{}\" = \"tr-test\"\n\"{}\" = \"tr-other\"\n",
+        ORGANIZATION_ID, OTHER_ORGANIZATION_ID
+    ));
+    std::fs::write(trieve.profiles_path(), profiles).unwrap();
+
+    let config = |args: &[&str]| {
+        let output = trieve.command().arg("config").args(args).output().unwrap();
+        assert!(output.status.success(), "{}", stderr(&output));
+        stdout(&output)
+    };
+
+    config(&["set", "api_key", "tr-new"]);
+    config(&["set", "organization_id", OTHER_ORGANIZATION_ID]);
+    assert_eq!(config(&["get", "api_key"]), "tr-other\n");
+    config(&["set", "organization_id", ORGANIZATION_ID]);
+    assert_eq!(config(&["get", "api_key"]), "tr-new\n");
+}
@@ -0,0 +1,129 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+
+use common::{stderr, stdout, Trieve, DATASET_ID};
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{body_partial_json, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const PROMPTS: &str = r#"
+prompts:
+  - How do I rotate an API key?
+  - What happens when a dataset is deleted?
+configurations:
+  - name: small
+    n_retrievals: 2
+    temperature: 0.2
+  - name: other-model
+    model: gpt-4o
+"#;
+
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/chunk/search"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "score_chunks": [
+                { "metadata": [{ "id": "c1", "tracking_id": "keys" }], "score": 0.9 },
+                { "metadata": [{ "id": "c2", "tracking_id": "datasets" }], "score": 0.5 },
+            ]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/chunk/generate"))
+        .and(body_partial_json(json!({ "temperature": 0.2 })))
+        .respond_with(ResponseTemplate::new(200).set_body_string("[]||A cold answer."))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/chunk/generate"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("Another answer."))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/api/dataset/{}", DATASET_ID)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": DATASET_ID,
+            "server_configuration": { "LLM_DEFAULT_MODEL": "gpt-4o-mini", "RAG_PROMPT": "" }
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/api/dataset"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "id": DATASET_ID })))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn rag_test_reports_the_answer_of_every_configuration_to_every_prompt() {
+    let server = mock_server().await;
+    let trieve = Trieve::new();
+    let prompts = trieve.home.path().join("prompts.yaml");
+    fs::write(&prompts, PROMPTS).unwrap();
+    let report = trieve.home.path().join("report.json");
+
+    let output = trieve
+        .logged_in(&server)
+        .args(["rag", "test", "--dataset-id", DATASET_ID, "--prompt-file"])
+        .arg(&prompts)
+        .arg("--report")
+        .arg(&report)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("other-model"));
+    let report: Value = serde_json::from_str(&fs::read_to_string(&report).unwrap()).unwrap();
+    assert_eq!(report["prompts"].as_array().unwrap().len(), 2);
+    for prompt in report["prompts"].as_array().unwrap() {
+        assert_eq!(prompt["answers"][0]["answer"], "A cold answer.");
+        assert_eq!(prompt["answers"][1]["answer"], "Another answer.");
+        assert_eq!(prompt["answers"][0]["chunks"], json!(["keys", "datasets"]));
+    }
+
+    let requests = server.received_requests().await.unwrap();
+    let page_sizes = requests
+        .iter()
+        .filter(|request| request.url.path() == "/api/chunk/search")
+        .map(|request| request.body_json::<Value>().unwrap()["page_size"].clone())
+        .collect::<Vec<Value>>();
+    assert_eq!(page_sizes, [2, 2, 8, 8]);
+}
+
+#[tokio::test]
+async fn rag_test_switches_the_model_of_the_dataset_back_afterwards() {
+    let server = mock_server().await;
+    let trieve = Trieve::new();
+    let prompts = trieve.home.path().join("prompts.yaml");
+    fs::write(&prompts, PROMPTS).unwrap();
+
+    let output = trieve
+        .logged_in(&server)
+        .args(["rag", "test", "--dataset-id", DATASET_ID, "--quiet"])
+        .arg("--prompt-file")
+        .arg(&prompts)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    let models = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.method.as_str() == "PUT")
+        .map(|request| {
+            request.body_json::<Value>().unwrap()["server_configuration"]["LLM_DEFAULT_MODEL"]
+                .clone()
+        })
+        .collect::<Vec<Value>>();
+    assert_eq!(models, ["gpt-4o", "gpt-4o-mini"]);
+}
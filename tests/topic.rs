@@ -0,0 +1,139 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+
+use common::{stderr, stdout, Trieve, DATASET_ID};
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{body_partial_json, header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const TOPIC_ID: &str = "00000000-0000-0000-0000-0000000000e1";
+const ORPHAN_TOPIC_ID: &str = "00000000-0000-0000-0000-0000000000e2";
+const OWNER_ID: &str = "visitor-1";
+
+async fn mock_messages(server: &MockServer, topic_id: &str, messages: Value) {
+    Mock::given(method("GET"))
+        .and(path(format!("/api/messages/{}", topic_id)))
+        .and(header("TR-Dataset", DATASET_ID))
+        .respond_with(ResponseTemplate::new(200).set_body_json(messages))
+        .mount(server)
+        .await;
+}
+
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/api/analytics/rag"))
+        .and(body_partial_json(
+            json!({ "type": "rag_queries", "page": 1 }),
+        ))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "queries": [
+                { "topic_id": TOPIC_ID, "user_id": OWNER_ID },
+                { "topic_id": TOPIC_ID, "user_id": OWNER_ID },
+                { "topic_id": ORPHAN_TOPIC_ID, "user_id": "" },
+            ]
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/api/analytics/rag"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "queries": [] })))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!("/api/topic/owner/{}", OWNER_ID)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+            "id": TOPIC_ID,
+            "name": "Rotating keys",
+            "owner_id": OWNER_ID,
+            "created_at": "2024-05-01T10:00:00",
+            "updated_at": "2024-05-01T10:01:00",
+        }])))
+        .mount(&server)
+        .await;
+    mock_messages(
+        &server,
+        TOPIC_ID,
+        json!([
+            {
+                "id": "m1",
+                "sort_order": 1,
+                "role": "user",
+                "content": "How do I rotate a key?",
+                "created_at": "2024-05-01T10:00:00",
+            },
+            {
+                "id": "m2",
+                "sort_order": 2,
+                "role": "assistant",
+                "content": "[{\"id\":\"c1\",\"tracking_id\":\"keys\",\"link\":\"https://example.com/keys\",\"chunk_html\":\"<p>Keys</p>\"}]||Use `trieve api-key generate`.",
+                "created_at": "2024-05-01T10:00:05",
+            },
+        ]),
+    )
+    .await;
+    mock_messages(&server, ORPHAN_TOPIC_ID, json!([])).await;
+    server
+}
+
+#[tokio::test]
+async fn topic_export_writes_every_topic_found_in_the_analytics_with_its_messages() {
+    let server = mock_server().await;
+    let trieve = Trieve::new();
+    let out = trieve.home.path().join("topics.jsonl");
+
+    let output = trieve
+        .logged_in(&server)
+        .args(["topic", "export", "--dataset-id", DATASET_ID, "--out"])
+        .arg(&out)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("Exported 2 topics with 2 messages"));
+    let topics = fs::read_to_string(&out)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect::<Vec<Value>>();
+    assert_eq!(topics.len(), 2);
+    assert_eq!(topics[0]["name"], "Rotating keys");
+    assert_eq!(topics[0]["owner_id"], OWNER_ID);
+    assert_eq!(
+        topics[0]["messages"][1],
+        json!({
+            "id": "m2",
+            "sort_order": 2,
+            "role": "assistant",
+            "content": "Use `trieve api-key generate`.",
+            "citations": [{ "id": "c1", "tracking_id": "keys", "link": "https://example.com/keys" }],
+            "created_at": "2024-05-01T10:00:05",
+        })
+    );
+    assert_eq!(topics[1]["id"], ORPHAN_TOPIC_ID);
+    assert_eq!(topics[1]["messages"], json!([]));
+}
+
+#[tokio::test]
+async fn topic_export_lists_only_the_topics_of_the_given_owners() {
+    let server = mock_server().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["topic", "export", "--dataset-id", DATASET_ID, "--out", "-"])
+        .args(["--owner-id", OWNER_ID])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output).lines().count(), 1);
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests
+        .iter()
+        .all(|request| request.url.path() != "/api/analytics/rag"));
+}
@@ -0,0 +1,77 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{stderr, stdout, Trieve, ORGANIZATION_ID};
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{header, method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+const USER_ID: &str = "00000000-0000-0000-0000-0000000000b1";
+
+async fn mock_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(format!("/api/user/organization/{}", ORGANIZATION_ID)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+            "id": USER_ID,
+            "email": "Ada@example.com",
+            "user_orgs": [
+                { "organization_id": "00000000-0000-0000-0000-0000000000a2", "role": 2 },
+                { "organization_id": ORGANIZATION_ID, "role": 0 },
+            ],
+        }])))
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/api/user"))
+        .and(header("TR-Organization", ORGANIZATION_ID))
+        .respond_with(ResponseTemplate::new(204))
+        .mount(&server)
+        .await;
+    server
+}
+
+#[tokio::test]
+async fn users_set_role_updates_the_member_found_by_email() {
+    let server = mock_server().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["organization", "users", "set-role", "ada@example.com"])
+        .args(["--role", "admin"])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("is now an admin"));
+    let requests = server.received_requests().await.unwrap();
+    let update = requests
+        .iter()
+        .find(|request| request.method.as_str() == "PUT")
+        .unwrap()
+        .body_json::<Value>()
+        .unwrap();
+    assert_eq!(update, json!({ "user_id": USER_ID, "role": 1 }));
+}
+
+#[tokio::test]
+async fn users_set_role_fails_for_someone_outside_the_organization() {
+    let server = mock_server().await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["organization", "users", "set-role", "grace@example.com"])
+        .args(["--role", "owner"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("grace@example.com is not a member"));
+    let requests = server.received_requests().await.unwrap();
+    assert!(requests
+        .iter()
+        .all(|request| request.method.as_str() == "GET"));
+}
@@ -0,0 +1,50 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{dataset_and_usage, stderr, Trieve, DATASET_ID, ORGANIZATION_ID};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+#[tokio::test]
+async fn a_failed_server_version_lookup_is_cached() {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/api-docs/openapi.json"))
+        .respond_with(ResponseTemplate::new(404))
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path(format!(
+            "/api/dataset/organization/{}",
+            ORGANIZATION_ID
+        )))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(vec![dataset_and_usage(DATASET_ID, "docs", 3)]),
+        )
+        .mount(&server)
+        .await;
+    let trieve = Trieve::new();
+
+    for _ in 0..2 {
+        let output = trieve
+            .logged_in(&server)
+            .env("TRIEVE_NO_VERSION_CHECK", "false")
+            .args(["dataset", "list"])
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", stderr(&output));
+    }
+
+    let lookups = server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.url.path() == "/api-docs/openapi.json")
+        .count();
+    assert_eq!(lookups, 1);
+}
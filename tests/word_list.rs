@@ -0,0 +1,162 @@
+#![cfg(unix)]
+
+mod common;
+
+use std::fs;
+
+use common::{run_typing, stderr, stdout, Trieve, DATASET_ID};
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{method, path},
+    Mock, MockServer, ResponseTemplate,
+};
+
+async fn mock_server(server_configuration: Value) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path(format!("/api/dataset/{}", DATASET_ID)))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": DATASET_ID,
+            "server_configuration": server_configuration,
+        })))
+        .mount(&server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/api/dataset"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "id": DATASET_ID,
+            "server_configuration": { "SYNONYMS": [], "STOP_WORDS": [] },
+        })))
+        .mount(&server)
+        .await;
+    server
+}
+
+/// The `server_configuration` of every `PUT /api/dataset` request the server received.
+async fn updated_configurations(server: &MockServer) -> Vec<Value> {
+    server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.method.as_str() == "PUT")
+        .map(|request| request.body_json::<Value>().unwrap()["server_configuration"].clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn synonyms_import_appends_the_groups_and_keeps_the_other_settings() {
+    let server = mock_server(json!({
+        "LLM_DEFAULT_MODEL": "gpt-4o",
+        "SYNONYMS": [["laptop", "notebook"]],
+    }))
+    .await;
+    let trieve = Trieve::new();
+    let file = trieve.home.path().join("synonyms.csv");
+    fs::write(&file, "# sizes\nXL, extra large\n\ntv,television,TV\n").unwrap();
+
+    let output = trieve
+        .logged_in(&server)
+        .args([
+            "dataset",
+            "synonyms",
+            "import",
+            "--append",
+            "--dataset-id",
+            DATASET_ID,
+        ])
+        .arg(&file)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        updated_configurations(&server).await,
+        [json!({
+            "LLM_DEFAULT_MODEL": "gpt-4o",
+            "SYNONYMS": [["laptop", "notebook"], ["xl", "extra large"], ["tv", "television"]],
+        })]
+    );
+}
+
+#[tokio::test]
+async fn synonyms_import_reports_every_invalid_line_and_changes_nothing() {
+    let server = mock_server(json!({})).await;
+    let trieve = Trieve::new();
+    let file = trieve.home.path().join("synonyms.csv");
+    fs::write(&file, "tv,television\nlaptop\nscreen,tv\n").unwrap();
+
+    let output = trieve
+        .logged_in(&server)
+        .args(["dataset", "synonyms", "import", "--dataset-id", DATASET_ID])
+        .arg(&file)
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let error = stderr(&output);
+    assert!(error.contains("line 2: a synonym group needs at least two terms"));
+    assert!(error.contains("line 3: 'tv' is already in the group on line 1"));
+    assert!(updated_configurations(&server).await.is_empty());
+}
+
+#[tokio::test]
+async fn stopwords_list_prints_one_word_per_line() {
+    let server = mock_server(json!({ "STOP_WORDS": ["the", "a", "of"] })).await;
+
+    let output = Trieve::new()
+        .logged_in(&server)
+        .args(["dataset", "stopwords", "list", "--dataset-id", DATASET_ID])
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stdout(&output), "the\na\nof\n");
+}
+
+#[tokio::test]
+async fn stopwords_import_replacing_words_with_a_protected_profile_requires_typing_its_name() {
+    let server = mock_server(json!({ "STOP_WORDS": ["the", "a"] })).await;
+    let trieve = Trieve::new();
+    trieve.protected(&server);
+    let file = trieve.home.path().join("stopwords.txt");
+    fs::write(&file, "of\nand\n").unwrap();
+    let import = || {
+        let mut command = trieve.command();
+        command
+            .args(["dataset", "stopwords", "import", "--dataset-id", DATASET_ID])
+            .arg(&file);
+        command
+    };
+
+    let output = run_typing(&mut import(), "yes");
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("replace the 2 stop words of dataset"));
+    assert!(updated_configurations(&server).await.is_empty());
+
+    let output = run_typing(&mut import(), "production");
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        updated_configurations(&server).await,
+        [json!({ "STOP_WORDS": ["of", "and"] })]
+    );
+}
+
+#[tokio::test]
+async fn stopwords_import_into_an_empty_list_with_a_protected_profile_does_not_ask() {
+    let server = mock_server(json!({})).await;
+    let trieve = Trieve::new();
+    trieve.protected(&server);
+    let file = trieve.home.path().join("stopwords.txt");
+    fs::write(&file, "of\nand\n").unwrap();
+
+    let output = trieve
+        .command()
+        .args(["dataset", "stopwords", "import", "--dataset-id", DATASET_ID])
+        .arg(&file)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(updated_configurations(&server).await.len(), 1);
+}